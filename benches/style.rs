@@ -0,0 +1,113 @@
+//! Benchmarks of the style commands encoding.
+//!
+//! Run with `cargo bench --bench style`.
+
+use std::io::Write;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{
+        Attribute, Color, Print, PrintStyledContent, ResetColor, SetAttribute, SetBackgroundColor,
+        SetForegroundColor, Stylize,
+    },
+};
+
+const COLUMNS: u16 = 80;
+const ROWS: u16 = 24;
+
+fn bench_colors(c: &mut Criterion) {
+    let mut buffer = Vec::with_capacity(64);
+
+    c.bench_function("SetForegroundColor(Red)", |b| {
+        b.iter(|| {
+            buffer.clear();
+            queue!(buffer, SetForegroundColor(black_box(Color::Red))).unwrap();
+        })
+    });
+
+    c.bench_function("SetBackgroundColor(AnsiValue)", |b| {
+        b.iter(|| {
+            buffer.clear();
+            queue!(buffer, SetBackgroundColor(black_box(Color::AnsiValue(208)))).unwrap();
+        })
+    });
+
+    c.bench_function("SetForegroundColor(Rgb)", |b| {
+        b.iter(|| {
+            buffer.clear();
+            queue!(
+                buffer,
+                SetForegroundColor(black_box(Color::Rgb {
+                    r: 10,
+                    g: 200,
+                    b: 30
+                }))
+            )
+            .unwrap();
+        })
+    });
+}
+
+fn bench_attributes(c: &mut Criterion) {
+    let mut buffer = Vec::with_capacity(64);
+
+    c.bench_function("SetAttribute(Bold)", |b| {
+        b.iter(|| {
+            buffer.clear();
+            queue!(buffer, SetAttribute(black_box(Attribute::Bold))).unwrap();
+        })
+    });
+}
+
+/// Renders a full screen where every cell has a different style.
+fn bench_styled_screen(c: &mut Criterion) {
+    let mut buffer = Vec::with_capacity(64 * 1024);
+
+    c.bench_function("styled screen (commands)", |b| {
+        b.iter(|| {
+            buffer.clear();
+            for y in 0..ROWS {
+                queue!(buffer, MoveTo(0, y)).unwrap();
+                for x in 0..COLUMNS {
+                    let value = (x + y * COLUMNS) as u8;
+                    queue!(
+                        buffer,
+                        SetForegroundColor(Color::AnsiValue(value)),
+                        SetBackgroundColor(Color::AnsiValue(255 - value)),
+                        SetAttribute(if x % 2 == 0 {
+                            Attribute::Bold
+                        } else {
+                            Attribute::NormalIntensity
+                        }),
+                        Print('x'),
+                    )
+                    .unwrap();
+                }
+            }
+            queue!(buffer, ResetColor).unwrap();
+            buffer.flush().unwrap();
+        })
+    });
+
+    c.bench_function("styled screen (styled content)", |b| {
+        b.iter(|| {
+            buffer.clear();
+            for y in 0..ROWS {
+                queue!(buffer, MoveTo(0, y)).unwrap();
+                for x in 0..COLUMNS {
+                    let value = (x + y * COLUMNS) as u8;
+                    let content = 'x'
+                        .with(Color::AnsiValue(value))
+                        .on(Color::AnsiValue(255 - value))
+                        .italic();
+                    queue!(buffer, PrintStyledContent(content)).unwrap();
+                }
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_colors, bench_attributes, bench_styled_screen);
+criterion_main!(benches);
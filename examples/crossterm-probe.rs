@@ -0,0 +1,104 @@
+//! Prints the detected capabilities, every decoded event and the raw bytes it was
+//! decoded from. Attach the output to the bug reports of the terminal-specific issues.
+//!
+//! cargo run --example crossterm-probe --features probe | tee probe.txt
+
+use std::{
+    env,
+    io::{stdout, Write},
+};
+
+use crossterm::{
+    capabilities::capabilities,
+    event::{
+        read, take_raw_input, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+        EnableMouseCapture, Event, KeyCode, RawInput,
+    },
+    execute,
+    quirks::quirks,
+    terminal::{self, disable_raw_mode, enable_raw_mode},
+    Result,
+};
+
+const HELP: &str = r#"crossterm-probe
+ - Keyboard, mouse, paste and terminal resize events enabled
+ - Every event is printed next to the raw bytes it was decoded from
+ - Hit Esc twice to quit
+"#;
+
+const ENVIRONMENT: [&str; 6] = [
+    "TERM",
+    "COLORTERM",
+    "TERM_PROGRAM",
+    "TERM_PROGRAM_VERSION",
+    "LC_TERMINAL",
+    "WT_SESSION",
+];
+
+fn print_terminal() -> Result<()> {
+    println!("crossterm {}", env!("CARGO_PKG_VERSION"));
+    println!("OS: {} {}", env::consts::OS, env::consts::ARCH);
+    for name in ENVIRONMENT {
+        println!("{}: {:?}", name, env::var(name).ok());
+    }
+    println!("Size: {:?}", terminal::size()?);
+    println!("Capabilities: {:#?}", capabilities());
+    println!("Quirks: {:?}", quirks());
+    println!();
+    Ok(())
+}
+
+fn format_raw_input(inputs: &[RawInput]) -> String {
+    inputs
+        .iter()
+        .map(|input| {
+            let bytes = input.bytes.escape_ascii().to_string();
+            if input.parsed {
+                bytes
+            } else {
+                format!("{} (dropped)", bytes)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn print_events() -> Result<()> {
+    let mut escape_count = 0;
+
+    loop {
+        let event = read()?;
+
+        println!("{:<40} {:?}\r", format_raw_input(&take_raw_input()), event);
+
+        if event == Event::Key(KeyCode::Esc.into()) {
+            escape_count += 1;
+            if escape_count == 2 {
+                break;
+            }
+        } else if let Event::Key(_) = event {
+            escape_count = 0;
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    print_terminal()?;
+    println!("{}", HELP);
+
+    enable_raw_mode()?;
+
+    let mut stdout = stdout();
+    execute!(stdout, EnableBracketedPaste, EnableMouseCapture)?;
+
+    if let Err(e) = print_events() {
+        println!("Error: {:?}\r", e);
+    }
+
+    execute!(stdout, DisableBracketedPaste, DisableMouseCapture)?;
+    stdout.flush()?;
+
+    disable_raw_mode()
+}
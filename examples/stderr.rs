@@ -16,7 +16,7 @@ use crossterm::{
     event::{Event, KeyCode, KeyEvent},
     execute, queue,
     style::Print,
-    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen, OutputStream},
     Result,
 };
 
@@ -56,10 +56,11 @@ where
         Hide                  // hide the cursor
     )?;
 
-    let mut y = 1;
-    for line in TEXT.split('\n') {
-        queue!(write, MoveTo(1, y), Print(line.to_string()))?;
-        y += 1;
+    // The size of the terminal the stderr is drawn to, the stdout is redirected
+    let (columns, rows) = terminal::size()?;
+    for (y, line) in (1..rows).zip(TEXT.split('\n')) {
+        let line: String = line.chars().take(usize::from(columns) - 1).collect();
+        queue!(write, MoveTo(1, y), Print(line))?;
     }
 
     write.flush()?;
@@ -87,6 +88,9 @@ pub fn read_char() -> Result<char> {
 
 // cargo run --example stderr
 fn main() {
+    // The functions writing to the terminal on their own (e.g. the queries) use the stderr too
+    terminal::set_output_stream(OutputStream::Stderr);
+
     match run_app(&mut stderr()).unwrap() {
         '1' => print!(".."),
         '2' => print!("/"),
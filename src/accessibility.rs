@@ -0,0 +1,244 @@
+//! # Accessibility
+//!
+//! The `accessibility` module lets the applications announce their state changes (a file
+//! was saved, a search found no match, ...) to the assistive technologies. The screen
+//! readers read the text printed at the cursor, but the changes elsewhere on the screen of
+//! a full screen application go unnoticed.
+//!
+//! The announcements are disabled by default, the [announce](fn.announce.html) does
+//! nothing until a channel is set with the
+//! [set_announcement_channel](fn.set_announcement_channel.html):
+//!
+//! * a secondary stream, e.g. a named pipe read by a speech synthesizer or a braille
+//!   display bridge, one announcement per line,
+//! * the terminal notifications, which the iTerm2 hands over to the VoiceOver.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::fs::OpenOptions;
+//!
+//! use crossterm::{
+//!     accessibility::{self, AnnouncementChannel},
+//!     Result,
+//! };
+//!
+//! fn main() -> Result<()> {
+//!     let pipe = OpenOptions::new().write(true).open("/tmp/announcements")?;
+//!     accessibility::set_announcement_channel(AnnouncementChannel::Stream(Box::new(pipe)));
+//!
+//!     accessibility::announce("3 files saved")?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    fmt::{self, Display, Write as _},
+    io::{self, Write},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    capabilities::{capabilities, Capability},
+    terminal::output_stream,
+    Command, QueueableCommand, Result,
+};
+
+/// The channel the announcements are sent to.
+pub enum AnnouncementChannel {
+    /// The announcements are dropped.
+    Disabled,
+    /// The announcements are written to the stream, one per line.
+    Stream(Box<dyn Write + Send>),
+    /// The announcements are posted as the terminal notifications, see
+    /// [PostNotification](struct.PostNotification.html).
+    ///
+    /// They're dropped if the terminal doesn't have the
+    /// [ITerm2Extensions](../capabilities/enum.Capability.html#variant.ITerm2Extensions)
+    /// capability.
+    Terminal,
+}
+
+impl fmt::Debug for AnnouncementChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnnouncementChannel::Disabled => f.write_str("Disabled"),
+            AnnouncementChannel::Stream(_) => f.write_str("Stream(..)"),
+            AnnouncementChannel::Terminal => f.write_str("Terminal"),
+        }
+    }
+}
+
+/// The channel set by the application.
+static ANNOUNCEMENT_CHANNEL: Mutex<AnnouncementChannel> =
+    parking_lot::const_mutex(AnnouncementChannel::Disabled);
+
+/// Sets the channel the announcements are sent to and returns the previous one.
+pub fn set_announcement_channel(channel: AnnouncementChannel) -> AnnouncementChannel {
+    std::mem::replace(&mut ANNOUNCEMENT_CHANNEL.lock(), channel)
+}
+
+/// Returns whether the announcements are sent anywhere.
+///
+/// The applications can skip composing the announcements otherwise.
+pub fn is_announcing() -> bool {
+    !matches!(*ANNOUNCEMENT_CHANNEL.lock(), AnnouncementChannel::Disabled)
+}
+
+/// Announces the message to the assistive technologies through the channel set with the
+/// [set_announcement_channel](fn.set_announcement_channel.html).
+///
+/// The line breaks and the control characters of the message are replaced by spaces. The
+/// terminal notifications are written to the [output stream](../terminal/fn.output_stream.html).
+pub fn announce(message: impl Display) -> Result<()> {
+    let mut channel = ANNOUNCEMENT_CHANNEL.lock();
+    match &mut *channel {
+        AnnouncementChannel::Disabled => Ok(()),
+        AnnouncementChannel::Stream(stream) => {
+            let mut line = String::new();
+            write_sanitized(&mut line, &message).map_err(|_| formatting_error())?;
+            line.push('\n');
+            stream.write_all(line.as_bytes())?;
+            stream.flush()
+        }
+        AnnouncementChannel::Terminal if capabilities().iterm2_extensions() => {
+            let mut output = output_stream().writer();
+            output.queue(PostNotification(message))?;
+            output.flush()
+        }
+        AnnouncementChannel::Terminal => Ok(()),
+    }
+}
+
+/// A command that posts a notification of the terminal, `OSC 9`.
+///
+/// The iTerm2 shows it in the Notification Center, the VoiceOver reads it out. The line
+/// breaks and the control characters of the message are replaced by spaces.
+///
+/// # Notes
+///
+/// * Requires the [ITerm2Extensions](../capabilities/enum.Capability.html#variant.ITerm2Extensions)
+///   capability, the `OSC 9` sequences of the ConEmu and the Windows Terminal are
+///   unrelated.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostNotification<T>(pub T);
+
+impl<T: Display> Command for PostNotification<T> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B]9;")?;
+        write_sanitized(f, &self.0)?;
+        f.write_str("\x1B\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        // The legacy console has no notifications
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::ITerm2Extensions)
+    }
+}
+
+/// Writes the message with the control characters replaced by spaces, they'd end the
+/// line or the escape sequence.
+fn write_sanitized(f: &mut impl fmt::Write, message: &impl Display) -> fmt::Result {
+    struct Sanitizer<'a, W>(&'a mut W);
+
+    impl<W: fmt::Write> fmt::Write for Sanitizer<'_, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for c in s.chars() {
+                self.0.write_char(if c.is_control() { ' ' } else { c })?;
+            }
+            Ok(())
+        }
+    }
+
+    write!(Sanitizer(f), "{}", message)
+}
+
+fn formatting_error() -> io::Error {
+    io::Error::other("The announcement couldn't be formatted.")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, sync::Arc};
+
+    use parking_lot::Mutex;
+
+    use crate::cursor::tracking::TEST_LOCK;
+    use crate::Command;
+
+    use super::{
+        announce, is_announcing, set_announcement_channel, AnnouncementChannel, PostNotification,
+    };
+
+    /// A stream shared with the test.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_post_notification() {
+        let mut ansi = String::new();
+        PostNotification("Build\nfailed\x07")
+            .write_ansi(&mut ansi)
+            .unwrap();
+        assert_eq!(ansi, "\x1B]9;Build failed \x1B\\");
+    }
+
+    /// Restores the channel set before the test when dropped, it's disabled meanwhile.
+    struct RestoreChannel(Option<AnnouncementChannel>);
+
+    impl RestoreChannel {
+        fn new() -> RestoreChannel {
+            RestoreChannel(Some(set_announcement_channel(
+                AnnouncementChannel::Disabled,
+            )))
+        }
+    }
+
+    impl Drop for RestoreChannel {
+        fn drop(&mut self) {
+            if let Some(channel) = self.0.take() {
+                set_announcement_channel(channel);
+            }
+        }
+    }
+
+    #[test]
+    fn test_announce_to_stream() {
+        let _lock = TEST_LOCK.lock();
+        let _channel = RestoreChannel::new();
+        assert!(!is_announcing());
+        announce("dropped").unwrap();
+
+        let stream = Shared::default();
+        set_announcement_channel(AnnouncementChannel::Stream(Box::new(stream.clone())));
+        assert!(is_announcing());
+        announce("3 files saved").unwrap();
+        announce("no match\r\nfor \"foo\"").unwrap();
+
+        let previous = set_announcement_channel(AnnouncementChannel::Disabled);
+        assert!(matches!(previous, AnnouncementChannel::Stream(_)));
+        announce("dropped").unwrap();
+
+        assert_eq!(
+            String::from_utf8(stream.0.lock().clone()).unwrap(),
+            "3 files saved\nno match  for \"foo\"\n"
+        );
+    }
+}
@@ -38,7 +38,7 @@ pub fn supports_ansi() -> bool {
         // so when we try to enable the ANSI-flag for Windows this won't work.
         // Because of that we should check first if the TERM-variable is set
         // and see if the current terminal is a terminal who does support ANSI.
-        let supported = std::env::var("TERM").map_or(false, |term| term != "dumb")
+        let supported = std::env::var("TERM").is_ok_and(|term| term != "dumb")
             || enable_vt_processing().is_ok();
 
         SUPPORTS_ANSI_ESCAPE_CODES.store(supported, Ordering::SeqCst);
@@ -0,0 +1,182 @@
+//! # Buffer
+//!
+//! The `buffer` module provides functionality to render frames of styled cells.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::{stdout, Write};
+//!
+//! use crossterm::{
+//!     buffer::{write_row, StyledGrapheme},
+//!     style::{Color, ContentStyle},
+//!     Result,
+//! };
+//!
+//! fn main() -> Result<()> {
+//!     let mut style = ContentStyle::new();
+//!     style.foreground_color = Some(Color::Red);
+//!
+//!     let cells: Vec<StyledGrapheme> = "Hello"
+//!         .split("")
+//!         .filter(|symbol| !symbol.is_empty())
+//!         .map(|symbol| StyledGrapheme::new(symbol, style))
+//!         .collect();
+//!
+//!     let mut stdout = stdout();
+//!     write_row(&mut stdout, 0, &cells)?;
+//!     stdout.flush()?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::io;
+
+use crate::{
+    cursor::MoveTo,
+    style::{
+        Attribute, Color, ContentStyle, Print, SetAttribute, SetAttributes, SetBackgroundColor,
+        SetForegroundColor,
+    },
+    QueueableCommand, Result,
+};
+
+pub use self::inline::InlineViewport;
+pub use self::region::{fill_region, style_region};
+
+mod inline;
+mod region;
+
+/// A single cell of the terminal screen: the grapheme and the style to print it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StyledGrapheme<'a> {
+    /// The grapheme (user perceived character) occupying the cell.
+    pub symbol: &'a str,
+    /// The style of the grapheme.
+    pub style: ContentStyle,
+}
+
+impl<'a> StyledGrapheme<'a> {
+    /// Creates a new `StyledGrapheme`.
+    pub fn new(symbol: &'a str, style: ContentStyle) -> StyledGrapheme<'a> {
+        StyledGrapheme { symbol, style }
+    }
+}
+
+/// Writes the cells to the given row, starting at the first column.
+///
+/// A single [MoveTo](../cursor/struct.MoveTo.html) is emitted, followed by the graphemes.
+/// Only the style changes between the neighbouring cells are emitted and the style is
+/// reset at the end of the row.
+///
+/// # Notes
+///
+/// The commands are queued, the writer must be flushed to display the row.
+pub fn write_row(w: &mut impl io::Write, y: u16, cells: &[StyledGrapheme<'_>]) -> Result<()> {
+    w.queue(MoveTo(0, y))?;
+
+    let mut current = ContentStyle::default();
+    for cell in cells {
+        queue_style_diff(w, &mut current, cell.style)?;
+        w.queue(Print(cell.symbol))?;
+    }
+
+    if current != ContentStyle::default() {
+        w.queue(SetAttribute(Attribute::Reset))?;
+    }
+
+    Ok(())
+}
+
+/// Queues the commands changing the `current` style to the `new` one.
+fn queue_style_diff(
+    w: &mut impl io::Write,
+    current: &mut ContentStyle,
+    new: ContentStyle,
+) -> Result<()> {
+    if *current == new {
+        return Ok(());
+    }
+
+    // There's no general way to turn a single attribute off, start from scratch
+    let removed = (current.attributes ^ new.attributes) & current.attributes;
+    if !removed.is_empty() {
+        w.queue(SetAttribute(Attribute::Reset))?;
+        *current = ContentStyle::default();
+    }
+
+    if current.foreground_color != new.foreground_color {
+        w.queue(SetForegroundColor(
+            new.foreground_color.unwrap_or(Color::Reset),
+        ))?;
+    }
+    if current.background_color != new.background_color {
+        w.queue(SetBackgroundColor(
+            new.background_color.unwrap_or(Color::Reset),
+        ))?;
+    }
+
+    let added = (current.attributes ^ new.attributes) & new.attributes;
+    if !added.is_empty() {
+        w.queue(SetAttributes(added))?;
+    }
+
+    *current = new;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::style::{Attribute, Color, ContentStyle};
+
+    use super::{write_row, StyledGrapheme};
+
+    fn render(cells: &[StyledGrapheme<'_>]) -> String {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let mut buffer = Vec::new();
+        write_row(&mut buffer, 2, cells).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_write_row_without_style() {
+        let cells = [
+            StyledGrapheme::new("a", ContentStyle::new()),
+            StyledGrapheme::new("b", ContentStyle::new()),
+        ];
+
+        assert_eq!(render(&cells), "\x1B[3;1Hab");
+    }
+
+    #[test]
+    fn test_write_row_emits_style_changes_only() {
+        let mut red = ContentStyle::new();
+        red.foreground_color = Some(Color::Red);
+        let mut red_bold = red;
+        red_bold.attributes.set(Attribute::Bold);
+
+        let cells = [
+            StyledGrapheme::new("a", red),
+            StyledGrapheme::new("b", red),
+            StyledGrapheme::new("c", red_bold),
+            StyledGrapheme::new("d", red),
+            StyledGrapheme::new("e", ContentStyle::new()),
+        ];
+
+        assert_eq!(
+            render(&cells),
+            "\x1B[3;1H\x1B[38;5;9mab\x1B[1mc\x1B[0m\x1B[38;5;9md\x1B[39me"
+        );
+    }
+
+    #[test]
+    fn test_write_row_resets_style_at_the_end() {
+        let mut bold = ContentStyle::new();
+        bold.attributes.set(Attribute::Bold);
+
+        assert_eq!(
+            render(&[StyledGrapheme::new("a", bold)]),
+            "\x1B[3;1H\x1B[1ma\x1B[0m"
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! This module contains the viewport drawn inline, below the output of the shell.
+
+use std::io;
+
+use crate::{
+    cursor::MoveTo,
+    style::Print,
+    terminal::{Clear, ClearType, Rect},
+    text::str_width,
+    QueueableCommand, Result,
+};
+
+use super::{write_row, StyledGrapheme};
+
+/// A fixed number of lines reserved at the bottom of the normal screen.
+///
+/// The viewport is drawn below the output of the shell (like the progress bars), the
+/// alternate screen isn't used. Only the lines of the viewport are repainted, the text
+/// printed with the [print_above](#method.print_above) scrolls into the history above
+/// the viewport.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{
+///     buffer::{InlineViewport, StyledGrapheme},
+///     style::ContentStyle,
+///     Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     let mut stdout = stdout();
+///     let mut viewport = InlineViewport::new(&mut stdout, 2)?;
+///
+///     for file in ["a.txt", "b.txt"] {
+///         viewport.print_above(&mut stdout, &format!("Downloaded {}", file))?;
+///         viewport.draw_row(&mut stdout, 0, &[StyledGrapheme::new("#", ContentStyle::new())])?;
+///         stdout.flush()?;
+///     }
+///
+///     viewport.finish(&mut stdout)?;
+///     stdout.flush()?;
+///     Ok(())
+/// }
+/// ```
+///
+/// # Notes
+///
+/// The commands are queued, the writer must be flushed to display the changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InlineViewport {
+    height: u16,
+    top: u16,
+    size: (u16, u16),
+}
+
+impl InlineViewport {
+    /// Reserves the given number of lines below the cursor, scrolling the screen if there
+    /// isn't enough lines left.
+    ///
+    /// The height is clamped to the height of the screen.
+    ///
+    /// # Notes
+    ///
+    /// On UNIX, it requires the `events` feature to read the cursor position.
+    #[cfg(any(windows, feature = "events"))]
+    pub fn new(w: &mut impl io::Write, height: u16) -> Result<InlineViewport> {
+        let (column, row) = crate::cursor::position()?;
+        InlineViewport::with_cursor_position(w, height, column, row)
+    }
+
+    /// Same as the [new](#method.new), but the cursor position is provided by the caller
+    /// (from the [tracked_position](../cursor/fn.tracked_position.html) for example).
+    pub fn with_cursor_position(
+        w: &mut impl io::Write,
+        height: u16,
+        column: u16,
+        row: u16,
+    ) -> Result<InlineViewport> {
+        let size = crate::terminal::size()?;
+        InlineViewport::reserve(w, height, (column, row), size)
+    }
+
+    fn reserve(
+        w: &mut impl io::Write,
+        height: u16,
+        (column, row): (u16, u16),
+        (columns, rows): (u16, u16),
+    ) -> Result<InlineViewport> {
+        let mut viewport = InlineViewport {
+            height: height.min(rows),
+            top: row.min(rows.saturating_sub(1)),
+            size: (columns, rows),
+        };
+
+        // Keep the text before the cursor
+        if column != 0 {
+            w.queue(Print("\r\n"))?;
+            viewport.top = (viewport.top + 1).min(rows.saturating_sub(1));
+        }
+        viewport.make_room(w)?;
+
+        Ok(viewport)
+    }
+
+    /// Returns the area of the screen occupied by the viewport.
+    pub fn area(&self) -> Rect {
+        Rect::new(0, self.top, self.size.0, self.height)
+    }
+
+    /// Draws the cells to the given line of the viewport, clearing the rest of the line.
+    ///
+    /// The lines out of the viewport are ignored.
+    pub fn draw_row(
+        &self,
+        w: &mut impl io::Write,
+        index: u16,
+        cells: &[StyledGrapheme<'_>],
+    ) -> Result<()> {
+        if index >= self.height {
+            return Ok(());
+        }
+
+        write_row(w, self.top + index, cells)?;
+        w.queue(Clear(ClearType::UntilNewLine))?;
+        Ok(())
+    }
+
+    /// Clears all the lines of the viewport.
+    pub fn clear(&self, w: &mut impl io::Write) -> Result<()> {
+        w.queue(MoveTo(0, self.top))?;
+        w.queue(Clear(ClearType::FromCursorDown))?;
+        Ok(())
+    }
+
+    /// Prints the text above the viewport, the screen is scrolled to make room for it.
+    ///
+    /// The viewport is cleared and moved below the text, it must be drawn again.
+    pub fn print_above(&mut self, w: &mut impl io::Write, text: &str) -> Result<()> {
+        self.clear(w)?;
+
+        let columns = usize::from(self.size.0.max(1));
+        let mut lines = 0usize;
+        for line in text.lines() {
+            w.queue(Print(line))?;
+            w.queue(Print("\r\n"))?;
+            // The wrapped lines occupy several rows
+            lines += str_width(line).max(1).div_ceil(columns);
+        }
+
+        let last_row = self.size.1.saturating_sub(1);
+        self.top = u16::try_from(usize::from(self.top) + lines)
+            .unwrap_or(u16::MAX)
+            .min(last_row);
+        self.make_room(w)
+    }
+
+    /// Updates the viewport after the terminal was resized.
+    ///
+    /// The viewport is moved up if it doesn't fit into the screen anymore and cleared, it
+    /// must be drawn again. Returns the new area of the viewport.
+    pub fn resize(&mut self, w: &mut impl io::Write, columns: u16, rows: u16) -> Result<Rect> {
+        self.size = (columns, rows);
+        self.height = self.height.min(rows);
+        self.top = self.top.min(rows - self.height);
+
+        self.clear(w)?;
+        Ok(self.area())
+    }
+
+    /// Moves the cursor below the viewport, the last drawn lines are kept on the screen.
+    pub fn finish(self, w: &mut impl io::Write) -> Result<()> {
+        let last_row = (self.top + self.height).saturating_sub(1);
+        w.queue(MoveTo(0, last_row))?;
+        w.queue(Print("\r\n"))?;
+        Ok(())
+    }
+
+    /// Scrolls the screen until all the lines of the viewport fit in, moves the cursor to
+    /// the top of the viewport.
+    fn make_room(&mut self, w: &mut impl io::Write) -> Result<()> {
+        let rows = self.size.1;
+        if self.height == 0 || rows == 0 {
+            return Ok(());
+        }
+
+        // The line feeds scroll the screen once the cursor reaches the bottom
+        w.queue(MoveTo(0, self.top))?;
+        for _ in 1..self.height {
+            w.queue(Print("\n"))?;
+        }
+
+        self.top = self.top.min(rows - self.height);
+        w.queue(MoveTo(0, self.top))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::style::ContentStyle;
+    use crate::terminal::Rect;
+
+    use super::{InlineViewport, StyledGrapheme};
+
+    fn output(write: impl FnOnce(&mut Vec<u8>)) -> String {
+        let mut buffer = Vec::new();
+        write(&mut buffer);
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_reserve_scrolls_at_the_bottom() {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+
+        let mut viewport = None;
+        let written = output(|w| {
+            viewport = Some(InlineViewport::reserve(w, 3, (4, 8), (20, 10)).unwrap());
+        });
+        assert_eq!(written, "\r\n\x1B[10;1H\n\n\x1B[8;1H");
+        assert_eq!(viewport.unwrap().area(), Rect::new(0, 7, 20, 3));
+
+        // Enough room below the cursor
+        let viewport = InlineViewport::reserve(&mut Vec::new(), 3, (0, 2), (20, 10)).unwrap();
+        assert_eq!(viewport.area(), Rect::new(0, 2, 20, 3));
+
+        // Taller than the screen
+        let viewport = InlineViewport::reserve(&mut Vec::new(), 30, (0, 2), (20, 10)).unwrap();
+        assert_eq!(viewport.area(), Rect::new(0, 0, 20, 10));
+    }
+
+    #[test]
+    fn test_print_above_and_draw() {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+
+        let mut viewport = InlineViewport::reserve(&mut Vec::new(), 2, (0, 1), (4, 10)).unwrap();
+
+        let written = output(|w| viewport.print_above(w, "ab\nabcdef").unwrap());
+        assert_eq!(
+            written,
+            "\x1B[2;1H\x1B[Jab\r\nabcdef\r\n\x1B[5;1H\n\x1B[5;1H"
+        );
+        assert_eq!(viewport.area(), Rect::new(0, 4, 4, 2));
+
+        let written = output(|w| viewport.print_above(w, &"a\n".repeat(8)).unwrap());
+        assert!(written.ends_with("\x1B[10;1H\n\x1B[9;1H"));
+        assert_eq!(viewport.area(), Rect::new(0, 8, 4, 2));
+
+        let cells = [StyledGrapheme::new("x", ContentStyle::new())];
+        let written = output(|w| {
+            viewport.draw_row(w, 1, &cells).unwrap();
+            viewport.draw_row(w, 2, &cells).unwrap();
+        });
+        assert_eq!(written, "\x1B[10;1Hx\x1B[K");
+
+        assert_eq!(output(|w| viewport.finish(w).unwrap()), "\x1B[10;1H\r\n");
+    }
+
+    #[test]
+    fn test_resize() {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+
+        let mut viewport = InlineViewport::reserve(&mut Vec::new(), 3, (0, 9), (20, 10)).unwrap();
+        assert_eq!(viewport.area(), Rect::new(0, 7, 20, 3));
+
+        let mut written = Vec::new();
+        let area = viewport.resize(&mut written, 10, 5).unwrap();
+        assert_eq!(area, Rect::new(0, 2, 10, 3));
+        assert_eq!(written, b"\x1B[3;1H\x1B[J");
+
+        assert_eq!(
+            viewport.resize(&mut Vec::new(), 10, 2).unwrap(),
+            Rect::new(0, 0, 10, 2)
+        );
+    }
+}
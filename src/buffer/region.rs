@@ -0,0 +1,284 @@
+//! This module contains the batch operations on the rectangular areas of the screen.
+
+use std::{io, iter};
+
+use crate::{
+    capabilities::{capabilities, Capabilities},
+    csi,
+    cursor::MoveTo,
+    style::{Attribute, Attributes, ContentStyle, Print, SetAttribute},
+    terminal::Rect,
+    text::char_width,
+    QueueableCommand, Result,
+};
+
+use super::queue_style_diff;
+
+/// The attributes changed by the `DECCARA`, with their SGR parameters.
+const RECTANGULAR_ATTRIBUTES: [(Attribute, u8); 4] = [
+    (Attribute::Bold, 1),
+    (Attribute::Underlined, 4),
+    (Attribute::SlowBlink, 5),
+    (Attribute::Reverse, 7),
+];
+
+/// Fills the area with the character printed in the given style, e.g. to paint the
+/// background of a pane.
+///
+/// A single `DECFRA` (fill rectangular area) is emitted if the terminal supports the
+/// [rectangular editing](../capabilities/struct.Capabilities.html#method.rectangular_editing)
+/// and the character is a Latin-1 one. Otherwise the rows are printed one by one, the
+/// style is set once for all of them. The wide characters fill the area in pairs of
+/// columns, the odd column left is filled with a space.
+///
+/// The style is reset at the end, the cursor position is unspecified afterwards.
+///
+/// # Notes
+///
+/// * The commands are queued, the writer must be flushed to display the area.
+/// * An error is returned for the control characters, they don't occupy any cells.
+pub fn fill_region(
+    w: &mut impl io::Write,
+    rect: Rect,
+    ch: char,
+    style: ContentStyle,
+) -> Result<()> {
+    fill_region_with(w, rect, ch, style, &capabilities())
+}
+
+fn fill_region_with(
+    w: &mut impl io::Write,
+    rect: Rect,
+    ch: char,
+    style: ContentStyle,
+    capabilities: &Capabilities,
+) -> Result<()> {
+    let width = match char_width(ch) {
+        Some(width) if width > 0 => width,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} doesn't occupy any cells", ch),
+            ))
+        }
+    };
+
+    if rect.is_empty() {
+        return Ok(());
+    }
+
+    let mut current = ContentStyle::default();
+    queue_style_diff(w, &mut current, style)?;
+
+    if capabilities.rectangular_editing() && matches!(u32::from(ch), 32..=126 | 160..=255) {
+        // `DECFRA` fills with the current rendition
+        let (top, left, bottom, right) = bounds(rect);
+        write!(
+            w,
+            csi!("{};{};{};{};{}$x"),
+            u32::from(ch),
+            top,
+            left,
+            bottom,
+            right
+        )?;
+    } else {
+        let columns = usize::from(rect.width);
+        let row: String = iter::repeat_n(ch, columns / width)
+            .chain(iter::repeat_n(' ', columns % width))
+            .collect();
+
+        for offset in 0..rect.height {
+            w.queue(MoveTo(rect.x, rect.y.saturating_add(offset)))?;
+            w.queue(Print(&row))?;
+        }
+    }
+
+    if current != ContentStyle::default() {
+        w.queue(SetAttribute(Attribute::Reset))?;
+    }
+
+    Ok(())
+}
+
+/// Changes the style of the cells of the area, their content is kept.
+///
+/// A single `DECCARA` (change attributes in rectangular area) is emitted, it replaces the
+/// attributes of the cells with the ones of the style. The terminals implement it for a
+/// few attributes only, the bold, the underline, the slow blink and the reverse.
+///
+/// Returns `false` and writes nothing if the terminal doesn't support the
+/// [rectangular editing](../capabilities/struct.Capabilities.html#method.rectangular_editing)
+/// or the style has the colors or other attributes. The cells must be reprinted then,
+/// e.g. with the [write_row](fn.write_row.html).
+///
+/// # Notes
+///
+/// The commands are queued, the writer must be flushed to display the area.
+pub fn style_region(w: &mut impl io::Write, rect: Rect, style: ContentStyle) -> Result<bool> {
+    style_region_with(w, rect, style, &capabilities())
+}
+
+fn style_region_with(
+    w: &mut impl io::Write,
+    rect: Rect,
+    style: ContentStyle,
+    capabilities: &Capabilities,
+) -> Result<bool> {
+    let supported = RECTANGULAR_ATTRIBUTES
+        .iter()
+        .fold(Attributes::default(), |attributes, &(attribute, _)| {
+            attributes | attribute
+        });
+    let unsupported = (style.attributes ^ supported) & style.attributes;
+
+    if !capabilities.rectangular_editing()
+        || style.foreground_color.is_some()
+        || style.background_color.is_some()
+        || !unsupported.is_empty()
+    {
+        return Ok(false);
+    }
+
+    if rect.is_empty() {
+        return Ok(true);
+    }
+
+    let (top, left, bottom, right) = bounds(rect);
+    // The attributes are turned off first, the rest is turned on
+    write!(w, csi!("{};{};{};{};0"), top, left, bottom, right)?;
+    for &(attribute, parameter) in &RECTANGULAR_ATTRIBUTES {
+        if style.attributes.has(attribute) {
+            write!(w, ";{}", parameter)?;
+        }
+    }
+    w.write_all(b"$r")?;
+
+    Ok(true)
+}
+
+/// Returns the 1-based top, left, bottom and right bounds of the area.
+fn bounds(rect: Rect) -> (u32, u32, u32, u32) {
+    (
+        u32::from(rect.y) + 1,
+        u32::from(rect.x) + 1,
+        u32::from(rect.y) + u32::from(rect.height),
+        u32::from(rect.x) + u32::from(rect.width),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capabilities::Capabilities;
+    use crate::style::{Attribute, Color, ContentStyle};
+    use crate::terminal::Rect;
+
+    use super::{fill_region_with, style_region_with};
+
+    fn rectangular_editing(supported: bool) -> Capabilities {
+        Capabilities::builder()
+            .rectangular_editing(supported)
+            .build()
+    }
+
+    fn red() -> ContentStyle {
+        let mut style = ContentStyle::new();
+        style.background_color = Some(Color::Red);
+        style
+    }
+
+    #[test]
+    fn test_fill_region_with_decfra() {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let mut buffer = Vec::new();
+
+        fill_region_with(
+            &mut buffer,
+            Rect::new(2, 1, 10, 3),
+            ' ',
+            red(),
+            &rectangular_editing(true),
+        )
+        .unwrap();
+        assert_eq!(buffer, b"\x1B[48;5;9m\x1B[32;2;3;4;12$x\x1B[0m");
+    }
+
+    #[test]
+    fn test_fill_region_with_rows() {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let mut buffer = Vec::new();
+
+        fill_region_with(
+            &mut buffer,
+            Rect::new(0, 0, 3, 2),
+            '─',
+            ContentStyle::new(),
+            &rectangular_editing(true),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "\x1B[1;1H───\x1B[2;1H───"
+        );
+
+        // The odd column is filled with a space
+        let mut buffer = Vec::new();
+        fill_region_with(
+            &mut buffer,
+            Rect::new(1, 0, 3, 1),
+            '界',
+            red(),
+            &rectangular_editing(false),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "\x1B[48;5;9m\x1B[1;2H界 \x1B[0m"
+        );
+
+        assert!(fill_region_with(
+            &mut Vec::new(),
+            Rect::new(0, 0, 1, 1),
+            '\n',
+            red(),
+            &rectangular_editing(false),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_style_region_with_deccara() {
+        let mut style = ContentStyle::new();
+        style.attributes.set(Attribute::Reverse);
+        style.attributes.set(Attribute::Bold);
+        let mut buffer = Vec::new();
+
+        assert!(style_region_with(
+            &mut buffer,
+            Rect::new(0, 4, 80, 1),
+            style,
+            &rectangular_editing(true)
+        )
+        .unwrap());
+        assert_eq!(buffer, b"\x1B[5;1;5;80;0;1;7$r");
+    }
+
+    #[test]
+    fn test_style_region_is_not_applied_without_support() {
+        let mut italic = ContentStyle::new();
+        italic.attributes.set(Attribute::Italic);
+        let mut buffer = Vec::new();
+
+        for (style, capabilities) in [
+            (ContentStyle::new(), rectangular_editing(false)),
+            (red(), rectangular_editing(true)),
+            (italic, rectangular_editing(true)),
+        ] {
+            assert!(
+                !style_region_with(&mut buffer, Rect::new(0, 0, 1, 1), style, &capabilities)
+                    .unwrap()
+            );
+        }
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,786 @@
+//! # Capabilities
+//!
+//! The `capabilities` module describes the features supported by the terminal.
+//!
+//! The capabilities are detected from the environment variables (`TERM`, `COLORTERM`)
+//! and the [quirks](../quirks/index.html) of the terminal. The detection isn't always
+//! right, the capabilities can be overridden:
+//!
+//! * by the application, with the [set_capabilities](fn.set_capabilities.html) and
+//!   the [builder](struct.Capabilities.html#method.builder),
+//! * by the user, with the `CROSSTERM_CAPABILITIES` environment variable, which takes
+//!   precedence over the application.
+//!
+//! The environment variable contains comma separated `name=value` pairs, the names
+//! being the names of the [builder](struct.CapabilitiesBuilder.html) methods. The
+//! values of the boolean capabilities are `true`/`false` or `1`/`0`, invalid pairs are
+//! ignored. For example `CROSSTERM_CAPABILITIES=truecolor=false,color_count=256`. It's
+//! read when the capabilities are detected or set, the later changes aren't noticed.
+//!
+//! The [FallbackWriter](struct.FallbackWriter.html) replaces the escape sequences
+//! unsupported by the terminal with the supported ones.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use crossterm::capabilities::{self, Capabilities};
+//!
+//! // The terminal claims to support 24-bit colors, but it doesn't
+//! capabilities::set_capabilities(Capabilities::builder().truecolor(false).build());
+//!
+//! assert!(!capabilities::capabilities().truecolor());
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{env, error::Error, fmt, io};
+
+use parking_lot::Mutex;
+
+use crate::quirks::{quirks, Quirks};
+use crate::{Command, Result};
+
+#[cfg(any(feature = "terminal", feature = "log"))]
+pub(crate) use self::fallback::is_sequence_complete;
+pub use self::fallback::FallbackWriter;
+
+mod fallback;
+
+/// The name of the environment variable overriding the capabilities.
+const OVERRIDE_VAR: &str = "CROSSTERM_CAPABILITIES";
+
+/// Capabilities set with the `set_capabilities` or detected, with the overrides applied.
+/// `None` until they're detected.
+static CAPABILITIES: Mutex<Option<Capabilities>> = parking_lot::const_mutex(None);
+
+/// Whether the capabilities were set with the `set_capabilities`, they aren't detected
+/// again then.
+static CAPABILITIES_SET: AtomicBool = AtomicBool::new(false);
+
+/// Whether the commands requiring unsupported capabilities fail.
+static STRICT_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Represents a feature which isn't supported by all the terminals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// 24-bit colors (`Color::Rgb`).
+    Truecolor,
+    /// Erasing the scrollback (`CSI 3J`).
+    Purge,
+    /// The left and right margins (`DECSLRM`).
+    LeftRightMargins,
+    /// Colon separated SGR sub-parameters (e.g. `SGR 4:3`).
+    SgrSubparameters,
+    /// Hyperlinks (`OSC 8`).
+    Hyperlinks,
+    /// Disabling the automatic wrapping (`DECAWM`).
+    Autowrap,
+    /// The top and bottom margins (`DECSTBM`).
+    ScrollRegion,
+    /// Inserting and deleting the lines (`IL`, `DL`).
+    InsertDeleteLines,
+    /// Setting the volume of the bells (`DECSWBV`, `DECSMBV`).
+    BellVolume,
+    /// The ConEmu `OSC 9` extensions (progress, working directory), supported by the
+    /// ConEmu and the Windows Terminal.
+    ConEmuExtensions,
+    /// The iTerm2 proprietary `OSC 1337` sequences (badge, attention, Touch Bar).
+    ITerm2Extensions,
+    /// The user variables (`OSC 1337;SetUserVar`), supported by the iTerm2 and the WezTerm.
+    UserVars,
+    /// Reporting the current directory (`OSC 7`).
+    CurrentDirectory,
+    /// Clearing with the current background color (back color erase).
+    BackColorErase,
+    /// The alternate screen (mode 1049).
+    AlternateScreen,
+    /// The rectangular area operations (`DECFRA`, `DECCARA`).
+    RectangularEditing,
+    /// The Unicode box-drawing characters (e.g. `┌─┐`).
+    BoxDrawing,
+}
+
+/// The error returned in the [strict mode](fn.set_strict_mode.html) when a command
+/// requires a capability the terminal doesn't support.
+///
+/// It's wrapped in an `io::Error` of the `io::ErrorKind::Unsupported` kind.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+///
+/// use crossterm::{
+///     capabilities::{self, UnsupportedCapability},
+///     style::{Color, SetForegroundColor},
+///     QueueableCommand,
+/// };
+///
+/// capabilities::set_strict_mode(true);
+///
+/// if let Err(e) = stdout().queue(SetForegroundColor(Color::Rgb { r: 0, g: 0, b: 0 })) {
+///     if let Some(UnsupportedCapability(capability)) =
+///         e.get_ref().and_then(|e| e.downcast_ref::<UnsupportedCapability>())
+///     {
+///         println!("{:?} isn't supported", capability);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedCapability(pub Capability);
+
+impl fmt::Display for UnsupportedCapability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The terminal doesn't support the {:?} capability",
+            self.0
+        )
+    }
+}
+
+impl Error for UnsupportedCapability {}
+
+/// Represents the features supported by the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    truecolor: bool,
+    color_count: u16,
+    purge: bool,
+    left_right_margins: bool,
+    sgr_subparameters: bool,
+    hyperlinks: bool,
+    autowrap: bool,
+    scroll_region: bool,
+    insert_delete_lines: bool,
+    bell_volume: bool,
+    conemu_extensions: bool,
+    iterm2_extensions: bool,
+    user_vars: bool,
+    current_directory: bool,
+    back_color_erase: bool,
+    alternate_screen: bool,
+    rectangular_editing: bool,
+    box_drawing: bool,
+}
+
+impl Capabilities {
+    /// Detects the capabilities of the current terminal.
+    ///
+    /// The `CROSSTERM_CAPABILITIES` environment variable isn't taken into account.
+    pub fn detect() -> Capabilities {
+        let term = env::var("TERM").unwrap_or_default();
+        let truecolor = env::var("COLORTERM")
+            .map(|x| x == "truecolor" || x == "24bit")
+            .unwrap_or(false);
+        let quirks = quirks();
+        let program = env::var("TERM_PROGRAM").unwrap_or_default();
+        // The `LC_TERMINAL` is forwarded by the ssh, the `TERM_PROGRAM` isn't
+        let iterm2 = program == "iTerm.app"
+            || env::var("LC_TERMINAL").is_ok_and(|terminal| terminal == "iTerm2");
+        let wezterm = program == "WezTerm" || env::var_os("WEZTERM_PANE").is_some();
+
+        Capabilities {
+            truecolor,
+            color_count: if truecolor || term.contains("256color") {
+                256
+            } else {
+                8
+            },
+            purge: !quirks.contains(Quirks::NO_PURGE),
+            left_right_margins: !quirks.contains(Quirks::NO_LEFT_RIGHT_MARGINS),
+            sgr_subparameters: !quirks.contains(Quirks::NO_SGR_SUBPARAMETERS),
+            hyperlinks: !quirks.contains(Quirks::NO_HYPERLINKS),
+            #[cfg(unix)]
+            autowrap: true,
+            #[cfg(windows)]
+            autowrap: crate::ansi_support::supports_ansi(),
+            #[cfg(unix)]
+            scroll_region: true,
+            #[cfg(windows)]
+            scroll_region: crate::ansi_support::supports_ansi(),
+            #[cfg(unix)]
+            insert_delete_lines: true,
+            #[cfg(windows)]
+            insert_delete_lines: crate::ansi_support::supports_ansi(),
+            // Only the xterm itself is known to implement them
+            bell_volume: env::var_os("XTERM_VERSION").is_some(),
+            conemu_extensions: env::var_os("WT_SESSION").is_some()
+                || env::var("ConEmuANSI").is_ok_and(|ansi| ansi == "ON"),
+            iterm2_extensions: iterm2,
+            user_vars: iterm2 || wezterm,
+            current_directory: iterm2
+                || wezterm
+                || term.starts_with("xterm-kitty")
+                || env::var_os("VTE_VERSION").is_some(),
+            back_color_erase: !quirks.contains(Quirks::NO_BACK_COLOR_ERASE),
+            alternate_screen: !quirks.contains(Quirks::NO_ALTERNATE_SCREEN),
+            // Only the xterm itself is known to implement them
+            rectangular_editing: env::var_os("XTERM_VERSION").is_some(),
+            #[cfg(unix)]
+            box_drawing: is_utf8_locale(),
+            #[cfg(windows)]
+            box_drawing: true,
+        }
+    }
+
+    /// Creates a builder starting from the detected capabilities.
+    pub fn builder() -> CapabilitiesBuilder {
+        CapabilitiesBuilder {
+            capabilities: Capabilities::detect(),
+        }
+    }
+
+    /// Returns whether the given capability is supported.
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Truecolor => self.truecolor,
+            Capability::Purge => self.purge,
+            Capability::LeftRightMargins => self.left_right_margins,
+            Capability::SgrSubparameters => self.sgr_subparameters,
+            Capability::Hyperlinks => self.hyperlinks,
+            Capability::Autowrap => self.autowrap,
+            Capability::ScrollRegion => self.scroll_region,
+            Capability::InsertDeleteLines => self.insert_delete_lines,
+            Capability::BellVolume => self.bell_volume,
+            Capability::ConEmuExtensions => self.conemu_extensions,
+            Capability::ITerm2Extensions => self.iterm2_extensions,
+            Capability::UserVars => self.user_vars,
+            Capability::CurrentDirectory => self.current_directory,
+            Capability::BackColorErase => self.back_color_erase,
+            Capability::AlternateScreen => self.alternate_screen,
+            Capability::RectangularEditing => self.rectangular_editing,
+            Capability::BoxDrawing => self.box_drawing,
+        }
+    }
+
+    /// Returns whether 24-bit colors (`Color::Rgb`) are supported.
+    pub fn truecolor(&self) -> bool {
+        self.truecolor
+    }
+
+    /// Returns the number of colors of the palette (`Color::AnsiValue`).
+    pub fn color_count(&self) -> u16 {
+        self.color_count
+    }
+
+    /// Returns whether the scrollback can be erased (`CSI 3J`).
+    pub fn purge(&self) -> bool {
+        self.purge
+    }
+
+    /// Returns whether the left and right margins (`DECSLRM`) are supported.
+    pub fn left_right_margins(&self) -> bool {
+        self.left_right_margins
+    }
+
+    /// Returns whether colon separated SGR sub-parameters (e.g. `SGR 4:3`) are supported.
+    pub fn sgr_subparameters(&self) -> bool {
+        self.sgr_subparameters
+    }
+
+    /// Returns whether hyperlinks (`OSC 8`) are supported.
+    pub fn hyperlinks(&self) -> bool {
+        self.hyperlinks
+    }
+
+    /// Returns whether the automatic wrapping (`DECAWM`) can be disabled.
+    pub fn autowrap(&self) -> bool {
+        self.autowrap
+    }
+
+    /// Returns whether the top and bottom margins (`DECSTBM`) are supported.
+    pub fn scroll_region(&self) -> bool {
+        self.scroll_region
+    }
+
+    /// Returns whether the lines can be inserted and deleted (`IL`, `DL`).
+    pub fn insert_delete_lines(&self) -> bool {
+        self.insert_delete_lines
+    }
+
+    /// Returns whether the volume of the bells (`DECSWBV`, `DECSMBV`) can be set.
+    pub fn bell_volume(&self) -> bool {
+        self.bell_volume
+    }
+
+    /// Returns whether the ConEmu `OSC 9` extensions (progress, working directory) are
+    /// supported.
+    pub fn conemu_extensions(&self) -> bool {
+        self.conemu_extensions
+    }
+
+    /// Returns whether the iTerm2 `OSC 1337` sequences are supported.
+    pub fn iterm2_extensions(&self) -> bool {
+        self.iterm2_extensions
+    }
+
+    /// Returns whether the user variables (`OSC 1337;SetUserVar`) are supported.
+    pub fn user_vars(&self) -> bool {
+        self.user_vars
+    }
+
+    /// Returns whether reporting the current directory (`OSC 7`) is supported.
+    pub fn current_directory(&self) -> bool {
+        self.current_directory
+    }
+
+    /// Returns whether the clears fill the cells with the current background color (back
+    /// color erase).
+    pub fn back_color_erase(&self) -> bool {
+        self.back_color_erase
+    }
+
+    /// Returns whether the alternate screen is supported, it's emulated otherwise (see the
+    /// [EnterAlternateScreen](../terminal/struct.EnterAlternateScreen.html)).
+    pub fn alternate_screen(&self) -> bool {
+        self.alternate_screen
+    }
+
+    /// Returns whether the rectangular area operations (`DECFRA`, `DECCARA`) are supported.
+    pub fn rectangular_editing(&self) -> bool {
+        self.rectangular_editing
+    }
+
+    /// Returns whether the Unicode box-drawing characters can be displayed, the ASCII ones
+    /// are drawn otherwise (see the [draw](../draw/index.html) module).
+    pub fn box_drawing(&self) -> bool {
+        self.box_drawing
+    }
+}
+
+/// A builder of the [Capabilities](struct.Capabilities.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapabilitiesBuilder {
+    capabilities: Capabilities,
+}
+
+impl CapabilitiesBuilder {
+    /// Sets whether 24-bit colors (`Color::Rgb`) are supported.
+    pub fn truecolor(mut self, truecolor: bool) -> Self {
+        self.capabilities.truecolor = truecolor;
+        self
+    }
+
+    /// Sets the number of colors of the palette (`Color::AnsiValue`).
+    pub fn color_count(mut self, color_count: u16) -> Self {
+        self.capabilities.color_count = color_count;
+        self
+    }
+
+    /// Sets whether the scrollback can be erased (`CSI 3J`).
+    pub fn purge(mut self, purge: bool) -> Self {
+        self.capabilities.purge = purge;
+        self
+    }
+
+    /// Sets whether the left and right margins (`DECSLRM`) are supported.
+    pub fn left_right_margins(mut self, left_right_margins: bool) -> Self {
+        self.capabilities.left_right_margins = left_right_margins;
+        self
+    }
+
+    /// Sets whether colon separated SGR sub-parameters (e.g. `SGR 4:3`) are supported.
+    pub fn sgr_subparameters(mut self, sgr_subparameters: bool) -> Self {
+        self.capabilities.sgr_subparameters = sgr_subparameters;
+        self
+    }
+
+    /// Sets whether hyperlinks (`OSC 8`) are supported.
+    pub fn hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.capabilities.hyperlinks = hyperlinks;
+        self
+    }
+
+    /// Sets whether the automatic wrapping (`DECAWM`) can be disabled.
+    pub fn autowrap(mut self, autowrap: bool) -> Self {
+        self.capabilities.autowrap = autowrap;
+        self
+    }
+
+    /// Sets whether the top and bottom margins (`DECSTBM`) are supported.
+    pub fn scroll_region(mut self, scroll_region: bool) -> Self {
+        self.capabilities.scroll_region = scroll_region;
+        self
+    }
+
+    /// Sets whether the lines can be inserted and deleted (`IL`, `DL`).
+    pub fn insert_delete_lines(mut self, insert_delete_lines: bool) -> Self {
+        self.capabilities.insert_delete_lines = insert_delete_lines;
+        self
+    }
+
+    /// Sets whether the volume of the bells (`DECSWBV`, `DECSMBV`) can be set.
+    pub fn bell_volume(mut self, bell_volume: bool) -> Self {
+        self.capabilities.bell_volume = bell_volume;
+        self
+    }
+
+    /// Sets whether the ConEmu `OSC 9` extensions (progress, working directory) are
+    /// supported.
+    pub fn conemu_extensions(mut self, conemu_extensions: bool) -> Self {
+        self.capabilities.conemu_extensions = conemu_extensions;
+        self
+    }
+
+    /// Sets whether the iTerm2 `OSC 1337` sequences are supported.
+    pub fn iterm2_extensions(mut self, iterm2_extensions: bool) -> Self {
+        self.capabilities.iterm2_extensions = iterm2_extensions;
+        self
+    }
+
+    /// Sets whether the user variables (`OSC 1337;SetUserVar`) are supported.
+    pub fn user_vars(mut self, user_vars: bool) -> Self {
+        self.capabilities.user_vars = user_vars;
+        self
+    }
+
+    /// Sets whether reporting the current directory (`OSC 7`) is supported.
+    pub fn current_directory(mut self, current_directory: bool) -> Self {
+        self.capabilities.current_directory = current_directory;
+        self
+    }
+
+    /// Sets whether the clears fill the cells with the current background color (back
+    /// color erase).
+    pub fn back_color_erase(mut self, back_color_erase: bool) -> Self {
+        self.capabilities.back_color_erase = back_color_erase;
+        self
+    }
+
+    /// Sets whether the alternate screen is supported, `false` turns on its emulation.
+    pub fn alternate_screen(mut self, alternate_screen: bool) -> Self {
+        self.capabilities.alternate_screen = alternate_screen;
+        self
+    }
+
+    /// Sets whether the rectangular area operations (`DECFRA`, `DECCARA`) are supported.
+    pub fn rectangular_editing(mut self, rectangular_editing: bool) -> Self {
+        self.capabilities.rectangular_editing = rectangular_editing;
+        self
+    }
+
+    /// Sets whether the Unicode box-drawing characters can be displayed.
+    pub fn box_drawing(mut self, box_drawing: bool) -> Self {
+        self.capabilities.box_drawing = box_drawing;
+        self
+    }
+
+    /// Builds the capabilities.
+    pub fn build(self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Applies the comma separated `name=value` pairs, invalid pairs are ignored.
+    fn apply_overrides(mut self, overrides: &str) -> Self {
+        for (name, value) in overrides
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(name, value)| (name.trim(), value.trim()))
+        {
+            let flag = match value {
+                "true" | "1" => Some(true),
+                "false" | "0" => Some(false),
+                _ => None,
+            };
+
+            self = match (name, flag) {
+                ("truecolor", Some(flag)) => self.truecolor(flag),
+                ("purge", Some(flag)) => self.purge(flag),
+                ("left_right_margins", Some(flag)) => self.left_right_margins(flag),
+                ("sgr_subparameters", Some(flag)) => self.sgr_subparameters(flag),
+                ("hyperlinks", Some(flag)) => self.hyperlinks(flag),
+                ("autowrap", Some(flag)) => self.autowrap(flag),
+                ("scroll_region", Some(flag)) => self.scroll_region(flag),
+                ("insert_delete_lines", Some(flag)) => self.insert_delete_lines(flag),
+                ("bell_volume", Some(flag)) => self.bell_volume(flag),
+                ("conemu_extensions", Some(flag)) => self.conemu_extensions(flag),
+                ("iterm2_extensions", Some(flag)) => self.iterm2_extensions(flag),
+                ("user_vars", Some(flag)) => self.user_vars(flag),
+                ("current_directory", Some(flag)) => self.current_directory(flag),
+                ("back_color_erase", Some(flag)) => self.back_color_erase(flag),
+                ("alternate_screen", Some(flag)) => self.alternate_screen(flag),
+                ("rectangular_editing", Some(flag)) => self.rectangular_editing(flag),
+                ("box_drawing", Some(flag)) => self.box_drawing(flag),
+                ("color_count", _) => match value.parse() {
+                    Ok(color_count) => self.color_count(color_count),
+                    Err(_) => self,
+                },
+                _ => self,
+            };
+        }
+        self
+    }
+}
+
+/// Returns whether the locale uses the UTF-8 encoding, the first of the `LC_ALL`, the
+/// `LC_CTYPE` and the `LANG` environment variables set decides.
+#[cfg(unix)]
+fn is_utf8_locale() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|locale| !locale.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+/// Returns the capabilities of the current terminal.
+///
+/// These are the capabilities set with the [set_capabilities](fn.set_capabilities.html)
+/// or the detected ones, overridden by the `CROSSTERM_CAPABILITIES` environment variable.
+/// They're detected once, on the first call, and again once the terminal version is set
+/// with the [set_terminal_version](../quirks/fn.set_terminal_version.html).
+pub fn capabilities() -> Capabilities {
+    *CAPABILITIES
+        .lock()
+        .get_or_insert_with(|| with_overrides(Capabilities::detect()))
+}
+
+/// Sets the capabilities of the current terminal, replacing the detected ones.
+///
+/// The `CROSSTERM_CAPABILITIES` environment variable still takes precedence.
+pub fn set_capabilities(capabilities: Capabilities) {
+    let mut set = CAPABILITIES.lock();
+    *set = Some(with_overrides(capabilities));
+    CAPABILITIES_SET.store(true, Ordering::SeqCst);
+}
+
+/// Forgets the detected capabilities, they're detected again on the next use. The ones
+/// set with the `set_capabilities` are kept.
+pub(crate) fn forget_detected_capabilities() {
+    let mut detected = CAPABILITIES.lock();
+    if !CAPABILITIES_SET.load(Ordering::SeqCst) {
+        *detected = None;
+    }
+}
+
+/// Sets the capabilities until the guard is dropped.
+#[cfg(all(test, feature = "terminal"))]
+pub(crate) fn set_test_capabilities(capabilities: Capabilities) -> TestCapabilitiesGuard {
+    let guard = TestCapabilitiesGuard {
+        capabilities: *CAPABILITIES.lock(),
+        set: CAPABILITIES_SET.load(Ordering::SeqCst),
+    };
+    set_capabilities(capabilities);
+    guard
+}
+
+/// Restores the capabilities when dropped, even if the test fails.
+#[cfg(all(test, feature = "terminal"))]
+pub(crate) struct TestCapabilitiesGuard {
+    capabilities: Option<Capabilities>,
+    set: bool,
+}
+
+#[cfg(all(test, feature = "terminal"))]
+impl Drop for TestCapabilitiesGuard {
+    fn drop(&mut self) {
+        let mut capabilities = CAPABILITIES.lock();
+        *capabilities = self.capabilities;
+        CAPABILITIES_SET.store(self.set, Ordering::SeqCst);
+    }
+}
+
+/// Applies the overrides of the `CROSSTERM_CAPABILITIES` environment variable.
+fn with_overrides(capabilities: Capabilities) -> Capabilities {
+    match env::var(OVERRIDE_VAR) {
+        Ok(overrides) => CapabilitiesBuilder { capabilities }
+            .apply_overrides(&overrides)
+            .build(),
+        Err(_) => capabilities,
+    }
+}
+
+/// Enables or disables the strict mode.
+///
+/// Queueing/executing a command which requires a capability the terminal doesn't
+/// support fails with the [UnsupportedCapability](struct.UnsupportedCapability.html)
+/// error in the strict mode, instead of falling back to the supported sequences.
+/// It helps to catch the accidental reliance on the niche features in the tests.
+pub fn set_strict_mode(enabled: bool) {
+    STRICT_MODE.store(enabled, Ordering::SeqCst);
+}
+
+/// Returns whether the strict mode is enabled.
+pub fn is_strict_mode_enabled() -> bool {
+    STRICT_MODE.load(Ordering::SeqCst)
+}
+
+/// Fails if the strict mode is enabled and the command requires an unsupported capability.
+pub(crate) fn check_capability(command: &impl Command) -> Result<()> {
+    if !is_strict_mode_enabled() {
+        return Ok(());
+    }
+
+    match command.required_capability() {
+        Some(capability) if !capabilities().supports(capability) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            UnsupportedCapability(capability),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "style")]
+    use crate::style::{Color, SetForegroundColor};
+    #[cfg(feature = "style")]
+    use crate::QueueableCommand;
+
+    #[cfg(feature = "style")]
+    use std::sync::atomic::Ordering;
+
+    #[cfg(feature = "style")]
+    use crate::quirks::{self, QUIRKS, TERMINAL_VERSION};
+
+    #[cfg(feature = "style")]
+    use super::{
+        is_strict_mode_enabled, set_capabilities, set_strict_mode, Capability,
+        UnsupportedCapability, CAPABILITIES, CAPABILITIES_SET,
+    };
+    use super::{Capabilities, CapabilitiesBuilder};
+
+    /// Restores the capabilities, the terminal version and the strict mode when dropped.
+    #[cfg(feature = "style")]
+    struct Restore {
+        capabilities: Option<Capabilities>,
+        capabilities_set: bool,
+        terminal_version: Option<String>,
+        strict_mode: bool,
+    }
+
+    #[cfg(feature = "style")]
+    impl Restore {
+        fn new() -> Restore {
+            Restore {
+                capabilities: *CAPABILITIES.lock(),
+                capabilities_set: CAPABILITIES_SET.load(Ordering::SeqCst),
+                terminal_version: TERMINAL_VERSION.lock().clone(),
+                strict_mode: is_strict_mode_enabled(),
+            }
+        }
+    }
+
+    #[cfg(feature = "style")]
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            *CAPABILITIES.lock() = self.capabilities;
+            CAPABILITIES_SET.store(self.capabilities_set, Ordering::SeqCst);
+            *TERMINAL_VERSION.lock() = self.terminal_version.take();
+            *QUIRKS.lock() = None;
+            set_strict_mode(self.strict_mode);
+        }
+    }
+
+    fn capabilities() -> Capabilities {
+        Capabilities {
+            truecolor: true,
+            color_count: 256,
+            purge: true,
+            left_right_margins: true,
+            sgr_subparameters: true,
+            hyperlinks: true,
+            autowrap: true,
+            scroll_region: true,
+            insert_delete_lines: true,
+            bell_volume: true,
+            conemu_extensions: true,
+            iterm2_extensions: true,
+            user_vars: true,
+            current_directory: true,
+            back_color_erase: true,
+            alternate_screen: true,
+            rectangular_editing: true,
+            box_drawing: true,
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let capabilities = CapabilitiesBuilder {
+            capabilities: capabilities(),
+        }
+        .truecolor(false)
+        .color_count(16)
+        .purge(false)
+        .build();
+
+        assert!(!capabilities.truecolor());
+        assert_eq!(capabilities.color_count(), 16);
+        assert!(!capabilities.purge());
+        assert!(capabilities.left_right_margins());
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let builder = CapabilitiesBuilder {
+            capabilities: capabilities(),
+        };
+
+        let overridden = builder
+            .apply_overrides("truecolor=false, color_count=88,sgr_subparameters=0")
+            .build();
+        assert!(!overridden.truecolor());
+        assert_eq!(overridden.color_count(), 88);
+        assert!(!overridden.sgr_subparameters());
+        assert!(overridden.purge());
+
+        // Invalid pairs are ignored
+        let ignored = builder
+            .apply_overrides("truecolor=maybe,color_count=many,foo=1,purge")
+            .build();
+        assert_eq!(ignored, capabilities());
+    }
+
+    #[cfg(feature = "style")]
+    #[test]
+    fn test_strict_mode() {
+        // The other tests queue the commands too
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let _restore = Restore::new();
+        let rgb = SetForegroundColor(Color::Rgb { r: 1, g: 2, b: 3 });
+        let mut buffer = Vec::new();
+
+        set_capabilities(Capabilities {
+            truecolor: false,
+            ..capabilities()
+        });
+        set_strict_mode(true);
+
+        let error = buffer.queue(rgb).unwrap_err();
+        assert_eq!(
+            error
+                .get_ref()
+                .and_then(|e| e.downcast_ref::<UnsupportedCapability>()),
+            Some(&UnsupportedCapability(Capability::Truecolor))
+        );
+        assert!(buffer.queue(SetForegroundColor(Color::Red)).is_ok());
+
+        set_strict_mode(false);
+        assert!(buffer.queue(rgb).is_ok());
+    }
+
+    #[cfg(feature = "style")]
+    #[test]
+    fn test_terminal_version_updates_detected_capabilities() {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let _restore = Restore::new();
+        *CAPABILITIES.lock() = None;
+        CAPABILITIES_SET.store(false, Ordering::SeqCst);
+
+        quirks::set_terminal_version("kitty(0.26.5)");
+        assert!(!super::capabilities().left_right_margins());
+        quirks::set_terminal_version("XTerm(372)");
+        assert!(super::capabilities().left_right_margins());
+
+        // Kept once set by the user
+        set_capabilities(Capabilities {
+            left_right_margins: true,
+            ..super::capabilities()
+        });
+        quirks::set_terminal_version("kitty(0.26.5)");
+        assert!(super::capabilities().left_right_margins());
+    }
+}
@@ -0,0 +1,352 @@
+use std::io::{self, Write};
+
+use super::{capabilities, Capabilities};
+
+/// The maximum length of a buffered escape sequence, longer sequences are written as
+/// they are.
+const MAX_SEQUENCE_LEN: usize = 4096;
+
+/// A writer replacing the escape sequences unsupported by the terminal with the
+/// supported ones.
+///
+/// It allows to target the full feature set unconditionally:
+///
+/// * 24-bit colors are replaced with the closest 256 colors palette entry, unless
+///   [truecolor](struct.Capabilities.html#method.truecolor) is supported,
+/// * styled underlines (e.g. `SGR 4:3`, curly underline) are replaced with the plain
+///   underline and the other colon separated SGR sub-parameters are removed, unless
+///   [sgr_subparameters](struct.Capabilities.html#method.sgr_subparameters) are supported,
+/// * hyperlinks are replaced with the plain text, unless
+///   [hyperlinks](struct.Capabilities.html#method.hyperlinks) are supported.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{
+///     capabilities::FallbackWriter,
+///     execute,
+///     style::{Color, Print, SetForegroundColor},
+///     Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     let mut stdout = FallbackWriter::new(stdout());
+///
+///     execute!(
+///         stdout,
+///         SetForegroundColor(Color::Rgb { r: 255, g: 128, b: 0 }),
+///         Print("Orange text")
+///     )
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FallbackWriter<W: Write> {
+    inner: W,
+    capabilities: Capabilities,
+    /// The escape sequence split between the writes.
+    sequence: Vec<u8>,
+}
+
+impl<W: Write> FallbackWriter<W> {
+    /// Creates a new `FallbackWriter` for the capabilities of the current terminal.
+    pub fn new(inner: W) -> FallbackWriter<W> {
+        FallbackWriter::with_capabilities(inner, capabilities())
+    }
+
+    /// Creates a new `FallbackWriter` for the given capabilities.
+    pub fn with_capabilities(inner: W, capabilities: Capabilities) -> FallbackWriter<W> {
+        FallbackWriter {
+            inner,
+            capabilities,
+            sequence: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps this `FallbackWriter`, returning the underlying writer.
+    ///
+    /// An incomplete escape sequence is discarded.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn write_sequence(&mut self) -> io::Result<()> {
+        let sequence = std::mem::take(&mut self.sequence);
+
+        match sequence.get(1) {
+            Some(b'[') if sequence.last() == Some(&b'm') => {
+                let params = &sequence[2..sequence.len() - 1];
+                match std::str::from_utf8(params)
+                    .ok()
+                    .and_then(|params| self.rewrite_sgr(params))
+                {
+                    Some(params) if params.is_empty() => Ok(()),
+                    Some(params) => write!(self.inner, "\x1B[{}m", params),
+                    None => self.inner.write_all(&sequence),
+                }
+            }
+            Some(b']') if !self.capabilities.hyperlinks() && sequence[2..].starts_with(b"8;") => {
+                Ok(())
+            }
+            _ => self.inner.write_all(&sequence),
+        }
+    }
+
+    /// Rewrites the SGR parameters, returns `None` if there's nothing to rewrite.
+    fn rewrite_sgr(&self, params: &str) -> Option<String> {
+        let truecolor = self.capabilities.truecolor();
+        let subparameters = self.capabilities.sgr_subparameters();
+
+        if truecolor && subparameters {
+            return None;
+        }
+
+        let params: Vec<&str> = params.split(';').collect();
+        let mut rewritten: Vec<String> = Vec::with_capacity(params.len());
+        let mut changed = false;
+        let mut idx = 0;
+
+        while idx < params.len() {
+            let param = params[idx];
+
+            if param.contains(':') {
+                let subparams: Vec<&str> = param.split(':').collect();
+
+                match (subparams[0], subparams.get(1)) {
+                    ("38" | "48", Some(&"2")) => {
+                        // The color space identifier is optional
+                        let rgb = &subparams[subparams.len().saturating_sub(3).max(2)..];
+                        rewritten.push(self.rgb_color(subparams[0], rgb));
+                        changed = true;
+                    }
+                    ("38" | "48", Some(&"5")) if !subparameters => {
+                        rewritten.push(subparams.join(";"));
+                        changed = true;
+                    }
+                    ("4", Some(style)) if !subparameters => {
+                        rewritten.push(if *style == "0" { "24" } else { "4" }.to_string());
+                        changed = true;
+                    }
+                    _ if !subparameters => changed = true,
+                    _ => rewritten.push(param.to_string()),
+                }
+                idx += 1;
+                continue;
+            }
+
+            match (param, params.get(idx + 1)) {
+                ("38" | "48" | "58", Some(&"5")) => {
+                    let end = (idx + 3).min(params.len());
+                    if param == "58" && !subparameters {
+                        changed = true;
+                    } else {
+                        rewritten.extend(params[idx..end].iter().map(|x| x.to_string()));
+                    }
+                    idx = end;
+                }
+                ("38" | "48" | "58", Some(&"2")) => {
+                    let end = (idx + 5).min(params.len());
+                    if param == "58" && !subparameters {
+                        changed = true;
+                    } else if !truecolor {
+                        rewritten.push(self.rgb_color(param, &params[idx + 2..end]));
+                        changed = true;
+                    } else {
+                        rewritten.extend(params[idx..end].iter().map(|x| x.to_string()));
+                    }
+                    idx = end;
+                }
+                _ => {
+                    rewritten.push(param.to_string());
+                    idx += 1;
+                }
+            }
+        }
+
+        if changed {
+            Some(rewritten.join(";"))
+        } else {
+            None
+        }
+    }
+
+    /// Formats the 24-bit color for the given SGR parameter (`38`, `48`).
+    fn rgb_color(&self, param: &str, rgb: &[&str]) -> String {
+        let component = |idx: usize| rgb.get(idx).and_then(|x| x.parse::<u8>().ok()).unwrap_or(0);
+        let (r, g, b) = (component(0), component(1), component(2));
+
+        if self.capabilities.truecolor() {
+            format!("{};2;{};{};{}", param, r, g, b)
+        } else {
+            format!("{};5;{}", param, rgb_to_ansi(r, g, b))
+        }
+    }
+}
+
+impl<W: Write> Write for FallbackWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Start of the text to write as it is
+        let mut start = 0;
+
+        for (idx, &byte) in buf.iter().enumerate() {
+            if self.sequence.is_empty() {
+                if byte == b'\x1B' {
+                    self.inner.write_all(&buf[start..idx])?;
+                    self.sequence.push(byte);
+                }
+                continue;
+            }
+
+            self.sequence.push(byte);
+            if is_sequence_complete(&self.sequence) {
+                self.write_sequence()?;
+                start = idx + 1;
+            }
+        }
+
+        if self.sequence.is_empty() {
+            self.inner.write_all(&buf[start..])?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Returns whether the escape sequence is complete (or too long to be buffered).
+pub(crate) fn is_sequence_complete(sequence: &[u8]) -> bool {
+    if sequence.len() >= MAX_SEQUENCE_LEN {
+        return true;
+    }
+
+    let last = sequence[sequence.len() - 1];
+    match sequence.get(1) {
+        None => false,
+        // CSI
+        Some(b'[') => sequence.len() > 2 && (0x40..=0x7E).contains(&last),
+        // OSC, terminated by BEL or ST
+        Some(b']') => last == b'\x07' || sequence[2..].ends_with(b"\x1B\\"),
+        // DCS, SOS, PM, APC, terminated by ST
+        Some(b'P' | b'X' | b'^' | b'_') => sequence[2..].ends_with(b"\x1B\\"),
+        // Intermediate bytes are followed by the final byte
+        Some(_) => !(0x20..=0x2F).contains(&last),
+    }
+}
+
+/// Returns the closest 256 colors palette entry for the 24-bit color.
+pub(crate) fn rgb_to_ansi(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            249..=255 => 231,
+            // The ramp ends at 238, the lighter greys map to its last entry
+            _ => 232 + ((r - 8) / 10).min(23),
+        };
+    }
+
+    let cube = |x: u8| match x {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (x - 35) / 40,
+    };
+    16 + 36 * cube(r) + 6 * cube(g) + cube(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::capabilities::Capabilities;
+
+    use super::{rgb_to_ansi, FallbackWriter};
+
+    fn render(capabilities: Capabilities, chunks: &[&str]) -> String {
+        let mut writer = FallbackWriter::with_capabilities(Vec::new(), capabilities);
+        for chunk in chunks {
+            writer.write_all(chunk.as_bytes()).unwrap();
+        }
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    fn limited() -> Capabilities {
+        Capabilities::builder()
+            .truecolor(false)
+            .sgr_subparameters(false)
+            .hyperlinks(false)
+            .build()
+    }
+
+    fn full() -> Capabilities {
+        Capabilities::builder()
+            .truecolor(true)
+            .sgr_subparameters(true)
+            .hyperlinks(true)
+            .build()
+    }
+
+    #[test]
+    fn test_rgb_to_ansi() {
+        assert_eq!(rgb_to_ansi(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi(255, 255, 255), 231);
+        assert_eq!(rgb_to_ansi(255, 0, 0), 196);
+        assert_eq!(rgb_to_ansi(0, 135, 255), 33);
+        assert_eq!(rgb_to_ansi(128, 128, 128), 244);
+        assert_eq!(rgb_to_ansi(248, 248, 248), 255);
+    }
+
+    #[test]
+    fn test_rgb_is_replaced_with_ansi() {
+        let text = "a\x1B[1;38;2;255;0;0;48:2::0:0:0mb";
+
+        assert_eq!(render(limited(), &[text]), "a\x1B[1;38;5;196;48;5;16mb");
+        assert_eq!(render(full(), &[text]), text);
+    }
+
+    #[test]
+    fn test_styled_underline_is_replaced_with_plain_one() {
+        assert_eq!(
+            render(limited(), &["\x1B[4:3m\x1B[4:0m"]),
+            "\x1B[4m\x1B[24m"
+        );
+        assert_eq!(render(limited(), &["\x1B[58:5:1m"]), "");
+        assert_eq!(render(limited(), &["\x1B[38:5:1m"]), "\x1B[38;5;1m");
+        assert_eq!(render(limited(), &["\x1B[58;5;1;1m"]), "\x1B[1m");
+    }
+
+    #[test]
+    fn test_hyperlinks_are_replaced_with_text() {
+        let text = "\x1B]8;;https://example.com\x1B\\link\x1B]8;;\x07";
+
+        assert_eq!(render(limited(), &[text]), "link");
+        assert_eq!(render(full(), &[text]), text);
+    }
+
+    #[test]
+    fn test_split_sequences() {
+        assert_eq!(
+            render(
+                limited(),
+                &["a\x1B", "[38;2;2", "55;0;0mb\x1B]8", ";;x\x1B", "\\c"]
+            ),
+            "a\x1B[38;5;196mbc"
+        );
+        assert_eq!(
+            render(limited(), &["\x1B[2J\x1B(B\x1B7"]),
+            "\x1B[2J\x1B(B\x1B7"
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use std::cell::{Cell, RefCell};
 use std::fmt;
 use std::io::{self, Write};
 
+use super::capabilities::{check_capability, Capability};
 use super::error::Result;
 
 /// An interface for a command that performs an action on the terminal.
@@ -34,6 +36,21 @@ pub trait Command {
     fn is_ansi_code_supported(&self) -> bool {
         super::ansi_support::supports_ansi()
     }
+
+    /// Returns the capability the terminal must support to execute this command, `None` if
+    /// it's supported by all the terminals.
+    ///
+    /// It's checked in the [strict mode](capabilities/fn.set_strict_mode.html).
+    fn required_capability(&self) -> Option<Capability> {
+        None
+    }
+
+    /// Updates the terminal state tracked by crossterm, e.g. the pushed keyboard
+    /// enhancement flags.
+    ///
+    /// It's called once the command is queued or executed, the commands which are only
+    /// formatted (e.g. displayed or written to a string) don't change the tracked state.
+    fn update_state(&self) {}
 }
 
 impl<T: Command + ?Sized> Command for &T {
@@ -52,6 +69,84 @@ impl<T: Command + ?Sized> Command for &T {
     fn is_ansi_code_supported(&self) -> bool {
         T::is_ansi_code_supported(self)
     }
+
+    #[inline]
+    fn required_capability(&self) -> Option<Capability> {
+        T::required_capability(self)
+    }
+
+    #[inline]
+    fn update_state(&self) {
+        T::update_state(self)
+    }
+}
+
+thread_local! {
+    /// The dry run is enabled on the current thread.
+    static DRY_RUN: Cell<bool> = const { Cell::new(false) };
+    /// The commands recorded by the dry run on the current thread.
+    static RECORDED_COMMANDS: RefCell<Vec<RecordedCommand>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A command queued or executed in the [dry run](fn.set_dry_run.html).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordedCommand {
+    /// The type name of the command, e.g. `crossterm::cursor::MoveTo`.
+    pub name: &'static str,
+    /// The ANSI representation of the command.
+    pub ansi: String,
+}
+
+/// Enables or disables the dry run on the current thread.
+///
+/// The commands queued or executed with the [queue](trait.QueueableCommand.html) and the
+/// [execute](trait.ExecutableCommand.html) (and the macros) are recorded instead of
+/// being written, the writers aren't flushed either. The recorded commands are retrieved
+/// with the [take_recorded_commands](fn.take_recorded_commands.html).
+///
+/// The dry run is per thread, the tests running in parallel don't record the commands of
+/// each other. The commands keep updating the crate state (e.g. the tracked cursor
+/// position), as if they were written.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::stdout;
+///
+/// use crossterm::{cursor::MoveTo, execute, set_dry_run, take_recorded_commands};
+///
+/// set_dry_run(true);
+/// execute!(stdout(), MoveTo(4, 2)).unwrap();
+/// set_dry_run(false);
+///
+/// let commands = take_recorded_commands();
+/// assert_eq!(commands[0].name, "crossterm::cursor::MoveTo");
+/// assert_eq!(commands[0].ansi, "\x1B[3;5H");
+/// ```
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.with(|dry_run| dry_run.set(enabled));
+}
+
+/// Returns whether the dry run is enabled on the current thread.
+pub fn is_dry_run() -> bool {
+    DRY_RUN.with(Cell::get)
+}
+
+/// Returns the commands recorded by the dry run on the current thread and forgets them.
+pub fn take_recorded_commands() -> Vec<RecordedCommand> {
+    RECORDED_COMMANDS.with(|commands| commands.take())
+}
+
+/// Records the command queued in the dry run.
+fn record_command<C: Command>(command: C) -> Result<()> {
+    let mut ansi = String::new();
+    command
+        .write_ansi(&mut ansi)
+        .map_err(|_| io::Error::other("The command couldn't be formatted."))?;
+
+    let name = std::any::type_name::<C>().trim_start_matches('&');
+    RECORDED_COMMANDS.with(|commands| commands.borrow_mut().push(RecordedCommand { name, ansi }));
+    Ok(())
 }
 
 /// An interface for types that can queue commands for further execution.
@@ -115,22 +210,29 @@ impl<T: Write + ?Sized> QueueableCommand for T {
     ///
     /// * In the case of UNIX and Windows 10, ANSI codes are written to the given 'writer'.
     /// * In case of Windows versions lower than 10, a direct WinAPI call will be made.
-    ///     The reason for this is that Windows versions lower than 10 do not support ANSI codes,
-    ///     and can therefore not be written to the given `writer`.
-    ///     Therefore, there is no difference between [execute](./trait.ExecutableCommand.html)
-    ///     and [queue](./trait.QueueableCommand.html) for those old Windows versions.
+    ///   The reason for this is that Windows versions lower than 10 do not support ANSI codes,
+    ///   and can therefore not be written to the given `writer`.
+    ///   Therefore, there is no difference between [execute](./trait.ExecutableCommand.html)
+    ///   and [queue](./trait.QueueableCommand.html) for those old Windows versions.
     fn queue(&mut self, command: impl Command) -> Result<&mut Self> {
+        check_capability(&command)?;
+
+        if is_dry_run() {
+            tracked(|| record_command(&command).map(|()| command.update_state()))?;
+            return Ok(self);
+        }
+
         #[cfg(windows)]
         if !command.is_ansi_code_supported() {
             // There may be queued commands in this writer, but `execute_winapi` will execute the
             // command immediately. To prevent commands being executed out of order we flush the
             // writer now.
             self.flush()?;
-            command.execute_winapi()?;
+            tracked(|| command.execute_winapi().map(|()| command.update_state()))?;
             return Ok(self);
         }
 
-        write_command_ansi(self, command)?;
+        tracked(|| write_command_ansi(self, &command).map(|()| command.update_state()))?;
         Ok(self)
     }
 }
@@ -173,17 +275,29 @@ impl<T: Write + ?Sized> ExecutableCommand for T {
     ///
     /// * In the case of UNIX and Windows 10, ANSI codes are written to the given 'writer'.
     /// * In case of Windows versions lower than 10, a direct WinAPI call will be made.
-    ///     The reason for this is that Windows versions lower than 10 do not support ANSI codes,
-    ///     and can therefore not be written to the given `writer`.
-    ///     Therefore, there is no difference between [execute](./trait.ExecutableCommand.html)
-    ///     and [queue](./trait.QueueableCommand.html) for those old Windows versions.
+    ///   The reason for this is that Windows versions lower than 10 do not support ANSI codes,
+    ///   and can therefore not be written to the given `writer`.
+    ///   Therefore, there is no difference between [execute](./trait.ExecutableCommand.html)
+    ///   and [queue](./trait.QueueableCommand.html) for those old Windows versions.
     fn execute(&mut self, command: impl Command) -> Result<&mut Self> {
         self.queue(command)?;
-        self.flush()?;
+        if !is_dry_run() {
+            self.flush()?;
+        }
         Ok(self)
     }
 }
 
+/// Runs the function writing the queued command and updating the state, the tracked
+/// cursor position is updated by it.
+fn tracked<T>(f: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "terminal")]
+    return crate::cursor::tracking::tracked(f);
+
+    #[cfg(not(feature = "terminal"))]
+    f()
+}
+
 /// Writes the ANSI representation of a command to the given writer.
 fn write_command_ansi<C: Command>(
     io: &mut (impl io::Write + ?Sized),
@@ -220,6 +334,7 @@ fn write_command_ansi<C: Command>(
 }
 
 /// Executes the ANSI representation of a command, using the given `fmt::Write`.
+#[cfg_attr(not(feature = "terminal"), allow(dead_code))]
 pub(crate) fn execute_fmt(f: &mut impl fmt::Write, command: impl Command) -> fmt::Result {
     #[cfg(windows)]
     if !command.is_ansi_code_supported() {
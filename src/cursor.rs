@@ -43,14 +43,127 @@
 //! For manual execution control check out [crossterm::queue](../macro.queue.html).
 
 use std::fmt;
-
-#[cfg(windows)]
+#[cfg(feature = "style")]
+use std::io;
+
+use crate::terminal::snapshot::{self, DecModes};
+use crate::terminal::{Index, Position, Rect};
+#[cfg(feature = "style")]
+use crate::QueueableCommand;
+#[cfg(any(windows, feature = "events", feature = "style"))]
 use crate::Result;
 use crate::{csi, impl_display, Command};
 
-pub use sys::position;
-
 pub(crate) mod sys;
+pub(crate) mod tracking;
+
+/// Returns the cursor position (column, row).
+///
+/// The top left cell is represented `0,0`. The tuple converts to a
+/// [Position](../terminal/struct.Position.html) with the `Position::from`.
+///
+/// The position is queried from the terminal, it also resynchronizes the
+/// [tracked position](fn.tracked_position.html).
+///
+/// On UNIX, it requires the `events` feature to read the answer of the terminal.
+#[cfg(any(windows, feature = "events"))]
+pub fn position() -> Result<(u16, u16)> {
+    let (column, row) = sys::position()?;
+    tracking::tracked(|| tracking::set_position(column, row));
+    Ok((column, row))
+}
+
+/// Enables or disables the tracking of the cursor position by the crate, it's disabled by
+/// default.
+///
+/// See the [tracked_position](fn.tracked_position.html). The tracked position is forgotten
+/// when the tracking is disabled.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+///
+/// use crossterm::{
+///     cursor::{self, MoveTo},
+///     execute,
+/// };
+///
+/// cursor::set_position_tracking(true);
+///
+/// execute!(stdout(), MoveTo(4, 2))?;
+/// assert_eq!(cursor::tracked_position(), Some((4, 2)));
+/// # Ok::<(), crossterm::ErrorKind>(())
+/// ```
+pub fn set_position_tracking(enabled: bool) {
+    tracking::set_enabled(enabled);
+}
+
+/// Returns whether the cursor position is tracked by the crate.
+pub fn is_position_tracking_enabled() -> bool {
+    tracking::is_enabled()
+}
+
+/// Returns the cursor position (column, row) tracked by the crate, `None` if it's unknown.
+///
+/// The tracked position is updated by the commands as they're written, it spares the
+/// renderers querying the terminal with the [position](fn.position.html). It's tracked
+/// only once enabled with the [set_position_tracking](fn.set_position_tracking.html).
+///
+/// # Notes
+///
+/// * The position is kept within the screen like the terminal does, e.g. the
+///   [MoveRight](struct.MoveRight.html) stops at the last column.
+/// * The position is known once the cursor was moved to an absolute position (e.g.
+///   [MoveTo](struct.MoveTo.html)) or queried with the [position](fn.position.html).
+/// * The printed text advances the position by its width (see the
+///   [text](../text/index.html) module), the position becomes unknown when the text
+///   reaches the right edge of the screen or contains escape sequences.
+/// * The commands moving the cursor by an unknown distance (e.g.
+///   [RestorePosition](struct.RestorePosition.html)) and the output written without the
+///   commands make the position unknown or wrong, queue a `MoveTo` to resynchronize it.
+/// * The tracked position is shared by all the writers. It's updated by the commands
+///   queued or executed only, not by the ones displayed or written to a string (e.g. with
+///   the [render](../macro.render.html)) as they may never reach the terminal.
+pub fn tracked_position() -> Option<(u16, u16)> {
+    tracking::position()
+}
+
+/// Erases the last `count` graphemes printed on the current row and moves the cursor
+/// back to the first erased column, e.g. on the backspace in a prompt.
+///
+/// Unlike writing `"\x08 \x08"`, the wide characters (`你`) and the characters followed
+/// by the combining characters (`é`) are erased entirely. The widths of the graphemes
+/// are recorded by the [tracked position](fn.tracked_position.html) as the text is
+/// printed with the [Print](../style/struct.Print.html) command. The number of erased
+/// columns is returned.
+///
+/// # Notes
+///
+/// * The position must be tracked (see
+///   [set_position_tracking](fn.set_position_tracking.html)), nothing is erased otherwise.
+/// * Only the graphemes printed since the cursor was last moved otherwise (or since the
+///   tracked position became known) can be erased, fewer graphemes are erased if fewer
+///   are known. A grapheme split by the line wrap can't be erased.
+/// * The text right of the cursor is overwritten with the spaces, not shifted.
+/// * The sequences are written to the writer, they aren't flushed.
+#[cfg(feature = "style")]
+pub fn erase_graphemes_back(writer: &mut impl io::Write, count: usize) -> Result<u16> {
+    tracking::tracked(|| {
+        let columns = tracking::pop_graphemes(count);
+
+        if columns != 0 {
+            tracking::keeping_graphemes(|| -> Result<()> {
+                writer.queue(MoveLeft(columns))?;
+                write!(writer, "{:1$}", "", usize::from(columns))?;
+                tracking::move_by(i32::from(columns), 0);
+                writer.queue(MoveLeft(columns))?;
+                Ok(())
+            })?;
+        }
+        Ok(columns)
+    })
+}
 
 /// A command that moves the terminal cursor to the given position (column, row).
 ///
@@ -63,15 +176,270 @@ pub struct MoveTo(pub u16, pub u16);
 
 impl Command for MoveTo {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::set_position(self.0, self.1);
         write!(f, csi!("{};{}H"), self.1 + 1, self.0 + 1)
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        tracking::set_position(self.0, self.1);
         sys::move_to(self.0, self.1)
     }
 }
 
+impl From<Position> for MoveTo {
+    fn from(position: Position) -> MoveTo {
+        MoveTo(position.x, position.y)
+    }
+}
+
+/// A command that moves the terminal cursor to the given position (column, row) using
+/// the shortest sequence.
+///
+/// The cursor position is tracked by the cursor commands. If it's known, the cheaper of
+/// the relative moves and the absolute [MoveTo](./struct.MoveTo.html) is emitted.
+/// Nothing is emitted if the cursor is already at the given position.
+///
+/// # Notes
+///
+/// * Top left cell is represented as `0,0`.
+/// * The position must be tracked (see
+///   [set_position_tracking](fn.set_position_tracking.html)), the absolute move is
+///   emitted otherwise.
+/// * The tracked position (see [tracked_position](fn.tracked_position.html)) is
+///   invalidated by the commands moving the cursor by an unknown distance
+///   (`RestorePosition`, printing escape sequences, ...), the absolute move is emitted
+///   in this case.
+/// * Output written without commands isn't tracked, queue a `MoveTo` to resynchronize
+///   the tracked position after it.
+/// * The tracked position is shared by all the writers.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmartMoveTo(pub u16, pub u16);
+
+impl Command for SmartMoveTo {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let (column, row) = match tracking::position() {
+            Some(position) => position,
+            None => return MoveTo(self.0, self.1).write_ansi(f),
+        };
+
+        let absolute_len = 4 + digits(self.1 + 1) + digits(self.0 + 1);
+        let relative_len = match self.0 {
+            _ if self.0 == column => 0,
+            0 => 1,
+            _ => 3 + digits(self.0.abs_diff(column)),
+        } + match self.1 {
+            _ if self.1 == row => 0,
+            _ => 3 + digits(self.1.abs_diff(row)),
+        };
+
+        if relative_len >= absolute_len {
+            return MoveTo(self.0, self.1).write_ansi(f);
+        }
+
+        tracking::set_position(self.0, self.1);
+
+        if self.0 != column {
+            match self.0 {
+                0 => f.write_str("\r")?,
+                _ if self.0 > column => write!(f, csi!("{}C"), self.0 - column)?,
+                _ => write!(f, csi!("{}D"), column - self.0)?,
+            }
+        }
+
+        if self.1 > row {
+            write!(f, csi!("{}B"), self.1 - row)?;
+        } else if self.1 < row {
+            write!(f, csi!("{}A"), row - self.1)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        MoveTo(self.0, self.1).execute_winapi()
+    }
+}
+
+/// Returns the number of decimal digits of the given number.
+fn digits(n: u16) -> usize {
+    match n {
+        0..=9 => 1,
+        10..=99 => 2,
+        100..=999 => 3,
+        1000..=9999 => 4,
+        _ => 5,
+    }
+}
+
+/// A command that moves the terminal cursor to the given position (column, row), clamped
+/// to the given bounds.
+///
+/// It never addresses a cell outside of the bounds, e.g. a viewport of a widget.
+///
+/// # Notes
+///
+/// * Top left cell is represented as `0,0`.
+/// * Nothing is emitted if the bounds are empty.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveToClamped(pub u16, pub u16, pub Rect);
+
+impl Command for MoveToClamped {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self.2.clamp(self.0, self.1) {
+            Some((column, row)) => MoveTo(column, row).write_ansi(f),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        match self.2.clamp(self.0, self.1) {
+            Some((column, row)) => MoveTo(column, row).execute_winapi(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the tracked cursor position moved by the given number of columns and rows and
+/// clamped to the bounds.
+fn clamped_target(columns: i32, rows: i32, bounds: Rect) -> Option<SmartMoveTo> {
+    let (column, row) = tracking::offset(columns, rows)?;
+    let (column, row) = bounds.clamp(column, row)?;
+    Some(SmartMoveTo(column, row))
+}
+
+/// A command that moves the terminal cursor a given number of rows up,
+/// stopping at the edge of the given bounds.
+///
+/// It never addresses a cell outside of the bounds, e.g. a viewport of a widget.
+///
+/// # Notes
+///
+/// * The cursor is moved into the bounds if it's outside of them.
+/// * The cursor position is tracked by the cursor commands, nothing is emitted if it's
+///   unknown (e.g. after a `Print`) or if the bounds are empty. Queue a
+///   [MoveToClamped](./struct.MoveToClamped.html) first.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveUpClamped(pub u16, pub Rect);
+
+impl Command for MoveUpClamped {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match clamped_target(0, -i32::from(self.0), self.1) {
+            Some(target) => target.write_ansi(f),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        match clamped_target(0, -i32::from(self.0), self.1) {
+            Some(target) => target.execute_winapi(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A command that moves the terminal cursor a given number of columns to the right,
+/// stopping at the edge of the given bounds.
+///
+/// It never addresses a cell outside of the bounds, e.g. a viewport of a widget.
+///
+/// # Notes
+///
+/// * The cursor is moved into the bounds if it's outside of them.
+/// * The cursor position is tracked by the cursor commands, nothing is emitted if it's
+///   unknown (e.g. after a `Print`) or if the bounds are empty. Queue a
+///   [MoveToClamped](./struct.MoveToClamped.html) first.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveRightClamped(pub u16, pub Rect);
+
+impl Command for MoveRightClamped {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match clamped_target(i32::from(self.0), 0, self.1) {
+            Some(target) => target.write_ansi(f),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        match clamped_target(i32::from(self.0), 0, self.1) {
+            Some(target) => target.execute_winapi(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A command that moves the terminal cursor a given number of rows down,
+/// stopping at the edge of the given bounds.
+///
+/// It never addresses a cell outside of the bounds, e.g. a viewport of a widget.
+///
+/// # Notes
+///
+/// * The cursor is moved into the bounds if it's outside of them.
+/// * The cursor position is tracked by the cursor commands, nothing is emitted if it's
+///   unknown (e.g. after a `Print`) or if the bounds are empty. Queue a
+///   [MoveToClamped](./struct.MoveToClamped.html) first.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveDownClamped(pub u16, pub Rect);
+
+impl Command for MoveDownClamped {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match clamped_target(0, i32::from(self.0), self.1) {
+            Some(target) => target.write_ansi(f),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        match clamped_target(0, i32::from(self.0), self.1) {
+            Some(target) => target.execute_winapi(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A command that moves the terminal cursor a given number of columns to the left,
+/// stopping at the edge of the given bounds.
+///
+/// It never addresses a cell outside of the bounds, e.g. a viewport of a widget.
+///
+/// # Notes
+///
+/// * The cursor is moved into the bounds if it's outside of them.
+/// * The cursor position is tracked by the cursor commands, nothing is emitted if it's
+///   unknown (e.g. after a `Print`) or if the bounds are empty. Queue a
+///   [MoveToClamped](./struct.MoveToClamped.html) first.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveLeftClamped(pub u16, pub Rect);
+
+impl Command for MoveLeftClamped {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match clamped_target(-i32::from(self.0), 0, self.1) {
+            Some(target) => target.write_ansi(f),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        match clamped_target(-i32::from(self.0), 0, self.1) {
+            Some(target) => target.execute_winapi(),
+            None => Ok(()),
+        }
+    }
+}
+
 /// A command that moves the terminal cursor down the given number of lines,
 /// and moves it to the first column.
 ///
@@ -84,6 +452,8 @@ pub struct MoveToNextLine(pub u16);
 impl Command for MoveToNextLine {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         if self.0 != 0 {
+            tracking::move_by(0, i32::from(self.0));
+            tracking::move_to_column(0);
             write!(f, csi!("{}E"), self.0)?;
         }
         Ok(())
@@ -92,6 +462,8 @@ impl Command for MoveToNextLine {
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
         if self.0 != 0 {
+            tracking::move_by(0, i32::from(self.0));
+            tracking::move_to_column(0);
             sys::move_to_next_line(self.0)?;
         }
         Ok(())
@@ -110,6 +482,8 @@ pub struct MoveToPreviousLine(pub u16);
 impl Command for MoveToPreviousLine {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         if self.0 != 0 {
+            tracking::move_by(0, -i32::from(self.0));
+            tracking::move_to_column(0);
             write!(f, csi!("{}F"), self.0)?;
         }
         Ok(())
@@ -118,6 +492,8 @@ impl Command for MoveToPreviousLine {
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
         if self.0 != 0 {
+            tracking::move_by(0, -i32::from(self.0));
+            tracking::move_to_column(0);
             sys::move_to_previous_line(self.0)?;
         }
         Ok(())
@@ -128,12 +504,16 @@ impl Command for MoveToPreviousLine {
 ///
 /// # Notes
 ///
-/// Commands must be executed/queued for execution otherwise they do nothing.
+/// * The column is written to the escape sequence as is, so it's 1-based (`0` doesn't
+///   move the cursor), but it's 0-based on the legacy Windows console. Use the
+///   [MoveToColumnAt](struct.MoveToColumnAt.html) to state the indexing explicitly.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MoveToColumn(pub u16);
 
 impl Command for MoveToColumn {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::invalidate();
         if self.0 != 0 {
             write!(f, csi!("{}G"), self.0)?;
         }
@@ -142,6 +522,7 @@ impl Command for MoveToColumn {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        tracking::invalidate();
         sys::move_to_column(self.0)
     }
 }
@@ -150,12 +531,16 @@ impl Command for MoveToColumn {
 ///
 /// # Notes
 ///
-/// Commands must be executed/queued for execution otherwise they do nothing.
+/// * The row is written to the escape sequence as is, so it's 1-based (`0` doesn't
+///   move the cursor), but it's 0-based on the legacy Windows console. Use the
+///   [MoveToRowAt](struct.MoveToRowAt.html) to state the indexing explicitly.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MoveToRow(pub u16);
 
 impl Command for MoveToRow {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::invalidate();
         if self.0 != 0 {
             write!(f, csi!("{}d"), self.0)?
         }
@@ -164,10 +549,55 @@ impl Command for MoveToRow {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        tracking::invalidate();
         sys::move_to_row(self.0)
     }
 }
 
+/// A command that moves the terminal cursor to the given column on the current row, the
+/// column being [0-based or 1-based](../terminal/enum.Index.html) explicitly.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveToColumnAt(pub Index);
+
+impl Command for MoveToColumnAt {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::move_to_column(self.0.zero_based());
+        write!(f, csi!("{}G"), self.0.one_based())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        tracking::move_to_column(self.0.zero_based());
+        sys::move_to_column(self.0.zero_based())
+    }
+}
+
+/// A command that moves the terminal cursor to the given row on the current column, the
+/// row being [0-based or 1-based](../terminal/enum.Index.html) explicitly.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveToRowAt(pub Index);
+
+impl Command for MoveToRowAt {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::move_to_row(self.0.zero_based());
+        write!(f, csi!("{}d"), self.0.one_based())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        tracking::move_to_row(self.0.zero_based());
+        sys::move_to_row(self.0.zero_based())
+    }
+}
+
 /// A command that moves the terminal cursor a given number of rows up.
 ///
 /// # Notes
@@ -178,6 +608,7 @@ pub struct MoveUp(pub u16);
 
 impl Command for MoveUp {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::move_by(0, -i32::from(self.0));
         if self.0 != 0 {
             write!(f, csi!("{}A"), self.0)?;
         }
@@ -186,6 +617,7 @@ impl Command for MoveUp {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        tracking::move_by(0, -i32::from(self.0));
         sys::move_up(self.0)
     }
 }
@@ -200,6 +632,7 @@ pub struct MoveRight(pub u16);
 
 impl Command for MoveRight {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::move_by(i32::from(self.0), 0);
         if self.0 != 0 {
             write!(f, csi!("{}C"), self.0)?;
         }
@@ -208,6 +641,7 @@ impl Command for MoveRight {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        tracking::move_by(i32::from(self.0), 0);
         sys::move_right(self.0)
     }
 }
@@ -222,6 +656,7 @@ pub struct MoveDown(pub u16);
 
 impl Command for MoveDown {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::move_by(0, i32::from(self.0));
         if self.0 != 0 {
             write!(f, csi!("{}B"), self.0)?;
         }
@@ -230,6 +665,7 @@ impl Command for MoveDown {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        tracking::move_by(0, i32::from(self.0));
         sys::move_down(self.0)
     }
 }
@@ -244,6 +680,7 @@ pub struct MoveLeft(pub u16);
 
 impl Command for MoveLeft {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::move_by(-i32::from(self.0), 0);
         if self.0 != 0 {
             write!(f, csi!("{}D"), self.0)?;
         }
@@ -252,6 +689,7 @@ impl Command for MoveLeft {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        tracking::move_by(-i32::from(self.0), 0);
         sys::move_left(self.0)
     }
 }
@@ -291,11 +729,13 @@ pub struct RestorePosition;
 
 impl Command for RestorePosition {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        tracking::invalidate();
         f.write_str("\x1B8")
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        tracking::invalidate();
         sys::restore_position()
     }
 }
@@ -317,6 +757,10 @@ impl Command for Hide {
     fn execute_winapi(&self) -> Result<()> {
         sys::show_cursor(false)
     }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::CURSOR_HIDDEN, true);
+    }
 }
 
 /// A command that shows the terminal cursor.
@@ -336,6 +780,10 @@ impl Command for Show {
     fn execute_winapi(&self) -> Result<()> {
         sys::show_cursor(true)
     }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::CURSOR_HIDDEN, false);
+    }
 }
 
 /// A command that enables blinking of the terminal cursor.
@@ -417,6 +865,14 @@ impl Command for SetCursorShape {
 impl_display!(for MoveTo);
 impl_display!(for MoveToColumn);
 impl_display!(for MoveToRow);
+impl_display!(for MoveToColumnAt);
+impl_display!(for MoveToRowAt);
+impl_display!(for SmartMoveTo);
+impl_display!(for MoveToClamped);
+impl_display!(for MoveUpClamped);
+impl_display!(for MoveRightClamped);
+impl_display!(for MoveDownClamped);
+impl_display!(for MoveLeftClamped);
 impl_display!(for MoveToNextLine);
 impl_display!(for MoveToPreviousLine);
 impl_display!(for MoveUp);
@@ -433,17 +889,162 @@ impl_display!(for SetCursorShape);
 
 #[cfg(test)]
 mod tests {
+    #[cfg(any(windows, feature = "events"))]
     use std::io::{self, stdout};
 
+    #[cfg(any(windows, feature = "events"))]
     use crate::execute;
 
+    #[cfg(feature = "style")]
+    use crate::style::Print;
+    use crate::{Command, QueueableCommand};
+
+    use crate::terminal::{Index, Position, Rect};
+
+    #[cfg(any(windows, feature = "events"))]
+    use super::{position, MoveLeft, RestorePosition, SavePosition};
     use super::{
-        position, MoveDown, MoveLeft, MoveRight, MoveTo, MoveUp, RestorePosition, SavePosition,
+        tracked_position, MoveDown, MoveDownClamped, MoveLeftClamped, MoveRight, MoveRightClamped,
+        MoveTo, MoveToClamped, MoveToColumnAt, MoveToRowAt, MoveUp, MoveUpClamped, SmartMoveTo,
     };
 
+    /// Returns the ANSI representation of the command, tracked as if it was queued.
+    fn ansi(command: impl Command) -> String {
+        let _tracking = super::tracking::start_test_tracking(1000, 1000);
+        let mut ansi = String::new();
+        command.write_ansi(&mut ansi).unwrap();
+        ansi
+    }
+
+    #[test]
+    fn test_smart_move_to() {
+        let _lock = super::tracking::TEST_LOCK.lock();
+        let _enabled = super::tracking::enable_tracking();
+        ansi(MoveTo(10, 10));
+
+        // Already there
+        assert_eq!(ansi(SmartMoveTo(10, 10)), "");
+        // Relative moves are cheaper
+        assert_eq!(ansi(SmartMoveTo(12, 10)), "\x1B[2C");
+        assert_eq!(ansi(SmartMoveTo(0, 9)), "\r\x1B[1A");
+        assert_eq!(ansi(SmartMoveTo(0, 11)), "\x1B[2B");
+        assert_eq!(ansi(SmartMoveTo(5, 11)), "\x1B[5C");
+        assert_eq!(ansi(SmartMoveTo(3, 11)), "\x1B[2D");
+        // Absolute move is cheaper
+        assert_eq!(ansi(SmartMoveTo(100, 200)), "\x1B[201;101H");
+        // Tracked position is updated by the other cursor commands
+        ansi(MoveUp(100));
+        assert_eq!(ansi(SmartMoveTo(101, 101)), "\x1B[1C\x1B[1B");
+        // Position is unknown after printing the escape sequences
+        #[cfg(feature = "style")]
+        {
+            ansi(Print("\x1B[1m"));
+            assert_eq!(ansi(SmartMoveTo(102, 101)), "\x1B[102;103H");
+        }
+    }
+
+    #[test]
+    fn test_formatted_commands_are_not_tracked() {
+        let _lock = super::tracking::TEST_LOCK.lock();
+        let _enabled = super::tracking::enable_tracking();
+        ansi(MoveTo(3, 4));
+
+        // Rendered to a string, it may never reach the terminal
+        let mut rendered = String::new();
+        MoveTo(10, 10).write_ansi(&mut rendered).unwrap();
+        assert_eq!(rendered, "\x1B[11;11H");
+        assert_eq!(tracked_position(), Some((3, 4)));
+
+        let mut queued = Vec::new();
+        queued.queue(MoveDown(2)).unwrap();
+        assert_eq!(tracked_position(), Some((3, 6)));
+    }
+
+    #[test]
+    fn test_indexed_moves() {
+        let _lock = super::tracking::TEST_LOCK.lock();
+        let _enabled = super::tracking::enable_tracking();
+        ansi(MoveTo(3, 4));
+
+        assert_eq!(ansi(MoveToColumnAt(Index::ZeroBased(0))), "\x1B[1G");
+        assert_eq!(tracked_position(), Some((0, 4)));
+        assert_eq!(ansi(MoveToRowAt(Index::OneBased(2))), "\x1B[2d");
+        assert_eq!(tracked_position(), Some((0, 1)));
+        assert_eq!(
+            ansi(MoveTo::from(Position::from_one_based(2, 3))),
+            "\x1B[3;2H"
+        );
+    }
+
+    #[test]
+    fn test_tracked_position() {
+        let _lock = super::tracking::TEST_LOCK.lock();
+        let _enabled = super::tracking::enable_tracking();
+
+        ansi(MoveTo(3, 4));
+        assert_eq!(tracked_position(), Some((3, 4)));
+        ansi(MoveDown(2));
+        assert_eq!(tracked_position(), Some((3, 6)));
+        #[cfg(feature = "style")]
+        {
+            ansi(Print("\x07"));
+            assert_eq!(tracked_position(), None);
+        }
+
+        // Forgotten once disabled
+        ansi(MoveTo(3, 4));
+        super::set_position_tracking(false);
+        assert_eq!(tracked_position(), None);
+        ansi(MoveTo(3, 4));
+        assert_eq!(tracked_position(), None);
+    }
+
+    #[test]
+    fn test_tracked_position_stops_at_the_screen_edges() {
+        let _lock = super::tracking::TEST_LOCK.lock();
+        let _enabled = super::tracking::enable_tracking();
+        let _tracking = super::tracking::start_test_tracking(80, 24);
+        fn queued(command: impl Command) -> String {
+            let mut output = Vec::new();
+            output.queue(command).unwrap();
+            String::from_utf8(output).unwrap()
+        }
+
+        queued(MoveTo(3, 4));
+        queued(MoveRight(500));
+        assert_eq!(tracked_position(), Some((79, 4)));
+        assert_eq!(queued(SmartMoveTo(78, 4)), "\x1B[1D");
+        queued(MoveTo(100, 200));
+        assert_eq!(tracked_position(), Some((79, 23)));
+    }
+
+    #[test]
+    fn test_clamped_moves() {
+        let _lock = super::tracking::TEST_LOCK.lock();
+        let _enabled = super::tracking::enable_tracking();
+        let bounds = Rect::new(10, 5, 20, 10);
+
+        assert_eq!(ansi(MoveToClamped(0, 100, bounds)), "\x1B[15;11H");
+        assert_eq!(ansi(MoveUpClamped(3, bounds)), "\x1B[3A");
+        assert_eq!(ansi(MoveUpClamped(100, bounds)), "\x1B[6A");
+        assert_eq!(ansi(MoveRightClamped(100, bounds)), "\x1B[19C");
+        assert_eq!(ansi(MoveRightClamped(1, bounds)), "");
+        assert_eq!(ansi(MoveDownClamped(2, bounds)), "\x1B[2B");
+        assert_eq!(ansi(MoveLeftClamped(4, bounds)), "\x1B[4D");
+        // Empty bounds
+        assert_eq!(ansi(MoveToClamped(0, 0, Rect::new(0, 0, 0, 10))), "");
+        // Unknown position
+        #[cfg(feature = "style")]
+        {
+            ansi(Print("\x1B[1m"));
+            assert_eq!(ansi(MoveUpClamped(1, bounds)), "");
+        }
+    }
+
     // Test is disabled, because it's failing on Travis
     #[test]
     #[ignore]
+    #[cfg(any(windows, feature = "events"))]
     fn test_move_to() {
         let (saved_x, saved_y) = position().unwrap();
 
@@ -457,6 +1058,7 @@ mod tests {
     // Test is disabled, because it's failing on Travis
     #[test]
     #[ignore]
+    #[cfg(any(windows, feature = "events"))]
     fn test_move_right() {
         let (saved_x, saved_y) = position().unwrap();
         execute!(io::stdout(), MoveRight(1)).unwrap();
@@ -466,6 +1068,7 @@ mod tests {
     // Test is disabled, because it's failing on Travis
     #[test]
     #[ignore]
+    #[cfg(any(windows, feature = "events"))]
     fn test_move_left() {
         execute!(stdout(), MoveTo(2, 0), MoveLeft(2)).unwrap();
         assert_eq!(position().unwrap(), (0, 0));
@@ -474,6 +1077,7 @@ mod tests {
     // Test is disabled, because it's failing on Travis
     #[test]
     #[ignore]
+    #[cfg(any(windows, feature = "events"))]
     fn test_move_up() {
         execute!(stdout(), MoveTo(0, 2), MoveUp(2)).unwrap();
         assert_eq!(position().unwrap(), (0, 0));
@@ -482,6 +1086,7 @@ mod tests {
     // Test is disabled, because it's failing on Travis
     #[test]
     #[ignore]
+    #[cfg(any(windows, feature = "events"))]
     fn test_move_down() {
         execute!(stdout(), MoveTo(0, 0), MoveDown(2)).unwrap();
 
@@ -491,6 +1096,7 @@ mod tests {
     // Test is disabled, because it's failing on Travis
     #[test]
     #[ignore]
+    #[cfg(any(windows, feature = "events"))]
     fn test_save_restore_position() {
         let (saved_x, saved_y) = position().unwrap();
 
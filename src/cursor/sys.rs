@@ -1,9 +1,9 @@
 //! This module provides platform related functions.
 
-#[cfg(unix)]
-pub use self::unix::position;
+#[cfg(all(unix, feature = "events"))]
+pub(crate) use self::unix::position;
 #[cfg(windows)]
-pub use self::windows::position;
+pub(crate) use self::windows::position;
 #[cfg(windows)]
 pub(crate) use self::windows::{
     move_down, move_left, move_right, move_to, move_to_column, move_to_next_line,
@@ -13,5 +13,5 @@ pub(crate) use self::windows::{
 #[cfg(windows)]
 pub(crate) mod windows;
 
-#[cfg(unix)]
+#[cfg(all(unix, feature = "events"))]
 pub(crate) mod unix;
@@ -0,0 +1,669 @@
+//! Crate side model of the cursor position.
+//!
+//! The position is updated by the cursor commands and the printed text when they're
+//! written and invalidated by the commands moving the cursor by an unknown distance
+//! (restoring the saved position, scrolling, ...).
+//!
+//! The widths of the graphemes printed on the current row are recorded as well, so the
+//! last ones can be erased. They're forgotten as soon as the cursor is moved otherwise.
+//!
+//! The wide graphemes printed on the row are recorded with their columns, so clearing the
+//! row from the middle of one doesn't leave its other half behind.
+//!
+//! Only the commands queued or executed update the model, the ones just formatted (e.g.
+//! displayed or written to a string) read it only. The model is updated only once the
+//! tracking is enabled with the `cursor::set_position_tracking`.
+//!
+//! The position is kept within the screen, the size of which is looked up once per
+//! tracked command.
+
+#[cfg(feature = "style")]
+use std::fmt::{self, Display, Write};
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+
+#[cfg(feature = "style")]
+use crate::text;
+
+/// The position tracking is enabled.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Tracked cursor position (column, row), `None` if it's unknown.
+static TRACKED_POSITION: Mutex<Option<(u16, u16)>> = parking_lot::const_mutex(None);
+
+/// Tracked cursor position on the main screen, saved while the alternate screen is active.
+static MAIN_SCREEN_POSITION: Mutex<Option<(u16, u16)>> = parking_lot::const_mutex(None);
+
+/// Graphemes printed on the current row, left of the tracked position.
+static ROW_GRAPHEMES: Mutex<RowGraphemes> = parking_lot::const_mutex(RowGraphemes::new());
+
+/// Wide graphemes printed on the row the cursor was last on.
+static WIDE_CELLS: Mutex<WideCells> = parking_lot::const_mutex(WideCells::new());
+
+/// The widths of the graphemes printed on the current row, the last one on the top.
+#[derive(Debug)]
+struct RowGraphemes {
+    widths: Vec<u16>,
+    // The last character was a zero width joiner
+    join_next: bool,
+}
+
+impl RowGraphemes {
+    const fn new() -> RowGraphemes {
+        RowGraphemes {
+            widths: Vec::new(),
+            join_next: false,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.widths.clear();
+        self.join_next = false;
+    }
+
+    /// Records the characters printed on the row, `false` if the row changed.
+    #[cfg(feature = "style")]
+    fn push_str(&mut self, s: &str) -> bool {
+        for c in s.chars() {
+            let width = match text::char_width(c) {
+                Some(width) => u16::try_from(width).unwrap_or(u16::MAX),
+                // The line feed, the tab, ...
+                None => return false,
+            };
+
+            match self.widths.last_mut() {
+                // Combining characters, variation selectors and the joined sequences
+                Some(last) if width == 0 || self.join_next => *last = last.saturating_add(width),
+                _ if width == 0 => {}
+                _ => self.widths.push(width),
+            }
+            self.join_next = c == '\u{200D}';
+        }
+        true
+    }
+}
+
+/// The wide graphemes printed on a row, the columns they start at and their widths.
+#[derive(Debug)]
+struct WideCells {
+    row: u16,
+    cells: Vec<(u16, u16)>,
+}
+
+impl WideCells {
+    const fn new() -> WideCells {
+        WideCells {
+            row: 0,
+            cells: Vec::new(),
+        }
+    }
+
+    /// Forgets the graphemes overlapping the columns from `start` to `end` (exclusive).
+    fn overwrite(&mut self, start: u16, end: u16) {
+        self.cells
+            .retain(|&(column, width)| column.saturating_add(width) <= start || column >= end);
+    }
+
+    /// Records the text printed from the given position.
+    #[cfg(feature = "style")]
+    fn push_str(&mut self, (mut column, row): (u16, u16), s: &str, line_feed_returns: bool) {
+        if row != self.row {
+            self.cells.clear();
+            self.row = row;
+        }
+
+        // The column of the grapheme the joined character belongs to
+        let mut last: Option<u16> = None;
+        let mut join_next = false;
+        for c in s.chars() {
+            match c {
+                '\r' => column = 0,
+                '\x08' => column = column.saturating_sub(1),
+                '\t' => column = (column / 8 + 1).saturating_mul(8),
+                // The following characters are printed on the next row
+                '\n' => {
+                    self.cells.clear();
+                    self.row = self.row.saturating_add(1);
+                    if line_feed_returns {
+                        column = 0;
+                    }
+                }
+                _ => {
+                    let width = text::char_width(c)
+                        .and_then(|width| u16::try_from(width).ok())
+                        .unwrap_or(0);
+                    if width > 0 {
+                        let start = match last {
+                            Some(start) if join_next => start,
+                            _ => column,
+                        };
+                        column = column.saturating_add(width);
+                        self.overwrite(start, column);
+                        if column - start > 1 {
+                            self.cells.push((start, column - start));
+                        }
+                        last = Some(start);
+                    }
+                }
+            }
+            join_next = c == '\u{200D}';
+        }
+    }
+}
+
+thread_local! {
+    /// The commands written on this thread are queued or executed, not just formatted.
+    static TRACKING: Cell<bool> = const { Cell::new(false) };
+    /// The screen size looked up by the tracked command, `None` until it's needed.
+    static SCREEN_SIZE: Cell<Option<Option<(u16, u16)>>> = const { Cell::new(None) };
+}
+
+/// Enables or disables the position tracking, the tracked state is forgotten when it's
+/// disabled.
+pub(crate) fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+    if !enabled {
+        *TRACKED_POSITION.lock() = None;
+        *MAIN_SCREEN_POSITION.lock() = None;
+        ROW_GRAPHEMES.lock().clear();
+        WIDE_CELLS.lock().cells.clear();
+    }
+}
+
+/// Returns whether the position tracking is enabled.
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Turns the tracking on this thread back off when dropped.
+pub(crate) struct TrackingGuard(bool);
+
+impl Drop for TrackingGuard {
+    fn drop(&mut self) {
+        TRACKING.with(|tracking| tracking.set(self.0));
+        if !self.0 {
+            SCREEN_SIZE.with(|size| size.set(None));
+        }
+    }
+}
+
+/// Turns the tracking on this thread on until the guard is dropped.
+///
+/// The nested calls (e.g. the commands queued by a command) share the screen size looked
+/// up by the outermost one.
+pub(crate) fn start_tracking() -> TrackingGuard {
+    TrackingGuard(TRACKING.with(|tracking| tracking.replace(true)))
+}
+
+/// Runs the function writing the commands to the terminal, they update the tracked
+/// position.
+pub(crate) fn tracked<T>(f: impl FnOnce() -> T) -> T {
+    let _guard = start_tracking();
+    f()
+}
+
+/// Returns whether the commands written on this thread update the tracked position.
+fn is_tracking() -> bool {
+    is_enabled() && TRACKING.with(Cell::get)
+}
+
+/// Returns the screen size (columns, rows) if the command is queued or executed, `None` if
+/// it's unknown or the command is only formatted.
+///
+/// It's looked up once per command, the formatting doesn't query the terminal.
+#[cfg(feature = "style")]
+pub(crate) fn queued_screen_size() -> Option<(u16, u16)> {
+    TRACKING.with(Cell::get).then(screen_size).flatten()
+}
+
+/// Returns the screen size (columns, rows), `None` if it's unknown.
+///
+/// It's looked up once per tracked command.
+fn screen_size() -> Option<(u16, u16)> {
+    SCREEN_SIZE.with(|size| match size.get() {
+        Some(screen_size) => screen_size,
+        None => {
+            let screen_size = crate::terminal::size().ok();
+            size.set(Some(screen_size));
+            screen_size
+        }
+    })
+}
+
+/// Serializes the tests writing the commands which update the tracked position.
+#[cfg(test)]
+pub(crate) static TEST_LOCK: Mutex<()> = parking_lot::const_mutex(());
+
+/// Returns the tracked cursor position, `None` if it's unknown.
+pub(crate) fn position() -> Option<(u16, u16)> {
+    *TRACKED_POSITION.lock()
+}
+
+/// Sets the tracked cursor position.
+///
+/// The cursor stops at the edges of the screen.
+pub(crate) fn set_position(column: u16, row: u16) {
+    if !is_tracking() {
+        return;
+    }
+    *TRACKED_POSITION.lock() = Some(clamp_to_screen(column, row));
+    ROW_GRAPHEMES.lock().clear();
+}
+
+/// Moves the tracked cursor position by the given number of columns and rows.
+///
+/// The cursor stops at the edges of the screen.
+pub(crate) fn move_by(columns: i32, rows: i32) {
+    if !is_tracking() {
+        return;
+    }
+    let mut position = TRACKED_POSITION.lock();
+
+    if let Some((column, row)) = position.as_mut() {
+        (*column, *row) = clamp_to_screen(
+            clamp(i32::from(*column) + columns),
+            clamp(i32::from(*row) + rows),
+        );
+    }
+    ROW_GRAPHEMES.lock().clear();
+}
+
+/// Returns the tracked cursor position moved by the given number of columns and rows,
+/// `None` if it's unknown.
+pub(crate) fn offset(columns: i32, rows: i32) -> Option<(u16, u16)> {
+    position().map(|(column, row)| {
+        (
+            clamp(i32::from(column) + columns),
+            clamp(i32::from(row) + rows),
+        )
+    })
+}
+
+/// Moves the tracked cursor position to the given column on the current row.
+pub(crate) fn move_to_column(column: u16) {
+    if !is_tracking() {
+        return;
+    }
+    if let Some((x, y)) = TRACKED_POSITION.lock().as_mut() {
+        (*x, *y) = clamp_to_screen(column, *y);
+    }
+    ROW_GRAPHEMES.lock().clear();
+}
+
+/// Moves the tracked cursor position to the given row on the current column.
+pub(crate) fn move_to_row(row: u16) {
+    if !is_tracking() {
+        return;
+    }
+    if let Some((x, y)) = TRACKED_POSITION.lock().as_mut() {
+        (*x, *y) = clamp_to_screen(*x, row);
+    }
+    ROW_GRAPHEMES.lock().clear();
+}
+
+/// Forgets the tracked cursor position.
+pub(crate) fn invalidate() {
+    if !is_tracking() {
+        return;
+    }
+    *TRACKED_POSITION.lock() = None;
+    ROW_GRAPHEMES.lock().clear();
+    WIDE_CELLS.lock().cells.clear();
+}
+
+/// Saves the tracked cursor position as the one of the main screen.
+pub(crate) fn save_main_screen_position() {
+    if !is_tracking() {
+        return;
+    }
+    *MAIN_SCREEN_POSITION.lock() = position();
+}
+
+/// Returns the saved cursor position of the main screen, `None` if it was unknown.
+pub(crate) fn main_screen_position() -> Option<(u16, u16)> {
+    *MAIN_SCREEN_POSITION.lock()
+}
+
+/// Moves the tracked cursor position back to the saved one of the main screen, it's
+/// forgotten then.
+pub(crate) fn restore_main_screen_position() {
+    if !is_tracking() {
+        return;
+    }
+    let saved = MAIN_SCREEN_POSITION.lock().take();
+    if let Some((column, row)) = saved {
+        set_position(column, row);
+    }
+}
+
+/// Returns the number of columns between the start of the wide grapheme the cursor is on
+/// and the cursor, `None` if the cursor isn't on the right part of a recorded one.
+#[cfg(feature = "terminal")]
+pub(crate) fn wide_grapheme_offset() -> Option<u16> {
+    let (column, row) = position()?;
+    let wide = WIDE_CELLS.lock();
+    if wide.row != row {
+        return None;
+    }
+    wide.cells
+        .iter()
+        .find(|&&(start, width)| start < column && column < start.saturating_add(width))
+        .map(|&(start, _)| column - start)
+}
+
+/// Forgets the wide graphemes printed on the current row from the given column on.
+#[cfg(feature = "terminal")]
+pub(crate) fn forget_wide_graphemes_from(column: u16) {
+    if !is_tracking() {
+        return;
+    }
+    WIDE_CELLS.lock().overwrite(column, u16::MAX);
+}
+
+/// Forgets the wide graphemes printed on all the rows.
+#[cfg(feature = "terminal")]
+pub(crate) fn forget_wide_graphemes() {
+    if !is_tracking() {
+        return;
+    }
+    WIDE_CELLS.lock().cells.clear();
+}
+
+/// Removes the last `count` graphemes printed on the current row and returns their
+/// total width.
+///
+/// Fewer graphemes are removed if fewer are known.
+#[cfg(all(feature = "cursor", feature = "style"))]
+pub(crate) fn pop_graphemes(count: usize) -> u16 {
+    let mut graphemes = ROW_GRAPHEMES.lock();
+    let keep = graphemes.widths.len().saturating_sub(count);
+    graphemes.join_next = false;
+    graphemes
+        .widths
+        .drain(keep..)
+        .fold(0, |total: u16, width| total.saturating_add(width))
+}
+
+/// Runs the function, keeping the graphemes printed on the current row even if it moves
+/// the cursor.
+///
+/// The graphemes are kept only if the position is known afterwards.
+#[cfg(all(feature = "cursor", feature = "style"))]
+pub(crate) fn keeping_graphemes<T>(f: impl FnOnce() -> T) -> T {
+    let widths = std::mem::take(&mut ROW_GRAPHEMES.lock().widths);
+    let result = f();
+
+    if position().is_some() {
+        let mut graphemes = ROW_GRAPHEMES.lock();
+        graphemes.clear();
+        graphemes.widths = widths;
+    }
+    result
+}
+
+/// Writes the text, advancing the tracked cursor position by its width.
+#[cfg(feature = "style")]
+pub(crate) fn write_text(f: &mut impl fmt::Write, text: impl Display) -> fmt::Result {
+    write!(AdvancingWriter::new(f), "{}", text)
+}
+
+/// Writer advancing the tracked cursor position by the written text.
+#[cfg(feature = "style")]
+struct AdvancingWriter<'a, W: ?Sized> {
+    writer: &'a mut W,
+}
+
+#[cfg(feature = "style")]
+impl<'a, W: fmt::Write + ?Sized> AdvancingWriter<'a, W> {
+    fn new(writer: &'a mut W) -> AdvancingWriter<'a, W> {
+        AdvancingWriter { writer }
+    }
+}
+
+#[cfg(feature = "style")]
+impl<W: fmt::Write + ?Sized> fmt::Write for AdvancingWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_str(s)?;
+
+        if is_tracking() && position().is_some() {
+            let size = screen_size();
+            let mut position = TRACKED_POSITION.lock();
+            let mut graphemes = ROW_GRAPHEMES.lock();
+            if let Some(start) = *position {
+                WIDE_CELLS.lock().push_str(start, s, line_feed_returns());
+            }
+            *position = position
+                .zip(size)
+                .and_then(|(position, size)| advance(position, s, size, line_feed_returns()));
+            if position.is_none() || !graphemes.push_str(s) {
+                graphemes.clear();
+            }
+            if position.is_none() {
+                WIDE_CELLS.lock().cells.clear();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the cursor position after printing the text, `None` if it can't be tracked.
+///
+/// The text reaching the right edge of the screen isn't tracked, whether the cursor wraps
+/// depends on the terminal.
+#[cfg(feature = "style")]
+fn advance(
+    (mut column, mut row): (u16, u16),
+    text: &str,
+    (width, height): (u16, u16),
+    line_feed_returns: bool,
+) -> Option<(u16, u16)> {
+    for c in text.chars() {
+        match c {
+            '\r' => column = 0,
+            '\n' => {
+                // The screen scrolls at the bottom
+                row = row.saturating_add(1).min(height.saturating_sub(1));
+                if line_feed_returns {
+                    column = 0;
+                }
+            }
+            '\t' => {
+                column = (column / 8 + 1)
+                    .saturating_mul(8)
+                    .min(width.saturating_sub(1))
+            }
+            '\x08' => column = column.saturating_sub(1),
+            _ => {
+                let char_width = u16::try_from(text::char_width(c)?).ok()?;
+                column = column.checked_add(char_width)?;
+                if column >= width {
+                    return None;
+                }
+            }
+        }
+    }
+    Some((column, row))
+}
+
+/// Returns whether the line feed moves the cursor to the first column.
+#[cfg(feature = "style")]
+fn line_feed_returns() -> bool {
+    // The raw mode disables the output processing (`ONLCR`)
+    #[cfg(unix)]
+    {
+        !crate::terminal::sys::is_raw_mode_enabled()
+    }
+
+    #[cfg(windows)]
+    {
+        true
+    }
+}
+
+fn clamp(value: i32) -> u16 {
+    value.clamp(0, i32::from(u16::MAX)) as u16
+}
+
+/// Returns the position moved to the last column/row if it's beyond the screen, the
+/// terminal stops the cursor there.
+fn clamp_to_screen(column: u16, row: u16) -> (u16, u16) {
+    match screen_size() {
+        Some((columns, rows)) => (
+            column.min(columns.saturating_sub(1)),
+            row.min(rows.saturating_sub(1)),
+        ),
+        None => (column, row),
+    }
+}
+
+/// Enables the position tracking until the guard is dropped.
+#[cfg(test)]
+pub(crate) struct EnabledGuard(bool);
+
+#[cfg(test)]
+impl Drop for EnabledGuard {
+    fn drop(&mut self) {
+        set_enabled(self.0);
+    }
+}
+
+/// Enables the position tracking until the guard is dropped.
+#[cfg(test)]
+pub(crate) fn enable_tracking() -> EnabledGuard {
+    let enabled = EnabledGuard(is_enabled());
+    set_enabled(true);
+    enabled
+}
+
+/// Starts tracking the commands written on this thread on a screen of the given size.
+#[cfg(test)]
+pub(crate) fn start_test_tracking(columns: u16, rows: u16) -> TrackingGuard {
+    let tracking = start_tracking();
+    SCREEN_SIZE.with(|size| size.set(Some(Some((columns, rows)))));
+    tracking
+}
+
+#[cfg(all(test, feature = "style"))]
+mod tests {
+    #[cfg(feature = "cursor")]
+    use super::ROW_GRAPHEMES;
+    use super::{
+        advance, enable_tracking, position, set_position, start_test_tracking, RowGraphemes,
+        WideCells, TEST_LOCK, WIDE_CELLS,
+    };
+
+    #[test]
+    fn test_advance() {
+        const SIZE: (u16, u16) = (20, 5);
+
+        assert_eq!(advance((2, 1), "hello", SIZE, true), Some((7, 1)));
+        // Wide characters
+        assert_eq!(advance((0, 0), "你好", SIZE, true), Some((4, 0)));
+        assert_eq!(advance((5, 1), "ab\r\ncd", SIZE, false), Some((2, 2)));
+        assert_eq!(advance((5, 1), "ab\n", SIZE, false), Some((7, 2)));
+        assert_eq!(advance((5, 1), "ab\n", SIZE, true), Some((0, 2)));
+        // Scrolls at the bottom
+        assert_eq!(advance((0, 4), "\n\n", SIZE, true), Some((0, 4)));
+        assert_eq!(advance((3, 0), "\ta\x08", SIZE, true), Some((8, 0)));
+
+        // Reaches the right edge
+        assert_eq!(advance((15, 0), "hello", SIZE, true), None);
+        // Escape sequences
+        assert_eq!(advance((0, 0), "\x1B[1m", SIZE, true), None);
+    }
+
+    #[test]
+    fn test_row_graphemes() {
+        let mut graphemes = RowGraphemes::new();
+
+        // Wide, combining, emoji presentation and joined sequences
+        assert!(graphemes.push_str("a你e\u{301}"));
+        assert!(graphemes.push_str("\u{2764}\u{FE0F}\u{1F468}\u{200D}"));
+        assert!(graphemes.push_str("\u{1F469}"));
+        assert_eq!(graphemes.widths, [1, 2, 1, 1, 4]);
+
+        assert!(!graphemes.push_str("b\n"));
+    }
+
+    #[cfg(all(feature = "cursor", feature = "style"))]
+    #[test]
+    fn test_erase_graphemes_back() {
+        let _lock = TEST_LOCK.lock();
+        let _enabled = enable_tracking();
+        let _tracking = start_test_tracking(80, 24);
+        let erase = |count| {
+            let mut result = Vec::new();
+            let columns = crate::cursor::erase_graphemes_back(&mut result, count).unwrap();
+            (columns, String::from_utf8(result).unwrap())
+        };
+
+        set_position(10, 2);
+        ROW_GRAPHEMES.lock().push_str("a你e\u{301}");
+
+        assert_eq!(erase(1), (1, "\x1B[1D \x1B[1D".to_string()));
+        assert_eq!(position(), Some((9, 2)));
+        assert_eq!(erase(1), (2, "\x1B[2D  \x1B[2D".to_string()));
+        assert_eq!(position(), Some((7, 2)));
+        // Only one grapheme is left
+        assert_eq!(erase(5), (1, "\x1B[1D \x1B[1D".to_string()));
+        assert_eq!(erase(1), (0, String::new()));
+        assert_eq!(position(), Some((6, 2)));
+    }
+
+    #[test]
+    fn test_wide_cells() {
+        let mut wide = WideCells::new();
+
+        wide.push_str((2, 1), "a你e\u{301}\u{1F468}\u{200D}\u{1F469}", true);
+        assert_eq!(wide.cells, [(3, 2), (6, 4)]);
+        // Overwritten by the narrow characters
+        wide.push_str((4, 1), "bc", true);
+        assert_eq!(wide.cells, [(6, 4)]);
+        wide.push_str((0, 1), "\r好\n世", true);
+        assert_eq!(wide.row, 2);
+        assert_eq!(wide.cells, [(0, 2)]);
+        // Another row
+        wide.push_str((0, 3), "x", true);
+        assert!(wide.cells.is_empty());
+    }
+
+    #[cfg(feature = "terminal")]
+    #[test]
+    fn test_clear_until_new_line_from_wide_character() {
+        use crate::{
+            terminal::{Clear, ClearType},
+            Command,
+        };
+
+        let _lock = TEST_LOCK.lock();
+        let _enabled = enable_tracking();
+        let _tracking = start_test_tracking(80, 24);
+        let clear = |clear_type| {
+            let mut result = String::new();
+            Clear(clear_type).write_ansi(&mut result).unwrap();
+            result
+        };
+
+        set_position(0, 1);
+        WIDE_CELLS.lock().push_str((0, 1), "a你好", true);
+        set_position(2, 1);
+        assert_eq!(clear(ClearType::UntilNewLine), "\x1B[1D\x1B[K ");
+        assert_eq!(position(), Some((2, 1)));
+        // Forgotten once cleared
+        assert_eq!(clear(ClearType::UntilNewLine), "\x1B[K");
+
+        WIDE_CELLS.lock().push_str((0, 1), "a你好", true);
+        // On the start of the character
+        set_position(3, 1);
+        assert_eq!(clear(ClearType::UntilNewLine), "\x1B[K");
+        // On another row
+        set_position(2, 2);
+        assert_eq!(clear(ClearType::UntilNewLine), "\x1B[K");
+        set_position(2, 1);
+        assert_eq!(clear(ClearType::CurrentLine), "\x1B[2K");
+        assert_eq!(clear(ClearType::UntilNewLine), "\x1B[K");
+    }
+}
@@ -0,0 +1,311 @@
+//! # Draw
+//!
+//! The `draw` module provides the commands drawing the lines and the borders with the
+//! box-drawing characters, for the simple framed layouts.
+//!
+//! The ASCII characters (`+-|`) are drawn instead if the terminal can't display the
+//! Unicode ones, see the [box_drawing](../capabilities/struct.Capabilities.html#method.box_drawing)
+//! capability.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::{stdout, Write};
+//!
+//! use crossterm::{
+//!     draw::{Border, HorizontalLine, LineStyle},
+//!     execute,
+//!     terminal::Rect,
+//!     Result,
+//! };
+//!
+//! fn main() -> Result<()> {
+//!     execute!(
+//!         stdout(),
+//!         Border(Rect::new(0, 0, 40, 10), LineStyle::Rounded),
+//!         HorizontalLine(1, 2, 38, LineStyle::Light)
+//!     )
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::capabilities::{capabilities, Capability};
+use crate::cursor::{tracking, MoveTo};
+use crate::terminal::Rect;
+#[cfg(windows)]
+use crate::Result;
+use crate::{impl_display, Command};
+
+/// The style of the lines drawn by the commands of the [draw](index.html) module.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineStyle {
+    /// The light lines (`┌─┐`).
+    #[default]
+    Light,
+    /// The heavy lines (`┏━┓`).
+    Heavy,
+    /// The double lines (`╔═╗`).
+    Double,
+    /// The light lines with the rounded corners (`╭─╮`).
+    Rounded,
+    /// The ASCII characters (`+-+`), supported by all the terminals.
+    Ascii,
+}
+
+/// The characters drawing the lines of a style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LineChars {
+    horizontal: char,
+    vertical: char,
+    top_left: char,
+    top_right: char,
+    bottom_left: char,
+    bottom_right: char,
+}
+
+impl LineStyle {
+    /// Returns the characters of the style, the ASCII ones without the box drawing.
+    fn chars(self, box_drawing: bool) -> LineChars {
+        let style = if box_drawing { self } else { LineStyle::Ascii };
+        let (horizontal, vertical, top_left, top_right, bottom_left, bottom_right) = match style {
+            LineStyle::Light => ('─', '│', '┌', '┐', '└', '┘'),
+            LineStyle::Heavy => ('━', '┃', '┏', '┓', '┗', '┛'),
+            LineStyle::Double => ('═', '║', '╔', '╗', '╚', '╝'),
+            LineStyle::Rounded => ('─', '│', '╭', '╮', '╰', '╯'),
+            LineStyle::Ascii => ('-', '|', '+', '+', '+', '+'),
+        };
+
+        LineChars {
+            horizontal,
+            vertical,
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+        }
+    }
+
+    /// Returns the capability required to draw the style.
+    fn required_capability(self) -> Option<Capability> {
+        match self {
+            LineStyle::Ascii => None,
+            _ => Some(Capability::BoxDrawing),
+        }
+    }
+}
+
+/// Writes the characters at the position (column, row) to the right.
+fn write_horizontal(
+    f: &mut impl fmt::Write,
+    column: u16,
+    row: u16,
+    chars: impl Iterator<Item = char>,
+) -> fmt::Result {
+    MoveTo(column, row).write_ansi(f)?;
+    for ch in chars {
+        f.write_char(ch)?;
+    }
+    Ok(())
+}
+
+/// Writes the character to the given number of rows downwards from the position (column, row).
+fn write_vertical(
+    f: &mut impl fmt::Write,
+    column: u16,
+    row: u16,
+    length: u16,
+    ch: char,
+) -> fmt::Result {
+    for offset in 0..length {
+        MoveTo(column, row.saturating_add(offset)).write_ansi(f)?;
+        f.write_char(ch)?;
+    }
+    Ok(())
+}
+
+/// Writes the border of the area, the narrow areas are drawn as the lines.
+fn write_border(f: &mut impl fmt::Write, rect: Rect, chars: LineChars) -> fmt::Result {
+    let Rect {
+        x,
+        y,
+        width,
+        height,
+    } = rect;
+
+    if rect.is_empty() {
+        return Ok(());
+    } else if height == 1 {
+        write_horizontal(f, x, y, (0..width).map(|_| chars.horizontal))?;
+    } else if width == 1 {
+        write_vertical(f, x, y, height, chars.vertical)?;
+    } else {
+        let inner = usize::from(width - 2);
+        let right = x.saturating_add(width - 1);
+        let bottom = y.saturating_add(height - 1);
+
+        write_horizontal(
+            f,
+            x,
+            y,
+            std::iter::once(chars.top_left)
+                .chain(std::iter::repeat_n(chars.horizontal, inner))
+                .chain(std::iter::once(chars.top_right)),
+        )?;
+        write_vertical(f, x, y + 1, height - 2, chars.vertical)?;
+        write_vertical(f, right, y + 1, height - 2, chars.vertical)?;
+        write_horizontal(
+            f,
+            x,
+            bottom,
+            std::iter::once(chars.bottom_left)
+                .chain(std::iter::repeat_n(chars.horizontal, inner))
+                .chain(std::iter::once(chars.bottom_right)),
+        )?;
+    }
+
+    // The cursor may wait to wrap in the last column
+    tracking::invalidate();
+    Ok(())
+}
+
+/// A command that draws the border along the edges of the area.
+///
+/// The area narrower or lower than two cells is drawn as a line.
+///
+/// # Notes
+///
+/// * The cursor position is unspecified afterwards.
+/// * The text drawn is styled with the current colors and attributes.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Border(pub Rect, pub LineStyle);
+
+impl Command for Border {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write_border(f, self.0, self.1.chars(capabilities().box_drawing()))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        panic!("tried to execute Border command using WinAPI, use ANSI instead");
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        true
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        self.1.required_capability()
+    }
+}
+
+/// A command that draws the horizontal line of the given length (columns), starting at the
+/// position (column, row) to the right.
+///
+/// # Notes
+///
+/// * The cursor position is unspecified afterwards.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HorizontalLine(pub u16, pub u16, pub u16, pub LineStyle);
+
+impl Command for HorizontalLine {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write_border(
+            f,
+            Rect::new(self.0, self.1, self.2, 1),
+            self.3.chars(capabilities().box_drawing()),
+        )
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        panic!("tried to execute HorizontalLine command using WinAPI, use ANSI instead");
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        true
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        self.3.required_capability()
+    }
+}
+
+/// A command that draws the vertical line of the given length (rows), starting at the
+/// position (column, row) downwards.
+///
+/// # Notes
+///
+/// * The cursor position is unspecified afterwards.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerticalLine(pub u16, pub u16, pub u16, pub LineStyle);
+
+impl Command for VerticalLine {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write_border(
+            f,
+            Rect::new(self.0, self.1, 1, self.2),
+            self.3.chars(capabilities().box_drawing()),
+        )
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        panic!("tried to execute VerticalLine command using WinAPI, use ANSI instead");
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        true
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        self.3.required_capability()
+    }
+}
+
+impl_display!(for Border, HorizontalLine, VerticalLine);
+
+#[cfg(test)]
+mod tests {
+    use crate::terminal::Rect;
+
+    use super::{write_border, LineStyle};
+
+    fn border(rect: Rect, style: LineStyle, box_drawing: bool) -> String {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let mut output = String::new();
+        write_border(&mut output, rect, style.chars(box_drawing)).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_border() {
+        assert_eq!(
+            border(Rect::new(1, 0, 4, 3), LineStyle::Rounded, true),
+            "\x1B[1;2H╭──╮\x1B[2;2H│\x1B[2;5H│\x1B[3;2H╰──╯"
+        );
+        assert_eq!(
+            border(Rect::new(0, 0, 2, 2), LineStyle::Double, false),
+            "\x1B[1;1H++\x1B[2;1H++"
+        );
+    }
+
+    #[test]
+    fn test_narrow_border_is_line() {
+        assert_eq!(
+            border(Rect::new(0, 2, 3, 1), LineStyle::Heavy, true),
+            "\x1B[3;1H━━━"
+        );
+        assert_eq!(
+            border(Rect::new(4, 0, 1, 2), LineStyle::Light, true),
+            "\x1B[1;5H│\x1B[2;5H│"
+        );
+        assert_eq!(border(Rect::new(0, 0, 0, 2), LineStyle::Light, true), "");
+    }
+}
@@ -1,8 +1,38 @@
 //! Module containing error handling logic.
 
+use std::error::Error;
+use std::fmt;
 use std::io;
 
 /// The `crossterm` result type.
 pub type Result<T> = std::result::Result<T, ErrorKind>;
 
 pub type ErrorKind = io::Error;
+
+/// The error returned when there's no terminal to read the events from or to query.
+///
+/// Neither the standard input is a terminal nor the `/dev/tty` can be opened, e.g. the
+/// process was started from the GUI (an app bundle, launchd) without the controlling
+/// terminal. It's wrapped in the `io::Error` of the `NotFound` kind.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{event, NotATty};
+///
+/// if let Err(e) = event::read() {
+///     if e.get_ref().map_or(false, |e| e.is::<NotATty>()) {
+///         println!("Not running in a terminal");
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotATty;
+
+impl fmt::Display for NotATty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Neither the standard input nor /dev/tty is a terminal")
+    }
+}
+
+impl Error for NotATty {}
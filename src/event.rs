@@ -9,11 +9,19 @@
 //! within the given period of time. In other words - if subsequent call to the [`read`](fn.read.html)
 //! function will block or not.
 //!
-//! It's **not allowed** to call these functions from different threads or combine them with the
-//! [`EventStream`](struct.EventStream.html). You're allowed to either:
+//! ## Threads
 //!
-//! * use the [`read`](fn.read.html) & [`poll`](fn.poll.html) functions on any, but same, thread
-//! * or the [`EventStream`](struct.EventStream.html).
+//! The functions can be called from multiple threads at once, e.g. from a thread handling
+//! the input and from a thread polling with a timeout to schedule the redraws:
+//!
+//! * every [`Event`](enum.Event.html) is returned by one read only, the
+//!   [`EventStream`](struct.EventStream.html) is just another reader,
+//! * the events are returned in the order they were read from the terminal,
+//! * one thread reads the terminal at a time, the other ones are woken up as soon as an
+//!   event is available, they don't wait for each other's timeouts (e.g. of the
+//!   [`cursor::position`](../cursor/fn.position.html)),
+//! * `Ok(true)` returned by the [`poll`](fn.poll.html) guarantees that the
+//!   [`read`](fn.read.html) won't block only if no other thread reads the events meanwhile.
 //!
 //! **Make sure to enable [raw mode](../terminal/#raw-mode) in order for keyboard events to work properly**
 //!
@@ -37,6 +45,16 @@
 //!             Event::Key(event) => println!("{:?}", event),
 //!             Event::Mouse(event) => println!("{:?}", event),
 //!             Event::Resize(width, height) => println!("New size {}x{}", width, height),
+//!             Event::Interrupt => println!("Interrupted"),
+//!             Event::Signal(signal) => println!("Signal {:?}", signal),
+//!             Event::Fd(token, readiness) => println!("{:?} is {:?}", token, readiness),
+//!             Event::Wake(token) => println!("Woken up with {:?}", token),
+//!             Event::Paste(text) => println!("Pasted {:?}", text),
+//!             Event::PixelMouse(event) => println!("{:?}", event),
+//!             Event::PixelResize(width, height) => println!("{}x{}", width, height),
+//!             Event::Suspended => println!("Suspended"),
+//!             Event::Resumed => println!("Resumed"),
+//!             Event::ThemeChanged(scheme) => println!("Theme {:?}", scheme),
 //!         }
 //!     }
 //!     Ok(())
@@ -60,6 +78,16 @@
 //!                 Event::Key(event) => println!("{:?}", event),
 //!                 Event::Mouse(event) => println!("{:?}", event),
 //!                 Event::Resize(width, height) => println!("New size {}x{}", width, height),
+//!                 Event::Interrupt => println!("Interrupted"),
+//!                 Event::Signal(signal) => println!("Signal {:?}", signal),
+//!                 Event::Fd(token, readiness) => println!("{:?} is {:?}", token, readiness),
+//!                 Event::Wake(token) => println!("Woken up with {:?}", token),
+//!                 Event::Paste(text) => println!("Pasted {:?}", text),
+//!                 Event::PixelMouse(event) => println!("{:?}", event),
+//!                 Event::PixelResize(width, height) => println!("{}x{}", width, height),
+//!                 Event::Suspended => println!("Suspended"),
+//!                 Event::Resumed => println!("Resumed"),
+//!                 Event::ThemeChanged(scheme) => println!("Theme {:?}", scheme),
 //!             }
 //!         } else {
 //!             // Timeout expired and no `Event` is available
@@ -72,46 +100,72 @@
 //! Check the [examples](https://github.com/crossterm-rs/crossterm/tree/master/examples) folder for more of
 //! them (`event-*`).
 
+use std::cmp;
 use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::time::Duration;
+#[cfg(windows)]
+use std::io;
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    OnceLock,
+};
+use std::time::{Duration, Instant};
 
 use bitflags::bitflags;
-use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
+use parking_lot::Mutex;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::terminal::snapshot::{self, DecModes};
+use crate::terminal::Position;
 use crate::{csi, Command, Result};
+pub use backend::{event_backend, EventBackend};
+pub use broadcast::{subscribe, EventSubscriber};
 use filter::{EventFilter, Filter};
+pub use gesture::{Gesture, GestureRecognizer};
+pub use normalize::{
+    key_normalization, set_key_normalization, ControlKeys, KeyNormalization, ShiftedChars,
+};
+pub use physical_key::PhysicalKey;
+#[cfg(feature = "probe")]
+pub use probe::{take_raw_input, RawInput};
+#[cfg(unix)]
+pub use query::{
+    query, BackgroundColorQuery, CursorPositionQuery, DecModeQuery, DeviceAttributesQuery,
+    ForegroundColorQuery, KeyboardEnhancementFlagsQuery, Query, Response, TitleQuery,
+};
 use read::InternalEventReader;
+pub use source::custom::{set_event_source, EventSource};
 #[cfg(feature = "event-stream")]
-pub use stream::EventStream;
-use timeout::PollTimeout;
+pub use stream::{EventStream, TakeUntil};
+pub use wake::{inject, Waker};
 
+mod backend;
+mod broadcast;
+mod clock;
 pub(crate) mod filter;
+mod gesture;
+pub(crate) mod normalize;
+mod physical_key;
+#[cfg(feature = "probe")]
+mod probe;
+#[cfg(unix)]
+mod query;
 mod read;
 mod source;
 #[cfg(feature = "event-stream")]
 mod stream;
 pub(crate) mod sys;
-mod timeout;
+pub(crate) mod timeout;
+pub(crate) mod wake;
 
 /// Static instance of `InternalEventReader`.
-/// This needs to be static because there can be one event reader.
-static INTERNAL_EVENT_READER: Mutex<Option<InternalEventReader>> = parking_lot::const_mutex(None);
+/// This needs to be static because there can be one event reader. It's shared by all the
+/// threads, it locks its parts internally.
+static INTERNAL_EVENT_READER: OnceLock<InternalEventReader> = OnceLock::new();
 
-fn lock_internal_event_reader() -> MappedMutexGuard<'static, InternalEventReader> {
-    MutexGuard::map(INTERNAL_EVENT_READER.lock(), |reader| {
-        reader.get_or_insert_with(InternalEventReader::default)
-    })
-}
-fn try_lock_internal_event_reader_for(
-    duration: Duration,
-) -> Option<MappedMutexGuard<'static, InternalEventReader>> {
-    Some(MutexGuard::map(
-        INTERNAL_EVENT_READER.try_lock_for(duration)?,
-        |reader| reader.get_or_insert_with(InternalEventReader::default),
-    ))
+fn internal_event_reader() -> &'static InternalEventReader {
+    INTERNAL_EVENT_READER.get_or_init(InternalEventReader::default)
 }
 
 /// Checks if there is an [`Event`](enum.Event.html) available.
@@ -146,157 +200,1398 @@ fn try_lock_internal_event_reader_for(
 /// ```no_run
 /// use std::time::Duration;
 ///
-/// use crossterm::{event::poll, Result};
+/// use crossterm::{event::poll, Result};
+///
+/// fn is_event_available() -> Result<bool> {
+///     // Wait for an `Event` availability for 100ms. It returns immediately
+///     // if an `Event` is/becomes available.
+///     poll(Duration::from_millis(100))
+/// }
+/// ```
+pub fn poll(timeout: Duration) -> Result<bool> {
+    poll_internal(Some(timeout), &EventFilter)
+}
+
+/// Checks if there is an [`Event`](enum.Event.html) available, waits until the given
+/// deadline.
+///
+/// It's the [`poll`](fn.poll.html) function taking the instant instead of the duration.
+/// The loops waiting for the events and the other things (timers, frames) don't accumulate
+/// the drift of the conversions between the instants and the durations.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::{Duration, Instant};
+///
+/// use crossterm::{
+///     event::{poll_deadline, read},
+///     Result,
+/// };
+///
+/// fn handle_events_for_frame() -> Result<()> {
+///     // All the events read before the end of the frame
+///     let frame_end = Instant::now() + Duration::from_millis(16);
+///     while poll_deadline(frame_end)? {
+///         println!("{:?}", read()?);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn poll_deadline(deadline: Instant) -> Result<bool> {
+    internal_event_reader().poll_until(deadline, &EventFilter)
+}
+
+/// Reads a single [`Event`](enum.Event.html).
+///
+/// This function blocks until an [`Event`](enum.Event.html) is available. Combine it with the
+/// [`poll`](fn.poll.html) function to get non-blocking reads.
+///
+/// # Examples
+///
+/// Blocking read:
+///
+/// ```no_run
+/// use crossterm::{event::read, Result};
+///
+/// fn print_events() -> Result<bool> {
+///     loop {
+///         // Blocks until an `Event` is available
+///         println!("{:?}", read()?);
+///     }
+/// }
+/// ```
+///
+/// Non-blocking read:
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::{event::{read, poll}, Result};
+///
+/// fn print_events() -> Result<bool> {
+///     loop {
+///         if poll(Duration::from_millis(100))? {
+///             // It's guaranteed that `read` wont block, because `poll` returned
+///             // `Ok(true)`.
+///             println!("{:?}", read()?);
+///         } else {
+///             // Timeout expired, no `Event` is available
+///         }
+///     }
+/// }
+/// ```
+pub fn read() -> Result<Event> {
+    match read_internal(&EventFilter)? {
+        InternalEvent::Event(event) => Ok(event),
+        #[cfg(unix)]
+        _ => unreachable!(),
+    }
+}
+
+/// Reads a single [`Event`](enum.Event.html) along with the time it was received.
+///
+/// The timestamp is taken from the monotonic clock as soon as the event is read from the
+/// terminal (all the events read at once share it), not when it's returned by this
+/// function. It allows to measure the input latency, to tell the double clicks apart or
+/// to replay the input with the original timing.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::event::read_with_timestamp;
+///
+/// fn print_latency() -> crossterm::Result<()> {
+///     let (event, received) = read_with_timestamp()?;
+///     // Handle the event
+///     println!("{:?} handled after {:?}", event, received.elapsed());
+///     Ok(())
+/// }
+/// ```
+pub fn read_with_timestamp() -> Result<(Event, Instant)> {
+    match internal_event_reader().read_timed(&EventFilter)? {
+        (InternalEvent::Event(event), received) => Ok((event, received)),
+        #[cfg(unix)]
+        _ => unreachable!(),
+    }
+}
+
+/// Reads a single [`Event`](enum.Event.html), waits up to the given duration.
+///
+/// Returns `Ok(None)` if no [`Event`](enum.Event.html) is available within the duration.
+/// The deadline is fixed when the function is called, the internal retries (interrupted
+/// system calls, incomplete escape sequences, another thread reading the event in
+/// between) don't extend it, see the [`read_deadline`](fn.read_deadline.html).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::event::read_timeout;
+///
+/// fn tick() -> crossterm::Result<()> {
+///     match read_timeout(Duration::from_millis(250))? {
+///         Some(event) => println!("{:?}", event),
+///         None => println!("tick"),
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn read_timeout(timeout: Duration) -> Result<Option<Event>> {
+    match Instant::now().checked_add(timeout) {
+        Some(deadline) => read_deadline(deadline),
+        // Too far away to be represented
+        None => read().map(Some),
+    }
+}
+
+/// Reads a single [`Event`](enum.Event.html), waits until the given deadline.
+///
+/// Returns `Ok(None)` if no [`Event`](enum.Event.html) is available before the deadline.
+/// Unlike the [`poll_deadline`](fn.poll_deadline.html) followed by the [`read`](fn.read.html),
+/// it doesn't block past the deadline if another thread reads the event in between.
+pub fn read_deadline(deadline: Instant) -> Result<Option<Event>> {
+    let reader = internal_event_reader();
+
+    loop {
+        if !reader.poll_until(deadline, &EventFilter)? {
+            return Ok(None);
+        }
+
+        match reader.take(&EventFilter) {
+            Some(InternalEvent::Event(event)) => return Ok(Some(event)),
+            #[cfg(unix)]
+            Some(_) => unreachable!(),
+            None => {}
+        }
+    }
+}
+
+/// Returns the next [`Event`](enum.Event.html) without consuming it.
+///
+/// This function blocks until an [`Event`](enum.Event.html) is available, like the
+/// [`read`](fn.read.html) function, but the event is left in the queue and returned by the
+/// next [`read`](fn.read.html) call. It allows an input router to decide which part of the
+/// application should consume the event.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{
+///     event::{peek, read, Event},
+///     Result,
+/// };
+///
+/// fn handle_mouse_events() -> Result<()> {
+///     while let Event::Mouse(event) = peek()? {
+///         // The event is consumed only if it's a mouse event
+///         read()?;
+///         println!("{:?}", event);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn peek() -> Result<Event> {
+    let reader = internal_event_reader();
+
+    loop {
+        if reader.poll(None, &EventFilter)? {
+            if let Some(InternalEvent::Event(event)) = reader.peek(&EventFilter) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+/// Returns the next [`Event`](enum.Event.html) without consuming it, waits up to the given
+/// duration.
+///
+/// Returns `Ok(None)` if no [`Event`](enum.Event.html) is available within the duration,
+/// see the [`peek`](fn.peek.html) function.
+pub fn peek_timeout(timeout: Duration) -> Result<Option<Event>> {
+    let reader = internal_event_reader();
+
+    if !reader.poll(Some(timeout), &EventFilter)? {
+        return Ok(None);
+    }
+
+    match reader.peek(&EventFilter) {
+        Some(InternalEvent::Event(event)) => Ok(Some(event)),
+        _ => Ok(None),
+    }
+}
+
+/// Reads all the available [`Event`](enum.Event.html)s.
+///
+/// This function blocks until an [`Event`](enum.Event.html) is available, like the
+/// [`read`](fn.read.html) function, and then drains all the events queued at the moment.
+/// It allows to process a burst of events (e.g. pasted text, fast mouse moves) and redraw
+/// the screen just once.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{event::read_batch, Result};
+///
+/// fn print_events() -> Result<bool> {
+///     loop {
+///         for event in read_batch()? {
+///             println!("{:?}", event);
+///         }
+///         // Redraw the screen
+///     }
+/// }
+/// ```
+pub fn read_batch() -> Result<Vec<Event>> {
+    let reader = internal_event_reader();
+    let first = reader.read(&EventFilter)?;
+    let ready = reader.read_ready(&EventFilter)?;
+
+    Ok(std::iter::once(first)
+        .chain(ready)
+        .map(|event| match event {
+            InternalEvent::Event(event) => event,
+            #[cfg(unix)]
+            _ => unreachable!(),
+        })
+        .collect())
+}
+
+/// Coalesces the bursts of the [`Event::Resize`](enum.Event.html#variant.Resize)s, e.g. while
+/// the window is dragged, into a single event carrying the final size.
+///
+/// The resize event is delivered once no other resize event is received within the given
+/// time, the earlier events of the burst are dropped. The other events aren't delayed, they
+/// can be delivered before the resize event received earlier. Zero turns the coalescing
+/// off, the resize event waiting for the end of the burst is delivered right away.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use crossterm::{
+///     event::{self, Event},
+///     Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     // Redraw once the user stops dragging the window
+///     event::set_resize_coalescing(Duration::from_millis(100));
+///
+///     loop {
+///         if let Event::Resize(columns, rows) = event::read()? {
+///             println!("Redraw to {}x{}", columns, rows);
+///         }
+///     }
+/// }
+/// ```
+pub fn set_resize_coalescing(window: Duration) {
+    internal_event_reader().set_resize_coalescing(window)
+}
+
+/// Marks the kinds of the events delivered ahead of the other events waiting in the queue.
+///
+/// The events of the given kinds skip the queued events of the other kinds, e.g. a resize
+/// event isn't stuck behind a flood of the mouse moves, the layout changes stay responsive.
+/// The priority events are delivered in the order they were received. None of the kinds
+/// are marked by default, all the events are delivered in the order they were received.
+///
+/// # Notes
+///
+/// * It applies to the events received from now on, the queued events aren't reordered.
+/// * The coalesced resize events (see the [set_resize_coalescing](fn.set_resize_coalescing.html))
+///   are queued once the burst is over.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::event::{self, EventKinds};
+///
+/// event::set_priority_events(EventKinds::RESIZE | EventKinds::INTERRUPT);
+/// ```
+pub fn set_priority_events(kinds: EventKinds) {
+    internal_event_reader().set_priority_events(kinds)
+}
+
+/// Tears down the reading of the events, so a library embedding crossterm can initialize
+/// it again later within the same process.
+///
+/// * The terminal (or the [custom event source](fn.set_event_source.html)) is closed, the
+///   blocked [read](fn.read.html)/[poll](fn.poll.html) is interrupted.
+/// * The events which weren't read yet are dropped, the
+///   [subscribers](fn.subscribe.html) are disconnected.
+/// * The [registered signals](fn.register_signal.html) terminate the process again, the
+///   [registered file descriptors](fn.register_fd.html) are forgotten.
+///
+/// The terminal is opened again by the next `read`/`poll`.
+///
+/// # Notes
+///
+/// Close the `EventStream`s with the `EventStream::close` first, a pending stream keeps
+/// reading the events (and opens the terminal again).
+pub fn shutdown() -> Result<()> {
+    if let Some(reader) = INTERNAL_EVENT_READER.get() {
+        reader.shutdown()?;
+    }
+
+    #[cfg(unix)]
+    {
+        sys::unix::user_fd::forget_user_fds();
+        sys::unix::signal::restore_signals()?;
+    }
+    Ok(())
+}
+
+/// Polls to check if there are any `InternalEvent`s that can be read within the given duration.
+pub(crate) fn poll_internal<F>(timeout: Option<Duration>, filter: &F) -> Result<bool>
+where
+    F: Filter,
+{
+    internal_event_reader().poll(timeout, filter)
+}
+
+/// Polls to check if there are any `InternalEvent`s that can be read before the deadline.
+#[cfg(unix)]
+pub(crate) fn poll_internal_until<F>(deadline: Instant, filter: &F) -> Result<bool>
+where
+    F: Filter,
+{
+    internal_event_reader().poll_until(deadline, filter)
+}
+
+/// Reads a single `InternalEvent`.
+pub(crate) fn read_internal<F>(filter: &F) -> Result<InternalEvent>
+where
+    F: Filter,
+{
+    internal_event_reader().read(filter)
+}
+
+/// Whether Ctrl-C should be delivered as `Event::Interrupt`.
+static INTERRUPT_EVENTS: AtomicBool = AtomicBool::new(false);
+
+/// Whether Ctrl-C sent a signal before the `enable_interrupt_events`.
+static INTERRUPT_SIGNAL_BEFORE: AtomicBool = AtomicBool::new(true);
+
+/// Enables delivery of Ctrl-C as an [`Event::Interrupt`](enum.Event.html#variant.Interrupt)
+/// instead of a signal.
+///
+/// The process is not terminated when the user presses Ctrl-C, the interrupt is delivered
+/// through the normal event queue instead. Apps can use it to prompt to save the work
+/// before exiting.
+///
+/// # Notes
+///
+/// * On UNIX, the `ISIG` flag is kept off, even if the raw mode is not enabled. It means
+///   that the other signal generating keys (Ctrl-\ and Ctrl-Z) are delivered as key events
+///   as well.
+/// * On Windows, the `ENABLE_PROCESSED_INPUT` console mode is kept off and a console control
+///   handler turning `CTRL_C_EVENT` into an `Event::Interrupt` is registered.
+/// * Call [`disable_interrupt_events`](fn.disable_interrupt_events.html) to restore the
+///   previous behavior.
+pub fn enable_interrupt_events() -> Result<()> {
+    // The state saved by the first call is kept
+    if INTERRUPT_EVENTS.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    match crate::terminal::sys::set_interrupt_signal(false) {
+        Ok(signal_before) => {
+            INTERRUPT_SIGNAL_BEFORE.store(signal_before, Ordering::SeqCst);
+            Ok(())
+        }
+        Err(e) => {
+            INTERRUPT_EVENTS.store(false, Ordering::SeqCst);
+            Err(e)
+        }
+    }
+}
+
+/// Disables delivery of Ctrl-C as an [`Event::Interrupt`](enum.Event.html#variant.Interrupt).
+///
+/// Ctrl-C behaves as it did before the
+/// [`enable_interrupt_events`](fn.enable_interrupt_events.html): it sends a signal again
+/// unless the signals were turned off already (or produces a key event in the raw mode).
+pub fn disable_interrupt_events() -> Result<()> {
+    if !is_interrupt_events_enabled() {
+        return Ok(());
+    }
+
+    crate::terminal::sys::set_interrupt_signal(INTERRUPT_SIGNAL_BEFORE.load(Ordering::SeqCst))?;
+    INTERRUPT_EVENTS.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Returns whether Ctrl-C is delivered as an [`Event::Interrupt`](enum.Event.html#variant.Interrupt).
+pub fn is_interrupt_events_enabled() -> bool {
+    INTERRUPT_EVENTS.load(Ordering::SeqCst)
+}
+
+/// Registers the signal to be delivered as an [`Event::Signal`](enum.Event.html#variant.Signal).
+///
+/// Registered signals are received in the same loop as the other events, via the
+/// [read](./fn.read.html)/[poll](./fn.poll.html) functions or the `EventStream`.
+///
+/// # Notes
+///
+/// * The default action of the signal (process termination) is not performed until the
+///   [shutdown](fn.shutdown.html).
+/// * Signals are not supported on Windows, an error is returned.
+pub fn register_signal(signal: Signal) -> Result<()> {
+    #[cfg(unix)]
+    return sys::unix::signal::register_signal(signal);
+
+    #[cfg(windows)]
+    return Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{:?} signal is not supported on Windows", signal),
+    ));
+}
+
+/// How the stop of the process (`SIGTSTP`) is handled, see the
+/// [enable_suspend_events](fn.enable_suspend_events.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SuspendHandling {
+    /// The [`Event::Suspended`](enum.Event.html#variant.Suspended) is delivered, the process
+    /// isn't stopped. The application restores the terminal and stops itself with the
+    /// [suspend_process](../terminal/fn.suspend_process.html).
+    Notify,
+    /// The terminal is restored and the process is stopped right away, the terminal modes
+    /// are put back when it's continued. The [`Event::Suspended`](enum.Event.html#variant.Suspended)
+    /// is delivered after the continuation, followed by a resize event to redraw the screen.
+    RestoreTerminal,
+}
+
+/// Enables delivery of the stop (`SIGTSTP`) and the continuation (`SIGCONT`) of the process
+/// as the [`Event::Suspended`](enum.Event.html#variant.Suspended) and the
+/// [`Event::Resumed`](enum.Event.html#variant.Resumed).
+///
+/// The stop is handled as specified by the `handling`. With the
+/// [`SuspendHandling::RestoreTerminal`](enum.SuspendHandling.html#variant.RestoreTerminal)
+/// the raw mode is left and the main screen is restored (like the
+/// [suspend_to](../terminal/fn.suspend_to.html) does) while the process is stopped.
+///
+/// # Notes
+///
+/// * The signals are handled in the same loop as the other events, via the
+///   [read](./fn.read.html)/[poll](./fn.poll.html) functions or the `EventStream`. The
+///   process isn't stopped until the events are read.
+/// * The raw mode turns off the signal generating keys, `Ctrl+Z` is delivered as a key
+///   event. Call the [suspend_process](../terminal/fn.suspend_process.html) to stop the
+///   process on it.
+/// * The events are not supported on Windows, an error is returned.
+pub fn enable_suspend_events(handling: SuspendHandling) -> Result<()> {
+    #[cfg(unix)]
+    return sys::unix::signal::enable_suspend_signals(handling);
+
+    #[cfg(windows)]
+    return Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "{:?} suspend handling is not supported on Windows",
+            handling
+        ),
+    ));
+}
+
+/// Disables delivery of the [`Event::Suspended`](enum.Event.html#variant.Suspended) and the
+/// [`Event::Resumed`](enum.Event.html#variant.Resumed), `SIGTSTP` stops the process again.
+pub fn disable_suspend_events() -> Result<()> {
+    #[cfg(unix)]
+    return sys::unix::signal::disable_suspend_signals();
+
+    #[cfg(windows)]
+    Ok(())
+}
+
+/// Registers the file descriptor (socket, pipe, timerfd, ...) to be polled along with the terminal.
+///
+/// Readiness of the file descriptor is delivered as an [`Event::Fd`](enum.Event.html#variant.Fd)
+/// with the given `token`. Single threaded applications can multiplex the terminal and other I/O
+/// without a second event loop. If the file descriptor is already registered, its token and interest
+/// are updated.
+///
+/// # Notes
+///
+/// * The readiness is edge-triggered. The file descriptor must be read (written) until it returns
+///   `WouldBlock`, otherwise no other event is delivered.
+/// * The file descriptor must stay open until it's deregistered with
+///   [`deregister_fd`](fn.deregister_fd.html).
+#[cfg(unix)]
+pub fn register_fd(fd: std::os::unix::io::RawFd, token: Token, interest: Readiness) -> Result<()> {
+    sys::unix::user_fd::register_fd(fd, token, interest)
+}
+
+/// Deregisters the file descriptor registered with [`register_fd`](fn.register_fd.html).
+#[cfg(unix)]
+pub fn deregister_fd(fd: std::os::unix::io::RawFd) -> Result<()> {
+    sys::unix::user_fd::deregister_fd(fd)
+}
+
+/// The input the events are read from, see the [set_input](fn.set_input.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputSource {
+    /// The terminal, the stdin if it's a terminal or the `/dev/tty` otherwise (the default).
+    ///
+    /// The console input buffer (`CONIN$`) on Windows.
+    Tty,
+    /// The file descriptor of the terminal opened by the application, it isn't closed.
+    #[cfg(unix)]
+    Fd(std::os::unix::io::RawFd),
+    /// The console input handle opened by the application (e.g. with the
+    /// `CreateFile("CONIN$")`), it isn't closed.
+    #[cfg(windows)]
+    Handle(std::os::windows::io::RawHandle),
+}
+
+/// Sets the input the events are read from.
+///
+/// The terminal is read even if the stdin is redirected (e.g. `my-tui < data.txt`), the
+/// `/dev/tty` is opened then. The file descriptor (the console input handle on Windows)
+/// allows to read another terminal, e.g. the one the program was given by its parent. The
+/// raw mode, the mouse capture and the queries (like the `cursor::position`) use the input
+/// too.
+///
+/// The event source is opened again if it's opened already, the events read already are
+/// kept. It replaces the [custom event source](fn.set_event_source.html) too.
+///
+/// # Notes
+///
+/// * Set the input before the raw mode is enabled, the raw mode is disabled for the input
+///   set at the moment.
+/// * The file descriptor (the handle) must stay open while the events are read.
+pub fn set_input(input: InputSource) -> Result<()> {
+    #[cfg(unix)]
+    crate::terminal::sys::file_descriptor::set_input_fd(match input {
+        InputSource::Tty => None,
+        InputSource::Fd(fd) => Some(fd),
+    });
+    #[cfg(windows)]
+    crate::terminal::sys::set_input_handle(match input {
+        InputSource::Tty => None,
+        InputSource::Handle(handle) => Some(handle),
+    });
+
+    match INTERNAL_EVENT_READER.get() {
+        Some(reader) => reader.reopen_terminal_source(),
+        None => Ok(()),
+    }
+}
+
+/// The number of `EnableMouseCapture` commands that weren't balanced by `DisableMouseCapture` yet.
+static MOUSE_CAPTURE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of `EnableMouseCapture` commands that weren't balanced yet.
+pub(crate) fn mouse_capture_count() -> usize {
+    MOUSE_CAPTURE_COUNT.load(Ordering::SeqCst)
+}
+
+/// Returns `true` if the `EnableMouseCapture` should enable the capture.
+fn should_enable_mouse_capture() -> bool {
+    mouse_capture_count() == 0
+}
+
+/// Returns `true` if the `DisableMouseCapture` should disable the capture.
+///
+/// Unbalanced calls (counter is already zero) disable the capture too, the application
+/// may have enabled it without crossterm.
+fn should_disable_mouse_capture() -> bool {
+    mouse_capture_count() <= 1
+}
+
+/// Increments the mouse capture counter.
+fn acquire_mouse_capture() {
+    MOUSE_CAPTURE_COUNT.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Decrements the mouse capture counter, unless it's already zero.
+fn release_mouse_capture() {
+    let _ =
+        MOUSE_CAPTURE_COUNT.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+}
+
+/// Which mouse motion is reported by the mouse capture.
+///
+/// See [`SetMouseCaptureMode`](struct.SetMouseCaptureMode.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseCaptureMode {
+    /// The button presses & releases and the scrolling only (mode 1000).
+    Buttons,
+    /// The [`MouseEventKind::Drag`](enum.MouseEventKind.html#variant.Drag) events (the
+    /// motion with a button held) too (mode 1002).
+    Drag,
+    /// The [`MouseEventKind::Moved`](enum.MouseEventKind.html#variant.Moved) events (the
+    /// motion with no button held) too (mode 1003). It's the default.
+    AllMotion,
+}
+
+impl MouseCaptureMode {
+    /// Returns the tracked DEC private mode of this mode, empty for the default one.
+    pub(crate) fn dec_mode(self) -> DecModes {
+        match self {
+            MouseCaptureMode::Buttons => DecModes::MOUSE_BUTTONS_ONLY,
+            MouseCaptureMode::Drag => DecModes::MOUSE_DRAG_ONLY,
+            MouseCaptureMode::AllMotion => DecModes::empty(),
+        }
+    }
+
+    /// Returns the mode of the tracked DEC private modes.
+    pub(crate) fn from_dec_modes(modes: DecModes) -> MouseCaptureMode {
+        if modes.contains(DecModes::MOUSE_BUTTONS_ONLY) {
+            MouseCaptureMode::Buttons
+        } else if modes.contains(DecModes::MOUSE_DRAG_ONLY) {
+            MouseCaptureMode::Drag
+        } else {
+            MouseCaptureMode::AllMotion
+        }
+    }
+
+    /// Returns the sequence enabling the tracking of this mode.
+    fn enable_ansi(self) -> &'static str {
+        match self {
+            MouseCaptureMode::Buttons => csi!("?1000h"),
+            MouseCaptureMode::Drag => concat!(csi!("?1000h"), csi!("?1002h")),
+            MouseCaptureMode::AllMotion => {
+                concat!(csi!("?1000h"), csi!("?1002h"), csi!("?1003h"))
+            }
+        }
+    }
+
+    /// Returns the sequence switching the enabled tracking to this mode.
+    fn switch_ansi(self) -> &'static str {
+        match self {
+            MouseCaptureMode::Buttons => concat!(csi!("?1003l"), csi!("?1002l")),
+            MouseCaptureMode::Drag => concat!(csi!("?1003l"), csi!("?1002h")),
+            MouseCaptureMode::AllMotion => concat!(csi!("?1002h"), csi!("?1003h")),
+        }
+    }
+}
+
+/// Returns which mouse motion is reported by the mouse capture.
+pub fn mouse_capture_mode() -> MouseCaptureMode {
+    MouseCaptureMode::from_dec_modes(snapshot::dec_modes())
+}
+
+/// Tracks the mode set with the `SetMouseCaptureMode` command.
+fn set_mouse_capture_mode(mode: MouseCaptureMode) {
+    snapshot::set_dec_mode(
+        DecModes::MOUSE_BUTTONS_ONLY | DecModes::MOUSE_DRAG_ONLY,
+        false,
+    );
+    snapshot::set_dec_mode(mode.dec_mode(), true);
+}
+
+/// A command that sets which mouse motion is reported by the mouse capture.
+///
+/// It applies to the mouse capture which is already enabled and to the later
+/// [`EnableMouseCapture`](struct.EnableMouseCapture.html) too. The mouse capture reports all
+/// the motion ([`MouseCaptureMode::AllMotion`](enum.MouseCaptureMode.html#variant.AllMotion))
+/// by default.
+///
+/// # Notes
+///
+/// The Windows console reports all the motion, the events not reported by the mode are
+/// dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+///
+/// use crossterm::{
+///     event::{EnableMouseCapture, MouseCaptureMode, SetMouseCaptureMode},
+///     execute,
+/// };
+///
+/// // A hover-less UI doesn't need the flood of the `Moved` events
+/// execute!(stdout(), SetMouseCaptureMode(MouseCaptureMode::Drag), EnableMouseCapture)?;
+/// # Ok::<(), crossterm::ErrorKind>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetMouseCaptureMode(pub MouseCaptureMode);
+
+impl Command for SetMouseCaptureMode {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if mouse_capture_count() > 0 {
+            f.write_str(self.0.switch_ansi())?;
+        }
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn update_state(&self) {
+        set_mouse_capture_mode(self.0);
+    }
+}
+
+/// A command that enables mouse event capturing.
+///
+/// Mouse events can be captured with [read](./fn.read.html)/[poll](./fn.poll.html). The motion
+/// is reported according to the [`mouse_capture_mode`](fn.mouse_capture_mode.html).
+///
+/// # Notes
+///
+/// Mouse capture is reference counted. Only the first `EnableMouseCapture` enables it and
+/// only the matching (last) [`DisableMouseCapture`](struct.DisableMouseCapture.html) disables
+/// it again. A widget can enable & disable the mouse capture without breaking the application
+/// which already had it enabled.
+///
+/// On the legacy Windows console the Quick Edit mode is disabled while the mouse is captured
+/// and restored afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableMouseCapture;
+
+impl Command for EnableMouseCapture {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if !should_enable_mouse_capture() {
+            return Ok(());
+        }
+
+        // Normal tracking (1000): Send mouse X & Y on button press and release
+        // Button-event tracking (1002): Report button motion events (dragging)
+        // Any-event tracking (1003): Report all motion events
+        f.write_str(mouse_capture_mode().enable_ansi())?;
+        f.write_str(concat!(
+            // RXVT mouse mode: Allows mouse coordinates of >223
+            csi!("?1015h"),
+            // SGR mouse mode: Allows mouse coordinates of >223, preferred over RXVT mode
+            csi!("?1006h"),
+        ))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        if !should_enable_mouse_capture() {
+            return Ok(());
+        }
+
+        sys::windows::enable_mouse_capture()
+    }
+
+    fn update_state(&self) {
+        acquire_mouse_capture();
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+/// A command that disables mouse event capturing.
+///
+/// Mouse events can be captured with [read](./fn.read.html)/[poll](./fn.poll.html).
+///
+/// # Notes
+///
+/// The mouse capture is disabled only if this command balances the last outstanding
+/// [`EnableMouseCapture`](struct.EnableMouseCapture.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisableMouseCapture;
+
+impl Command for DisableMouseCapture {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if !should_disable_mouse_capture() {
+            return Ok(());
+        }
+
+        f.write_str(concat!(
+            // The inverse commands of EnableMouseCapture, in reverse order.
+            csi!("?1006l"),
+            csi!("?1015l"),
+            csi!("?1003l"),
+            csi!("?1002l"),
+            csi!("?1000l"),
+        ))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        if !should_disable_mouse_capture() {
+            return Ok(());
+        }
+
+        sys::windows::disable_mouse_capture()
+    }
+
+    fn update_state(&self) {
+        release_mouse_capture();
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+/// Returns whether the mouse events are reported in pixels (SGR-Pixel mode).
+#[cfg(unix)]
+pub(crate) fn is_pixel_mouse_capture_enabled() -> bool {
+    snapshot::is_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE)
+}
+
+/// A command that enables mouse event capturing with the pixel coordinates (SGR-Pixel mode).
+///
+/// Mouse events are delivered as the
+/// [`Event::PixelMouse`](enum.Event.html#variant.PixelMouse) with the position in pixels
+/// instead of the cells.
+///
+/// # Notes
+///
+/// * It should be paired with [`DisablePixelMouseCapture`](struct.DisablePixelMouseCapture.html)
+///   at the end of execution.
+/// * It enables the mouse capture like the
+///   [`EnableMouseCapture`](struct.EnableMouseCapture.html) does, the terminals which don't
+///   support the SGR-Pixel mode (e.g. kitty and WezTerm do) report the cells.
+/// * This command is not supported on Windows, the console reports the cells only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnablePixelMouseCapture;
+
+impl Command for EnablePixelMouseCapture {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        EnableMouseCapture.write_ansi(f)?;
+        // SGR-Pixel mouse mode: Reports the position in pixels, replaces the SGR mode
+        f.write_str(csi!("?1016h"))
+    }
+
+    fn update_state(&self) {
+        EnableMouseCapture.update_state();
+        snapshot::set_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE, true);
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Err(pixel_mouse_capture_unsupported_error())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+/// A command that disables mouse event capturing with the pixel coordinates.
+///
+/// See [`EnablePixelMouseCapture`](struct.EnablePixelMouseCapture.html). The mouse capture
+/// stays enabled (in the SGR mode) if another
+/// [`EnableMouseCapture`](struct.EnableMouseCapture.html) wasn't balanced yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisablePixelMouseCapture;
+
+impl Command for DisablePixelMouseCapture {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?1016l"))?;
+        DisableMouseCapture.write_ansi(f)?;
+        if !should_disable_mouse_capture() {
+            // Resetting the SGR-Pixel mode doesn't restore the SGR mode
+            f.write_str(csi!("?1006h"))?;
+        }
+        Ok(())
+    }
+
+    fn update_state(&self) {
+        DisableMouseCapture.update_state();
+        snapshot::set_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE, false);
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Err(pixel_mouse_capture_unsupported_error())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(windows)]
+fn pixel_mouse_capture_unsupported_error() -> crate::ErrorKind {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Pixel mouse capture is not implemented for the Windows API.",
+    )
+}
+
+/// A command that enables the bracketed paste mode.
+///
+/// The terminal wraps the pasted text in the `CSI 200~` and `CSI 201~` sequences, it's
+/// delivered as a single [`Event::Paste`](enum.Event.html#variant.Paste) instead of the
+/// key events.
+///
+/// # Notes
+///
+/// * It should be paired with [`DisableBracketedPaste`](struct.DisableBracketedPaste.html)
+///   at the end of execution.
+/// * This command is not supported on Windows, the console input isn't read as the escape
+///   sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableBracketedPaste;
+
+impl Command for EnableBracketedPaste {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2004h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Err(bracketed_paste_unsupported_error())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::BRACKETED_PASTE, true);
+    }
+}
+
+/// A command that disables the bracketed paste mode.
+///
+/// See [`EnableBracketedPaste`](struct.EnableBracketedPaste.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisableBracketedPaste;
+
+impl Command for DisableBracketedPaste {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2004l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Err(bracketed_paste_unsupported_error())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::BRACKETED_PASTE, false);
+    }
+}
+
+/// A command that enables the in-band resize notifications (mode 2048).
+///
+/// The terminal reports its size in the input stream, the resize events are delivered
+/// where the `SIGWINCH` never arrives, e.g. over the serial links. Each report is delivered
+/// as an [`Event::Resize`](enum.Event.html#variant.Resize) followed by an
+/// [`Event::PixelResize`](enum.Event.html#variant.PixelResize) if the terminal knows the
+/// size in pixels. The current size is reported right after the mode is enabled.
+///
+/// # Notes
+///
+/// * It should be paired with [`DisableInBandResize`](struct.DisableInBandResize.html)
+///   at the end of execution.
+/// * The `SIGWINCH` is still delivered where it arrives, the resize events can come twice
+///   (see the [set_resize_coalescing](fn.set_resize_coalescing.html)).
+/// * Not all the terminals support it, it's ignored by the others.
+/// * This command does nothing on Windows, the console reports the resizes on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableInBandResize;
+
+impl Command for EnableInBandResize {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2048h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::IN_BAND_RESIZE, true);
+    }
+}
+
+/// A command that disables the in-band resize notifications.
+///
+/// See [`EnableInBandResize`](struct.EnableInBandResize.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisableInBandResize;
+
+impl Command for DisableInBandResize {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2048l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::IN_BAND_RESIZE, false);
+    }
+}
+
+/// A command that enables the color scheme change notifications (mode 2031).
+///
+/// The terminal reports the switch between the dark and the light color schemes, e.g.
+/// when the OS toggles the dark mode, as an
+/// [`Event::ThemeChanged`](enum.Event.html#variant.ThemeChanged). The application can
+/// switch its palette then.
+///
+/// # Notes
+///
+/// * It should be paired with [`DisableThemeChangeEvents`](struct.DisableThemeChangeEvents.html)
+///   at the end of execution.
+/// * Only the changes are reported, not the current color scheme.
+/// * Not all the terminals support it, it's ignored by the others.
+/// * This command does nothing on Windows, the console doesn't report the color scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableThemeChangeEvents;
+
+impl Command for EnableThemeChangeEvents {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2031h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::THEME_CHANGE_EVENTS, true);
+    }
+}
+
+/// A command that disables the color scheme change notifications.
+///
+/// See [`EnableThemeChangeEvents`](struct.EnableThemeChangeEvents.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisableThemeChangeEvents;
+
+impl Command for DisableThemeChangeEvents {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2031l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::THEME_CHANGE_EVENTS, false);
+    }
+}
+
+#[cfg(windows)]
+fn bracketed_paste_unsupported_error() -> crate::ErrorKind {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Bracketed paste is not implemented for the Windows API.",
+    )
+}
+
+/// The flags of the `PushKeyboardEnhancementFlags` commands that weren't popped yet.
+static KEYBOARD_ENHANCEMENT_FLAGS: Mutex<Vec<KeyboardEnhancementFlags>> =
+    parking_lot::const_mutex(Vec::new());
+
+/// Returns the keyboard enhancement flags pushed last, empty if there are none.
+pub(crate) fn keyboard_enhancement_flags() -> KeyboardEnhancementFlags {
+    KEYBOARD_ENHANCEMENT_FLAGS
+        .lock()
+        .last()
+        .copied()
+        .unwrap_or_else(KeyboardEnhancementFlags::empty)
+}
+
+/// Returns the keyboard enhancement flags that weren't popped yet, the first pushed first.
+pub(crate) fn pushed_keyboard_enhancement_flags() -> Vec<KeyboardEnhancementFlags> {
+    KEYBOARD_ENHANCEMENT_FLAGS.lock().clone()
+}
+
+bitflags! {
+    /// Represents special flags that tell compatible terminals to add extra information to keyboard events.
+    ///
+    /// See <https://sw.kovidgoyal.net/kitty/keyboard-protocol/#progressive-enhancement> for more information.
+    pub struct KeyboardEnhancementFlags: u8 {
+        /// Represent Escape and modified keys using CSI-u sequences, so they can be unambiguously
+        /// read.
+        const DISAMBIGUATE_ESCAPE_CODES = 0b0000_0001;
+        /// Add extra events with `KeyEvent.kind` set to `KeyEventKind::Repeat` or
+        /// `KeyEventKind::Release` when keys are autorepeated or released.
+        const REPORT_EVENT_TYPES = 0b0000_0010;
+        /// Send alternate keycodes in addition to the base keycode.
+        const REPORT_ALTERNATE_KEYS = 0b0000_0100;
+        /// Represent all keyboard events as CSI-u sequences. This is required to get repeat/release
+        /// events for plain-text keys.
+        const REPORT_ALL_KEYS_AS_ESCAPE_CODES = 0b0000_1000;
+    }
+}
+
+/// A command that enables the [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/),
+/// which adds extra information to keyboard events and removes ambiguity for modifier keys.
+///
+/// It should be paired with [`PopKeyboardEnhancementFlags`](struct.PopKeyboardEnhancementFlags.html)
+/// at the end of execution. Pushed flags are counted, the ones that are still outstanding can be
+/// popped at once with [`PopAllKeyboardEnhancementFlags`](struct.PopAllKeyboardEnhancementFlags.html).
+///
+/// # Notes
+///
+/// * The Windows console input reports the key releases and the repeats natively, they're
+///   delivered if the [`REPORT_EVENT_TYPES`](struct.KeyboardEnhancementFlags.html#associatedconstant.REPORT_EVENT_TYPES)
+///   flag is pushed. The other flags have no effect on Windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PushKeyboardEnhancementFlags(pub KeyboardEnhancementFlags);
+
+impl Command for PushKeyboardEnhancementFlags {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "{}{}u", csi!(">"), self.0.bits())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn update_state(&self) {
+        KEYBOARD_ENHANCEMENT_FLAGS.lock().push(self.0);
+    }
+}
+
+/// A command that disables extra kinds of keyboard events.
+///
+/// Specifically, it pops one level of keyboard enhancement flags.
+///
+/// See [`PushKeyboardEnhancementFlags`](struct.PushKeyboardEnhancementFlags.html) and
+/// <https://sw.kovidgoyal.net/kitty/keyboard-protocol/> for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PopKeyboardEnhancementFlags;
+
+impl Command for PopKeyboardEnhancementFlags {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("<1u"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn update_state(&self) {
+        KEYBOARD_ENHANCEMENT_FLAGS.lock().pop();
+    }
+}
+
+/// A command that pops all the keyboard enhancement flags pushed with
+/// [`PushKeyboardEnhancementFlags`](struct.PushKeyboardEnhancementFlags.html)
+/// that weren't popped yet.
+///
+/// Nothing is written if there are no outstanding flags. Libraries are free to push
+/// their own flags, this command makes sure none of them leak into the shell once
+/// the application is done. It's queued by the [`suspend_to`](../terminal/fn.suspend_to.html)
+/// and the [exit guard](../terminal/fn.enable_exit_guard.html) too.
 ///
-/// fn is_event_available() -> Result<bool> {
-///     // Wait for an `Event` availability for 100ms. It returns immediately
-///     // if an `Event` is/becomes available.
-///     poll(Duration::from_millis(100))
-/// }
-/// ```
-pub fn poll(timeout: Duration) -> Result<bool> {
-    poll_internal(Some(timeout), &EventFilter)
+/// The flags are counted when the commands are queued or executed, not when they're only
+/// formatted (e.g. with the `Display`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PopAllKeyboardEnhancementFlags;
+
+impl Command for PopAllKeyboardEnhancementFlags {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let count = KEYBOARD_ENHANCEMENT_FLAGS.lock().len();
+        if count == 0 {
+            return Ok(());
+        }
+
+        write!(f, "{}{}u", csi!("<"), count)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn update_state(&self) {
+        KEYBOARD_ENHANCEMENT_FLAGS.lock().clear();
+    }
 }
 
-/// Reads a single [`Event`](enum.Event.html).
-///
-/// This function blocks until an [`Event`](enum.Event.html) is available. Combine it with the
-/// [`poll`](fn.poll.html) function to get non-blocking reads.
+/// The level of the xterm `modifyOtherKeys` key encoding.
 ///
-/// # Examples
+/// See [`EnableModifyOtherKeys`](struct.EnableModifyOtherKeys.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifyOtherKeysLevel {
+    /// The modified keys are reported as escape sequences unless they produce a well known
+    /// control character, e.g. `Ctrl+A`, `Ctrl+[` are still reported as `0x01`, `ESC`.
+    Level1,
+    /// All the modified keys are reported as escape sequences, including the ones producing
+    /// the control characters, e.g. `Ctrl+I` is distinguished from `Tab`.
+    Level2,
+}
+
+/// A command that enables the xterm [`modifyOtherKeys`](https://invisible-island.net/xterm/modified-keys.html)
+/// key encoding, the alternative to the [kitty keyboard protocol](struct.PushKeyboardEnhancementFlags.html)
+/// for the terminals that don't support it (xterm, older VTE, ...).
 ///
-/// Blocking read:
+/// The modified keys are reported as `CSI 27 ; modifiers ; key ~` and delivered as the
+/// same `KeyEvent`s the kitty keyboard protocol produces. The key releases and the repeats
+/// aren't reported.
 ///
-/// ```no_run
-/// use crossterm::{event::read, Result};
+/// It should be paired with [`DisableModifyOtherKeys`](struct.DisableModifyOtherKeys.html) at
+/// the end of execution.
 ///
-/// fn print_events() -> Result<bool> {
-///     loop {
-///         // Blocks until an `Event` is available
-///         println!("{:?}", read()?);
-///     }
-/// }
-/// ```
+/// # Notes
 ///
-/// Non-blocking read:
+/// * The Windows console input reports the modified keys unambiguously, the command does
+///   nothing on Windows.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableModifyOtherKeys(pub ModifyOtherKeysLevel);
+
+impl Command for EnableModifyOtherKeys {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let level = match self.0 {
+            ModifyOtherKeysLevel::Level1 => 1,
+            ModifyOtherKeysLevel::Level2 => 2,
+        };
+        write!(f, csi!(">4;{}m"), level)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn update_state(&self) {
+        let mode = match self.0 {
+            ModifyOtherKeysLevel::Level1 => DecModes::MODIFY_OTHER_KEYS_1,
+            ModifyOtherKeysLevel::Level2 => DecModes::MODIFY_OTHER_KEYS_2,
+        };
+        snapshot::set_dec_mode(
+            DecModes::MODIFY_OTHER_KEYS_1 | DecModes::MODIFY_OTHER_KEYS_2,
+            false,
+        );
+        snapshot::set_dec_mode(mode, true);
+    }
+}
+
+/// A command that restores the default xterm `modifyOtherKeys` key encoding.
 ///
-/// ```no_run
-/// use std::time::Duration;
+/// See [`EnableModifyOtherKeys`](struct.EnableModifyOtherKeys.html).
 ///
-/// use crossterm::{event::{read, poll}, Result};
+/// # Notes
 ///
-/// fn print_events() -> Result<bool> {
-///     loop {
-///         if poll(Duration::from_millis(100))? {
-///             // It's guaranteed that `read` wont block, because `poll` returned
-///             // `Ok(true)`.
-///             println!("{:?}", read()?);
-///         } else {
-///             // Timeout expired, no `Event` is available
-///         }
-///     }
-/// }
-/// ```
-pub fn read() -> Result<Event> {
-    match read_internal(&EventFilter)? {
-        InternalEvent::Event(event) => Ok(event),
-        #[cfg(unix)]
-        _ => unreachable!(),
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisableModifyOtherKeys;
+
+impl Command for DisableModifyOtherKeys {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!(">4m"))
     }
-}
 
-/// Polls to check if there are any `InternalEvent`s that can be read within the given duration.
-pub(crate) fn poll_internal<F>(timeout: Option<Duration>, filter: &F) -> Result<bool>
-where
-    F: Filter,
-{
-    let (mut reader, timeout) = if let Some(timeout) = timeout {
-        let poll_timeout = PollTimeout::new(Some(timeout));
-        if let Some(reader) = try_lock_internal_event_reader_for(timeout) {
-            (reader, poll_timeout.leftover())
-        } else {
-            return Ok(false);
-        }
-    } else {
-        (lock_internal_event_reader(), None)
-    };
-    reader.poll(timeout, filter)
-}
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
 
-/// Reads a single `InternalEvent`.
-pub(crate) fn read_internal<F>(filter: &F) -> Result<InternalEvent>
-where
-    F: Filter,
-{
-    let mut reader = lock_internal_event_reader();
-    reader.read(filter)
+    fn update_state(&self) {
+        snapshot::set_dec_mode(
+            DecModes::MODIFY_OTHER_KEYS_1 | DecModes::MODIFY_OTHER_KEYS_2,
+            false,
+        );
+    }
 }
 
-/// A command that enables mouse event capturing.
+/// A command that enables the `win32-input-mode` of the ConPTY, the key events of the
+/// Windows console are reported as `CSI Vk ; Sc ; Uc ; Kd ; Cs ; Rc _`.
 ///
-/// Mouse events can be captured with [read](./fn.read.html)/[poll](./fn.poll.html).
+/// It lets the applications running in the WSL or over the SSH under the Windows Terminal
+/// read the keys as the Windows applications do, with the
+/// [raw key codes](struct.KeyEvent.html#structfield.raw_codes) and the
+/// [physical keys](struct.KeyEvent.html#structfield.physical_key). The releases and the
+/// repeats are reported if the [`REPORT_EVENT_TYPES`](struct.KeyboardEnhancementFlags.html#associatedconstant.REPORT_EVENT_TYPES)
+/// flag is pushed, like on Windows.
+///
+/// It should be paired with [`DisableWin32InputMode`](struct.DisableWin32InputMode.html) at
+/// the end of execution.
+///
+/// # Notes
+///
+/// * The Windows applications read the console input natively, the command does nothing
+///   on Windows.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct EnableMouseCapture;
+pub struct EnableWin32InputMode;
 
-impl Command for EnableMouseCapture {
+impl Command for EnableWin32InputMode {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        f.write_str(concat!(
-            // Normal tracking: Send mouse X & Y on button press and release
-            csi!("?1000h"),
-            // Button-event tracking: Report button motion events (dragging)
-            csi!("?1002h"),
-            // Any-event tracking: Report all motion events
-            csi!("?1003h"),
-            // RXVT mouse mode: Allows mouse coordinates of >223
-            csi!("?1015h"),
-            // SGR mouse mode: Allows mouse coordinates of >223, preferred over RXVT mode
-            csi!("?1006h"),
-        ))
+        f.write_str(csi!("?9001h"))
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        sys::windows::enable_mouse_capture()
+        Ok(())
     }
 
-    #[cfg(windows)]
-    fn is_ansi_code_supported(&self) -> bool {
-        false
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::WIN32_INPUT_MODE, true);
     }
 }
 
-/// A command that disables mouse event capturing.
+/// A command that disables the `win32-input-mode` of the ConPTY.
 ///
-/// Mouse events can be captured with [read](./fn.read.html)/[poll](./fn.poll.html).
+/// See [`EnableWin32InputMode`](struct.EnableWin32InputMode.html).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct DisableMouseCapture;
+pub struct DisableWin32InputMode;
 
-impl Command for DisableMouseCapture {
+impl Command for DisableWin32InputMode {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        f.write_str(concat!(
-            // The inverse commands of EnableMouseCapture, in reverse order.
-            csi!("?1006l"),
-            csi!("?1015l"),
-            csi!("?1003l"),
-            csi!("?1002l"),
-            csi!("?1000l"),
-        ))
+        f.write_str(csi!("?9001l"))
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        sys::windows::disable_mouse_capture()
+        Ok(())
     }
 
-    #[cfg(windows)]
-    fn is_ansi_code_supported(&self) -> bool {
-        false
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::WIN32_INPUT_MODE, false);
     }
 }
 
 /// Represents an event.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Hash)]
 pub enum Event {
     /// A single key event with additional pressed modifiers.
     Key(KeyEvent),
@@ -305,6 +1600,145 @@ pub enum Event {
     /// An resize event with new dimensions after resize (columns, rows).
     /// **Note** that resize events can be occur in batches.
     Resize(u16, u16),
+    /// The user pressed Ctrl-C.
+    ///
+    /// Delivered only if enabled with [`enable_interrupt_events`](fn.enable_interrupt_events.html).
+    Interrupt,
+    /// A signal registered with [`register_signal`](fn.register_signal.html) was received.
+    ///
+    /// **Note** that it's delivered on UNIX only.
+    Signal(Signal),
+    /// A file descriptor registered with `register_fd` is ready.
+    ///
+    /// **Note** that it's delivered on UNIX only.
+    Fd(Token, Readiness),
+    /// The [read](./fn.read.html)/[poll](./fn.poll.html) was woken up by the
+    /// [`Waker`](struct.Waker.html) with the given token.
+    Wake(Token),
+    /// A string that was pasted into the terminal.
+    ///
+    /// Delivered only if the bracketed paste is enabled with
+    /// [`EnableBracketedPaste`](struct.EnableBracketedPaste.html), otherwise the pasted text
+    /// arrives as the key events.
+    Paste(String),
+    /// A mouse event with the `column` and the `row` in pixels (0-based).
+    ///
+    /// Delivered instead of the [`Event::Mouse`](enum.Event.html#variant.Mouse) if the
+    /// mouse capture is enabled with
+    /// [`EnablePixelMouseCapture`](struct.EnablePixelMouseCapture.html).
+    PixelMouse(MouseEvent),
+    /// The process was asked to stop (`SIGTSTP`, e.g. `Ctrl+Z`).
+    ///
+    /// Delivered only if enabled with [`enable_suspend_events`](fn.enable_suspend_events.html).
+    ///
+    /// **Note** that it's delivered on UNIX only.
+    Suspended,
+    /// The size of the text area in pixels (width, height).
+    ///
+    /// Delivered after the [`Event::Resize`](enum.Event.html#variant.Resize) by the in-band
+    /// resize notifications, see [`EnableInBandResize`](struct.EnableInBandResize.html).
+    PixelResize(u16, u16),
+    /// The stopped process was continued (`SIGCONT`, e.g. `fg`).
+    ///
+    /// Delivered only if enabled with [`enable_suspend_events`](fn.enable_suspend_events.html).
+    ///
+    /// **Note** that it's delivered on UNIX only.
+    Resumed,
+    /// The terminal switched to the dark or the light color scheme.
+    ///
+    /// Delivered only if enabled with
+    /// [`EnableThemeChangeEvents`](struct.EnableThemeChangeEvents.html).
+    ThemeChanged(ColorScheme),
+}
+
+impl Event {
+    /// Returns the kind of the event.
+    pub fn kind(&self) -> EventKinds {
+        match self {
+            Event::Key(_) => EventKinds::KEY,
+            Event::Mouse(_) => EventKinds::MOUSE,
+            Event::Resize(..) => EventKinds::RESIZE,
+            Event::Interrupt => EventKinds::INTERRUPT,
+            Event::Signal(_) => EventKinds::SIGNAL,
+            Event::Fd(..) => EventKinds::FD,
+            Event::Wake(_) => EventKinds::WAKE,
+            Event::Paste(_) => EventKinds::PASTE,
+            Event::PixelMouse(_) => EventKinds::PIXEL_MOUSE,
+            Event::Suspended => EventKinds::SUSPENDED,
+            Event::PixelResize(..) => EventKinds::PIXEL_RESIZE,
+            Event::Resumed => EventKinds::RESUMED,
+            Event::ThemeChanged(_) => EventKinds::THEME_CHANGED,
+        }
+    }
+}
+
+bitflags! {
+    /// Represents the kinds of the [`Event`](enum.Event.html)s, one flag per variant.
+    ///
+    /// See the [set_priority_events](fn.set_priority_events.html).
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct EventKinds: u16 {
+        const KEY = 0b0000_0000_0000_0001;
+        const MOUSE = 0b0000_0000_0000_0010;
+        const RESIZE = 0b0000_0000_0000_0100;
+        const INTERRUPT = 0b0000_0000_0000_1000;
+        const SIGNAL = 0b0000_0000_0001_0000;
+        const FD = 0b0000_0000_0010_0000;
+        const WAKE = 0b0000_0000_0100_0000;
+        const PASTE = 0b0000_0000_1000_0000;
+        const PIXEL_MOUSE = 0b0000_0001_0000_0000;
+        const SUSPENDED = 0b0000_0010_0000_0000;
+        const PIXEL_RESIZE = 0b0000_0100_0000_0000;
+        const RESUMED = 0b0000_1000_0000_0000;
+        const THEME_CHANGED = 0b0001_0000_0000_0000;
+    }
+}
+
+/// Identifies a file descriptor registered with `register_fd`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Token(pub usize);
+
+bitflags! {
+    /// Represents the readiness of a file descriptor delivered as an
+    /// [`Event::Fd`](enum.Event.html#variant.Fd).
+    ///
+    /// Only `READABLE` and `WRITABLE` can be used as an interest when registering
+    /// the file descriptor.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Readiness: u8 {
+        const READABLE = 0b0000_0001;
+        const WRITABLE = 0b0000_0010;
+        const READ_CLOSED = 0b0000_0100;
+        const WRITE_CLOSED = 0b0000_1000;
+        const ERROR = 0b0001_0000;
+    }
+}
+
+/// Represents a signal which can be delivered as an [`Event::Signal`](enum.Event.html#variant.Signal).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Signal {
+    /// `SIGHUP`, the controlling terminal was closed.
+    Hangup,
+    /// `SIGTERM`, the process was asked to terminate.
+    Terminate,
+    /// `SIGUSR1`, user-defined signal 1.
+    User1,
+    /// `SIGUSR2`, user-defined signal 2.
+    User2,
+}
+
+/// Represents the color scheme of the terminal delivered as an
+/// [`Event::ThemeChanged`](enum.Event.html#variant.ThemeChanged).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ColorScheme {
+    /// The light text on the dark background.
+    Dark,
+    /// The dark text on the light background.
+    Light,
 }
 
 /// Represents a mouse event.
@@ -335,6 +1769,16 @@ pub struct MouseEvent {
     pub modifiers: KeyModifiers,
 }
 
+impl MouseEvent {
+    /// Returns the 0-based cell the event occurred on.
+    ///
+    /// It's the same cell the [MoveTo](../cursor/struct.MoveTo.html) created from it moves
+    /// the cursor to.
+    pub fn position(&self) -> Position {
+        Position::new(self.column, self.row)
+    }
+}
+
 /// A mouse event kind.
 ///
 /// # Platform-specific Notes
@@ -359,6 +1803,10 @@ pub enum MouseEventKind {
     ScrollDown,
     /// Scrolled mouse wheel upwards (away from the user).
     ScrollUp,
+    /// Scrolled horizontally to the left (trackpad or tilted mouse wheel).
+    ScrollLeft,
+    /// Scrolled horizontally to the right (trackpad or tilted mouse wheel).
+    ScrollRight,
 }
 
 /// Represents a mouse button.
@@ -374,29 +1822,70 @@ pub enum MouseButton {
 }
 
 bitflags! {
-    /// Represents key modifiers (shift, control, alt).
+    /// Represents key modifiers (shift, control, alt, ...).
+    ///
+    /// The `SUPER`, `HYPER` and `META` modifiers are reported by the terminals only with the
+    /// [kitty keyboard protocol](struct.PushKeyboardEnhancementFlags.html) enabled.
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct KeyModifiers: u8 {
         const SHIFT = 0b0000_0001;
         const CONTROL = 0b0000_0010;
         const ALT = 0b0000_0100;
+        const SUPER = 0b0000_1000;
+        const HYPER = 0b0001_0000;
+        const META = 0b0010_0000;
         const NONE = 0b0000_0000;
     }
 }
 
 /// Represents a key event.
+///
+/// The [raw_codes](#structfield.raw_codes) and the [physical_key](#structfield.physical_key)
+/// aren't compared/hashed, the events of the same key are equal regardless of the platform.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialOrd, Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 pub struct KeyEvent {
     /// The key itself.
     pub code: KeyCode,
     /// Additional key modifiers.
     pub modifiers: KeyModifiers,
+    /// Whether the key was pressed, repeated or released.
+    pub kind: KeyEventKind,
+    /// The platform specific codes of the key, `None` if they're not reported.
+    pub raw_codes: Option<RawKeyCodes>,
+    /// The layout independent physical key, `None` if it can't be determined.
+    pub physical_key: Option<PhysicalKey>,
 }
 
 impl KeyEvent {
     pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
-        KeyEvent { code, modifiers }
+        KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+    }
+
+    pub const fn new_with_kind(
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        kind: KeyEventKind,
+    ) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind,
+            raw_codes: None,
+            physical_key: None,
+        }
+    }
+
+    /// Returns the event with the platform specific codes of the key.
+    pub const fn with_raw_codes(mut self, raw_codes: RawKeyCodes) -> KeyEvent {
+        self.raw_codes = Some(raw_codes);
+        self
+    }
+
+    /// Returns the event with the layout independent physical key.
+    pub const fn with_physical_key(mut self, physical_key: PhysicalKey) -> KeyEvent {
+        self.physical_key = Some(physical_key);
+        self
     }
 
     // modifies the KeyEvent,
@@ -419,10 +1908,7 @@ impl KeyEvent {
 
 impl From<KeyCode> for KeyEvent {
     fn from(code: KeyCode) -> Self {
-        KeyEvent {
-            code,
-            modifiers: KeyModifiers::empty(),
-        }
+        KeyEvent::new(code, KeyModifiers::empty())
     }
 }
 
@@ -431,25 +1917,74 @@ impl PartialEq for KeyEvent {
         let KeyEvent {
             code: lhs_code,
             modifiers: lhs_modifiers,
+            kind: lhs_kind,
+            ..
         } = self.normalize_case();
         let KeyEvent {
             code: rhs_code,
             modifiers: rhs_modifiers,
+            kind: rhs_kind,
+            ..
         } = other.normalize_case();
-        (lhs_code == rhs_code) && (lhs_modifiers == rhs_modifiers)
+        (lhs_code == rhs_code) && (lhs_modifiers == rhs_modifiers) && (lhs_kind == rhs_kind)
     }
 }
 
 impl Eq for KeyEvent {}
 
+impl PartialOrd for KeyEvent {
+    fn partial_cmp(&self, other: &KeyEvent) -> Option<cmp::Ordering> {
+        // The same fields as the `PartialEq`, the equal events are ordered equal
+        let lhs = self.normalize_case();
+        let rhs = other.normalize_case();
+        (lhs.code, lhs.modifiers, lhs.kind).partial_cmp(&(rhs.code, rhs.modifiers, rhs.kind))
+    }
+}
+
 impl Hash for KeyEvent {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        let KeyEvent { code, modifiers } = self.normalize_case();
+        let KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        } = self.normalize_case();
         code.hash(state);
         modifiers.hash(state);
+        kind.hash(state);
     }
 }
 
+/// Represents whether the key was pressed, repeated or released.
+///
+/// The terminals report the repeats and the releases only with the
+/// [REPORT_EVENT_TYPES](struct.KeyboardEnhancementFlags.html#associatedconstant.REPORT_EVENT_TYPES)
+/// flag, all the other key events are presses.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum KeyEventKind {
+    /// The key was pressed.
+    Press,
+    /// The key is held down and autorepeated.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
+/// The platform specific codes of a key.
+///
+/// They identify the physical key regardless of the keyboard layout (e.g. for the WASD
+/// bindings), but they differ between the platforms. Only the Windows console reports
+/// them, the terminals don't.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct RawKeyCodes {
+    /// The scan code generated by the keyboard.
+    pub scan_code: Option<u16>,
+    /// The virtual-key code (`VK_*`) on Windows.
+    pub virtual_key_code: Option<u16>,
+}
+
 /// Represents a key.
 #[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -504,17 +2039,61 @@ pub enum KeyCode {
 pub(crate) enum InternalEvent {
     /// An event.
     Event(Event),
-    /// A cursor position (`col`, `row`).
+    /// A response to a terminal query, the parser doesn't know which query it answers.
+    #[cfg(unix)]
+    Response(Response),
+    /// A response routed to the query waiting for it.
+    #[cfg(unix)]
+    QueryResponse(query::QueryId, Response),
+    /// A key event reported in the `win32-input-mode`, the parser turns it into the key
+    /// events.
+    #[cfg(unix)]
+    Win32Key(sys::win32_key::Win32KeyRecord),
+    /// An in-band resize notification, the parser turns it into the resize events.
     #[cfg(unix)]
-    CursorPosition(u16, u16),
+    InBandResize {
+        columns: u16,
+        rows: u16,
+        width: u16,
+        height: u16,
+    },
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cmp::Ordering;
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
+    use std::sync::atomic;
 
-    use super::{KeyCode, KeyEvent, KeyModifiers};
+    use crate::cursor::tracking::TEST_LOCK;
+    use crate::terminal::snapshot::{self, DecModes};
+    use crate::{Command, QueueableCommand};
+
+    use super::{
+        mouse_capture_count, mouse_capture_mode, DisableModifyOtherKeys, DisableMouseCapture,
+        DisablePixelMouseCapture, DisableWin32InputMode, EnableModifyOtherKeys, EnableMouseCapture,
+        EnablePixelMouseCapture, EnableWin32InputMode, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, KeyboardEnhancementFlags, ModifyOtherKeysLevel, MouseCaptureMode,
+        PopAllKeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+        RawKeyCodes, SetMouseCaptureMode, MOUSE_CAPTURE_COUNT,
+    };
+
+    fn ansi(command: impl Command) -> String {
+        let mut ansi = String::new();
+        command.write_ansi(&mut ansi).unwrap();
+        ansi
+    }
+
+    #[test]
+    fn test_public_types_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<super::Event>();
+        assert_send_sync::<super::Waker>();
+        #[cfg(feature = "event-stream")]
+        assert_send_sync::<super::EventStream>();
+    }
 
     #[test]
     fn test_equality() {
@@ -523,6 +2102,28 @@ mod tests {
         let uppercase_d = KeyEvent::new(KeyCode::Char('D'), KeyModifiers::NONE);
         assert_eq!(lowercase_d_with_shift, uppercase_d_with_shift);
         assert_eq!(uppercase_d, uppercase_d_with_shift);
+        assert_ne!(
+            uppercase_d,
+            KeyEvent::new_with_kind(
+                KeyCode::Char('D'),
+                KeyModifiers::NONE,
+                KeyEventKind::Release
+            )
+        );
+
+        let d_with_raw_codes = uppercase_d.with_raw_codes(RawKeyCodes {
+            scan_code: Some(0x20),
+            virtual_key_code: Some(0x44),
+        });
+        assert_eq!(uppercase_d, d_with_raw_codes);
+        assert_eq!(
+            uppercase_d.partial_cmp(&d_with_raw_codes),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            lowercase_d_with_shift.partial_cmp(&uppercase_d_with_shift),
+            Some(Ordering::Equal)
+        );
     }
 
     #[test]
@@ -545,4 +2146,145 @@ mod tests {
         assert_eq!(lowercase_d_with_shift_hash, uppercase_d_with_shift_hash);
         assert_eq!(uppercase_d_hash, uppercase_d_with_shift_hash);
     }
+
+    fn queued(command: impl Command) -> String {
+        let mut output = Vec::new();
+        output.queue(command).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_keyboard_enhancement_flags_stack() {
+        let _lock = TEST_LOCK.lock();
+
+        // Nothing was pushed yet, nothing to pop
+        assert_eq!(queued(PopAllKeyboardEnhancementFlags), "");
+
+        let push = PushKeyboardEnhancementFlags(
+            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES,
+        );
+        assert_eq!(queued(push), "\x1B[>3u");
+        assert_eq!(queued(push), "\x1B[>3u");
+        assert_eq!(queued(push), "\x1B[>3u");
+        assert_eq!(queued(PopKeyboardEnhancementFlags), "\x1B[<1u");
+
+        // Formatting only doesn't change the stack
+        assert_eq!(ansi(push), "\x1B[>3u");
+        assert_eq!(ansi(PopAllKeyboardEnhancementFlags), "\x1B[<2u");
+
+        // Two pushes are still outstanding
+        assert_eq!(queued(PopAllKeyboardEnhancementFlags), "\x1B[<2u");
+        assert_eq!(queued(PopAllKeyboardEnhancementFlags), "");
+
+        // Unbalanced pop doesn't underflow the stack
+        assert_eq!(queued(PopKeyboardEnhancementFlags), "\x1B[<1u");
+        assert_eq!(queued(PopAllKeyboardEnhancementFlags), "");
+    }
+
+    #[test]
+    fn test_key_encoding_modes() {
+        assert_eq!(
+            ansi(EnableModifyOtherKeys(ModifyOtherKeysLevel::Level1)),
+            "\x1B[>4;1m"
+        );
+        assert_eq!(
+            ansi(EnableModifyOtherKeys(ModifyOtherKeysLevel::Level2)),
+            "\x1B[>4;2m"
+        );
+        assert_eq!(ansi(DisableModifyOtherKeys), "\x1B[>4m");
+        assert_eq!(ansi(EnableWin32InputMode), "\x1B[?9001h");
+        assert_eq!(ansi(DisableWin32InputMode), "\x1B[?9001l");
+    }
+
+    #[test]
+    fn test_mouse_capture_is_reference_counted() {
+        let _lock = TEST_LOCK.lock();
+        let count = MOUSE_CAPTURE_COUNT.swap(0, atomic::Ordering::SeqCst);
+
+        // Formatting alone doesn't count
+        assert_eq!(ansi(EnableMouseCapture), ansi(EnableMouseCapture));
+        assert_eq!(mouse_capture_count(), 0);
+
+        let enable = queued(EnableMouseCapture);
+        assert!(enable.starts_with("\x1B[?1000h"));
+
+        // Nested borrower doesn't enable/disable it again
+        assert_eq!(queued(EnableMouseCapture), "");
+        assert_eq!(queued(DisableMouseCapture), "");
+
+        let disable = queued(DisableMouseCapture);
+        assert!(disable.ends_with("\x1B[?1000l"));
+        assert_eq!(mouse_capture_count(), 0);
+
+        // Unbalanced disable is still honored
+        assert_eq!(queued(DisableMouseCapture), disable);
+        assert_eq!(queued(EnableMouseCapture), enable);
+        assert_eq!(queued(DisableMouseCapture), disable);
+
+        MOUSE_CAPTURE_COUNT.store(count, atomic::Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_pixel_mouse_capture_mode_is_tracked() {
+        let _lock = TEST_LOCK.lock();
+        let count = MOUSE_CAPTURE_COUNT.swap(0, atomic::Ordering::SeqCst);
+        let before = snapshot::is_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE);
+        snapshot::set_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE, false);
+
+        // Formatting alone doesn't change the mode
+        assert!(ansi(EnablePixelMouseCapture).ends_with("\x1B[?1016h"));
+        assert!(!snapshot::is_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE));
+
+        queued(EnablePixelMouseCapture);
+        assert!(snapshot::is_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE));
+        queued(DisablePixelMouseCapture);
+        assert!(!snapshot::is_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE));
+        assert_eq!(mouse_capture_count(), 0);
+
+        snapshot::set_dec_mode(DecModes::PIXEL_MOUSE_CAPTURE, before);
+        MOUSE_CAPTURE_COUNT.store(count, atomic::Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_mouse_capture_mode_is_tracked() {
+        let _lock = TEST_LOCK.lock();
+        let before = mouse_capture_mode();
+        queued(SetMouseCaptureMode(MouseCaptureMode::AllMotion));
+
+        // Formatting alone doesn't change the mode
+        ansi(SetMouseCaptureMode(MouseCaptureMode::Drag));
+        assert_eq!(mouse_capture_mode(), MouseCaptureMode::AllMotion);
+
+        queued(SetMouseCaptureMode(MouseCaptureMode::Drag));
+        assert_eq!(mouse_capture_mode(), MouseCaptureMode::Drag);
+
+        queued(SetMouseCaptureMode(before));
+    }
+
+    #[test]
+    fn test_mouse_capture_modes() {
+        for mode in [
+            MouseCaptureMode::Buttons,
+            MouseCaptureMode::Drag,
+            MouseCaptureMode::AllMotion,
+        ] {
+            assert_eq!(MouseCaptureMode::from_dec_modes(mode.dec_mode()), mode);
+        }
+
+        assert_eq!(MouseCaptureMode::Buttons.enable_ansi(), "\x1B[?1000h");
+        assert_eq!(
+            MouseCaptureMode::AllMotion.enable_ansi(),
+            "\x1B[?1000h\x1B[?1002h\x1B[?1003h"
+        );
+        // Switching from any mode leaves the tracking of the new one only
+        assert_eq!(
+            MouseCaptureMode::Drag.switch_ansi(),
+            "\x1B[?1003l\x1B[?1002h"
+        );
+        assert_eq!(
+            MouseCaptureMode::Buttons.switch_ansi(),
+            "\x1B[?1003l\x1B[?1002l"
+        );
+    }
 }
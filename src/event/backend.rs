@@ -0,0 +1,78 @@
+//! This module contains the report of the implementation waiting for the events.
+
+use std::fmt;
+
+/// The implementation waiting for the input, the signals and the wake-ups.
+///
+/// Reporting the active one helps to diagnose the platform specific input bugs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventBackend {
+    /// The `epoll` selector of the mio (Linux, Android, illumos).
+    Epoll,
+    /// The `kqueue` selector of the mio (macOS, iOS, BSD).
+    Kqueue,
+    /// The console input records read with the WinAPI (Windows).
+    WindowsConsole,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "illumos"))]
+const AVAILABLE: &[EventBackend] = &[EventBackend::Epoll];
+#[cfg(all(
+    unix,
+    not(any(target_os = "linux", target_os = "android", target_os = "illumos"))
+))]
+const AVAILABLE: &[EventBackend] = &[EventBackend::Kqueue];
+#[cfg(windows)]
+const AVAILABLE: &[EventBackend] = &[EventBackend::WindowsConsole];
+
+impl EventBackend {
+    /// Returns the backends available on the current platform, the default one first.
+    pub fn available() -> &'static [EventBackend] {
+        AVAILABLE
+    }
+
+    /// Returns whether the backend is available on the current platform.
+    pub fn is_available(self) -> bool {
+        AVAILABLE.contains(&self)
+    }
+}
+
+impl fmt::Display for EventBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EventBackend::Epoll => "epoll",
+            EventBackend::Kqueue => "kqueue",
+            EventBackend::WindowsConsole => "windows console",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Returns the backend used to wait for the events.
+///
+/// # Notes
+///
+/// A single backend is available on each platform at the moment, the one the mio picks
+/// on UNIX and the console input on Windows, so it can't be selected at runtime.
+pub fn event_backend() -> EventBackend {
+    AVAILABLE[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{event_backend, EventBackend};
+
+    #[test]
+    fn test_backend_report() {
+        let backend = event_backend();
+        assert_eq!(EventBackend::available()[0], backend);
+        assert!(backend.is_available());
+
+        let unavailable = if backend == EventBackend::WindowsConsole {
+            EventBackend::Epoll
+        } else {
+            EventBackend::WindowsConsole
+        };
+        assert!(!unavailable.is_available());
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use super::{internal_event_reader, Event};
+
+/// Subscribes to the copies of all the events, it opts in to the broadcast delivery.
+///
+/// By default every [`Event`](enum.Event.html) is returned by one reader only. The
+/// subscribers receive a copy of every event read from the terminal from now on, no matter
+/// which reader (the [read](fn.read.html), the [EventStream](struct.EventStream.html), ...)
+/// consumes it. It allows to log the input or record the macros in a separate thread.
+///
+/// The events are copied as they're read from the terminal, so someone must keep reading
+/// them. Dropping the subscriber unsubscribes it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::thread;
+///
+/// use crossterm::event::{read, subscribe};
+///
+/// fn main() -> crossterm::Result<()> {
+///     let subscriber = subscribe();
+///     thread::spawn(move || {
+///         for event in subscriber {
+///             eprintln!("{:?}", event);
+///         }
+///     });
+///
+///     loop {
+///         // Handle the events
+///         read()?;
+///     }
+/// }
+/// ```
+pub fn subscribe() -> EventSubscriber {
+    EventSubscriber {
+        receiver: internal_event_reader().subscribe(),
+    }
+}
+
+/// A receiver of the copies of the events, see the [subscribe](fn.subscribe.html) function.
+///
+/// It iterates over the events, blocking until the next one is read.
+#[derive(Debug)]
+pub struct EventSubscriber {
+    receiver: mpsc::Receiver<Event>,
+}
+
+impl EventSubscriber {
+    /// Returns the next event, blocks until it's read from the terminal.
+    pub fn recv(&self) -> Option<Event> {
+        self.receiver.recv().ok()
+    }
+
+    /// Returns the next event, `None` if it isn't read within the given duration.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Event> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Returns the next event if it was read already, doesn't block.
+    pub fn try_recv(&self) -> Option<Event> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Iterator for EventSubscriber {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        self.recv()
+    }
+}
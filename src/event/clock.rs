@@ -0,0 +1,57 @@
+//! This module contains the source of the current time used by the timeouts.
+
+use std::fmt;
+use std::time::Instant;
+#[cfg(test)]
+use std::{sync::Arc, time::Duration};
+
+#[cfg(test)]
+use parking_lot::Mutex;
+
+/// A source of the current time.
+pub(crate) trait Clock: fmt::Debug {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// The monotonic clock of the operating system.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock which stands still until it's advanced manually.
+///
+/// The clones share the time, the clock can be advanced by a fake event source while
+/// the reader is polling.
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub(crate) struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    /// Creates a new `MockClock` starting at the current time.
+    pub(crate) fn new() -> MockClock {
+        MockClock {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Moves the clock forward.
+    pub(crate) fn advance(&self, duration: Duration) {
+        *self.now.lock() += duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock()
+    }
+}
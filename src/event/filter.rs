@@ -1,22 +1,11 @@
 use crate::event::InternalEvent;
 
 /// Interface for filtering an `InternalEvent`.
-pub(crate) trait Filter: Send + Sync + 'static {
+pub(crate) trait Filter {
     /// Returns whether the given event fulfills the filter.
     fn eval(&self, event: &InternalEvent) -> bool;
 }
 
-#[cfg(unix)]
-#[derive(Debug, Clone)]
-pub(crate) struct CursorPositionFilter;
-
-#[cfg(unix)]
-impl Filter for CursorPositionFilter {
-    fn eval(&self, event: &InternalEvent) -> bool {
-        matches!(*event, InternalEvent::CursorPosition(_, _))
-    }
-}
-
 #[derive(Debug, Clone)]
 pub(crate) struct EventFilter;
 
@@ -32,9 +21,25 @@ impl Filter for EventFilter {
     }
 }
 
+#[cfg(all(test, unix))]
+#[derive(Debug, Clone)]
+pub(crate) struct CursorPositionFilter;
+
+#[cfg(all(test, unix))]
+impl Filter for CursorPositionFilter {
+    fn eval(&self, event: &InternalEvent) -> bool {
+        matches!(
+            *event,
+            InternalEvent::Response(super::Response::CursorPosition(_, _))
+        )
+    }
+}
+
+#[cfg(test)]
 #[derive(Debug, Clone)]
 pub(crate) struct InternalEventFilter;
 
+#[cfg(test)]
 impl Filter for InternalEventFilter {
     fn eval(&self, _: &InternalEvent) -> bool {
         true
@@ -45,24 +50,27 @@ impl Filter for InternalEventFilter {
 #[cfg(unix)]
 mod tests {
     use super::{
-        super::Event, CursorPositionFilter, EventFilter, Filter, InternalEvent, InternalEventFilter,
+        super::{Event, Response},
+        CursorPositionFilter, EventFilter, Filter, InternalEvent, InternalEventFilter,
     };
 
+    const CURSOR_EVENT: InternalEvent = InternalEvent::Response(Response::CursorPosition(0, 0));
+
     #[test]
     fn test_cursor_position_filter_filters_cursor_position() {
         assert!(!CursorPositionFilter.eval(&InternalEvent::Event(Event::Resize(10, 10))));
-        assert!(CursorPositionFilter.eval(&InternalEvent::CursorPosition(0, 0)));
+        assert!(CursorPositionFilter.eval(&CURSOR_EVENT));
     }
 
     #[test]
     fn test_event_filter_filters_events() {
         assert!(EventFilter.eval(&InternalEvent::Event(Event::Resize(10, 10))));
-        assert!(!EventFilter.eval(&InternalEvent::CursorPosition(0, 0)));
+        assert!(!EventFilter.eval(&CURSOR_EVENT));
     }
 
     #[test]
     fn test_event_filter_filters_internal_events() {
         assert!(InternalEventFilter.eval(&InternalEvent::Event(Event::Resize(10, 10))));
-        assert!(InternalEventFilter.eval(&InternalEvent::CursorPosition(0, 0)));
+        assert!(InternalEventFilter.eval(&CURSOR_EVENT));
     }
 }
@@ -0,0 +1,352 @@
+//! This module contains the recognizer of the mouse gestures.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::clock::{Clock, SystemClock};
+use super::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+/// The default maximum time between the scroll events to accelerate the scrolling.
+const MOMENTUM_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The maximum number of lines scrolled by a single wheel event.
+const MAX_SCROLL_LINES: u16 = 8;
+
+/// Represents a mouse gesture recognized by the
+/// [GestureRecognizer](struct.GestureRecognizer.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Gesture {
+    /// The button was pressed and released without moving the mouse.
+    Click {
+        button: MouseButton,
+        column: u16,
+        row: u16,
+        modifiers: KeyModifiers,
+    },
+    /// The mouse moved with the button pressed, the `origin` is where it was pressed.
+    DragStart {
+        button: MouseButton,
+        origin: (u16, u16),
+        column: u16,
+        row: u16,
+        modifiers: KeyModifiers,
+    },
+    /// The mouse moved during the drag.
+    DragMove {
+        button: MouseButton,
+        origin: (u16, u16),
+        column: u16,
+        row: u16,
+        modifiers: KeyModifiers,
+    },
+    /// The button was released, the drag is over.
+    DragEnd {
+        button: MouseButton,
+        origin: (u16, u16),
+        column: u16,
+        row: u16,
+        modifiers: KeyModifiers,
+    },
+    /// The wheel was scrolled by the given number of lines, negative towards the top.
+    ///
+    /// The number grows when the wheel is scrolled quickly (momentum).
+    Scroll {
+        lines: i16,
+        column: u16,
+        row: u16,
+        modifiers: KeyModifiers,
+    },
+}
+
+/// The button pressed at the origin.
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    button: MouseButton,
+    origin: (u16, u16),
+    dragging: bool,
+}
+
+/// The scroll events in the same direction.
+#[derive(Debug, Clone, Copy)]
+struct ScrollStreak {
+    down: bool,
+    count: u16,
+    last: Instant,
+}
+
+/// Converts the mouse events into the [Gesture](enum.Gesture.html)s.
+///
+/// It tells the clicks from the drags (the mouse moved by more than the drag threshold
+/// with the button pressed), keeps the origin of the drag and accelerates the quick
+/// scrolling.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{
+///     event::{read, Event, Gesture, GestureRecognizer},
+///     Result,
+/// };
+///
+/// fn print_gestures() -> Result<()> {
+///     let mut recognizer = GestureRecognizer::new();
+///
+///     loop {
+///         if let Event::Mouse(event) = read()? {
+///             if let Some(gesture) = recognizer.feed(&event) {
+///                 println!("{:?}", gesture);
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    drag_threshold: u16,
+    momentum_interval: Duration,
+    press: Option<Press>,
+    scroll: Option<ScrollStreak>,
+    clock: Arc<dyn Clock + Send + Sync>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        GestureRecognizer {
+            drag_threshold: 0,
+            momentum_interval: MOMENTUM_INTERVAL,
+            press: None,
+            scroll: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl GestureRecognizer {
+    /// Creates a new `GestureRecognizer`.
+    pub fn new() -> GestureRecognizer {
+        GestureRecognizer::default()
+    }
+
+    /// Sets the number of cells the mouse can move with the button pressed before it's a
+    /// drag, `0` by default.
+    pub fn with_drag_threshold(mut self, cells: u16) -> GestureRecognizer {
+        self.drag_threshold = cells;
+        self
+    }
+
+    /// Sets the maximum time between the scroll events to accelerate the scrolling, 50ms
+    /// by default.
+    pub fn with_momentum_interval(mut self, interval: Duration) -> GestureRecognizer {
+        self.momentum_interval = interval;
+        self
+    }
+
+    /// Measures the time between the scroll events with the given clock.
+    #[cfg(test)]
+    fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> GestureRecognizer {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Processes the mouse event, returns the recognized gesture if any.
+    pub fn feed(&mut self, event: &MouseEvent) -> Option<Gesture> {
+        let MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers,
+        } = *event;
+
+        match kind {
+            MouseEventKind::Down(button) => {
+                self.press = Some(Press {
+                    button,
+                    origin: (column, row),
+                    dragging: false,
+                });
+                None
+            }
+            MouseEventKind::Drag(_) => {
+                let press = self.press.as_mut()?;
+                let (x, y) = press.origin;
+                let distance = column.abs_diff(x).max(row.abs_diff(y));
+
+                if press.dragging {
+                    Some(Gesture::DragMove {
+                        button: press.button,
+                        origin: press.origin,
+                        column,
+                        row,
+                        modifiers,
+                    })
+                } else if distance > self.drag_threshold {
+                    press.dragging = true;
+                    Some(Gesture::DragStart {
+                        button: press.button,
+                        origin: press.origin,
+                        column,
+                        row,
+                        modifiers,
+                    })
+                } else {
+                    None
+                }
+            }
+            // Some terminals don't report the released button, the pressed one is used
+            MouseEventKind::Up(_) => {
+                let press = self.press.take()?;
+
+                if press.dragging {
+                    Some(Gesture::DragEnd {
+                        button: press.button,
+                        origin: press.origin,
+                        column,
+                        row,
+                        modifiers,
+                    })
+                } else {
+                    Some(Gesture::Click {
+                        button: press.button,
+                        column: press.origin.0,
+                        row: press.origin.1,
+                        modifiers,
+                    })
+                }
+            }
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let down = kind == MouseEventKind::ScrollDown;
+                let now = self.clock.now();
+                let count = match self.scroll {
+                    Some(streak)
+                        if streak.down == down
+                            && now.duration_since(streak.last) <= self.momentum_interval =>
+                    {
+                        streak.count.saturating_add(1)
+                    }
+                    _ => 0,
+                };
+                self.scroll = Some(ScrollStreak {
+                    down,
+                    count,
+                    last: now,
+                });
+
+                let lines = (1 + count / 2).min(MAX_SCROLL_LINES) as i16;
+                Some(Gesture::Scroll {
+                    lines: if down { lines } else { -lines },
+                    column,
+                    row,
+                    modifiers,
+                })
+            }
+            MouseEventKind::Moved | MouseEventKind::ScrollLeft | MouseEventKind::ScrollRight => {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::super::clock::MockClock;
+    use super::{Gesture, GestureRecognizer};
+    use crate::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+    fn event(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_click() {
+        let mut recognizer = GestureRecognizer::new().with_drag_threshold(1);
+        let down = event(MouseEventKind::Down(MouseButton::Right), 5, 5);
+        assert_eq!(recognizer.feed(&down), None);
+        // Within the threshold
+        let drag = event(MouseEventKind::Drag(MouseButton::Right), 6, 5);
+        assert_eq!(recognizer.feed(&drag), None);
+
+        let up = event(MouseEventKind::Up(MouseButton::Left), 6, 5);
+        assert_eq!(
+            recognizer.feed(&up),
+            Some(Gesture::Click {
+                button: MouseButton::Right,
+                column: 5,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            })
+        );
+    }
+
+    #[test]
+    fn test_drag() {
+        let mut recognizer = GestureRecognizer::new();
+        let down = event(MouseEventKind::Down(MouseButton::Left), 1, 1);
+        assert_eq!(recognizer.feed(&down), None);
+
+        let drag = event(MouseEventKind::Drag(MouseButton::Left), 2, 1);
+        assert!(matches!(
+            recognizer.feed(&drag),
+            Some(Gesture::DragStart {
+                origin: (1, 1),
+                column: 2,
+                ..
+            })
+        ));
+        let drag = event(MouseEventKind::Drag(MouseButton::Left), 3, 2);
+        assert!(matches!(
+            recognizer.feed(&drag),
+            Some(Gesture::DragMove {
+                origin: (1, 1),
+                column: 3,
+                row: 2,
+                ..
+            })
+        ));
+        let up = event(MouseEventKind::Up(MouseButton::Left), 3, 2);
+        assert!(matches!(
+            recognizer.feed(&up),
+            Some(Gesture::DragEnd { origin: (1, 1), .. })
+        ));
+
+        // The drag without the press is ignored
+        assert_eq!(recognizer.feed(&drag), None);
+    }
+
+    #[test]
+    fn test_scroll_momentum() {
+        let clock = MockClock::new();
+        let mut recognizer = GestureRecognizer::new().with_clock(clock.clone());
+        let lines = |gesture| match gesture {
+            Some(Gesture::Scroll { lines, .. }) => lines,
+            _ => panic!("not a scroll: {:?}", gesture),
+        };
+
+        let down = event(MouseEventKind::ScrollDown, 0, 0);
+        let up = event(MouseEventKind::ScrollUp, 0, 0);
+
+        let quick: Vec<i16> = (0..5)
+            .map(|_| {
+                clock.advance(Duration::from_millis(10));
+                lines(recognizer.feed(&down))
+            })
+            .collect();
+        assert_eq!(quick, vec![1, 1, 2, 2, 3]);
+
+        // The direction changed
+        assert_eq!(lines(recognizer.feed(&up)), -1);
+        // Too slow
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(lines(recognizer.feed(&up)), -1);
+    }
+}
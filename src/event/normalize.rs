@@ -0,0 +1,295 @@
+//! This module contains the normalization of the key events reported differently by the
+//! terminals.
+
+use parking_lot::Mutex;
+
+use super::{KeyCode, KeyEvent, KeyModifiers};
+
+/// How the characters typed with the Shift key are reported.
+///
+/// The legacy terminals send the uppercase character only, crossterm adds the `SHIFT`
+/// modifier. The terminals with the kitty keyboard protocol enabled and the Windows
+/// console may report the lowercase (base) character with the `SHIFT` modifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShiftedChars {
+    /// The characters are kept as reported.
+    AsReported,
+    /// The uppercase character with the `SHIFT` modifier, e.g. `Char('A')` + `SHIFT`.
+    Uppercase,
+    /// The lowercase character with the `SHIFT` modifier, e.g. `Char('a')` + `SHIFT`.
+    Lowercase,
+}
+
+/// How the Ctrl+H, Ctrl+I, Ctrl+M and Ctrl+[ keys are reported.
+///
+/// The legacy terminals send the same bytes for them as for the Backspace (some of them),
+/// Tab, Enter and Esc keys, the terminals with the kitty keyboard protocol enabled and the
+/// Windows console tell them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControlKeys {
+    /// The keys are kept as reported.
+    AsReported,
+    /// The character with the `CONTROL` modifier, e.g. `Char('i')` + `CONTROL`. The
+    /// Backspace, Tab, Enter and Esc keys pressed with the Ctrl key are reported as such.
+    Char,
+    /// The dedicated key code without the `CONTROL` modifier, e.g. `Tab`, like the legacy
+    /// terminals report them.
+    Dedicated,
+}
+
+/// The normalization of the key events read by the [read](fn.read.html) and the
+/// [poll](fn.poll.html), see the [set_key_normalization](fn.set_key_normalization.html).
+///
+/// The events are kept as reported by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyNormalization {
+    /// How the characters typed with the Shift key are reported.
+    pub shifted_chars: ShiftedChars,
+    /// How the Ctrl+H, Ctrl+I, Ctrl+M and Ctrl+[ keys are reported.
+    pub control_keys: ControlKeys,
+}
+
+impl Default for KeyNormalization {
+    fn default() -> Self {
+        AS_REPORTED
+    }
+}
+
+const AS_REPORTED: KeyNormalization = KeyNormalization {
+    shifted_chars: ShiftedChars::AsReported,
+    control_keys: ControlKeys::AsReported,
+};
+
+static KEY_NORMALIZATION: Mutex<KeyNormalization> = parking_lot::const_mutex(AS_REPORTED);
+
+/// Sets the normalization of the key events parsed from the terminal input.
+///
+/// The keymaps can match the events the same way regardless of the terminal. The events
+/// read from the custom [EventSource](trait.EventSource.html) aren't normalized.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::event::{self, ControlKeys, KeyNormalization, ShiftedChars};
+///
+/// event::set_key_normalization(KeyNormalization {
+///     shifted_chars: ShiftedChars::Uppercase,
+///     control_keys: ControlKeys::Dedicated,
+/// });
+/// ```
+pub fn set_key_normalization(normalization: KeyNormalization) {
+    *KEY_NORMALIZATION.lock() = normalization;
+}
+
+/// Returns the normalization of the key events set with the
+/// [set_key_normalization](fn.set_key_normalization.html).
+pub fn key_normalization() -> KeyNormalization {
+    *KEY_NORMALIZATION.lock()
+}
+
+/// Normalizes the parsed key event with the current normalization.
+pub(crate) fn normalize_key_event(event: KeyEvent) -> KeyEvent {
+    normalize(event, key_normalization())
+}
+
+fn normalize(mut event: KeyEvent, normalization: KeyNormalization) -> KeyEvent {
+    match normalization.control_keys {
+        ControlKeys::AsReported => {}
+        ControlKeys::Char => {
+            // The kitty keyboard protocol reports Ctrl+Tab etc. too, keep them
+            if event.modifiers.contains(KeyModifiers::CONTROL) {
+                if let Some(c) = control_char(event.code) {
+                    event.code = KeyCode::Char(c);
+                }
+            }
+        }
+        ControlKeys::Dedicated => {
+            if event.modifiers.contains(KeyModifiers::CONTROL) {
+                if let Some(code) = dedicated_code(event.code) {
+                    event.code = code;
+                    event.modifiers.remove(KeyModifiers::CONTROL);
+                }
+            }
+        }
+    }
+
+    if let KeyCode::Char(c) = event.code {
+        match normalization.shifted_chars {
+            ShiftedChars::AsReported => {}
+            ShiftedChars::Uppercase => {
+                if c.is_lowercase() && event.modifiers.contains(KeyModifiers::SHIFT) {
+                    event.code = KeyCode::Char(single_char(c.to_uppercase()).unwrap_or(c));
+                } else if c.is_uppercase() {
+                    event.modifiers.insert(KeyModifiers::SHIFT);
+                }
+            }
+            ShiftedChars::Lowercase => {
+                if c.is_uppercase() {
+                    event.code = KeyCode::Char(single_char(c.to_lowercase()).unwrap_or(c));
+                    event.modifiers.insert(KeyModifiers::SHIFT);
+                }
+            }
+        }
+    }
+
+    event
+}
+
+/// Returns the character typed with the Ctrl key producing the same byte as the key.
+fn control_char(code: KeyCode) -> Option<char> {
+    match code {
+        KeyCode::Backspace => Some('h'),
+        KeyCode::Tab => Some('i'),
+        KeyCode::Enter => Some('m'),
+        KeyCode::Esc => Some('['),
+        _ => None,
+    }
+}
+
+/// Returns the key producing the same byte as the character typed with the Ctrl key.
+fn dedicated_code(code: KeyCode) -> Option<KeyCode> {
+    match code {
+        KeyCode::Char('h') => Some(KeyCode::Backspace),
+        KeyCode::Char('i') => Some(KeyCode::Tab),
+        KeyCode::Char('m') => Some(KeyCode::Enter),
+        KeyCode::Char('[') => Some(KeyCode::Esc),
+        _ => None,
+    }
+}
+
+/// Returns the character if the case conversion results in a single one.
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{normalize, ControlKeys, KeyNormalization, ShiftedChars};
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> (KeyCode, KeyModifiers) {
+        (code, modifiers)
+    }
+
+    fn normalized(
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        shifted_chars: ShiftedChars,
+        control_keys: ControlKeys,
+    ) -> (KeyCode, KeyModifiers) {
+        let event = normalize(
+            KeyEvent::new(code, modifiers),
+            KeyNormalization {
+                shifted_chars,
+                control_keys,
+            },
+        );
+        (event.code, event.modifiers)
+    }
+
+    #[test]
+    fn test_shifted_chars() {
+        let shift = KeyModifiers::SHIFT;
+        let upper = |code, modifiers| {
+            normalized(
+                code,
+                modifiers,
+                ShiftedChars::Uppercase,
+                ControlKeys::AsReported,
+            )
+        };
+        let lower = |code, modifiers| {
+            normalized(
+                code,
+                modifiers,
+                ShiftedChars::Lowercase,
+                ControlKeys::AsReported,
+            )
+        };
+
+        assert_eq!(
+            upper(KeyCode::Char('a'), shift),
+            key(KeyCode::Char('A'), shift)
+        );
+        assert_eq!(
+            upper(KeyCode::Char('A'), KeyModifiers::NONE),
+            key(KeyCode::Char('A'), shift)
+        );
+        assert_eq!(
+            upper(KeyCode::Char('a'), KeyModifiers::NONE),
+            key(KeyCode::Char('a'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            lower(KeyCode::Char('Ž'), KeyModifiers::NONE),
+            key(KeyCode::Char('ž'), shift)
+        );
+        assert_eq!(
+            lower(KeyCode::Char('ž'), shift),
+            key(KeyCode::Char('ž'), shift)
+        );
+        assert_eq!(
+            lower(KeyCode::Char('!'), shift),
+            key(KeyCode::Char('!'), shift)
+        );
+        assert_eq!(
+            normalized(
+                KeyCode::Char('A'),
+                KeyModifiers::NONE,
+                ShiftedChars::AsReported,
+                ControlKeys::AsReported
+            ),
+            key(KeyCode::Char('A'), KeyModifiers::NONE)
+        );
+    }
+
+    #[test]
+    fn test_control_keys() {
+        let ctrl = KeyModifiers::CONTROL;
+        let chars = |code, modifiers| {
+            normalized(code, modifiers, ShiftedChars::AsReported, ControlKeys::Char)
+        };
+        let dedicated = |code, modifiers| {
+            normalized(
+                code,
+                modifiers,
+                ShiftedChars::AsReported,
+                ControlKeys::Dedicated,
+            )
+        };
+
+        assert_eq!(
+            chars(KeyCode::Backspace, ctrl),
+            key(KeyCode::Char('h'), ctrl)
+        );
+        assert_eq!(
+            chars(KeyCode::Tab, KeyModifiers::NONE),
+            key(KeyCode::Tab, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            dedicated(KeyCode::Char('i'), ctrl),
+            key(KeyCode::Tab, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            dedicated(KeyCode::Char('['), ctrl | KeyModifiers::ALT),
+            key(KeyCode::Esc, KeyModifiers::ALT)
+        );
+        assert_eq!(
+            dedicated(KeyCode::Char('a'), ctrl),
+            key(KeyCode::Char('a'), ctrl)
+        );
+        // Ctrl+Shift+M is reported as Ctrl+M by the legacy terminals
+        assert_eq!(
+            normalized(
+                KeyCode::Char('M'),
+                ctrl | KeyModifiers::SHIFT,
+                ShiftedChars::Lowercase,
+                ControlKeys::Dedicated
+            ),
+            key(KeyCode::Char('m'), ctrl | KeyModifiers::SHIFT)
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! This module contains the layout independent physical keys.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents a physical key, regardless of the keyboard layout.
+///
+/// The keys are named after the characters they produce on the US layout (like the USB
+/// HID usages), e.g. [KeyW](#variant.KeyW) is the `Z` key on the French layout. It allows
+/// to bind the keys by their position (e.g. WASD) consistently across the platforms.
+///
+/// It's derived on a best-effort basis from the base layout key reported by the terminals
+/// supporting the
+/// [REPORT_ALTERNATE_KEYS](struct.KeyboardEnhancementFlags.html#associatedconstant.REPORT_ALTERNATE_KEYS)
+/// or from the scan code on Windows.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Ord, Clone, Copy, Hash)]
+pub enum PhysicalKey {
+    KeyA,
+    KeyB,
+    KeyC,
+    KeyD,
+    KeyE,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyI,
+    KeyJ,
+    KeyK,
+    KeyL,
+    KeyM,
+    KeyN,
+    KeyO,
+    KeyP,
+    KeyQ,
+    KeyR,
+    KeyS,
+    KeyT,
+    KeyU,
+    KeyV,
+    KeyW,
+    KeyX,
+    KeyY,
+    KeyZ,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    /// The `-` key.
+    Minus,
+    /// The `=` key.
+    Equal,
+    /// The `[` key.
+    BracketLeft,
+    /// The `]` key.
+    BracketRight,
+    /// The `\` key.
+    Backslash,
+    /// The `;` key.
+    Semicolon,
+    /// The `'` key.
+    Quote,
+    /// The `` ` `` key.
+    Backquote,
+    /// The `,` key.
+    Comma,
+    /// The `.` key.
+    Period,
+    /// The `/` key.
+    Slash,
+    Space,
+    Enter,
+    Tab,
+    Backspace,
+    Escape,
+    /// F key.
+    ///
+    /// `PhysicalKey::F(1)` represents F1 key, etc.
+    F(u8),
+}
+
+/// The letter keys in the alphabetical order.
+const LETTERS: [PhysicalKey; 26] = [
+    PhysicalKey::KeyA,
+    PhysicalKey::KeyB,
+    PhysicalKey::KeyC,
+    PhysicalKey::KeyD,
+    PhysicalKey::KeyE,
+    PhysicalKey::KeyF,
+    PhysicalKey::KeyG,
+    PhysicalKey::KeyH,
+    PhysicalKey::KeyI,
+    PhysicalKey::KeyJ,
+    PhysicalKey::KeyK,
+    PhysicalKey::KeyL,
+    PhysicalKey::KeyM,
+    PhysicalKey::KeyN,
+    PhysicalKey::KeyO,
+    PhysicalKey::KeyP,
+    PhysicalKey::KeyQ,
+    PhysicalKey::KeyR,
+    PhysicalKey::KeyS,
+    PhysicalKey::KeyT,
+    PhysicalKey::KeyU,
+    PhysicalKey::KeyV,
+    PhysicalKey::KeyW,
+    PhysicalKey::KeyX,
+    PhysicalKey::KeyY,
+    PhysicalKey::KeyZ,
+];
+
+const DIGITS: [PhysicalKey; 10] = [
+    PhysicalKey::Digit0,
+    PhysicalKey::Digit1,
+    PhysicalKey::Digit2,
+    PhysicalKey::Digit3,
+    PhysicalKey::Digit4,
+    PhysicalKey::Digit5,
+    PhysicalKey::Digit6,
+    PhysicalKey::Digit7,
+    PhysicalKey::Digit8,
+    PhysicalKey::Digit9,
+];
+
+/// The keys of the set 1 scan codes `0x00..=0x39`, `None` if it's not a supported key.
+const SCAN_CODES: [Option<char>; 0x3A] = [
+    None,
+    Some('\x1B'),
+    Some('1'),
+    Some('2'),
+    Some('3'),
+    Some('4'),
+    Some('5'),
+    Some('6'),
+    Some('7'),
+    Some('8'),
+    Some('9'),
+    Some('0'),
+    Some('-'),
+    Some('='),
+    Some('\x7F'),
+    Some('\t'),
+    Some('q'),
+    Some('w'),
+    Some('e'),
+    Some('r'),
+    Some('t'),
+    Some('y'),
+    Some('u'),
+    Some('i'),
+    Some('o'),
+    Some('p'),
+    Some('['),
+    Some(']'),
+    Some('\r'),
+    // Left Ctrl
+    None,
+    Some('a'),
+    Some('s'),
+    Some('d'),
+    Some('f'),
+    Some('g'),
+    Some('h'),
+    Some('j'),
+    Some('k'),
+    Some('l'),
+    Some(';'),
+    Some('\''),
+    Some('`'),
+    // Left Shift
+    None,
+    Some('\\'),
+    Some('z'),
+    Some('x'),
+    Some('c'),
+    Some('v'),
+    Some('b'),
+    Some('n'),
+    Some('m'),
+    Some(','),
+    Some('.'),
+    Some('/'),
+    // Right Shift, Keypad *, Left Alt
+    None,
+    None,
+    None,
+    Some(' '),
+];
+
+impl PhysicalKey {
+    /// Returns the physical key producing the character on the US layout.
+    pub(crate) fn from_base_layout_key(c: char) -> Option<PhysicalKey> {
+        let key = match c {
+            'a'..='z' => LETTERS[c as usize - 'a' as usize],
+            'A'..='Z' => LETTERS[c as usize - 'A' as usize],
+            '0'..='9' => DIGITS[c as usize - '0' as usize],
+            '-' => PhysicalKey::Minus,
+            '=' => PhysicalKey::Equal,
+            '[' => PhysicalKey::BracketLeft,
+            ']' => PhysicalKey::BracketRight,
+            '\\' => PhysicalKey::Backslash,
+            ';' => PhysicalKey::Semicolon,
+            '\'' => PhysicalKey::Quote,
+            '`' => PhysicalKey::Backquote,
+            ',' => PhysicalKey::Comma,
+            '.' => PhysicalKey::Period,
+            '/' => PhysicalKey::Slash,
+            ' ' => PhysicalKey::Space,
+            '\r' => PhysicalKey::Enter,
+            '\t' => PhysicalKey::Tab,
+            '\x7F' => PhysicalKey::Backspace,
+            '\x1B' => PhysicalKey::Escape,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    /// Returns the physical key of the set 1 scan code (the Windows scan codes).
+    ///
+    /// The extended keys (e.g. arrows) share the scan codes with the keypad, they're not
+    /// supported.
+    pub(crate) fn from_scan_code(scan_code: u16) -> Option<PhysicalKey> {
+        match scan_code {
+            0x3B..=0x44 => Some(PhysicalKey::F((scan_code - 0x3A) as u8)),
+            0x57 => Some(PhysicalKey::F(11)),
+            0x58 => Some(PhysicalKey::F(12)),
+            _ => SCAN_CODES
+                .get(scan_code as usize)
+                .copied()
+                .flatten()
+                .and_then(PhysicalKey::from_base_layout_key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PhysicalKey;
+
+    #[test]
+    fn test_from_base_layout_key() {
+        assert_eq!(
+            PhysicalKey::from_base_layout_key('w'),
+            Some(PhysicalKey::KeyW)
+        );
+        assert_eq!(
+            PhysicalKey::from_base_layout_key('W'),
+            Some(PhysicalKey::KeyW)
+        );
+        assert_eq!(
+            PhysicalKey::from_base_layout_key('7'),
+            Some(PhysicalKey::Digit7)
+        );
+        assert_eq!(PhysicalKey::from_base_layout_key('ф'), None);
+    }
+
+    #[test]
+    fn test_from_scan_code() {
+        assert_eq!(PhysicalKey::from_scan_code(0x11), Some(PhysicalKey::KeyW));
+        assert_eq!(PhysicalKey::from_scan_code(0x1E), Some(PhysicalKey::KeyA));
+        assert_eq!(PhysicalKey::from_scan_code(0x2C), Some(PhysicalKey::KeyZ));
+        assert_eq!(PhysicalKey::from_scan_code(0x39), Some(PhysicalKey::Space));
+        assert_eq!(PhysicalKey::from_scan_code(0x3B), Some(PhysicalKey::F(1)));
+        assert_eq!(PhysicalKey::from_scan_code(0x58), Some(PhysicalKey::F(12)));
+        assert_eq!(PhysicalKey::from_scan_code(0x1D), None);
+    }
+}
@@ -0,0 +1,76 @@
+//! This module contains the recording of the raw input printed by the `crossterm-probe`.
+
+use std::collections::VecDeque;
+
+use parking_lot::Mutex;
+
+/// The most inputs kept by the recording, the older ones are dropped.
+#[cfg(unix)]
+const MAX_RAW_INPUTS: usize = 256;
+
+/// The inputs parsed (or dropped) since the last `take_raw_input`.
+static RAW_INPUTS: Mutex<VecDeque<RawInput>> = parking_lot::const_mutex(VecDeque::new());
+
+/// Represents the bytes read from the terminal for a single sequence.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct RawInput {
+    /// The bytes of the sequence.
+    pub bytes: Vec<u8>,
+    /// Whether the sequence was parsed, the bytes of the unknown or malformed sequences
+    /// are dropped.
+    pub parsed: bool,
+}
+
+/// Takes the raw input read from the terminal since the last call.
+///
+/// The input is recorded per sequence, in the order it was read. The sequences are read
+/// ahead of the events, the input of the events still waiting in the queue is returned
+/// too. At most the last 256 sequences are kept.
+///
+/// # Notes
+///
+/// * It's available with the `probe` feature only.
+/// * The input is recorded on UNIX only, the Windows console reports the input records,
+///   not the bytes.
+pub fn take_raw_input() -> Vec<RawInput> {
+    RAW_INPUTS.lock().drain(..).collect()
+}
+
+/// Records the bytes of a sequence read from the terminal.
+#[cfg(unix)]
+pub(crate) fn record_raw_input(bytes: &[u8], parsed: bool) {
+    let mut inputs = RAW_INPUTS.lock();
+    if inputs.len() == MAX_RAW_INPUTS {
+        inputs.pop_front();
+    }
+    inputs.push_back(RawInput {
+        bytes: bytes.to_vec(),
+        parsed,
+    });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{record_raw_input, take_raw_input, RawInput, MAX_RAW_INPUTS};
+
+    #[test]
+    fn test_oldest_raw_inputs_are_dropped() {
+        take_raw_input();
+        for byte in 0..=MAX_RAW_INPUTS {
+            record_raw_input(&[byte as u8], true);
+        }
+        record_raw_input(b"\x1B[", false);
+
+        let inputs = take_raw_input();
+        assert_eq!(inputs.len(), MAX_RAW_INPUTS);
+        assert_eq!(inputs[0].bytes, [2]);
+        assert_eq!(
+            inputs.last(),
+            Some(&RawInput {
+                bytes: b"\x1B[".to_vec(),
+                parsed: false
+            })
+        );
+        assert!(take_raw_input().is_empty());
+    }
+}
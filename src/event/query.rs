@@ -0,0 +1,546 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::terminal::{output_stream, sys::query_raw, DeviceAttributes};
+use crate::{csi, Result};
+
+use super::{
+    filter::Filter, poll_internal_until, read_internal, InternalEvent, KeyboardEnhancementFlags,
+};
+
+/// The queries waiting for their responses, in the order they were sent.
+static PENDING_QUERIES: Mutex<Vec<PendingQuery>> = parking_lot::const_mutex(Vec::new());
+
+/// The identifier of the next query.
+static NEXT_QUERY_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A response of the terminal to a [Query](trait.Query.html).
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Hash)]
+pub enum Response {
+    /// The cursor position (`column`, `row`), the top left cell is `0, 0`.
+    CursorPosition(u16, u16),
+    /// The DEC private mode report (`mode`, `setting`), `None` if the mode isn't recognized.
+    DecModeReport(u16, Option<bool>),
+    /// The current keyboard enhancement flags.
+    KeyboardEnhancementFlags(KeyboardEnhancementFlags),
+    /// The primary device attributes, the terminal class followed by the supported features.
+    PrimaryDeviceAttributes(Vec<u16>),
+    /// The secondary device attributes (`terminal type`, `version`).
+    SecondaryDeviceAttributes(u16, u16),
+    /// The default foreground color (`r`, `g`, `b`).
+    ForegroundColor(u8, u8, u8),
+    /// The default background color (`r`, `g`, `b`).
+    BackgroundColor(u8, u8, u8),
+    /// The window title.
+    Title(String),
+}
+
+/// A query asking the terminal for the [Response](enum.Response.html)s, see the
+/// [query](fn.query.html) function.
+///
+/// The query picks its responses out of the ones read from the terminal, the other
+/// responses are left for the other queries and the events are left for the
+/// [read](fn.read.html).
+pub trait Query {
+    /// The answer to the query.
+    type Output;
+
+    /// Writes the ANSI sequence asking the terminal for the responses.
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result;
+
+    /// Returns whether the response answers the query.
+    fn accepts(&self, response: &Response) -> bool;
+
+    /// Takes the accepted response, returns the answer once the query is complete.
+    fn answer(&mut self, response: Response) -> Option<Self::Output>;
+
+    /// Returns how long to wait for the answer.
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(2000)
+    }
+}
+
+/// Asks the terminal and waits for the answer to the query.
+///
+/// The raw mode is enabled for the time of the query if it isn't enabled already, the
+/// responses aren't echoed then. It fails if the terminal doesn't answer within the
+/// [timeout](trait.Query.html#method.timeout).
+///
+/// The responses are routed to the queries waiting for them in the order the queries were
+/// sent, the concurrent queries don't take each other's responses. The responses nobody
+/// waits for (e.g. the late ones) are dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::event::{query, BackgroundColorQuery, CursorPositionQuery};
+///
+/// fn main() -> crossterm::Result<()> {
+///     let (column, row) = query(CursorPositionQuery)?;
+///     let (r, g, b) = query(BackgroundColorQuery)?;
+///     println!("The cursor is at {}x{} on rgb({}, {}, {})", column, row, r, g, b);
+///     Ok(())
+/// }
+/// ```
+pub fn query<Q>(query: Q) -> Result<Q::Output>
+where
+    Q: Query + Clone + Send + 'static,
+{
+    query_raw(|| query_raw_mode(query))
+}
+
+fn query_raw_mode<Q>(mut query: Q) -> Result<Q::Output>
+where
+    Q: Query + Clone + Send + 'static,
+{
+    // The responses can come right away, the query must be waiting for them already
+    let pending = PendingGuard::register(query.clone());
+
+    let mut ansi = String::new();
+    query
+        .write_ansi(&mut ansi)
+        .map_err(|_| io::Error::other("Failed to write the query"))?;
+
+    let mut output = output_stream().writer();
+    output.write_all(ansi.as_bytes())?;
+    output.flush()?;
+
+    // The retries (the reader woken up, ...) don't extend the deadline
+    let deadline = Instant::now() + query.timeout();
+    loop {
+        match poll_internal_until(deadline, &QueryFilter(pending.id)) {
+            Ok(true) => {
+                if let Ok(InternalEvent::QueryResponse(_, response)) =
+                    read_internal(&QueryFilter(pending.id))
+                {
+                    if let Some(output) = query.answer(response) {
+                        return Ok(output);
+                    }
+                }
+            }
+            Ok(false) if Instant::now() >= deadline => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "The answer to the query could not be read within a normal duration",
+                ));
+            }
+            Ok(false) => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Identifies the query a response is routed to.
+#[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
+pub(crate) struct QueryId(u64);
+
+/// A query waiting for its responses.
+struct PendingQuery {
+    id: QueryId,
+    query: Box<dyn PendingAnswer>,
+}
+
+/// The object safe part of the `Query`, tracks which responses the query still waits for.
+trait PendingAnswer: Send {
+    fn accepts(&self, response: &Response) -> bool;
+
+    /// Takes the response, returns whether the query is complete.
+    fn complete(&mut self, response: Response) -> bool;
+}
+
+impl<Q: Query + Send> PendingAnswer for Q {
+    fn accepts(&self, response: &Response) -> bool {
+        Query::accepts(self, response)
+    }
+
+    fn complete(&mut self, response: Response) -> bool {
+        self.answer(response).is_some()
+    }
+}
+
+/// Registers the pending query, unregisters it when dropped (the query is answered, timed
+/// out, ...).
+struct PendingGuard {
+    id: QueryId,
+}
+
+impl PendingGuard {
+    fn register<Q: Query + Send + 'static>(query: Q) -> PendingGuard {
+        let id = QueryId(NEXT_QUERY_ID.fetch_add(1, Ordering::Relaxed));
+        PENDING_QUERIES.lock().push(PendingQuery {
+            id,
+            query: Box::new(query),
+        });
+        PendingGuard { id }
+    }
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        PENDING_QUERIES
+            .lock()
+            .retain(|pending| pending.id != self.id);
+    }
+}
+
+/// Routes the response read from the terminal to the first pending query accepting it.
+///
+/// The query stops receiving the responses once it's complete, the next response of the
+/// same kind goes to the next query. The responses nobody waits for are dropped.
+pub(crate) fn route_response(response: Response) -> Option<InternalEvent> {
+    let mut pending_queries = PENDING_QUERIES.lock();
+
+    if let Some(index) = pending_queries
+        .iter()
+        .position(|pending| pending.query.accepts(&response))
+    {
+        let id = pending_queries[index].id;
+        if pending_queries[index].query.complete(response.clone()) {
+            pending_queries.remove(index);
+        }
+        return Some(InternalEvent::QueryResponse(id, response));
+    }
+
+    None
+}
+
+/// Matches the responses routed to the query.
+struct QueryFilter(QueryId);
+
+impl Filter for QueryFilter {
+    fn eval(&self, event: &InternalEvent) -> bool {
+        matches!(*event, InternalEvent::QueryResponse(id, _) if id == self.0)
+    }
+}
+
+/// Queries the cursor position (`column`, `row`) with the `CSI 6 n`.
+///
+/// The top left cell is represented `0,0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorPositionQuery;
+
+impl Query for CursorPositionQuery {
+    type Output = (u16, u16);
+
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("6n"))
+    }
+
+    fn accepts(&self, response: &Response) -> bool {
+        matches!(response, Response::CursorPosition(_, _))
+    }
+
+    fn answer(&mut self, response: Response) -> Option<Self::Output> {
+        match response {
+            Response::CursorPosition(column, row) => Some((column, row)),
+            _ => None,
+        }
+    }
+}
+
+/// Queries whether the DEC private mode is set with the `DECRQM`, `None` if the terminal
+/// doesn't recognize the mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecModeQuery(pub u16);
+
+impl Query for DecModeQuery {
+    type Output = Option<bool>;
+
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, csi!("?{}$p"), self.0)
+    }
+
+    fn accepts(&self, response: &Response) -> bool {
+        matches!(*response, Response::DecModeReport(mode, _) if mode == self.0)
+    }
+
+    fn answer(&mut self, response: Response) -> Option<Self::Output> {
+        match response {
+            Response::DecModeReport(_, setting) => Some(setting),
+            _ => None,
+        }
+    }
+}
+
+/// Queries the current keyboard enhancement flags with the `CSI ? u`, `None` if the
+/// terminal doesn't support the [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/).
+///
+/// The query is followed by the primary device attributes query. All the terminals answer
+/// it, the ones answering it alone don't support the protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyboardEnhancementFlagsQuery {
+    flags: Option<KeyboardEnhancementFlags>,
+}
+
+impl KeyboardEnhancementFlagsQuery {
+    /// Creates a new `KeyboardEnhancementFlagsQuery`.
+    pub fn new() -> KeyboardEnhancementFlagsQuery {
+        KeyboardEnhancementFlagsQuery::default()
+    }
+}
+
+impl Query for KeyboardEnhancementFlagsQuery {
+    type Output = Option<KeyboardEnhancementFlags>;
+
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(concat!(csi!("?u"), csi!("c")))
+    }
+
+    fn accepts(&self, response: &Response) -> bool {
+        matches!(
+            response,
+            Response::KeyboardEnhancementFlags(_) | Response::PrimaryDeviceAttributes(_)
+        )
+    }
+
+    fn answer(&mut self, response: Response) -> Option<Self::Output> {
+        match response {
+            Response::KeyboardEnhancementFlags(flags) => {
+                self.flags = Some(flags);
+                None
+            }
+            // The answers come in order, the flags (if any) were read already
+            Response::PrimaryDeviceAttributes(_) => Some(self.flags),
+            _ => None,
+        }
+    }
+}
+
+/// Queries the [DeviceAttributes](../terminal/struct.DeviceAttributes.html) with the
+/// secondary (`CSI > c`) and the primary (`CSI c`) device attributes queries.
+///
+/// All the terminals answer the primary query, the ones answering it alone don't report
+/// their type and version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeviceAttributesQuery {
+    secondary: Option<(u16, u16)>,
+}
+
+impl DeviceAttributesQuery {
+    /// Creates a new `DeviceAttributesQuery`.
+    pub fn new() -> DeviceAttributesQuery {
+        DeviceAttributesQuery::default()
+    }
+}
+
+impl Query for DeviceAttributesQuery {
+    type Output = DeviceAttributes;
+
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(concat!(csi!(">c"), csi!("c")))
+    }
+
+    fn accepts(&self, response: &Response) -> bool {
+        matches!(
+            response,
+            Response::SecondaryDeviceAttributes(_, _) | Response::PrimaryDeviceAttributes(_)
+        )
+    }
+
+    fn answer(&mut self, response: Response) -> Option<Self::Output> {
+        match response {
+            Response::SecondaryDeviceAttributes(terminal_type, version) => {
+                self.secondary = Some((terminal_type, version));
+                None
+            }
+            // The answers come in order, the secondary ones (if any) were read already
+            Response::PrimaryDeviceAttributes(attributes) => {
+                let (&class, features) = attributes.split_first()?;
+                Some(DeviceAttributes {
+                    class,
+                    features: features.to_vec(),
+                    terminal_type: self.secondary.map(|(terminal_type, _)| terminal_type),
+                    version: self.secondary.map(|(_, version)| version),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Queries the default foreground color (`r`, `g`, `b`) with the `OSC 10 ; ?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForegroundColorQuery;
+
+impl Query for ForegroundColorQuery {
+    type Output = (u8, u8, u8);
+
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B]10;?\x1B\\")
+    }
+
+    fn accepts(&self, response: &Response) -> bool {
+        matches!(response, Response::ForegroundColor(_, _, _))
+    }
+
+    fn answer(&mut self, response: Response) -> Option<Self::Output> {
+        match response {
+            Response::ForegroundColor(r, g, b) => Some((r, g, b)),
+            _ => None,
+        }
+    }
+}
+
+/// Queries the default background color (`r`, `g`, `b`) with the `OSC 11 ; ?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackgroundColorQuery;
+
+impl Query for BackgroundColorQuery {
+    type Output = (u8, u8, u8);
+
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B]11;?\x1B\\")
+    }
+
+    fn accepts(&self, response: &Response) -> bool {
+        matches!(response, Response::BackgroundColor(_, _, _))
+    }
+
+    fn answer(&mut self, response: Response) -> Option<Self::Output> {
+        match response {
+            Response::BackgroundColor(r, g, b) => Some((r, g, b)),
+            _ => None,
+        }
+    }
+}
+
+/// Queries the window title with the `CSI 21 t`.
+///
+/// Many terminals don't answer the query for security reasons, it times out then.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TitleQuery;
+
+impl Query for TitleQuery {
+    type Output = String;
+
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("21t"))
+    }
+
+    fn accepts(&self, response: &Response) -> bool {
+        matches!(response, Response::Title(_))
+    }
+
+    fn answer(&mut self, response: Response) -> Option<Self::Output> {
+        match response {
+            Response::Title(title) => Some(title),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::KeyboardEnhancementFlags, route_response, CursorPositionQuery, DecModeQuery,
+        DeviceAttributes, DeviceAttributesQuery, InternalEvent, KeyboardEnhancementFlagsQuery,
+        PendingGuard, Query, Response,
+    };
+
+    fn ansi(query: impl Query) -> String {
+        let mut ansi = String::new();
+        query.write_ansi(&mut ansi).unwrap();
+        ansi
+    }
+
+    #[test]
+    fn test_dec_mode_query_accepts_its_mode_only() {
+        assert_eq!(ansi(DecModeQuery(7)), "\x1B[?7$p");
+        assert!(DecModeQuery(7).accepts(&Response::DecModeReport(7, Some(true))));
+        assert!(!DecModeQuery(7).accepts(&Response::DecModeReport(2026, Some(true))));
+        assert!(!DecModeQuery(7).accepts(&Response::CursorPosition(0, 0)));
+    }
+
+    #[test]
+    fn test_keyboard_enhancement_flags_query_waits_for_device_attributes() {
+        let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+
+        let mut query = KeyboardEnhancementFlagsQuery::new();
+        assert_eq!(
+            query.answer(Response::KeyboardEnhancementFlags(flags)),
+            None
+        );
+        assert_eq!(
+            query.answer(Response::PrimaryDeviceAttributes(vec![62])),
+            Some(Some(flags))
+        );
+
+        let mut query = KeyboardEnhancementFlagsQuery::new();
+        assert_eq!(
+            query.answer(Response::PrimaryDeviceAttributes(vec![62])),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn test_device_attributes_query_waits_for_primary_attributes() {
+        assert_eq!(ansi(DeviceAttributesQuery::new()), "\x1B[>c\x1B[c");
+
+        let mut query = DeviceAttributesQuery::new();
+        assert_eq!(
+            query.answer(Response::SecondaryDeviceAttributes(41, 390)),
+            None
+        );
+        assert_eq!(
+            query.answer(Response::PrimaryDeviceAttributes(vec![64, 4, 22])),
+            Some(DeviceAttributes {
+                class: 64,
+                features: vec![4, 22],
+                terminal_type: Some(41),
+                version: Some(390),
+            })
+        );
+
+        let mut query = DeviceAttributesQuery::new();
+        assert_eq!(
+            query.answer(Response::PrimaryDeviceAttributes(vec![1, 2])),
+            Some(DeviceAttributes {
+                class: 1,
+                features: vec![2],
+                terminal_type: None,
+                version: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_responses_are_routed_to_pending_queries_in_order() {
+        // The pending queries are shared with the other tests
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let first = PendingGuard::register(CursorPositionQuery);
+        let second = PendingGuard::register(CursorPositionQuery);
+        let mode = PendingGuard::register(DecModeQuery(2026));
+
+        assert_eq!(
+            route_response(Response::CursorPosition(1, 2)),
+            Some(InternalEvent::QueryResponse(
+                first.id,
+                Response::CursorPosition(1, 2)
+            ))
+        );
+        assert_eq!(
+            route_response(Response::DecModeReport(2026, None)),
+            Some(InternalEvent::QueryResponse(
+                mode.id,
+                Response::DecModeReport(2026, None)
+            ))
+        );
+        assert_eq!(
+            route_response(Response::CursorPosition(3, 4)),
+            Some(InternalEvent::QueryResponse(
+                second.id,
+                Response::CursorPosition(3, 4)
+            ))
+        );
+
+        // Nobody waits for them
+        assert_eq!(route_response(Response::CursorPosition(3, 4)), None);
+        assert_eq!(route_response(Response::DecModeReport(2026, None)), None);
+        // The late cursor position on the first row isn't a key
+        assert_eq!(route_response(Response::CursorPosition(4, 0)), None);
+    }
+}
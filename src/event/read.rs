@@ -1,150 +1,564 @@
-use std::{collections::vec_deque::VecDeque, io, time::Duration};
+use std::{
+    collections::vec_deque::VecDeque,
+    io, mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::{Condvar, Mutex, MutexGuard};
 
 #[cfg(unix)]
 use super::source::unix::UnixInternalEventSource;
 #[cfg(windows)]
 use super::source::windows::WindowsEventSource;
 #[cfg(feature = "event-stream")]
-use super::sys::Waker;
-use super::{filter::Filter, source::EventSource, timeout::PollTimeout, InternalEvent, Result};
-/// Can be used to read `InternalEvent`s.
+use super::source::SourceWaker;
+use super::{
+    clock::Clock,
+    filter::Filter,
+    source::EventSource,
+    timeout::PollTimeout,
+    wake::{interrupt_source, next_injected_event, take_source_interrupt},
+    Event, EventKinds, InternalEvent, Result,
+};
+
+/// How long the source is waited for before the reading thread is interrupted again.
+const INTERRUPT_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Can be used to read `InternalEvent`s, from multiple threads.
+///
+/// The reader is split in two shards, so the threads waiting for different events don't
+/// serialize on one lock:
+///
+/// * the queue of the events read from the source, locked just to check/take the events,
+/// * the source, locked by the one thread reading it at the moment.
+///
+/// The thread reading the source queues all the events, not just the ones matching its
+/// filter, and notifies the other threads. They wait for the queue to change, and one of
+/// them takes over the source when it's released.
 pub(crate) struct InternalEventReader {
-    events: VecDeque<InternalEvent>,
-    source: Option<Box<dyn EventSource>>,
-    skipped_events: Vec<InternalEvent>,
+    queue: Mutex<EventQueue>,
+    // Notified when an event is queued, the source is released or woken up
+    queue_changed: Condvar,
+    source: Mutex<Option<Box<dyn EventSource>>>,
+    // Receive the copies of the events read from the source
+    subscribers: Mutex<Vec<mpsc::Sender<Event>>>,
+    // The source was dropped by the `shutdown`, the terminal is opened again when read
+    reopen_source: AtomicBool,
+}
+
+#[derive(Default)]
+struct EventQueue {
+    // The events along with the time they were read from the source
+    events: VecDeque<(InternalEvent, Instant)>,
+    // Incremented when the source is woken up, the waiting threads return too
+    wake_count: usize,
+    // The resize events closer to each other are coalesced, zero turns it off
+    resize_coalescing: Duration,
+    // The last resize events of the burst, queued once the burst is over
+    pending_resize: Option<PendingResize>,
+    // The kinds of the events queued ahead of the other events
+    priority: EventKinds,
+}
+
+impl EventQueue {
+    /// Queues the event, the priority events skip the other queued events.
+    fn push(&mut self, event: InternalEvent, received: Instant) {
+        let is_priority = |event: &InternalEvent| match event {
+            InternalEvent::Event(event) => self.priority.intersects(event.kind()),
+            _ => false,
+        };
+
+        if is_priority(&event) {
+            // The priority events are at the front, in the order they were received
+            let index = self
+                .events
+                .iter()
+                .position(|(event, _)| !is_priority(event))
+                .unwrap_or(self.events.len());
+            self.events.insert(index, (event, received));
+        } else {
+            self.events.push_back((event, received));
+        }
+    }
+}
+
+struct PendingResize {
+    // The last `Event::Resize` and `Event::PixelResize`, in the order they were received
+    events: Vec<Event>,
+    received: Instant,
+    // The burst is over if no other resize event is received until then
+    deadline: Instant,
 }
 
 impl Default for InternalEventReader {
     fn default() -> Self {
-        #[cfg(windows)]
-        let source = WindowsEventSource::new();
-        #[cfg(unix)]
-        let source = UnixInternalEventSource::new();
+        InternalEventReader::with_events(VecDeque::with_capacity(32), terminal_source().ok())
+    }
+}
 
-        let source = source.ok().map(|x| Box::new(x) as Box<dyn EventSource>);
+/// Opens the event source reading the terminal.
+fn terminal_source() -> Result<Box<dyn EventSource>> {
+    #[cfg(windows)]
+    let source = WindowsEventSource::new();
+    #[cfg(unix)]
+    let source = UnixInternalEventSource::new();
+
+    source.map(|x| Box::new(x) as Box<dyn EventSource>)
+}
+
+impl InternalEventReader {
+    /// Creates a reader reading the events from the given source.
+    pub(crate) fn with_source(source: Box<dyn EventSource>) -> InternalEventReader {
+        InternalEventReader::with_events(VecDeque::with_capacity(32), Some(source))
+    }
+
+    fn with_events(
+        events: VecDeque<InternalEvent>,
+        source: Option<Box<dyn EventSource>>,
+    ) -> InternalEventReader {
+        let now = Instant::now();
 
         InternalEventReader {
-            source,
-            events: VecDeque::with_capacity(32),
-            skipped_events: Vec::with_capacity(32),
+            queue: Mutex::new(EventQueue {
+                events: events.into_iter().map(|event| (event, now)).collect(),
+                ..EventQueue::default()
+            }),
+            queue_changed: Condvar::new(),
+            source: Mutex::new(source),
+            subscribers: Mutex::new(Vec::new()),
+            reopen_source: AtomicBool::new(false),
         }
     }
-}
 
-impl InternalEventReader {
-    /// Returns a `Waker` allowing to wake/force the `poll` method to return `Ok(false)`.
+    /// Returns a receiver of the copies of all the events read from the source from now on.
+    pub(crate) fn subscribe(&self) -> mpsc::Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.lock().push(sender);
+        receiver
+    }
+
+    /// Sets the time the resize events are coalesced within, zero turns it off.
+    pub(crate) fn set_resize_coalescing(&self, window: Duration) {
+        let mut queue = self.queue.lock();
+        queue.resize_coalescing = window;
+        if window.is_zero() {
+            // The burst is over, the pending event isn't delayed anymore
+            if let Some(pending) = &mut queue.pending_resize {
+                pending.deadline = pending.received;
+            }
+            self.flush_resize(&mut queue);
+        }
+    }
+
+    /// Sets the kinds of the events queued ahead of the other events.
+    pub(crate) fn set_priority_events(&self, kinds: EventKinds) {
+        self.queue.lock().priority = kinds;
+    }
+
+    /// Replaces the source of the events, the events read already are kept.
+    ///
+    /// The thread reading the source is interrupted first, it reads the new one then.
+    pub(crate) fn set_source(&self, source: Box<dyn EventSource>) -> Result<()> {
+        let mut locked_source = self.lock_interrupted_source()?;
+        *locked_source = Some(source);
+        self.reopen_source.store(false, Ordering::SeqCst);
+        self.release_source(&mut self.queue.lock(), locked_source);
+        Ok(())
+    }
+
+    /// Drops the source along with the queued events and the subscribers, the waiting
+    /// threads return.
+    ///
+    /// The blocked reading thread is interrupted first, the source is dropped once it's
+    /// released. The terminal is opened again by the next `poll`/`read`.
+    pub(crate) fn shutdown(&self) -> Result<()> {
+        let mut source = self.lock_interrupted_source()?;
+        *source = None;
+        self.reopen_source.store(true, Ordering::SeqCst);
+
+        self.subscribers.lock().clear();
+        let mut queue = self.queue.lock();
+        queue.events.clear();
+        queue.pending_resize = None;
+        queue.wake_count += 1;
+        self.release_source(&mut queue, source);
+        Ok(())
+    }
+
+    /// Replaces the source with a new one reading the terminal, e.g. after the input file
+    /// descriptor was changed. The events read already are kept.
+    pub(crate) fn reopen_terminal_source(&self) -> Result<()> {
+        let mut source = self.lock_interrupted_source()?;
+        // Dropped first, it would unregister the signals & the wake-ups of the new one
+        *source = None;
+        self.reopen_source.store(true, Ordering::SeqCst);
+
+        let result = terminal_source().map(|terminal| {
+            *source = Some(terminal);
+            self.reopen_source.store(false, Ordering::SeqCst);
+        });
+        self.release_source(&mut self.queue.lock(), source);
+        result
+    }
+
+    /// Interrupts the thread reading the source and locks the source once it's released.
+    fn lock_interrupted_source(&self) -> Result<MutexGuard<'_, Option<Box<dyn EventSource>>>> {
+        loop {
+            interrupt_source()?;
+            // The interrupted thread can take the source again before it's locked here
+            if let Some(source) = self.source.try_lock_for(INTERRUPT_RETRY_INTERVAL) {
+                // Nobody was reading the source, the interrupt must not hit the next one
+                take_source_interrupt();
+                return Ok(source);
+            }
+        }
+    }
+
+    /// Releases the locked source and notifies the waiting threads.
+    ///
+    /// The queue must be locked. The waiting threads check the source with the queue locked,
+    /// the notification can't slip in between the check and the wait then.
+    fn release_source(
+        &self,
+        _queue: &mut MutexGuard<'_, EventQueue>,
+        source: MutexGuard<'_, Option<Box<dyn EventSource>>>,
+    ) {
+        drop(source);
+        self.queue_changed.notify_all();
+    }
+
+    /// Returns a `SourceWaker` allowing to wake/force the `poll` method to return `Ok(false)`.
     #[cfg(feature = "event-stream")]
-    pub(crate) fn waker(&self) -> Waker {
-        self.source.as_ref().expect("reader source not set").waker()
+    pub(crate) fn waker(&self) -> SourceWaker {
+        let source = self.source.lock();
+        let waker = source.as_ref().expect("reader source not set").waker();
+        self.release_source(&mut self.queue.lock(), source);
+        waker
     }
 
-    pub(crate) fn poll<F>(&mut self, timeout: Option<Duration>, filter: &F) -> Result<bool>
+    pub(crate) fn poll<F>(&self, timeout: Option<Duration>, filter: &F) -> Result<bool>
     where
         F: Filter,
     {
-        for event in &self.events {
-            if filter.eval(event) {
+        self.poll_timeout(PollTimeout::new(timeout), filter)
+    }
+
+    /// Polls for an `InternalEvent` matching the filter until the given deadline.
+    pub(crate) fn poll_until<F>(&self, deadline: Instant, filter: &F) -> Result<bool>
+    where
+        F: Filter,
+    {
+        self.poll_timeout(PollTimeout::until(deadline), filter)
+    }
+
+    #[cfg(all(test, unix))]
+    fn poll_with_clock<F, C>(&self, timeout: Option<Duration>, filter: &F, clock: C) -> Result<bool>
+    where
+        F: Filter,
+        C: Clock,
+    {
+        self.poll_timeout(PollTimeout::with_clock(timeout, clock), filter)
+    }
+
+    fn poll_timeout<F, C>(&self, timeout: PollTimeout<C>, filter: &F) -> Result<bool>
+    where
+        F: Filter,
+        C: Clock,
+    {
+        let mut queue = self.queue.lock();
+        let wake_count = queue.wake_count;
+
+        loop {
+            self.flush_resize(&mut queue);
+            if queue.events.iter().any(|(event, _)| filter.eval(event)) {
                 return Ok(true);
             }
+            if queue.wake_count != wake_count {
+                return Ok(false);
+            }
+
+            if let Some(mut source) = self.source.try_lock() {
+                let result = MutexGuard::unlocked(&mut queue, || {
+                    self.read_source(&mut source, &timeout, filter)
+                });
+                // Another thread can read the source now
+                self.release_source(&mut queue, source);
+                result?;
+
+                // The matching event (if any) can be taken by another thread meanwhile
+                return Ok(queue.events.iter().any(|(event, _)| filter.eval(event)));
+            }
+
+            if timeout.elapsed() {
+                return Ok(false);
+            }
+
+            // The source is released (and the threads notified) with the queue locked, it's
+            // either free already or the notification comes after the wait below
+            if !self.source.is_locked() {
+                continue;
+            }
+
+            match timeout.leftover() {
+                Some(leftover) => {
+                    self.queue_changed.wait_for(&mut queue, leftover);
+                }
+                None => self.queue_changed.wait(&mut queue),
+            }
         }
+    }
 
-        let event_source = match self.source.as_mut() {
+    /// Reads the source until an `InternalEvent` matching the filter is read, queues all
+    /// the events read.
+    fn read_source<F, C>(
+        &self,
+        source: &mut Option<Box<dyn EventSource>>,
+        timeout: &PollTimeout<C>,
+        filter: &F,
+    ) -> Result<()>
+    where
+        F: Filter,
+        C: Clock,
+    {
+        if source.is_none() && self.reopen_source.load(Ordering::SeqCst) {
+            *source = terminal_source().ok();
+            if source.is_some() {
+                self.reopen_source.store(false, Ordering::SeqCst);
+            }
+        }
+
+        let source = match source.as_mut() {
             Some(source) => source,
             None => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Failed to initialize input reader",
-                ))
+                // The injected events are delivered without the source
+                return match next_injected_event() {
+                    Some(event) => {
+                        self.queue_event(InternalEvent::Event(event));
+                        Ok(())
+                    }
+                    None => Err(std::io::Error::other("Failed to initialize input reader")),
+                };
             }
         };
 
-        let poll_timeout = PollTimeout::new(timeout);
-
         loop {
-            let maybe_event = match event_source.try_read(poll_timeout.leftover()) {
-                Ok(None) => None,
+            // The pending resize event is queued once the burst is over
+            let resize_deadline = self
+                .queue
+                .lock()
+                .pending_resize
+                .as_ref()
+                .map(|pending| pending.deadline);
+            let read_timeout = match (timeout.leftover(), resize_deadline) {
+                (leftover, Some(deadline)) => {
+                    let until_deadline = deadline.saturating_duration_since(Instant::now());
+                    Some(leftover.map_or(until_deadline, |leftover| leftover.min(until_deadline)))
+                }
+                (leftover, None) => leftover,
+            };
+
+            match source.try_read(read_timeout) {
+                Ok(None) => {}
                 Ok(Some(event)) => {
-                    if filter.eval(&event) {
-                        Some(event)
-                    } else {
-                        self.skipped_events.push(event);
-                        None
+                    let matches = filter.eval(&event);
+                    self.queue_event(event);
+
+                    if matches {
+                        return Ok(());
                     }
                 }
                 Err(e) => {
                     if e.kind() == io::ErrorKind::Interrupted {
-                        return Ok(false);
+                        self.queue.lock().wake_count += 1;
+                        self.queue_changed.notify_all();
+                        return Ok(());
                     }
 
                     return Err(e);
                 }
             };
 
-            if poll_timeout.elapsed() || maybe_event.is_some() {
-                self.events.extend(self.skipped_events.drain(..));
+            if self
+                .flush_resize(&mut self.queue.lock())
+                .into_iter()
+                .any(|event| filter.eval(&InternalEvent::Event(event)))
+            {
+                return Ok(());
+            }
 
-                if let Some(event) = maybe_event {
-                    self.events.push_front(event);
-                    return Ok(true);
-                }
+            if timeout.elapsed() {
+                return Ok(());
+            }
+        }
+    }
 
-                return Ok(false);
+    /// Queues the event read from the source, the subscribers get a copy of it.
+    ///
+    /// The resize events are held back while they're coalesced, until the burst is over.
+    fn queue_event(&self, event: InternalEvent) {
+        if let InternalEvent::Event(event @ (Event::Resize(..) | Event::PixelResize(..))) = &event {
+            let mut queue = self.queue.lock();
+            if !queue.resize_coalescing.is_zero() {
+                let received = Instant::now();
+                let deadline = received + queue.resize_coalescing;
+                let pending = queue.pending_resize.get_or_insert_with(|| PendingResize {
+                    events: Vec::with_capacity(2),
+                    received,
+                    deadline,
+                });
+
+                // The earlier event of the same kind is dropped
+                pending
+                    .events
+                    .retain(|pending| mem::discriminant(pending) != mem::discriminant(event));
+                pending.events.push(event.clone());
+                pending.received = received;
+                pending.deadline = deadline;
+                return;
             }
         }
+
+        if let InternalEvent::Event(event) = &event {
+            // The dropped receivers are forgotten
+            self.subscribers
+                .lock()
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+
+        // The time the event is received, not the time it's read by the user
+        let received = Instant::now();
+        self.queue.lock().push(event, received);
+        self.queue_changed.notify_all();
     }
 
-    pub(crate) fn read<F>(&mut self, filter: &F) -> Result<InternalEvent>
+    /// Queues the pending resize events if the burst is over, they're returned too.
+    fn flush_resize(&self, queue: &mut EventQueue) -> Vec<Event> {
+        let pending = match queue.pending_resize.take() {
+            Some(pending) if Instant::now() >= pending.deadline => pending,
+            pending => {
+                queue.pending_resize = pending;
+                return Vec::new();
+            }
+        };
+
+        let mut subscribers = self.subscribers.lock();
+        for event in &pending.events {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+            queue.push(InternalEvent::Event(event.clone()), pending.received);
+        }
+        self.queue_changed.notify_all();
+        pending.events
+    }
+
+    pub(crate) fn read<F>(&self, filter: &F) -> Result<InternalEvent>
     where
         F: Filter,
     {
-        let mut skipped_events = VecDeque::new();
+        self.read_timed(filter).map(|(event, _)| event)
+    }
 
+    /// Reads an `InternalEvent` along with the time it was read from the source.
+    pub(crate) fn read_timed<F>(&self, filter: &F) -> Result<(InternalEvent, Instant)>
+    where
+        F: Filter,
+    {
         loop {
-            while let Some(event) = self.events.pop_front() {
-                if filter.eval(&event) {
-                    while let Some(event) = skipped_events.pop_front() {
-                        self.events.push_back(event);
-                    }
-
-                    return Ok(event);
-                } else {
-                    // We can not directly write events back to `self.events`.
-                    // If we did, we would put our self's into an endless loop
-                    // that would enqueue -> dequeue -> enqueue etc.
-                    // This happens because `poll` in this function will always return true if there are events in it's.
-                    // And because we just put the non-fulfilling event there this is going to be the case.
-                    // Instead we can store them into the temporary buffer,
-                    // and then when the filter is fulfilled write all events back in order.
-                    skipped_events.push_back(event);
-                }
+            if let Some(event) = self.take_timed(filter) {
+                return Ok(event);
             }
 
             let _ = self.poll(None, filter)?;
         }
     }
+
+    /// Takes the next queued `InternalEvent` matching the filter, the other events stay in
+    /// the queue in the same order.
+    pub(crate) fn take<F>(&self, filter: &F) -> Option<InternalEvent>
+    where
+        F: Filter,
+    {
+        self.take_timed(filter).map(|(event, _)| event)
+    }
+
+    fn take_timed<F>(&self, filter: &F) -> Option<(InternalEvent, Instant)>
+    where
+        F: Filter,
+    {
+        let mut queue = self.queue.lock();
+        let index = queue
+            .events
+            .iter()
+            .position(|(event, _)| filter.eval(event))?;
+        queue.events.remove(index)
+    }
+
+    /// Returns the next queued `InternalEvent` matching the filter, it isn't consumed.
+    ///
+    /// Only the already queued events are checked, call `poll` first.
+    pub(crate) fn peek<F>(&self, filter: &F) -> Option<InternalEvent>
+    where
+        F: Filter,
+    {
+        self.queue
+            .lock()
+            .events
+            .iter()
+            .find(|(event, _)| filter.eval(event))
+            .map(|(event, _)| event.clone())
+    }
+
+    /// Reads all the `InternalEvent`s available without blocking.
+    pub(crate) fn read_ready<F>(&self, filter: &F) -> Result<Vec<InternalEvent>>
+    where
+        F: Filter,
+    {
+        let mut events = Vec::new();
+        while self.poll(Some(Duration::from_secs(0)), filter)? {
+            events.extend(self.take(filter));
+        }
+        Ok(events)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::io;
-    use std::{collections::VecDeque, time::Duration};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::{
+        collections::VecDeque,
+        thread,
+        time::{Duration, Instant},
+    };
 
     use crate::ErrorKind;
 
     #[cfg(unix)]
-    use super::super::filter::CursorPositionFilter;
+    use super::super::{
+        clock::MockClock,
+        filter::{CursorPositionFilter, EventFilter},
+        Response,
+    };
     use super::{
-        super::{filter::InternalEventFilter, Event},
+        super::{filter::InternalEventFilter, Event, EventKinds, KeyCode},
         EventSource, InternalEvent, InternalEventReader,
     };
 
+    fn reader(
+        events: Vec<InternalEvent>,
+        source: Option<Box<dyn EventSource>>,
+    ) -> InternalEventReader {
+        InternalEventReader::with_events(events.into(), source)
+    }
+
     #[test]
     fn test_poll_fails_without_event_source() {
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: None,
-            skipped_events: Vec::with_capacity(32),
-        };
+        // The injected events would be delivered without the source
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let reader = reader(vec![], None);
 
         assert!(reader.poll(None, &InternalEventFilter).is_err());
         assert!(reader
@@ -157,11 +571,7 @@ mod tests {
 
     #[test]
     fn test_poll_returns_true_for_matching_event_in_queue_at_front() {
-        let mut reader = InternalEventReader {
-            events: vec![InternalEvent::Event(Event::Resize(10, 10))].into(),
-            source: None,
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![InternalEvent::Event(Event::Resize(10, 10))], None);
 
         assert!(reader.poll(None, &InternalEventFilter).unwrap());
     }
@@ -169,15 +579,13 @@ mod tests {
     #[test]
     #[cfg(unix)]
     fn test_poll_returns_true_for_matching_event_in_queue_at_back() {
-        let mut reader = InternalEventReader {
-            events: vec![
+        let reader = reader(
+            vec![
                 InternalEvent::Event(Event::Resize(10, 10)),
-                InternalEvent::CursorPosition(10, 20),
-            ]
-            .into(),
-            source: None,
-            skipped_events: Vec::with_capacity(32),
-        };
+                InternalEvent::Response(Response::CursorPosition(10, 20)),
+            ],
+            None,
+        );
 
         assert!(reader.poll(None, &CursorPositionFilter).unwrap());
     }
@@ -186,11 +594,7 @@ mod tests {
     fn test_read_returns_matching_event_in_queue_at_front() {
         const EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
 
-        let mut reader = InternalEventReader {
-            events: vec![EVENT].into(),
-            source: None,
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![EVENT], None);
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
     }
@@ -198,13 +602,13 @@ mod tests {
     #[test]
     #[cfg(unix)]
     fn test_read_returns_matching_event_in_queue_at_back() {
-        const CURSOR_EVENT: InternalEvent = InternalEvent::CursorPosition(10, 20);
+        const CURSOR_EVENT: InternalEvent =
+            InternalEvent::Response(Response::CursorPosition(10, 20));
 
-        let mut reader = InternalEventReader {
-            events: vec![InternalEvent::Event(Event::Resize(10, 10)), CURSOR_EVENT].into(),
-            source: None,
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(
+            vec![InternalEvent::Event(Event::Resize(10, 10)), CURSOR_EVENT],
+            None,
+        );
 
         assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
     }
@@ -213,13 +617,10 @@ mod tests {
     #[cfg(unix)]
     fn test_read_does_not_consume_skipped_event() {
         const SKIPPED_EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
-        const CURSOR_EVENT: InternalEvent = InternalEvent::CursorPosition(10, 20);
+        const CURSOR_EVENT: InternalEvent =
+            InternalEvent::Response(Response::CursorPosition(10, 20));
 
-        let mut reader = InternalEventReader {
-            events: vec![SKIPPED_EVENT, CURSOR_EVENT].into(),
-            source: None,
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![SKIPPED_EVENT, CURSOR_EVENT], None);
 
         assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), SKIPPED_EVENT);
@@ -229,11 +630,7 @@ mod tests {
     fn test_poll_timeouts_if_source_has_no_events() {
         let source = FakeSource::default();
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
 
         assert!(!reader
             .poll(Some(Duration::from_secs(0)), &InternalEventFilter)
@@ -244,11 +641,7 @@ mod tests {
     fn test_poll_returns_true_if_source_has_at_least_one_event() {
         let source = FakeSource::with_events(&[InternalEvent::Event(Event::Resize(10, 10))]);
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
 
         assert!(reader.poll(None, &InternalEventFilter).unwrap());
         assert!(reader
@@ -262,13 +655,129 @@ mod tests {
 
         let source = FakeSource::with_events(&[EVENT]);
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
+
+        assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
+    }
+
+    #[test]
+    fn test_read_timed_returns_time_event_was_received() {
+        const EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+
+        let source = FakeSource::with_events(&[EVENT]);
+
+        let reader = reader(vec![], Some(Box::new(source)));
 
+        let before = Instant::now();
+        assert!(reader.poll(None, &InternalEventFilter).unwrap());
+        let after = Instant::now();
+        thread::sleep(Duration::from_millis(10));
+
+        let (event, received) = reader.read_timed(&InternalEventFilter).unwrap();
+        assert_eq!(event, EVENT);
+        assert!(before <= received && received <= after);
+    }
+
+    #[test]
+    fn test_subscribers_get_copies_of_events() {
+        const EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+
+        let source = FakeSource::with_events(&[EVENT, EVENT]);
+
+        let reader = reader(vec![], Some(Box::new(source)));
+        let first = reader.subscribe();
+        let second = reader.subscribe();
+
+        assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
+        drop(second);
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
+
+        assert_eq!(first.try_iter().count(), 2);
+        assert_eq!(reader.subscribers.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_shutdown_drops_source_queued_events_and_subscribers() {
+        // The source interrupt is shared with the other tests
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let source = FakeSource::with_events(&[InternalEvent::Event(Event::Resize(10, 10))]);
+        let reader = reader(
+            vec![InternalEvent::Event(Event::Interrupt)],
+            Some(Box::new(source)),
+        );
+        let subscriber = reader.subscribe();
+
+        reader.shutdown().unwrap();
+
+        assert!(reader.source.lock().is_none());
+        assert_eq!(reader.take(&InternalEventFilter), None);
+        assert_eq!(subscriber.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_resize_bursts_are_coalesced() {
+        // The source interrupt is shared with the other tests
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        const INTERRUPT: InternalEvent = InternalEvent::Event(Event::Interrupt);
+        let source = FakeSource::with_events(&[
+            InternalEvent::Event(Event::Resize(10, 10)),
+            INTERRUPT,
+            InternalEvent::Event(Event::Resize(20, 10)),
+        ]);
+        let reader = reader(vec![], Some(Box::new(source)));
+        reader.set_resize_coalescing(Duration::from_millis(50));
+
+        // The other events aren't delayed
+        let started = Instant::now();
+        assert_eq!(reader.read(&InternalEventFilter).unwrap(), INTERRUPT);
+        assert_eq!(
+            reader.read(&InternalEventFilter).unwrap(),
+            InternalEvent::Event(Event::Resize(20, 10))
+        );
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(!reader
+            .poll(Some(Duration::from_secs(0)), &InternalEventFilter)
+            .unwrap());
+
+        // Turning it off delivers the pending event
+        let source = FakeSource::with_events(&[InternalEvent::Event(Event::Resize(30, 10))]);
+        reader.set_source(Box::new(source)).unwrap();
+        reader.set_resize_coalescing(Duration::from_secs(3600));
+        assert!(!reader
+            .poll(Some(Duration::from_millis(10)), &InternalEventFilter)
+            .unwrap());
+        reader.set_resize_coalescing(Duration::ZERO);
+        assert_eq!(
+            reader.take(&InternalEventFilter),
+            Some(InternalEvent::Event(Event::Resize(30, 10)))
+        );
+    }
+
+    #[test]
+    fn test_priority_events_skip_queued_events() {
+        let key = |c| InternalEvent::Event(Event::Key(KeyCode::Char(c).into()));
+        let reader = reader(vec![], None);
+        reader.set_priority_events(EventKinds::RESIZE | EventKinds::INTERRUPT);
+
+        for event in [
+            key('a'),
+            InternalEvent::Event(Event::Resize(10, 10)),
+            key('b'),
+            InternalEvent::Event(Event::Interrupt),
+        ] {
+            reader.queue_event(event);
+        }
+
+        let events: Vec<_> = std::iter::from_fn(|| reader.take(&InternalEventFilter)).collect();
+        assert_eq!(
+            events,
+            vec![
+                InternalEvent::Event(Event::Resize(10, 10)),
+                InternalEvent::Event(Event::Interrupt),
+                key('a'),
+                key('b'),
+            ]
+        );
     }
 
     #[test]
@@ -277,11 +786,7 @@ mod tests {
 
         let source = FakeSource::with_events(&[EVENT, EVENT, EVENT]);
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
@@ -294,11 +799,7 @@ mod tests {
 
         let source = FakeSource::with_events(&[EVENT, EVENT, EVENT]);
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
@@ -312,11 +813,7 @@ mod tests {
     fn test_poll_propagates_error() {
         let source = FakeSource::with_error(ErrorKind::from(io::ErrorKind::Other));
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
 
         assert_eq!(
             reader
@@ -331,11 +828,7 @@ mod tests {
     fn test_read_propagates_error() {
         let source = FakeSource::with_error(ErrorKind::from(io::ErrorKind::Other));
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
 
         assert_eq!(
             reader
@@ -352,11 +845,7 @@ mod tests {
 
         let source = FakeSource::new(&[EVENT, EVENT], ErrorKind::from(io::ErrorKind::Other));
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
         assert!(reader.read(&InternalEventFilter).is_err());
@@ -371,17 +860,304 @@ mod tests {
 
         let source = FakeSource::new(&[EVENT, EVENT], ErrorKind::from(io::ErrorKind::Other));
 
-        let mut reader = InternalEventReader {
-            events: VecDeque::new(),
-            source: Some(Box::new(source)),
-            skipped_events: Vec::with_capacity(32),
-        };
+        let reader = reader(vec![], Some(Box::new(source)));
 
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
         assert!(reader.read(&InternalEventFilter).is_err());
         assert_eq!(reader.read(&InternalEventFilter).unwrap(), EVENT);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_peek_does_not_consume_event() {
+        const SKIPPED_EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+        const CURSOR_EVENT: InternalEvent =
+            InternalEvent::Response(Response::CursorPosition(10, 20));
+
+        let reader = reader(vec![SKIPPED_EVENT, CURSOR_EVENT], None);
+
+        assert_eq!(reader.peek(&CursorPositionFilter), Some(CURSOR_EVENT));
+        assert_eq!(reader.peek(&InternalEventFilter), Some(SKIPPED_EVENT));
+        assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
+        assert_eq!(reader.peek(&CursorPositionFilter), None);
+    }
+
+    #[test]
+    fn test_read_ready_drains_available_events() {
+        const EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+
+        let source = FakeSource::with_events(&[EVENT, EVENT]);
+
+        let reader = reader(vec![EVENT], Some(Box::new(source)));
+
+        assert_eq!(
+            reader.read_ready(&InternalEventFilter).unwrap(),
+            vec![EVENT, EVENT, EVENT]
+        );
+        assert!(reader.read_ready(&InternalEventFilter).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_poll_honors_the_deadline_under_event_storm() {
+        const NOISE: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+
+        let clock = MockClock::new();
+        let source = StormSource {
+            event: NOISE,
+            clock: clock.clone(),
+            delay: Duration::from_millis(30),
+            timeouts: Arc::new(Mutex::new(Vec::new())),
+        };
+        let timeouts = source.timeouts.clone();
+
+        let reader = reader(vec![], Some(Box::new(source)));
+
+        assert!(!reader
+            .poll_with_clock(
+                Some(Duration::from_millis(100)),
+                &CursorPositionFilter,
+                clock
+            )
+            .unwrap());
+
+        // Every retry waits for the remaining time only
+        let millis = Duration::from_millis;
+        assert_eq!(
+            *timeouts.lock().unwrap(),
+            vec![millis(100), millis(70), millis(40), millis(10)]
+        );
+
+        // The non-matching events are kept
+        assert_eq!(reader.queue.lock().events.len(), 4);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_read_is_not_blocked_by_poll_of_another_thread() {
+        const KEY_EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+        const CURSOR_EVENT: InternalEvent =
+            InternalEvent::Response(Response::CursorPosition(10, 20));
+
+        let (sender, source) = ChannelSource::new();
+        let reader = Arc::new(reader(vec![], Some(Box::new(source))));
+
+        // The cursor position query reads the source
+        let query = {
+            let reader = reader.clone();
+            thread::spawn(move || {
+                reader
+                    .poll(Some(Duration::from_secs(10)), &CursorPositionFilter)
+                    .unwrap()
+            })
+        };
+        while !reader.source.is_locked() {
+            thread::yield_now();
+        }
+
+        // The other events are delivered meanwhile
+        let start = Instant::now();
+        sender.send(Some(KEY_EVENT)).unwrap();
+        assert_eq!(reader.read(&EventFilter).unwrap(), KEY_EVENT);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(!query.is_finished());
+
+        sender.send(Some(CURSOR_EVENT)).unwrap();
+        assert!(query.join().unwrap());
+        assert_eq!(reader.read(&CursorPositionFilter).unwrap(), CURSOR_EVENT);
+    }
+
+    #[test]
+    fn test_each_event_is_read_by_one_thread() {
+        const THREADS: u16 = 4;
+        const EVENTS_PER_THREAD: u16 = 250;
+
+        let events: Vec<_> = (0..THREADS * EVENTS_PER_THREAD)
+            .map(|i| InternalEvent::Event(Event::Resize(i, 0)))
+            .collect();
+        let reader = Arc::new(reader(
+            vec![],
+            Some(Box::new(FakeSource::with_events(&events))),
+        ));
+
+        let threads: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let reader = reader.clone();
+                thread::spawn(move || {
+                    (0..EVENTS_PER_THREAD)
+                        .map(|_| reader.read(&InternalEventFilter).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut read: Vec<_> = threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .map(|event| match event {
+                InternalEvent::Event(Event::Resize(i, _)) => i,
+                event => panic!("unexpected event {:?}", event),
+            })
+            .collect();
+        read.sort_unstable();
+        assert_eq!(read, (0..THREADS * EVENTS_PER_THREAD).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_waiting_threads_return_when_source_is_woken_up() {
+        let (sender, source) = ChannelSource::new();
+        let reader = Arc::new(reader(vec![], Some(Box::new(source))));
+
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let reader = reader.clone();
+                thread::spawn(move || reader.poll(None, &InternalEventFilter).unwrap())
+            })
+            .collect();
+        while !reader.source.is_locked() {
+            thread::yield_now();
+        }
+        // Let the other thread wait for the queue
+        thread::sleep(Duration::from_millis(50));
+
+        sender.send(None).unwrap();
+        for thread in threads {
+            assert!(!thread.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_waiting_thread_takes_over_released_source() {
+        const EVENT: InternalEvent = InternalEvent::Event(Event::Resize(10, 10));
+
+        // The release is swept over the check of the source & the wait of the other thread
+        for delay in 0..200 {
+            let source = FakeSource::with_events(&[EVENT]);
+            let reader = Arc::new(reader(vec![], Some(Box::new(source))));
+            let locked_source = reader.source.lock();
+
+            let (sender, receiver) = mpsc::channel();
+            {
+                let reader = reader.clone();
+                thread::spawn(move || {
+                    let _ = sender.send(reader.poll(None, &InternalEventFilter).unwrap());
+                });
+            }
+            for _ in 0..delay {
+                thread::yield_now();
+            }
+            reader.release_source(&mut reader.queue.lock(), locked_source);
+
+            let polled = receiver.recv_timeout(Duration::from_secs(5));
+            assert_eq!(
+                polled,
+                Ok(true),
+                "the release was missed after {} yields",
+                delay
+            );
+        }
+    }
+
+    /// The model of the source hand-over between the polling threads, checked for the lost
+    /// wake-ups in all the interleavings.
+    #[cfg(crossterm_loom)]
+    #[test]
+    fn loom_released_source_is_taken_over() {
+        use loom::sync::atomic::{AtomicBool, Ordering};
+        use loom::sync::{Arc, Condvar, Mutex};
+
+        loom::model(|| {
+            // The queue & the (locked) source of the reader
+            let reader = Arc::new((Mutex::new(()), Condvar::new(), AtomicBool::new(true)));
+
+            let waiting = {
+                let reader = reader.clone();
+                loom::thread::spawn(move || {
+                    let (queue, queue_changed, source_locked) = &*reader;
+                    let mut queue = queue.lock().unwrap();
+                    // Like the `poll_timeout` without the timeout
+                    while source_locked
+                        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                        .is_err()
+                    {
+                        if source_locked.load(Ordering::SeqCst) {
+                            queue = queue_changed.wait(queue).unwrap();
+                        }
+                    }
+                })
+            };
+
+            // Like the `release_source`
+            let (queue, queue_changed, source_locked) = &*reader;
+            let locked_queue = queue.lock().unwrap();
+            source_locked.store(false, Ordering::SeqCst);
+            queue_changed.notify_all();
+            drop(locked_queue);
+
+            waiting.join().unwrap();
+        });
+    }
+
+    /// Returns the events sent through the channel, `None` wakes it up.
+    struct ChannelSource(Mutex<mpsc::Receiver<Option<InternalEvent>>>);
+
+    impl ChannelSource {
+        fn new() -> (mpsc::Sender<Option<InternalEvent>>, ChannelSource) {
+            let (sender, receiver) = mpsc::channel();
+            (sender, ChannelSource(Mutex::new(receiver)))
+        }
+    }
+
+    impl EventSource for ChannelSource {
+        fn try_read(
+            &mut self,
+            timeout: Option<Duration>,
+        ) -> Result<Option<InternalEvent>, ErrorKind> {
+            let receiver = self.0.get_mut().unwrap();
+            let event = match timeout {
+                Some(timeout) => receiver.recv_timeout(timeout).ok(),
+                None => receiver.recv().ok(),
+            };
+
+            match event {
+                Some(Some(event)) => Ok(Some(event)),
+                Some(None) => Err(io::Error::from(io::ErrorKind::Interrupted)),
+                None => Ok(None),
+            }
+        }
+
+        #[cfg(feature = "event-stream")]
+        fn waker(&self) -> super::super::source::SourceWaker {
+            unimplemented!();
+        }
+    }
+
+    /// Returns the same event on every call, the clock is moved forward by the delay.
+    #[cfg(unix)]
+    struct StormSource {
+        event: InternalEvent,
+        clock: MockClock,
+        delay: Duration,
+        timeouts: Arc<Mutex<Vec<Duration>>>,
+    }
+
+    #[cfg(unix)]
+    impl EventSource for StormSource {
+        fn try_read(
+            &mut self,
+            timeout: Option<Duration>,
+        ) -> Result<Option<InternalEvent>, ErrorKind> {
+            self.timeouts.lock().unwrap().extend(timeout);
+            self.clock.advance(self.delay);
+            Ok(Some(self.event.clone()))
+        }
+
+        #[cfg(feature = "event-stream")]
+        fn waker(&self) -> super::super::source::SourceWaker {
+            unimplemented!();
+        }
+    }
+
     #[derive(Default)]
     struct FakeSource {
         events: VecDeque<InternalEvent>,
@@ -438,7 +1214,7 @@ mod tests {
         }
 
         #[cfg(feature = "event-stream")]
-        fn waker(&self) -> super::super::sys::Waker {
+        fn waker(&self) -> super::super::source::SourceWaker {
             unimplemented!();
         }
     }
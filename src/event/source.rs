@@ -1,9 +1,15 @@
+#[cfg(feature = "event-stream")]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 use std::time::Duration;
 
 #[cfg(feature = "event-stream")]
 use super::sys::Waker;
 use super::InternalEvent;
 
+pub(crate) mod custom;
 #[cfg(unix)]
 pub(crate) mod unix;
 #[cfg(windows)]
@@ -16,12 +22,36 @@ pub(crate) trait EventSource: Sync + Send {
     /// # Arguments
     ///
     /// * `timeout` - `None` block indefinitely until an event is available, `Some(duration)` blocks
-    ///               for the given timeout
+    ///   for the given timeout
     ///
     /// Returns `Ok(None)` if there's no event available and timeout expires.
     fn try_read(&mut self, timeout: Option<Duration>) -> crate::Result<Option<InternalEvent>>;
 
-    /// Returns a `Waker` allowing to wake/force the `try_read` method to return `Ok(None)`.
+    /// Returns a `SourceWaker` allowing to wake/force the `try_read` method to return `Ok(None)`.
     #[cfg(feature = "event-stream")]
-    fn waker(&self) -> Waker;
+    fn waker(&self) -> SourceWaker;
+}
+
+/// Allows to wake up the `EventSource::try_read` from another thread.
+#[cfg(feature = "event-stream")]
+#[derive(Debug, Clone)]
+pub(crate) enum SourceWaker {
+    /// Wakes up the poll of the platform event source.
+    System(Waker),
+    /// Sets the flag checked by the custom event source between its reads.
+    Flag(Arc<AtomicBool>),
+}
+
+#[cfg(feature = "event-stream")]
+impl SourceWaker {
+    /// Wakes up the `try_read`, it returns `Ok(None)`.
+    pub(crate) fn wake(&self) -> crate::Result<()> {
+        match self {
+            SourceWaker::System(waker) => waker.wake(),
+            SourceWaker::Flag(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+    }
 }
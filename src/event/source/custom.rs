@@ -0,0 +1,253 @@
+//! This module contains the public interface of the event sources supplied by the
+//! applications.
+
+#[cfg(feature = "event-stream")]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::Result;
+
+#[cfg(feature = "event-stream")]
+use super::SourceWaker;
+use super::{
+    super::{
+        read::InternalEventReader,
+        timeout::PollTimeout,
+        wake::{next_injected_event, take_source_interrupt},
+        Event, InternalEvent, INTERNAL_EVENT_READER,
+    },
+    EventSource as InternalEventSource,
+};
+
+/// The longest time the custom source is waited for before the wake-ups are checked.
+const WAKE_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A source of the events read by the [read](fn.read.html) and the [poll](fn.poll.html),
+/// supplied by the application instead of the terminal.
+///
+/// It allows to read the events from an SSH channel, an embedded PTY or a test harness,
+/// see the [set_event_source](fn.set_event_source.html).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::{collections::VecDeque, time::Duration};
+///
+/// use crossterm::{
+///     event::{self, Event, EventSource, KeyCode, KeyEvent, KeyModifiers},
+///     Result,
+/// };
+///
+/// struct Script(VecDeque<Event>);
+///
+/// impl EventSource for Script {
+///     fn try_read(&mut self, _timeout: Option<Duration>) -> Result<Option<Event>> {
+///         Ok(self.0.pop_front())
+///     }
+/// }
+///
+/// fn main() -> Result<()> {
+///     let enter = Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+///     event::set_event_source(Box::new(Script(vec![enter.clone()].into())))?;
+///
+///     assert_eq!(event::read()?, enter);
+///     Ok(())
+/// }
+/// ```
+pub trait EventSource: Send {
+    /// Tries to read an event within the given duration, `None` means to block until an
+    /// event is available.
+    ///
+    /// Returns `Ok(None)` if there's no event available and the timeout expires. The
+    /// timeouts are at most 50 milliseconds long, the
+    /// [Waker](struct.Waker.html) and the `EventStream` wake-ups are delivered in between.
+    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<Event>>;
+}
+
+/// Replaces the source of the events read by the [read](fn.read.html) and the
+/// [poll](fn.poll.html).
+///
+/// The events already read from the previous source are delivered first. The terminal
+/// isn't read anymore, it isn't even opened if the source is set before the first read.
+///
+/// # Notes
+///
+/// * The queries of the terminal (e.g. the `cursor::position`) time out on UNIX, their
+///   replies can't be read from a custom source.
+/// * The source must be set before an `EventStream` is created.
+/// * The thread blocked in the `read`/`poll` is interrupted, it reads the new source
+///   then. The blocked `poll` returns `Ok(false)`.
+pub fn set_event_source(source: Box<dyn EventSource>) -> Result<()> {
+    let mut source: Option<Box<dyn InternalEventSource>> =
+        Some(Box::new(CustomEventSource::new(source)));
+    // The terminal isn't opened if there's no reader yet
+    let reader = INTERNAL_EVENT_READER.get_or_init(|| {
+        InternalEventReader::with_source(source.take().expect("the source is set once"))
+    });
+
+    match source {
+        Some(source) => reader.set_source(source),
+        None => Ok(()),
+    }
+}
+
+/// Adapts the custom `EventSource` to the internal one.
+pub(crate) struct CustomEventSource {
+    source: Mutex<Box<dyn EventSource>>,
+    #[cfg(feature = "event-stream")]
+    woken: Arc<AtomicBool>,
+}
+
+impl CustomEventSource {
+    pub(crate) fn new(source: Box<dyn EventSource>) -> CustomEventSource {
+        CustomEventSource {
+            source: Mutex::new(source),
+            #[cfg(feature = "event-stream")]
+            woken: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl InternalEventSource for CustomEventSource {
+    fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
+        let timeout = PollTimeout::new(timeout);
+
+        loop {
+            if let Some(event) = next_injected_event() {
+                return Ok(Some(InternalEvent::Event(event)));
+            }
+
+            #[cfg(feature = "event-stream")]
+            if self.woken.swap(false, Ordering::SeqCst) {
+                return Ok(None);
+            }
+
+            if take_source_interrupt() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Poll operation was interrupted by the shutdown",
+                ));
+            }
+
+            let interval = timeout.leftover().map_or(WAKE_CHECK_INTERVAL, |leftover| {
+                leftover.min(WAKE_CHECK_INTERVAL)
+            });
+            if let Some(event) = self.source.get_mut().try_read(Some(interval))? {
+                return Ok(Some(InternalEvent::Event(event)));
+            }
+
+            if timeout.elapsed() {
+                return Ok(None);
+            }
+        }
+    }
+
+    #[cfg(feature = "event-stream")]
+    fn waker(&self) -> SourceWaker {
+        SourceWaker::Flag(self.woken.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use crate::event::{
+        filter::EventFilter, read::InternalEventReader, Event, KeyCode, KeyEvent, KeyModifiers,
+    };
+    use crate::Result;
+
+    use super::{CustomEventSource, EventSource, InternalEvent};
+
+    struct ScriptSource {
+        events: VecDeque<Event>,
+        timeouts: Arc<Mutex<Vec<Option<Duration>>>>,
+    }
+
+    impl EventSource for ScriptSource {
+        fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<Event>> {
+            self.timeouts.lock().unwrap().push(timeout);
+            Ok(self.events.pop_front())
+        }
+    }
+
+    struct SilentSource;
+
+    impl EventSource for SilentSource {
+        fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<Event>> {
+            std::thread::sleep(timeout.unwrap_or(super::WAKE_CHECK_INTERVAL));
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_blocked_reader_reads_the_replaced_source() {
+        // The source interrupt is shared with the other tests
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let enter = Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let reader = InternalEventReader::with_source(Box::new(CustomEventSource::new(Box::new(
+            SilentSource,
+        ))));
+
+        std::thread::scope(|scope| {
+            let blocked = scope.spawn(|| reader.read(&EventFilter));
+            std::thread::sleep(Duration::from_millis(100));
+
+            let source = ScriptSource {
+                events: vec![enter.clone()].into(),
+                timeouts: Arc::new(Mutex::new(Vec::new())),
+            };
+            reader
+                .set_source(Box::new(CustomEventSource::new(Box::new(source))))
+                .unwrap();
+
+            assert_eq!(
+                blocked.join().unwrap().unwrap(),
+                InternalEvent::Event(enter)
+            );
+        });
+    }
+
+    #[test]
+    fn test_custom_source_events_are_read() {
+        // The injected events are shared with the other tests
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let enter = Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let timeouts = Arc::new(Mutex::new(Vec::new()));
+        let source = ScriptSource {
+            events: vec![enter.clone(), Event::Resize(10, 5)].into(),
+            timeouts: timeouts.clone(),
+        };
+        let reader =
+            InternalEventReader::with_source(Box::new(CustomEventSource::new(Box::new(source))));
+
+        assert_eq!(
+            reader.read(&EventFilter).unwrap(),
+            InternalEvent::Event(enter)
+        );
+        assert!(reader.poll(None, &EventFilter).unwrap());
+        assert_eq!(
+            reader.read(&EventFilter).unwrap(),
+            InternalEvent::Event(Event::Resize(10, 5))
+        );
+        assert!(!reader
+            .poll(Some(Duration::from_millis(0)), &EventFilter)
+            .unwrap());
+
+        // The blocking reads are split, so the wake-ups are delivered
+        assert!(timeouts
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|timeout| timeout.is_some_and(|timeout| timeout <= super::WAKE_CHECK_INTERVAL)));
+    }
+}
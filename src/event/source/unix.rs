@@ -1,40 +1,69 @@
-use std::{collections::VecDeque, io, time::Duration};
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+    os::unix::{io::AsRawFd, net::UnixStream},
+    time::Duration,
+};
 
-use mio::{unix::SourceFd, Events, Interest, Poll, Token};
-use signal_hook_mio::v0_7::Signals;
+use mio::{unix::SourceFd, Events, Interest, Poll, Registry, Token};
 
-use crate::Result;
+use crate::terminal::sys::file_descriptor::{tty_fd, FileDesc};
+use crate::{NotATty, Result};
 
+#[cfg(feature = "probe")]
+use super::super::probe::record_raw_input;
 #[cfg(feature = "event-stream")]
 use super::super::sys::Waker;
 use super::super::{
+    is_interrupt_events_enabled,
+    normalize::normalize_key_event,
+    query::route_response,
     source::EventSource,
-    sys::unix::{
-        file_descriptor::{tty_fd, FileDesc},
-        parse::parse_event,
+    sys::{
+        unix::{
+            parse::parse_event,
+            signal::{
+                pending_signals, register_signals, signal_from_number, suspend_handling,
+                unregister_signals,
+            },
+            user_fd::{register_user_fds, unregister_user_fds, user_fd_event, FIRST_USER_TOKEN},
+        },
+        win32_key::{handle_key_event, KeyEventState},
     },
     timeout::PollTimeout,
-    Event, InternalEvent,
+    wake::{
+        next_injected_event, register_wake_stream, take_source_interrupt, unregister_wake_stream,
+    },
+    Event, InternalEvent, SuspendHandling,
 };
+#[cfg(feature = "event-stream")]
+use super::SourceWaker;
 
 // Tokens to identify file descriptor
 const TTY_TOKEN: Token = Token(0);
 const SIGNAL_TOKEN: Token = Token(1);
 #[cfg(feature = "event-stream")]
 const WAKE_TOKEN: Token = Token(2);
+const USER_WAKE_TOKEN: Token = Token(3);
 
 // I (@zrzka) wasn't able to read more than 1_022 bytes when testing
 // reading on macOS/Linux -> we don't need bigger buffer and 1k of bytes
 // is enough.
 const TTY_BUFFER_SIZE: usize = 1_204;
 
+// The tty is read at most this many times per poll, so the flooded tty doesn't starve
+// the signals and the other file descriptors (and the reader gets the events early).
+const MAX_TTY_READS: usize = 8;
+
 pub(crate) struct UnixInternalEventSource {
     poll: Poll,
     events: Events,
     parser: Parser,
     tty_buffer: [u8; TTY_BUFFER_SIZE],
     tty_fd: FileDesc,
-    signals: Signals,
+    // The tty wasn't read until `WouldBlock`, there's no readiness event for the rest
+    tty_pending: bool,
+    wake_stream: UnixStream,
     #[cfg(feature = "event-stream")]
     waker: Waker,
 }
@@ -52,35 +81,128 @@ impl UnixInternalEventSource {
         let mut tty_ev = SourceFd(&tty_raw_fd);
         registry.register(&mut tty_ev, TTY_TOKEN, Interest::READABLE)?;
 
-        let mut signals = Signals::new(&[signal_hook::consts::SIGWINCH])?;
-        registry.register(&mut signals, SIGNAL_TOKEN, Interest::READABLE)?;
+        register_signals(registry, SIGNAL_TOKEN)?;
+        register_user_fds(registry)?;
+
+        let wake_stream = register_wake_stream()?;
+        registry.register(
+            &mut SourceFd(&wake_stream.as_raw_fd()),
+            USER_WAKE_TOKEN,
+            Interest::READABLE,
+        )?;
 
         #[cfg(feature = "event-stream")]
         let waker = Waker::new(registry, WAKE_TOKEN)?;
 
         Ok(UnixInternalEventSource {
             poll,
-            events: Events::with_capacity(3),
+            events: Events::with_capacity(16),
             parser: Parser::default(),
             tty_buffer: [0u8; TTY_BUFFER_SIZE],
             tty_fd: input_fd,
-            signals,
+            tty_pending: false,
+            wake_stream,
             #[cfg(feature = "event-stream")]
             waker,
         })
     }
+
+    /// Reads and parses the tty input, returns whether there may be more to read.
+    fn read_tty(&mut self) -> Result<bool> {
+        for _ in 0..MAX_TTY_READS {
+            match self.tty_fd.read(&mut self.tty_buffer, TTY_BUFFER_SIZE) {
+                // End of file, there's nothing to read until the next event
+                Ok(0) => return Ok(false),
+                Ok(read_count) => {
+                    self.parser.advance(
+                        &self.tty_buffer[..read_count],
+                        read_count == TTY_BUFFER_SIZE,
+                    );
+                }
+                Err(e) => {
+                    // No more data to read at the moment. We will receive another event
+                    // once more data is available to read.
+                    if e.kind() == io::ErrorKind::WouldBlock {
+                        return Ok(false);
+                    } else if e.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    } else if is_tty_revoked(&e) {
+                        reopen_tty(self.poll.registry(), &mut self.tty_fd)?;
+                        return Ok(false);
+                    } else {
+                        return Err(e);
+                    }
+                }
+            };
+        }
+        Ok(true)
+    }
+
+    /// Returns the next already parsed event or the next injected event.
+    fn next_event(&mut self) -> Option<InternalEvent> {
+        self.parser
+            .next()
+            .or_else(|| next_injected_event().map(InternalEvent::Event))
+    }
+}
+
+/// Replaces the revoked tty file descriptor with a newly opened one.
+///
+/// The new file descriptor usually has a different number (e.g. the `/dev/tty` is
+/// reopened on macOS after the session was revoked), the registration is replaced too.
+fn reopen_tty(registry: &Registry, tty: &mut FileDesc) -> Result<()> {
+    let new_tty = tty_fd()?;
+    if new_tty.raw_fd() == tty.raw_fd() {
+        // The revoked standard input is still the terminal, there's nothing to reopen
+        return Err(io::Error::new(io::ErrorKind::NotFound, NotATty));
+    }
+
+    // The old file descriptor may be closed already, the error is irrelevant
+    let _ = registry.deregister(&mut SourceFd(&tty.raw_fd()));
+    registry.register(
+        &mut SourceFd(&new_tty.raw_fd()),
+        TTY_TOKEN,
+        Interest::READABLE,
+    )?;
+
+    // Keep it last - the old file descriptor is closed when replaced
+    *tty = new_tty;
+    Ok(())
+}
+
+/// Returns whether the read error means the tty was revoked or closed.
+fn is_tty_revoked(error: &io::Error) -> bool {
+    matches!(
+        error.raw_os_error(),
+        Some(libc::EIO | libc::EBADF | libc::ENXIO)
+    )
+}
+
+impl Drop for UnixInternalEventSource {
+    fn drop(&mut self) {
+        unregister_signals();
+        unregister_user_fds();
+        unregister_wake_stream();
+    }
 }
 
 impl EventSource for UnixInternalEventSource {
     fn try_read(&mut self, timeout: Option<Duration>) -> Result<Option<InternalEvent>> {
-        if let Some(event) = self.parser.next() {
+        if let Some(event) = self.next_event() {
             return Ok(Some(event));
         }
 
         let timeout = PollTimeout::new(timeout);
 
         loop {
-            if let Err(e) = self.poll.poll(&mut self.events, timeout.leftover()) {
+            // Don't wait if the tty has more to read
+            let poll_timeout = if self.tty_pending {
+                Some(Duration::from_secs(0))
+            } else {
+                timeout.leftover()
+            };
+
+            if let Err(e) = self.poll.poll(&mut self.events, poll_timeout) {
                 // Mio will throw an interrupted error in case of cursor position retrieval. We need to retry until it succeeds.
                 // Previous versions of Mio (< 0.7) would automatically retry the poll call if it was interrupted (if EINTR was returned).
                 // https://docs.rs/mio/0.7.0/mio/struct.Poll.html#notes
@@ -91,44 +213,24 @@ impl EventSource for UnixInternalEventSource {
                 }
             };
 
-            if self.events.is_empty() {
+            if self.events.is_empty() && !self.tty_pending {
                 // No readiness events = timeout
                 return Ok(None);
             }
 
-            for token in self.events.iter().map(|x| x.token()) {
-                match token {
-                    TTY_TOKEN => {
-                        loop {
-                            match self.tty_fd.read(&mut self.tty_buffer, TTY_BUFFER_SIZE) {
-                                Ok(read_count) => {
-                                    if read_count > 0 {
-                                        self.parser.advance(
-                                            &self.tty_buffer[..read_count],
-                                            read_count == TTY_BUFFER_SIZE,
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    // No more data to read at the moment. We will receive another event
-                                    if e.kind() == io::ErrorKind::WouldBlock {
-                                        break;
-                                    }
-                                    // once more data is available to read.
-                                    else if e.kind() == io::ErrorKind::Interrupted {
-                                        continue;
-                                    }
-                                }
-                            };
+            #[cfg(feature = "event-stream")]
+            let mut woken = false;
+            let mut tty_ready = self.tty_pending;
 
-                            if let Some(event) = self.parser.next() {
-                                return Ok(Some(event));
-                            }
-                        }
-                    }
+            // All the readiness events must be processed, mio is edge-triggered and there's
+            // no other notification for them. Queue the events and return the first one.
+            // The tty is read last, so the signals aren't queued behind a flood of input.
+            for event in self.events.iter() {
+                match event.token() {
+                    TTY_TOKEN => tty_ready = true,
                     SIGNAL_TOKEN => {
-                        for signal in self.signals.pending() {
-                            match signal {
+                        for signal in pending_signals() {
+                            let event = match signal {
                                 signal_hook::consts::SIGWINCH => {
                                     // TODO Should we remove tput?
                                     //
@@ -138,25 +240,83 @@ impl EventSource for UnixInternalEventSource {
                                     // it's a really long time from the mio, async-std/tokio executor, ...
                                     // point of view.
                                     let new_size = crate::terminal::size()?;
-                                    return Ok(Some(InternalEvent::Event(Event::Resize(
-                                        new_size.0, new_size.1,
-                                    ))));
+                                    Event::Resize(new_size.0, new_size.1)
                                 }
-                                _ => unreachable!("Synchronize signal registration & handling"),
+                                signal_hook::consts::SIGTSTP => match suspend_handling() {
+                                    Some(SuspendHandling::Notify) => Event::Suspended,
+                                    Some(SuspendHandling::RestoreTerminal) => {
+                                        // Returns once the process is continued, the resize
+                                        // event and the `SIGCONT` follow
+                                        crate::terminal::suspend_process()?;
+                                        Event::Suspended
+                                    }
+                                    // Delivered after the suspend events were disabled
+                                    None => continue,
+                                },
+                                signal_hook::consts::SIGCONT => match suspend_handling() {
+                                    Some(_) => Event::Resumed,
+                                    None => continue,
+                                },
+                                signal => Event::Signal(
+                                    signal_from_number(signal)
+                                        .expect("Synchronize signal registration & handling"),
+                                ),
                             };
+                            self.parser
+                                .internal_events
+                                .push_back(InternalEvent::Event(event));
                         }
                     }
                     #[cfg(feature = "event-stream")]
                     WAKE_TOKEN => {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::Interrupted,
-                            "Poll operation was woken up by `Waker::wake`",
-                        ));
+                        woken = true;
+                    }
+                    USER_WAKE_TOKEN => {
+                        // Tokens are queued by the `Waker`, just drain the stream
+                        let mut buffer = [0u8; 32];
+                        loop {
+                            match self.wake_stream.read(&mut buffer) {
+                                Ok(0) => break,
+                                Ok(_) => continue,
+                                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                                Err(_) => break,
+                            }
+                        }
+                    }
+                    token if token.0 >= FIRST_USER_TOKEN => {
+                        let (token, readiness) = user_fd_event(event);
+                        self.parser
+                            .internal_events
+                            .push_back(InternalEvent::Event(Event::Fd(token, readiness)));
                     }
                     _ => unreachable!("Synchronize Evented handle registration & token handling"),
                 }
             }
 
+            if tty_ready {
+                self.tty_pending = self.read_tty()?;
+            }
+
+            if take_source_interrupt() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "Poll operation was interrupted by the shutdown",
+                ));
+            }
+
+            #[cfg(feature = "event-stream")]
+            if woken {
+                // Queued events are returned by the next `try_read` call
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Poll operation was woken up by `Waker::wake`",
+                ));
+            }
+
+            if let Some(event) = self.next_event() {
+                return Ok(Some(event));
+            }
+
             // Processing above can take some time, check if timeout expired
             if timeout.elapsed() {
                 return Ok(None);
@@ -165,8 +325,8 @@ impl EventSource for UnixInternalEventSource {
     }
 
     #[cfg(feature = "event-stream")]
-    fn waker(&self) -> Waker {
-        self.waker.clone()
+    fn waker(&self) -> SourceWaker {
+        SourceWaker::System(self.waker.clone())
     }
 }
 
@@ -180,6 +340,8 @@ impl EventSource for UnixInternalEventSource {
 struct Parser {
     buffer: Vec<u8>,
     internal_events: VecDeque<InternalEvent>,
+    // The state of the key events reported in the `win32-input-mode`
+    key_event_state: KeyEventState,
 }
 
 impl Default for Parser {
@@ -202,6 +364,7 @@ impl Default for Parser {
             // method implementation, all events are consumed before the next TTY_BUFFER
             // is processed -> events pushed.
             internal_events: VecDeque::with_capacity(128),
+            key_event_state: KeyEventState::default(),
         }
     }
 }
@@ -214,10 +377,7 @@ impl Parser {
             self.buffer.push(*byte);
 
             match parse_event(&self.buffer, more) {
-                Ok(Some(ie)) => {
-                    self.internal_events.push_back(ie);
-                    self.buffer.clear();
-                }
+                Ok(Some(ie)) => self.push_event(ie),
                 Ok(None) => {
                     // Event can't be parsed, because we don't have enough bytes for
                     // the current sequence. Keep the buffer and process next bytes.
@@ -225,11 +385,90 @@ impl Parser {
                 Err(_) => {
                     // Event can't be parsed (not enough parameters, parameter is not a number, ...).
                     // Clear the buffer and continue with another sequence.
+                    let interrupted = is_interrupted_utf8_sequence(&self.buffer);
+                    #[cfg(feature = "probe")]
+                    record_raw_input(
+                        &self.buffer[..self.buffer.len() - usize::from(interrupted)],
+                        false,
+                    );
                     self.buffer.clear();
+
+                    // A multi-byte character was cut short by a byte that can't continue it
+                    // (the rest got lost or the terminal sent garbage). Don't throw away the
+                    // byte that interrupted it, it can be the beginning of a new sequence.
+                    if interrupted {
+                        self.buffer.push(*byte);
+
+                        match parse_event(&self.buffer, more) {
+                            Ok(Some(ie)) => self.push_event(ie),
+                            Ok(None) => {}
+                            Err(_) => {
+                                #[cfg(feature = "probe")]
+                                record_raw_input(&self.buffer, false);
+                                self.buffer.clear();
+                            }
+                        }
+                    }
                 }
             }
         }
     }
+
+    fn push_event(&mut self, ie: InternalEvent) {
+        #[cfg(feature = "probe")]
+        record_raw_input(&self.buffer, true);
+
+        // Ctrl-C produces the ETX control character
+        if self.buffer == [b'\x03'] && is_interrupt_events_enabled() {
+            self.internal_events
+                .push_back(InternalEvent::Event(Event::Interrupt));
+        } else if let InternalEvent::Event(Event::Key(key_event)) = ie {
+            self.internal_events
+                .push_back(InternalEvent::Event(Event::Key(normalize_key_event(
+                    key_event,
+                ))));
+        } else if let InternalEvent::Response(response) = ie {
+            // The responses go to the queries waiting for them
+            self.internal_events.extend(route_response(response));
+        } else if let InternalEvent::InBandResize {
+            columns,
+            rows,
+            width,
+            height,
+        } = ie
+        {
+            self.internal_events
+                .push_back(InternalEvent::Event(Event::Resize(columns, rows)));
+            // The terminal doesn't know the size in pixels
+            if width != 0 && height != 0 {
+                self.internal_events
+                    .push_back(InternalEvent::Event(Event::PixelResize(width, height)));
+            }
+        } else if let InternalEvent::Win32Key(record) = ie {
+            // The repeats are split, the releases are dropped unless asked for, the
+            // characters outside of the BMP are joined
+            self.internal_events.extend(
+                handle_key_event(record, &mut self.key_event_state).map(InternalEvent::Event),
+            );
+        } else {
+            self.internal_events.push_back(ie);
+        }
+        self.buffer.clear();
+    }
+}
+
+/// Returns whether the buffer holds an incomplete UTF-8 character (optionally prefixed
+/// with `ESC`) whose last byte is not a continuation byte.
+fn is_interrupted_utf8_sequence(buffer: &[u8]) -> bool {
+    let sequence = match buffer {
+        [b'\x1B', rest @ ..] => rest,
+        _ => buffer,
+    };
+
+    match sequence {
+        [0xC0..=0xF7, .., last] => !(0x80..=0xBF).contains(last),
+        _ => false,
+    }
 }
 
 impl Iterator for Parser {
@@ -239,3 +478,300 @@ impl Iterator for Parser {
         self.internal_events.pop_front()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        os::unix::{io::AsRawFd, net::UnixStream},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use crate::cursor::tracking::TEST_LOCK;
+    use crate::event::{
+        disable_suspend_events, enable_suspend_events, register_signal, source::EventSource, Event,
+        KeyCode, KeyEvent, KeyModifiers, Signal, SuspendHandling, INTERRUPT_EVENTS,
+    };
+    use crate::terminal::sys::file_descriptor::FileDesc;
+
+    use super::{InternalEvent, Parser, UnixInternalEventSource, MAX_TTY_READS, TTY_BUFFER_SIZE};
+
+    fn advance_all(parser: &mut Parser, reads: &[&[u8]]) -> Vec<InternalEvent> {
+        for read in reads {
+            parser.advance(read, false);
+        }
+        parser.collect()
+    }
+
+    fn char_event(c: char) -> InternalEvent {
+        InternalEvent::Event(Event::Key(KeyEvent::new(
+            KeyCode::Char(c),
+            KeyModifiers::empty(),
+        )))
+    }
+
+    #[test]
+    fn test_paste_split_across_reads() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[b"\x1B[200~ab", b"c\x1B[2", b"01~d"]),
+            vec![
+                InternalEvent::Event(Event::Paste("abc".to_string())),
+                char_event('d')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_two_byte_char_split_across_reads() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[0xC3], &[0xB1]]),
+            vec![char_event('ñ')]
+        );
+    }
+
+    #[test]
+    fn test_three_byte_char_split_across_reads() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[0xE2], &[0x82, 0xAC]]),
+            vec![char_event('€')]
+        );
+
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[0xE2, 0x82], &[0xAC]]),
+            vec![char_event('€')]
+        );
+    }
+
+    #[test]
+    fn test_four_byte_char_split_into_single_bytes() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[0xF0], &[0x9F], &[0x98], &[0x80]]),
+            vec![char_event('😀')]
+        );
+    }
+
+    #[test]
+    fn test_char_split_after_other_input() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[b'a', 0xC3], &[0xB1, b'b']]),
+            vec![char_event('a'), char_event('ñ'), char_event('b')]
+        );
+    }
+
+    #[test]
+    fn test_alt_char_split_across_reads() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[b'\x1B', 0xC3], &[0xB1]]),
+            vec![InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('ñ'),
+                KeyModifiers::ALT
+            )))]
+        );
+    }
+
+    #[test]
+    fn test_interrupted_char_keeps_next_char() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[0xE2, 0x82], b"a"]),
+            vec![char_event('a')]
+        );
+    }
+
+    #[test]
+    fn test_interrupted_char_keeps_next_sequence() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[0xC3], b"\x1B[D"]),
+            vec![InternalEvent::Event(Event::Key(KeyCode::Left.into()))]
+        );
+    }
+
+    #[test]
+    fn test_stray_continuation_byte_is_dropped() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[&[0x80], &[0xC3, 0xB1]]),
+            vec![char_event('ñ')]
+        );
+    }
+
+    #[test]
+    fn test_ctrl_c_as_interrupt_event() {
+        let ctrl_c = InternalEvent::Event(Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )));
+
+        let mut parser = Parser::default();
+        assert_eq!(advance_all(&mut parser, &[b"\x03"]), vec![ctrl_c.clone()]);
+
+        INTERRUPT_EVENTS.store(true, Ordering::SeqCst);
+        let events = advance_all(&mut parser, &[b"a\x03", b"\x1B\x03"]);
+        INTERRUPT_EVENTS.store(false, Ordering::SeqCst);
+
+        assert_eq!(
+            events,
+            vec![
+                char_event('a'),
+                InternalEvent::Event(Event::Interrupt),
+                // Alt+Ctrl+c is still a key event
+                InternalEvent::Event(Event::Key(KeyEvent::new(
+                    KeyCode::Char('c'),
+                    KeyModifiers::CONTROL | KeyModifiers::ALT
+                ))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_win32_input_mode_keys() {
+        let mut parser = Parser::default();
+        let events = advance_all(
+            &mut parser,
+            &[
+                // Shift down, A down, A up, Shift up
+                b"\x1B[16;42;0;1;16;1_\x1B[65;30;65;1;16;1_",
+                b"\x1B[65;30;65;0;16;1_\x1B[16;42;0;0;0;1_",
+                // F5 down, split across the reads
+                b"\x1B[116;63;0;",
+                b"1;0;1_",
+            ],
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                InternalEvent::Event(Event::Key(KeyEvent::new(
+                    KeyCode::Char('A'),
+                    KeyModifiers::SHIFT
+                ))),
+                InternalEvent::Event(Event::Key(KeyCode::F(5).into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_in_band_resize() {
+        let mut parser = Parser::default();
+        assert_eq!(
+            advance_all(&mut parser, &[b"\x1B[48;24;80;480;800t", b"\x1B[48;25;81t"]),
+            vec![
+                InternalEvent::Event(Event::Resize(80, 24)),
+                InternalEvent::Event(Event::PixelResize(800, 480)),
+                InternalEvent::Event(Event::Resize(81, 25)),
+            ]
+        );
+    }
+
+    /// Reads the events until the expected one, returns the number of the events before it.
+    fn count_events_until(source: &mut UnixInternalEventSource, expected: &InternalEvent) -> usize {
+        let mut count = 0;
+        loop {
+            match source.try_read(Some(Duration::from_secs(5))).unwrap() {
+                Some(event) if &event == expected => return count,
+                Some(_) => count += 1,
+                None => panic!("{:?} wasn't read in time", expected),
+            }
+        }
+    }
+
+    #[test]
+    fn test_signals_and_tty_are_read_fairly_under_load() {
+        // The signals handle is replaced by the next source
+        let _lock = TEST_LOCK.lock();
+        register_signal(Signal::User1).unwrap();
+
+        let (tty, mut input) = UnixStream::pair().unwrap();
+        tty.set_nonblocking(true).unwrap();
+        let mut source =
+            UnixInternalEventSource::from_file_descriptor(FileDesc::new(tty.as_raw_fd(), false))
+                .unwrap();
+
+        // Flood the tty, the signal must not wait until it's drained
+        let stop = Arc::new(AtomicBool::new(false));
+        let flood = {
+            let stop = stop.clone();
+            let mut input = input.try_clone().unwrap();
+            thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    input.write_all(&[b'a'; 4096]).unwrap();
+                }
+            })
+        };
+        assert_eq!(count_events_until(&mut source, &char_event('a')), 0);
+
+        unsafe { libc::raise(libc::SIGUSR1) };
+        let signal = InternalEvent::Event(Event::Signal(Signal::User1));
+        assert!(count_events_until(&mut source, &signal) <= 2 * MAX_TTY_READS * TTY_BUFFER_SIZE);
+
+        stop.store(true, Ordering::SeqCst);
+        while source
+            .try_read(Some(Duration::from_millis(100)))
+            .unwrap()
+            .is_some()
+        {}
+        flood.join().unwrap();
+
+        // Flood the signals, the tty input must not wait
+        let stop = Arc::new(AtomicBool::new(false));
+        let flood = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    unsafe { libc::kill(libc::getpid(), libc::SIGUSR1) };
+                    thread::yield_now();
+                }
+            })
+        };
+        assert_eq!(count_events_until(&mut source, &signal), 0);
+
+        input.write_all(b"b").unwrap();
+        assert!(count_events_until(&mut source, &char_event('b')) <= 2);
+
+        stop.store(true, Ordering::SeqCst);
+        flood.join().unwrap();
+    }
+
+    #[test]
+    fn test_suspend_signals_are_delivered_as_events() {
+        let _lock = TEST_LOCK.lock();
+        enable_suspend_events(SuspendHandling::Notify).unwrap();
+
+        let (tty, _input) = UnixStream::pair().unwrap();
+        let mut source =
+            UnixInternalEventSource::from_file_descriptor(FileDesc::new(tty.as_raw_fd(), false))
+                .unwrap();
+
+        // The process isn't stopped, the application handles the event
+        unsafe { libc::raise(libc::SIGTSTP) };
+        assert_eq!(
+            count_events_until(&mut source, &InternalEvent::Event(Event::Suspended)),
+            0
+        );
+        unsafe { libc::raise(libc::SIGCONT) };
+        assert_eq!(
+            count_events_until(&mut source, &InternalEvent::Event(Event::Resumed)),
+            0
+        );
+
+        disable_suspend_events().unwrap();
+        unsafe { libc::raise(libc::SIGCONT) };
+        assert_eq!(
+            source.try_read(Some(Duration::from_millis(100))).unwrap(),
+            None
+        );
+    }
+}
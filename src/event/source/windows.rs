@@ -1,26 +1,34 @@
+use std::collections::VecDeque;
 use std::time::Duration;
 
-use crossterm_winapi::{Console, Handle, InputRecord};
+use crossterm_winapi::{Console, InputRecord};
 
 use crate::event::{sys::windows::poll::WinApiPoll, Event};
 
-#[cfg(feature = "event-stream")]
-use super::super::sys::Waker;
 use super::super::{
     source::EventSource,
-    sys::windows::parse::{handle_key_event, handle_mouse_event},
+    sys::{
+        win32_key::{handle_key_event, KeyEventState},
+        windows::parse::handle_mouse_event,
+    },
     timeout::PollTimeout,
+    wake::{next_injected_event, take_source_interrupt},
     InternalEvent, Result,
 };
+#[cfg(feature = "event-stream")]
+use super::SourceWaker;
 
 pub(crate) struct WindowsEventSource {
     console: Console,
     poll: WinApiPoll,
+    key_event_state: KeyEventState,
+    /// The events parsed from one input record which weren't read yet.
+    pending: VecDeque<Event>,
 }
 
 impl WindowsEventSource {
     pub(crate) fn new() -> Result<WindowsEventSource> {
-        let console = Console::from(Handle::current_in_handle()?);
+        let console = Console::from(crate::terminal::sys::input_handle()?);
         Ok(WindowsEventSource {
             console,
 
@@ -28,6 +36,9 @@ impl WindowsEventSource {
             poll: WinApiPoll::new(),
             #[cfg(feature = "event-stream")]
             poll: WinApiPoll::new()?,
+
+            key_event_state: KeyEventState::default(),
+            pending: VecDeque::new(),
         })
     }
 }
@@ -37,12 +48,40 @@ impl EventSource for WindowsEventSource {
         let poll_timeout = PollTimeout::new(timeout);
 
         loop {
-            if let Some(event_ready) = self.poll.poll(poll_timeout.leftover())? {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(InternalEvent::Event(event)));
+            }
+
+            if let Some(event) = next_injected_event() {
+                return Ok(Some(InternalEvent::Event(event)));
+            }
+
+            let event_ready = self.poll.poll(poll_timeout.leftover())?;
+
+            if take_source_interrupt() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "Poll operation was interrupted by the shutdown",
+                ));
+            }
+
+            if let Some(event_ready) = event_ready {
                 let number = self.console.number_of_console_input_events()?;
                 if event_ready && number != 0 {
                     let event = match self.console.read_single_input_event()? {
-                        InputRecord::KeyEvent(record) => handle_key_event(record),
-                        InputRecord::MouseEvent(record) => handle_mouse_event(record),
+                        InputRecord::KeyEvent(record) => {
+                            let mut events =
+                                handle_key_event(record.into(), &mut self.key_event_state);
+                            let event = events.next();
+                            self.pending.extend(events);
+                            event
+                        }
+                        InputRecord::MouseEvent(record) => {
+                            let mut events = handle_mouse_event(record);
+                            let event = events.next();
+                            self.pending.extend(events);
+                            event
+                        }
                         InputRecord::WindowBufferSizeEvent(record) => {
                             Some(Event::Resize(record.size.x as u16, record.size.y as u16))
                         }
@@ -62,7 +101,7 @@ impl EventSource for WindowsEventSource {
     }
 
     #[cfg(feature = "event-stream")]
-    fn waker(&self) -> Waker {
-        self.poll.waker()
+    fn waker(&self) -> SourceWaker {
+        SourceWaker::System(self.poll.waker())
     }
 }
@@ -1,4 +1,5 @@
 use std::{
+    future::Future,
     pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -6,7 +7,7 @@ use std::{
         Arc,
     },
     task::{Context, Poll},
-    thread,
+    thread::{self, JoinHandle},
     time::Duration,
 };
 
@@ -15,7 +16,7 @@ use futures_core::stream::Stream;
 use crate::Result;
 
 use super::{
-    filter::EventFilter, lock_internal_event_reader, poll_internal, read_internal, sys::Waker,
+    filter::EventFilter, internal_event_reader, poll_internal, read_internal, source::SourceWaker,
     Event, InternalEvent,
 };
 
@@ -32,17 +33,18 @@ use super::{
 /// it (`event-stream-*`).
 #[derive(Debug)]
 pub struct EventStream {
-    poll_internal_waker: Waker,
+    poll_internal_waker: SourceWaker,
     stream_wake_task_executed: Arc<AtomicBool>,
     stream_wake_task_should_shutdown: Arc<AtomicBool>,
-    task_sender: SyncSender<Task>,
+    task_sender: Option<SyncSender<Task>>,
+    wake_thread: Option<JoinHandle<()>>,
 }
 
 impl Default for EventStream {
     fn default() -> Self {
         let (task_sender, receiver) = mpsc::sync_channel::<Task>(1);
 
-        thread::spawn(move || {
+        let wake_thread = thread::spawn(move || {
             while let Ok(task) = receiver.recv() {
                 loop {
                     if let Ok(true) = poll_internal(None, &EventFilter) {
@@ -60,10 +62,11 @@ impl Default for EventStream {
         });
 
         EventStream {
-            poll_internal_waker: lock_internal_event_reader().waker(),
+            poll_internal_waker: internal_event_reader().waker(),
             stream_wake_task_executed: Arc::new(AtomicBool::new(false)),
             stream_wake_task_should_shutdown: Arc::new(AtomicBool::new(false)),
-            task_sender,
+            task_sender: Some(task_sender),
+            wake_thread: Some(wake_thread),
         }
     }
 }
@@ -73,6 +76,118 @@ impl EventStream {
     pub fn new() -> EventStream {
         EventStream::default()
     }
+
+    /// Returns all the events available at the moment, without waiting.
+    ///
+    /// It allows to process a burst of events and redraw the screen just once, e.g. drain
+    /// the queue after the stream yields an event. The returned events aren't yielded by
+    /// the stream.
+    pub fn ready_chunk(&mut self) -> Result<Vec<Event>> {
+        let events = internal_event_reader().read_ready(&EventFilter)?;
+
+        Ok(events
+            .into_iter()
+            .map(|event| match event {
+                InternalEvent::Event(event) => event,
+                #[cfg(unix)]
+                _ => unreachable!(),
+            })
+            .collect())
+    }
+
+    /// Closes the stream, waits until the thread waiting for the events quits.
+    ///
+    /// Dropping the stream stops the thread too, but it doesn't wait for it. Close the
+    /// stream before the [shutdown](fn.shutdown.html) of the event reading.
+    pub fn close(mut self) -> Result<()> {
+        self.stop_wake_thread()?;
+
+        match self.wake_thread.take().map(JoinHandle::join) {
+            Some(Err(_)) => Err(std::io::Error::other(
+                "EventStream thread panicked while waiting for the events",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Asks the thread waiting for the events to quit, the task channel is closed.
+    fn stop_wake_thread(&mut self) -> Result<()> {
+        if self.task_sender.take().is_none() {
+            // Already stopped by the `close`
+            return Ok(());
+        }
+
+        self.stream_wake_task_should_shutdown
+            .store(true, Ordering::SeqCst);
+        self.poll_internal_waker.wake()
+    }
+
+    /// Returns a stream yielding the events until the `signal` future completes.
+    ///
+    /// The `EventStream` is dropped as soon as the signal completes, which stops the thread
+    /// waiting for the events, so the other readers (e.g. [read](fn.read.html)) aren't
+    /// blocked by it. It's meant for the graceful shutdown, e.g. with the
+    /// `CancellationToken::cancelled_owned` future of the `tokio-util` crate.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use futures::{channel::oneshot, StreamExt};
+    ///
+    /// use crossterm::event::EventStream;
+    ///
+    /// async fn print_events(shutdown: oneshot::Receiver<()>) {
+    ///     let mut events = EventStream::new().take_until(shutdown);
+    ///
+    ///     while let Some(event) = events.next().await {
+    ///         println!("{:?}", event);
+    ///     }
+    /// }
+    /// ```
+    pub fn take_until<F: Future>(self, signal: F) -> TakeUntil<F> {
+        TakeUntil {
+            stream: Some(self),
+            signal: Box::pin(signal),
+        }
+    }
+}
+
+/// A stream of `Result<Event>` ending when the signal future completes.
+///
+/// This struct is created by the [take_until](struct.EventStream.html#method.take_until)
+/// method on the [EventStream](struct.EventStream.html).
+#[derive(Debug)]
+pub struct TakeUntil<F> {
+    stream: Option<EventStream>,
+    signal: Pin<Box<F>>,
+}
+
+impl<F> TakeUntil<F> {
+    /// Returns whether the signal completed and the stream ended.
+    pub fn is_stopped(&self) -> bool {
+        self.stream.is_none()
+    }
+}
+
+impl<F: Future> Stream for TakeUntil<F> {
+    type Item = Result<Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.stream.is_none() {
+            return Poll::Ready(None);
+        }
+
+        if self.signal.as_mut().poll(cx).is_ready() {
+            // Stops the thread waiting for the events
+            self.stream = None;
+            return Poll::Ready(None);
+        }
+
+        match self.stream.as_mut() {
+            Some(stream) => Pin::new(stream).poll_next(cx),
+            None => Poll::Ready(None),
+        }
+    }
 }
 
 struct Task {
@@ -124,11 +239,13 @@ impl Stream for EventStream {
 
                     stream_wake_task_should_shutdown.store(false, Ordering::SeqCst);
 
-                    let _ = self.task_sender.send(Task {
-                        stream_waker,
-                        stream_wake_task_executed,
-                        stream_wake_task_should_shutdown,
-                    });
+                    if let Some(task_sender) = &self.task_sender {
+                        let _ = task_sender.send(Task {
+                            stream_waker,
+                            stream_wake_task_executed,
+                            stream_wake_task_should_shutdown,
+                        });
+                    }
                 }
                 Poll::Pending
             }
@@ -140,8 +257,6 @@ impl Stream for EventStream {
 
 impl Drop for EventStream {
     fn drop(&mut self) {
-        self.stream_wake_task_should_shutdown
-            .store(true, Ordering::SeqCst);
-        let _ = self.poll_internal_waker.wake();
+        let _ = self.stop_wake_thread();
     }
 }
@@ -5,5 +5,6 @@ pub(crate) use windows::waker::Waker;
 
 #[cfg(unix)]
 pub(crate) mod unix;
+pub(crate) mod win32_key;
 #[cfg(windows)]
 pub(crate) mod windows;
@@ -1,5 +1,8 @@
 #[cfg(feature = "event-stream")]
 pub(crate) mod waker;
 
-pub(crate) mod file_descriptor;
+#[cfg(test)]
+pub(crate) mod encode;
 pub(crate) mod parse;
+pub(crate) mod signal;
+pub(crate) mod user_fd;
@@ -0,0 +1,370 @@
+use std::io::Write;
+
+use crate::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+// Key event encoding
+//
+// The inverse of the `parse` module. Turns a `KeyEvent` into the bytes a terminal
+// would send for it, so tests can feed realistic input to the parser without
+// hand-writing escape sequences.
+//
+// Two encodings are available:
+//
+// * legacy - what xterm-like terminals send by default (lossy, Ctrl+I is Tab, ...)
+// * CSI u  - `ESC [ codepoint ; modifiers u` as sent with the kitty keyboard protocol
+//
+
+/// Encodes the key event as sent by a legacy (xterm-like) terminal.
+///
+/// Returns `None` if there's no legacy encoding for the key event.
+pub(crate) fn encode_legacy(event: &KeyEvent) -> Option<Vec<u8>> {
+    // Only the kitty keyboard protocol reports these.
+    if event.kind != KeyEventKind::Press
+        || event
+            .modifiers
+            .intersects(KeyModifiers::SUPER | KeyModifiers::HYPER | KeyModifiers::META)
+    {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    let mut modifiers = event.modifiers;
+
+    match event.code {
+        KeyCode::Char(c) => {
+            if modifiers.contains(KeyModifiers::ALT) {
+                bytes.push(b'\x1B');
+            }
+
+            if modifiers.contains(KeyModifiers::CONTROL) {
+                bytes.push(control_byte(c)?);
+            } else if modifiers.contains(KeyModifiers::SHIFT) {
+                write!(bytes, "{}", c.to_uppercase()).ok()?;
+            } else {
+                write!(bytes, "{}", c).ok()?;
+            }
+        }
+        KeyCode::Enter | KeyCode::Tab | KeyCode::Backspace | KeyCode::Esc => {
+            if modifiers.contains(KeyModifiers::ALT) {
+                bytes.push(b'\x1B');
+            }
+            bytes.push(match event.code {
+                KeyCode::Enter => b'\r',
+                KeyCode::Tab => b'\t',
+                KeyCode::Backspace => b'\x7F',
+                _ => b'\x1B',
+            });
+        }
+        KeyCode::BackTab => {
+            // Shift is implied by the key code itself.
+            modifiers.remove(KeyModifiers::SHIFT);
+            if !modifiers.is_empty() {
+                return None;
+            }
+            bytes.extend_from_slice(b"\x1B[Z");
+        }
+        KeyCode::F(n @ 1..=4) => {
+            let key = b'P' + n - 1;
+            if modifiers.is_empty() {
+                bytes.extend_from_slice(&[b'\x1B', b'O', key]);
+            } else {
+                write!(bytes, "\x1B[1;{}{}", modifier_mask(modifiers), key as char).ok()?;
+            }
+        }
+        code => {
+            if let Some(key) = cursor_key(code) {
+                if modifiers.is_empty() {
+                    write!(bytes, "\x1B[{}", key as char).ok()?;
+                } else {
+                    write!(bytes, "\x1B[1;{}{}", modifier_mask(modifiers), key as char).ok()?;
+                }
+            } else {
+                let number = tilde_key_number(code)?;
+                if modifiers.is_empty() {
+                    write!(bytes, "\x1B[{}~", number).ok()?;
+                } else {
+                    write!(bytes, "\x1B[{};{}~", number, modifier_mask(modifiers)).ok()?;
+                }
+            }
+        }
+    }
+
+    Some(bytes)
+}
+
+/// Encodes the key event as sent by a terminal with the kitty keyboard protocol enabled.
+///
+/// Text producing keys, `Enter`, `Tab`, `Backspace` and `Esc` are encoded as `CSI u`,
+/// all the other keys use the same encoding as the legacy terminals.
+///
+/// Returns `None` if there's no encoding for the key event.
+pub(crate) fn encode_csi_u(event: &KeyEvent) -> Option<Vec<u8>> {
+    let mut modifiers = event.modifiers;
+
+    let codepoint = match event.code {
+        KeyCode::Char(c) => {
+            // The protocol reports the unshifted key along with the shift modifier.
+            if c.is_uppercase() {
+                modifiers.insert(KeyModifiers::SHIFT);
+            }
+            let mut lowercase = c.to_lowercase();
+            match (lowercase.next(), lowercase.next()) {
+                (Some(lower), None) => lower as u32,
+                _ => c as u32,
+            }
+        }
+        KeyCode::Enter => 13,
+        KeyCode::Tab => 9,
+        KeyCode::BackTab => {
+            modifiers.insert(KeyModifiers::SHIFT);
+            9
+        }
+        KeyCode::Backspace => 127,
+        KeyCode::Esc => 27,
+        _ if event.kind == KeyEventKind::Press
+            && !modifiers
+                .intersects(KeyModifiers::SUPER | KeyModifiers::HYPER | KeyModifiers::META) =>
+        {
+            return encode_legacy(event)
+        }
+        _ => return encode_functional_key(event),
+    };
+
+    let mut bytes = Vec::new();
+    if modifiers.is_empty() && event.kind == KeyEventKind::Press {
+        write!(bytes, "\x1B[{}u", codepoint).ok()?;
+    } else {
+        let parameter = modifier_parameter(modifiers, event.kind);
+        write!(bytes, "\x1B[{};{}u", codepoint, parameter).ok()?;
+    }
+
+    Some(bytes)
+}
+
+/// Encodes the keys without the `CSI u` encoding with the parameters of the kitty keyboard
+/// protocol (the event type, the super, hyper and meta modifiers).
+fn encode_functional_key(event: &KeyEvent) -> Option<Vec<u8>> {
+    let parameter = modifier_parameter(event.modifiers, event.kind);
+
+    let mut bytes = Vec::new();
+    match event.code {
+        KeyCode::F(n @ 1..=4) => write!(bytes, "\x1B[1;{}{}", parameter, (b'P' + n - 1) as char),
+        code => match cursor_key(code) {
+            Some(key) => write!(bytes, "\x1B[1;{}{}", parameter, key as char),
+            None => write!(bytes, "\x1B[{};{}~", tilde_key_number(code)?, parameter),
+        },
+    }
+    .ok()?;
+
+    Some(bytes)
+}
+
+/// Returns the `modifiers[:event type]` parameter of the kitty keyboard protocol.
+fn modifier_parameter(modifiers: KeyModifiers, kind: KeyEventKind) -> String {
+    match kind {
+        KeyEventKind::Press => modifier_mask(modifiers).to_string(),
+        KeyEventKind::Repeat => format!("{}:2", modifier_mask(modifiers)),
+        KeyEventKind::Release => format!("{}:3", modifier_mask(modifiers)),
+    }
+}
+
+/// Returns the modifier parameter (`1 + bitmask`) used by both encodings, the legacy one
+/// doesn't have the super, hyper and meta modifiers.
+fn modifier_mask(modifiers: KeyModifiers) -> u8 {
+    let mut mask = 0;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        mask |= 1;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        mask |= 2;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        mask |= 4;
+    }
+    if modifiers.contains(KeyModifiers::SUPER) {
+        mask |= 8;
+    }
+    if modifiers.contains(KeyModifiers::HYPER) {
+        mask |= 16;
+    }
+    if modifiers.contains(KeyModifiers::META) {
+        mask |= 32;
+    }
+    mask + 1
+}
+
+/// Returns the control character produced by pressing Ctrl with the given character.
+fn control_byte(c: char) -> Option<u8> {
+    match c {
+        'a'..='z' => Some(c as u8 - b'a' + 0x1),
+        'A'..='Z' => Some(c as u8 - b'A' + 0x1),
+        '4'..='7' => Some(c as u8 - b'4' + 0x1C),
+        ' ' | '@' => Some(b'\0'),
+        _ => None,
+    }
+}
+
+/// Returns the final byte of the `CSI [1;m] <key>` sequence for cursor keys.
+fn cursor_key(code: KeyCode) -> Option<u8> {
+    match code {
+        KeyCode::Up => Some(b'A'),
+        KeyCode::Down => Some(b'B'),
+        KeyCode::Right => Some(b'C'),
+        KeyCode::Left => Some(b'D'),
+        KeyCode::End => Some(b'F'),
+        KeyCode::Home => Some(b'H'),
+        _ => None,
+    }
+}
+
+/// Returns the number of the `CSI <number> [;m] ~` sequence (VT220 numbering).
+fn tilde_key_number(code: KeyCode) -> Option<u8> {
+    match code {
+        KeyCode::Insert => Some(2),
+        KeyCode::Delete => Some(3),
+        KeyCode::PageUp => Some(5),
+        KeyCode::PageDown => Some(6),
+        KeyCode::F(n @ 5) => Some(n + 10),
+        KeyCode::F(n @ 6..=10) => Some(n + 11),
+        KeyCode::F(n @ 11..=14) => Some(n + 12),
+        KeyCode::F(n @ 15..=16) => Some(n + 13),
+        KeyCode::F(n @ 17..=20) => Some(n + 14),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{Event, InternalEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+    use super::super::parse::parse_event;
+    use super::{encode_csi_u, encode_legacy};
+
+    fn parse(bytes: &[u8]) -> KeyEvent {
+        match parse_event(bytes, false).unwrap() {
+            Some(InternalEvent::Event(Event::Key(event))) => event,
+            other => panic!("{:?} is not a key event ({:?})", bytes, other),
+        }
+    }
+
+    #[test]
+    fn test_encode_legacy() {
+        assert_eq!(
+            encode_legacy(&KeyCode::Char('a').into()).unwrap(),
+            b"a".to_vec()
+        );
+        assert_eq!(
+            encode_legacy(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)).unwrap(),
+            b"\x03".to_vec()
+        );
+        assert_eq!(
+            encode_legacy(&KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL)).unwrap(),
+            b"\x1B[1;5D".to_vec()
+        );
+        assert_eq!(
+            encode_legacy(&KeyEvent::new(KeyCode::Delete, KeyModifiers::SHIFT)).unwrap(),
+            b"\x1B[3;2~".to_vec()
+        );
+        assert_eq!(
+            encode_legacy(&KeyCode::F(1).into()).unwrap(),
+            b"\x1BOP".to_vec()
+        );
+        assert_eq!(encode_legacy(&KeyCode::Null.into()), None);
+        assert_eq!(
+            encode_legacy(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::SUPER)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_encode_csi_u() {
+        assert_eq!(
+            encode_csi_u(&KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL)).unwrap(),
+            b"\x1B[97;5u".to_vec()
+        );
+        assert_eq!(
+            encode_csi_u(&KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT)).unwrap(),
+            b"\x1B[97;2u".to_vec()
+        );
+        assert_eq!(
+            encode_csi_u(&KeyCode::BackTab.into()).unwrap(),
+            b"\x1B[9;2u".to_vec()
+        );
+        assert_eq!(
+            encode_csi_u(&KeyCode::Up.into()).unwrap(),
+            b"\x1B[A".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_legacy_round_trip() {
+        let events = [
+            KeyEvent::from(KeyCode::Char('a')),
+            KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::Char('ž')),
+            KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+            KeyEvent::new(
+                KeyCode::Char('t'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+            ),
+            KeyEvent::new(KeyCode::Char('4'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL),
+            KeyEvent::from(KeyCode::Enter),
+            KeyEvent::from(KeyCode::Tab),
+            KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::Backspace),
+            KeyEvent::from(KeyCode::Esc),
+            KeyEvent::from(KeyCode::Home),
+            KeyEvent::new(KeyCode::End, KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Up, KeyModifiers::ALT),
+            KeyEvent::new(KeyCode::Down, KeyModifiers::CONTROL | KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::Insert),
+            KeyEvent::new(KeyCode::PageUp, KeyModifiers::CONTROL),
+            KeyEvent::from(KeyCode::PageDown),
+            KeyEvent::new(KeyCode::F(2), KeyModifiers::SHIFT),
+            KeyEvent::from(KeyCode::F(5)),
+            KeyEvent::new(KeyCode::F(12), KeyModifiers::ALT),
+        ];
+
+        for event in events.iter() {
+            assert_eq!(parse(&encode_legacy(event).unwrap()), *event);
+        }
+    }
+
+    #[test]
+    fn test_csi_u_round_trip() {
+        let events = [
+            KeyEvent::from(KeyCode::Char('a')),
+            KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Char('i'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Char('m'), KeyModifiers::CONTROL),
+            KeyEvent::new(
+                KeyCode::Char('€'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+            ),
+            KeyEvent::from(KeyCode::Enter),
+            KeyEvent::new(KeyCode::Tab, KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Backspace, KeyModifiers::ALT),
+            KeyEvent::from(KeyCode::Esc),
+            KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL),
+            KeyEvent::from(KeyCode::Delete),
+            KeyEvent::new(KeyCode::Char('s'), KeyModifiers::SUPER),
+            KeyEvent::new(KeyCode::Char('a'), KeyModifiers::HYPER | KeyModifiers::META),
+            KeyEvent::new_with_kind(
+                KeyCode::Char('a'),
+                KeyModifiers::NONE,
+                KeyEventKind::Release,
+            ),
+            KeyEvent::new_with_kind(KeyCode::Enter, KeyModifiers::SHIFT, KeyEventKind::Repeat),
+            KeyEvent::new_with_kind(KeyCode::Up, KeyModifiers::NONE, KeyEventKind::Repeat),
+            KeyEvent::new_with_kind(KeyCode::F(1), KeyModifiers::ALT, KeyEventKind::Release),
+            KeyEvent::new_with_kind(KeyCode::F(7), KeyModifiers::NONE, KeyEventKind::Release),
+            KeyEvent::new(KeyCode::PageDown, KeyModifiers::SUPER),
+        ];
+
+        for event in events.iter() {
+            assert_eq!(parse(&encode_csi_u(event).unwrap()), *event);
+        }
+    }
+}
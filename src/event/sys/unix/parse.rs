@@ -1,7 +1,11 @@
 use std::io;
 
 use crate::{
-    event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+    event::{
+        is_pixel_mouse_capture_enabled, sys::win32_key::Win32KeyRecord, ColorScheme, Event,
+        KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags, MouseButton,
+        MouseEvent, MouseEventKind, PhysicalKey, Response,
+    },
     ErrorKind, Result,
 };
 
@@ -20,7 +24,7 @@ use super::super::super::InternalEvent;
 //
 
 fn could_not_parse_event_error() -> ErrorKind {
-    io::Error::new(io::ErrorKind::Other, "Could not parse an event.")
+    io::Error::other("Could not parse an event.")
 }
 
 pub(crate) fn parse_event(buffer: &[u8], input_available: bool) -> Result<Option<InternalEvent>> {
@@ -53,6 +57,9 @@ pub(crate) fn parse_event(buffer: &[u8], input_available: bool) -> Result<Option
                         }
                     }
                     b'[' => parse_csi(buffer),
+                    // The answers to the queries start with the number (or `l` of the title)
+                    b']' if buffer.len() == 2 && input_available => Ok(None),
+                    b']' if matches!(buffer.get(2), Some(b'0'..=b'9' | b'l')) => parse_osc(buffer),
                     b'\x1B' => Ok(Some(InternalEvent::Event(Event::Key(KeyCode::Esc.into())))),
                     _ => parse_event(&buffer[1..], input_available).map(|event_option| {
                         event_option.map(|event| {
@@ -83,11 +90,11 @@ pub(crate) fn parse_event(buffer: &[u8], input_available: bool) -> Result<Option
             KeyCode::Backspace.into(),
         )))),
         c @ b'\x01'..=b'\x1A' => Ok(Some(InternalEvent::Event(Event::Key(KeyEvent::new(
-            KeyCode::Char((c as u8 - 0x1 + b'a') as char),
+            KeyCode::Char((c - 0x1 + b'a') as char),
             KeyModifiers::CONTROL,
         ))))),
         c @ b'\x1C'..=b'\x1F' => Ok(Some(InternalEvent::Event(Event::Key(KeyEvent::new(
-            KeyCode::Char((c as u8 - 0x1C + b'4') as char),
+            KeyCode::Char((c - 0x1C + b'4') as char),
             KeyModifiers::CONTROL,
         ))))),
         b'\0' => Ok(Some(InternalEvent::Event(Event::Key(KeyEvent::new(
@@ -114,7 +121,7 @@ fn char_code_to_event(code: KeyCode) -> KeyEvent {
 }
 
 pub(crate) fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
 
     if buffer.len() == 2 {
         return Ok(None);
@@ -139,12 +146,41 @@ pub(crate) fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
         b'B' => Some(Event::Key(KeyCode::Down.into())),
         b'H' => Some(Event::Key(KeyCode::Home.into())),
         b'F' => Some(Event::Key(KeyCode::End.into())),
-        b'Z' => Some(Event::Key(KeyEvent {
-            code: KeyCode::BackTab,
-            modifiers: KeyModifiers::SHIFT,
-        })),
+        b'Z' => Some(Event::Key(KeyEvent::new(
+            KeyCode::BackTab,
+            KeyModifiers::SHIFT,
+        ))),
         b'M' => return parse_csi_normal_mouse(buffer),
         b'<' => return parse_csi_sgr_mouse(buffer),
+        b'?' => {
+            // Private mode report, wait for the final byte
+            let last_byte = *buffer.last().unwrap();
+            if !(64..=126).contains(&last_byte) {
+                None
+            } else if buffer.ends_with(b"$y") {
+                return parse_csi_dec_mode_report(buffer);
+            } else if last_byte == b'u' {
+                return parse_csi_keyboard_enhancement_flags(buffer);
+            } else if last_byte == b'c' {
+                return parse_csi_primary_device_attributes(buffer);
+            } else if last_byte == b'n' {
+                return parse_csi_color_scheme_report(buffer);
+            } else {
+                return Err(could_not_parse_event_error());
+            }
+        }
+        b'>' => {
+            // Secondary device attributes, wait for the final byte
+            let last_byte = *buffer.last().unwrap();
+            if !(64..=126).contains(&last_byte) {
+                None
+            } else if last_byte == b'c' {
+                return parse_csi_secondary_device_attributes(buffer);
+            } else {
+                return Err(could_not_parse_event_error());
+            }
+        }
+        b'2' if buffer.starts_with(b"\x1B[200~") => return parse_csi_bracketed_paste(buffer),
         b'0'..=b'9' => {
             // Numbered escape code.
             if buffer.len() == 3 {
@@ -161,6 +197,8 @@ pub(crate) fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
                         b'~' => return parse_csi_special_key_code(buffer),
                         b'u' => return parse_csi_u_encoded_key_code(buffer),
                         b'R' => return parse_csi_cursor_position(buffer),
+                        b'_' => return parse_csi_win32_input_mode(buffer),
+                        b't' => return parse_csi_in_band_resize(buffer),
                         _ => return parse_csi_modifier_key_code(buffer),
                     }
                 }
@@ -172,6 +210,83 @@ pub(crate) fn parse_csi(buffer: &[u8]) -> Result<Option<InternalEvent>> {
     Ok(input_event.map(InternalEvent::Event))
 }
 
+/// Parses the text wrapped in the `CSI 200~` and `CSI 201~` sequences, waits until the
+/// end of the paste.
+pub(crate) fn parse_csi_bracketed_paste(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ 2 0 0 ~ text ESC [ 2 0 1 ~
+    assert!(buffer.starts_with(b"\x1B[200~"));
+
+    if !buffer.ends_with(b"\x1B[201~") {
+        return Ok(None);
+    }
+
+    let text = &buffer[6..buffer.len() - 6];
+    Ok(Some(InternalEvent::Event(Event::Paste(
+        String::from_utf8_lossy(text).into_owned(),
+    ))))
+}
+
+/// Parses the operating system command answering a query, waits until its terminator
+/// (`BEL` or `ST`).
+pub(crate) fn parse_osc(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC ] Ps ; Pt BEL
+    // ESC ] Ps ; Pt ESC \
+    // ESC ] l title ESC \
+    assert!(buffer.starts_with(b"\x1B]")); // ESC ]
+
+    let content = if buffer.ends_with(b"\x07") {
+        &buffer[2..buffer.len() - 1]
+    } else if buffer.ends_with(b"\x1B\\") {
+        &buffer[2..buffer.len() - 2]
+    } else {
+        return Ok(None);
+    };
+
+    let s = std::str::from_utf8(content).map_err(|_| could_not_parse_event_error())?;
+
+    if let Some(title) = s.strip_prefix('l') {
+        return Ok(Some(InternalEvent::Response(Response::Title(
+            title.to_string(),
+        ))));
+    }
+
+    let (ps, pt) = s.split_once(';').ok_or_else(could_not_parse_event_error)?;
+    let (r, g, b) = parse_rgb(pt)?;
+
+    let response = match ps {
+        "10" => Response::ForegroundColor(r, g, b),
+        "11" => Response::BackgroundColor(r, g, b),
+        _ => return Err(could_not_parse_event_error()),
+    };
+
+    Ok(Some(InternalEvent::Response(response)))
+}
+
+/// Parses the `rgb:R/G/B` color specification, the components have 1 to 4 hex digits.
+fn parse_rgb(spec: &str) -> Result<(u8, u8, u8)> {
+    let spec = spec
+        .strip_prefix("rgb:")
+        .ok_or_else(could_not_parse_event_error)?;
+
+    let mut split = spec.split('/').map(|component| {
+        if component.is_empty() || component.len() > 4 {
+            return Err(could_not_parse_event_error());
+        }
+
+        let value =
+            u32::from_str_radix(component, 16).map_err(|_| could_not_parse_event_error())?;
+        let max = (1 << (4 * component.len())) - 1;
+        Ok((value * 255 / max) as u8)
+    });
+
+    let mut next = || {
+        split
+            .next()
+            .unwrap_or_else(|| Err(could_not_parse_event_error()))
+    };
+    Ok((next()?, next()?, next()?))
+}
+
 pub(crate) fn next_parsed<T>(iter: &mut dyn Iterator<Item = &str>) -> Result<T>
 where
     T: std::str::FromStr,
@@ -186,8 +301,8 @@ pub(crate) fn parse_csi_cursor_position(buffer: &[u8]) -> Result<Option<Internal
     // ESC [ Cy ; Cx R
     //   Cy - cursor row number (starting from 1)
     //   Cx - cursor column number (starting from 1)
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'R']));
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"R"));
 
     let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
         .map_err(|_| could_not_parse_event_error())?;
@@ -197,7 +312,142 @@ pub(crate) fn parse_csi_cursor_position(buffer: &[u8]) -> Result<Option<Internal
     let y = next_parsed::<u16>(&mut split)? - 1;
     let x = next_parsed::<u16>(&mut split)? - 1;
 
-    Ok(Some(InternalEvent::CursorPosition(x, y)))
+    Ok(Some(InternalEvent::Response(Response::CursorPosition(
+        x, y,
+    ))))
+}
+
+pub(crate) fn parse_csi_in_band_resize(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ 48 ; height ; width ; height_pixels ; width_pixels t
+    //   height, width - the size in cells
+    //   height_pixels, width_pixels - the size in pixels, 0 if it's unknown
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"t"));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let mut split = s.split(';');
+    if next_parsed::<u16>(&mut split)? != 48 {
+        return Err(could_not_parse_event_error());
+    }
+
+    let rows = next_parsed::<u16>(&mut split)?;
+    let columns = next_parsed::<u16>(&mut split)?;
+    // The pixels are optional
+    let height = next_parsed::<u16>(&mut split).unwrap_or(0);
+    let width = next_parsed::<u16>(&mut split).unwrap_or(0);
+
+    Ok(Some(InternalEvent::InBandResize {
+        columns,
+        rows,
+        width,
+        height,
+    }))
+}
+
+pub(crate) fn parse_csi_dec_mode_report(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? Pd ; Ps $ y
+    //   Pd - DEC private mode number
+    //   Ps - 0 not recognized, 1 set, 2 reset, 3 permanently set, 4 permanently reset
+    assert!(buffer.starts_with(b"\x1B[?")); // ESC [ ?
+    assert!(buffer.ends_with(b"$y"));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 2])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let mut split = s.split(';');
+
+    let mode = next_parsed::<u16>(&mut split)?;
+    let setting = match next_parsed::<u8>(&mut split)? {
+        0 => None,
+        1 | 3 => Some(true),
+        2 | 4 => Some(false),
+        _ => return Err(could_not_parse_event_error()),
+    };
+
+    Ok(Some(InternalEvent::Response(Response::DecModeReport(
+        mode, setting,
+    ))))
+}
+
+pub(crate) fn parse_csi_color_scheme_report(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? 997 ; Ps n
+    //   Ps - 1 dark, 2 light
+    assert!(buffer.starts_with(b"\x1B[?")); // ESC [ ?
+    assert!(buffer.ends_with(b"n"));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let mut split = s.split(';');
+    if next_parsed::<u16>(&mut split)? != 997 {
+        return Err(could_not_parse_event_error());
+    }
+
+    let scheme = match next_parsed::<u8>(&mut split)? {
+        1 => ColorScheme::Dark,
+        2 => ColorScheme::Light,
+        _ => return Err(could_not_parse_event_error()),
+    };
+
+    Ok(Some(InternalEvent::Event(Event::ThemeChanged(scheme))))
+}
+
+pub(crate) fn parse_csi_keyboard_enhancement_flags(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? flags u
+    assert!(buffer.starts_with(b"\x1B[?")); // ESC [ ?
+    assert!(buffer.ends_with(b"u"));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let bits = next_parsed::<u8>(&mut s.split(';'))?;
+    Ok(Some(InternalEvent::Response(
+        Response::KeyboardEnhancementFlags(KeyboardEnhancementFlags::from_bits_truncate(bits)),
+    )))
+}
+
+pub(crate) fn parse_csi_primary_device_attributes(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    // ESC [ ? Ps ; ... c
+    //   Ps - the terminal class followed by the supported features
+    assert!(buffer.starts_with(b"\x1B[?")); // ESC [ ?
+    assert!(buffer.ends_with(b"c"));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let attributes = s
+        .split(';')
+        .map(|attribute| attribute.parse::<u16>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| could_not_parse_event_error())?;
+
+    Ok(Some(InternalEvent::Response(
+        Response::PrimaryDeviceAttributes(attributes),
+    )))
+}
+
+pub(crate) fn parse_csi_secondary_device_attributes(
+    buffer: &[u8],
+) -> Result<Option<InternalEvent>> {
+    // ESC [ > Pp ; Pv ; Pc c
+    //   Pp - the terminal type
+    //   Pv - the firmware version
+    //   Pc - the ROM cartridge registration number, always 0
+    assert!(buffer.starts_with(b"\x1B[>")); // ESC [ >
+    assert!(buffer.ends_with(b"c"));
+
+    let s = std::str::from_utf8(&buffer[3..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    let mut split = s.split(';');
+    let terminal_type = next_parsed::<u16>(&mut split)?;
+    let version = next_parsed::<u16>(&mut split)?;
+
+    Ok(Some(InternalEvent::Response(
+        Response::SecondaryDeviceAttributes(terminal_type, version),
+    )))
 }
 
 fn parse_modifiers(mask: u8) -> KeyModifiers {
@@ -212,16 +462,48 @@ fn parse_modifiers(mask: u8) -> KeyModifiers {
     if modifier_mask & 4 != 0 {
         modifiers |= KeyModifiers::CONTROL;
     }
+    if modifier_mask & 8 != 0 {
+        modifiers |= KeyModifiers::SUPER;
+    }
+    if modifier_mask & 16 != 0 {
+        modifiers |= KeyModifiers::HYPER;
+    }
+    if modifier_mask & 32 != 0 {
+        modifiers |= KeyModifiers::META;
+    }
     modifiers
 }
 
+/// Parses the `modifiers[:event type]` parameter, the event type is reported with the
+/// kitty keyboard protocol only.
+fn parse_modifiers_and_kind(parameter: Option<&str>) -> Result<(KeyModifiers, KeyEventKind)> {
+    let parameter = match parameter {
+        Some(parameter) if !parameter.is_empty() => parameter,
+        _ => return Ok((KeyModifiers::NONE, KeyEventKind::Press)),
+    };
+
+    let mut split = parameter.split(':');
+    let modifiers = parse_modifiers(next_parsed::<u8>(&mut split)?);
+    let kind = match split.next() {
+        None | Some("1") => KeyEventKind::Press,
+        Some("2") => KeyEventKind::Repeat,
+        Some("3") => KeyEventKind::Release,
+        Some(_) => return Err(could_not_parse_event_error()),
+    };
+
+    Ok((modifiers, kind))
+}
+
 pub(crate) fn parse_csi_modifier_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
 
-    let modifier_mask = buffer[buffer.len() - 2];
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
     let key = buffer[buffer.len() - 1];
 
-    let modifiers = parse_modifiers(modifier_mask);
+    // CSI 1 ; modifiers[:event type] key, some terminals omit the `1 ;`
+    let parameter = s.rsplit(';').next();
+    let (modifiers, kind) = parse_modifiers_and_kind(parameter)?;
 
     let keycode = match key {
         b'A' => KeyCode::Up,
@@ -237,63 +519,103 @@ pub(crate) fn parse_csi_modifier_key_code(buffer: &[u8]) -> Result<Option<Intern
         _ => return Err(could_not_parse_event_error()),
     };
 
-    let input_event = Event::Key(KeyEvent::new(keycode, modifiers));
+    let input_event = Event::Key(KeyEvent::new_with_kind(keycode, modifiers, kind));
 
     Ok(Some(InternalEvent::Event(input_event)))
 }
 
 pub(crate) fn parse_csi_u_encoded_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'u']));
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"u"));
 
     let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
         .map_err(|_| could_not_parse_event_error())?;
     let mut split = s.split(';');
 
     // This CSI sequence a tuple of semicolon-separated numbers.
-    // CSI [codepoint];[modifiers] u
+    // CSI [codepoint][:shifted key][:base layout key];[modifiers][:event type] u
     // codepoint: ASCII Dec value
-    let codepoint = next_parsed::<u32>(&mut split)?;
+    // shifted key, base layout key: reported with the `REPORT_ALTERNATE_KEYS` flag
+    let mut codepoints = split
+        .next()
+        .ok_or_else(could_not_parse_event_error)?
+        .split(':');
+    let codepoint = next_parsed::<u32>(&mut codepoints)?;
+    let mut alternate_key = || {
+        codepoints
+            .next()
+            .and_then(|codepoint| codepoint.parse::<u32>().ok())
+            .and_then(char::from_u32)
+    };
+    let shifted_key = alternate_key();
+    let base_layout_key = alternate_key();
 
-    let modifiers = if let Ok(modifier_mask) = next_parsed::<u8>(&mut split) {
-        parse_modifiers(modifier_mask)
-    } else {
-        KeyModifiers::NONE
+    // event type: reported with the `REPORT_EVENT_TYPES` flag
+    let (mut modifiers, kind) = parse_modifiers_and_kind(split.next())?;
+
+    let codepoint = match shifted_key {
+        Some(shifted_key) if modifiers.contains(KeyModifiers::SHIFT) => shifted_key as u32,
+        _ => codepoint,
     };
 
-    let keycode = {
-        if let Some(c) = char::from_u32(codepoint) {
-            match c {
-                '\x1B' => KeyCode::Esc,
-                '\r' => KeyCode::Enter,
-                // Issue #371: \n = 0xA, which is also the keycode for Ctrl+J. The only reason we get
-                // newlines as input is because the terminal converts \r into \n for us. When we
-                // enter raw mode, we disable that, so \n no longer has any meaning - it's better to
-                // use Ctrl+J. Waiting to handle it here means it gets picked up later
-                '\n' if !crate::terminal::sys::is_raw_mode_enabled() => KeyCode::Enter,
-                '\t' => {
-                    if modifiers.contains(KeyModifiers::SHIFT) {
-                        KeyCode::BackTab
-                    } else {
-                        KeyCode::Tab
-                    }
-                }
-                '\x7F' => KeyCode::Backspace,
-                _ => KeyCode::Char(c),
-            }
-        } else {
-            return Err(could_not_parse_event_error());
+    if let (Some(c), Some(base_layout_key)) = (char::from_u32(codepoint), base_layout_key) {
+        if is_alt_gr_character(c, base_layout_key, modifiers) {
+            modifiers.remove(KeyModifiers::CONTROL | KeyModifiers::ALT);
         }
-    };
+    }
 
-    let input_event = Event::Key(KeyEvent::new(keycode, modifiers));
+    let keycode = char::from_u32(codepoint)
+        .map(|c| char_key_code(c, modifiers))
+        .ok_or_else(could_not_parse_event_error)?;
+
+    let mut key_event = KeyEvent::new_with_kind(keycode, modifiers, kind);
+    // The base layout key is reported only if it differs from the key code
+    key_event.physical_key = base_layout_key.and_then(PhysicalKey::from_base_layout_key);
+
+    let input_event = Event::Key(key_event);
 
     Ok(Some(InternalEvent::Event(input_event)))
 }
 
+/// Returns the key code of the character reported by the `CSI u` and the `modifyOtherKeys`
+/// encodings.
+fn char_key_code(c: char, modifiers: KeyModifiers) -> KeyCode {
+    match c {
+        '\x1B' => KeyCode::Esc,
+        '\r' => KeyCode::Enter,
+        // Issue #371: \n = 0xA, which is also the keycode for Ctrl+J. The only reason we get
+        // newlines as input is because the terminal converts \r into \n for us. When we
+        // enter raw mode, we disable that, so \n no longer has any meaning - it's better to
+        // use Ctrl+J. Waiting to handle it here means it gets picked up later
+        '\n' if !crate::terminal::sys::is_raw_mode_enabled() => KeyCode::Enter,
+        '\t' => {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                KeyCode::BackTab
+            } else {
+                KeyCode::Tab
+            }
+        }
+        '\x7F' => KeyCode::Backspace,
+        _ => KeyCode::Char(c),
+    }
+}
+
+/// Returns whether the character reported with the Ctrl+Alt modifiers was produced by the
+/// AltGr (ISO level 3 shift) instead.
+///
+/// AltGr is reported as Ctrl+Alt by some terminals. The character typed with the AltGr
+/// (e.g. `@` on the German layout) isn't the base layout key (`q`), unlike the letters
+/// typed with Ctrl+Alt, which are kept as they are.
+fn is_alt_gr_character(c: char, base_layout_key: char, modifiers: KeyModifiers) -> bool {
+    modifiers.contains(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        && !c.is_alphanumeric()
+        && !c.is_control()
+        && c != base_layout_key
+}
+
 pub(crate) fn parse_csi_special_key_code(buffer: &[u8]) -> Result<Option<InternalEvent>> {
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'~']));
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"~"));
 
     let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
         .map_err(|_| could_not_parse_event_error())?;
@@ -302,11 +624,11 @@ pub(crate) fn parse_csi_special_key_code(buffer: &[u8]) -> Result<Option<Interna
     // This CSI sequence can be a list of semicolon-separated numbers.
     let first = next_parsed::<u8>(&mut split)?;
 
-    let modifiers = if let Ok(modifier_mask) = next_parsed::<u8>(&mut split) {
-        parse_modifiers(modifier_mask)
-    } else {
-        KeyModifiers::NONE
-    };
+    if first == 27 {
+        return parse_csi_modify_other_keys(split);
+    }
+
+    let (modifiers, kind) = parse_modifiers_and_kind(split.next())?;
 
     let keycode = match first {
         1 | 7 => KeyCode::Home,
@@ -323,17 +645,62 @@ pub(crate) fn parse_csi_special_key_code(buffer: &[u8]) -> Result<Option<Interna
         _ => return Err(could_not_parse_event_error()),
     };
 
+    let input_event = Event::Key(KeyEvent::new_with_kind(keycode, modifiers, kind));
+
+    Ok(Some(InternalEvent::Event(input_event)))
+}
+
+/// Parses the modified key reported with the xterm `modifyOtherKeys` encoding.
+///
+/// CSI 27 ; modifiers ; codepoint ~
+fn parse_csi_modify_other_keys(mut split: std::str::Split<char>) -> Result<Option<InternalEvent>> {
+    let modifiers = parse_modifiers(next_parsed::<u8>(&mut split)?);
+    let keycode = char::from_u32(next_parsed::<u32>(&mut split)?)
+        .map(|c| char_key_code(c, modifiers))
+        .ok_or_else(could_not_parse_event_error)?;
+
     let input_event = Event::Key(KeyEvent::new(keycode, modifiers));
 
     Ok(Some(InternalEvent::Event(input_event)))
 }
 
+/// Parses the key event reported by the ConPTY in the `win32-input-mode`.
+///
+/// CSI Vk ; Sc ; Uc ; Kd ; Cs ; Rc _
+pub(crate) fn parse_csi_win32_input_mode(buffer: &[u8]) -> Result<Option<InternalEvent>> {
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"_"));
+
+    let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
+        .map_err(|_| could_not_parse_event_error())?;
+
+    // The omitted parameters are zero, except for the repeat count
+    let mut parameters = [0, 0, 0, 0, 0, 1];
+    for (parameter, value) in parameters.iter_mut().zip(s.split(';')) {
+        if !value.is_empty() {
+            *parameter = value.parse().map_err(|_| could_not_parse_event_error())?;
+        }
+    }
+    let [virtual_key_code, virtual_scan_code, u_char, key_down, control_key_state, repeat_count] =
+        parameters;
+    let parameter = |value: u32| u16::try_from(value).map_err(|_| could_not_parse_event_error());
+
+    Ok(Some(InternalEvent::Win32Key(Win32KeyRecord {
+        key_down: key_down != 0,
+        repeat_count: parameter(repeat_count)?,
+        virtual_key_code: parameter(virtual_key_code)?,
+        virtual_scan_code: parameter(virtual_scan_code)?,
+        u_char: parameter(u_char)?,
+        control_key_state,
+    })))
+}
+
 pub(crate) fn parse_csi_rxvt_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
     // rxvt mouse encoding:
     // ESC [ Cb ; Cx ; Cy ; M
 
-    assert!(buffer.starts_with(&[b'\x1B', b'['])); // ESC [
-    assert!(buffer.ends_with(&[b'M']));
+    assert!(buffer.starts_with(b"\x1B[")); // ESC [
+    assert!(buffer.ends_with(b"M"));
 
     let s = std::str::from_utf8(&buffer[2..buffer.len() - 1])
         .map_err(|_| could_not_parse_event_error())?;
@@ -358,7 +725,7 @@ pub(crate) fn parse_csi_rxvt_mouse(buffer: &[u8]) -> Result<Option<InternalEvent
 pub(crate) fn parse_csi_normal_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
     // Normal mouse encoding: ESC [ M CB Cx Cy (6 characters only).
 
-    assert!(buffer.starts_with(&[b'\x1B', b'[', b'M'])); // ESC [ M
+    assert!(buffer.starts_with(b"\x1B[M")); // ESC [ M
 
     if buffer.len() < 6 {
         return Ok(None);
@@ -386,9 +753,9 @@ pub(crate) fn parse_csi_normal_mouse(buffer: &[u8]) -> Result<Option<InternalEve
 pub(crate) fn parse_csi_sgr_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>> {
     // ESC [ < Cb ; Cx ; Cy (;) (M or m)
 
-    assert!(buffer.starts_with(&[b'\x1B', b'[', b'<'])); // ESC [ <
+    assert!(buffer.starts_with(b"\x1B[<")); // ESC [ <
 
-    if !buffer.ends_with(&[b'm']) && !buffer.ends_with(&[b'M']) {
+    if !buffer.ends_with(b"m") && !buffer.ends_with(b"M") {
         return Ok(None);
     }
 
@@ -402,8 +769,9 @@ pub(crate) fn parse_csi_sgr_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>
     // See http://www.xfree86.org/current/ctlseqs.html#Mouse%20Tracking
     // The upper left character position on the terminal is denoted as 1,1.
     // Subtract 1 to keep it synced with cursor
-    let cx = next_parsed::<u16>(&mut split)? - 1;
-    let cy = next_parsed::<u16>(&mut split)? - 1;
+    // The SGR-Pixel mode reports the pixels the same way.
+    let cx = next_parsed::<u16>(&mut split)?.saturating_sub(1);
+    let cy = next_parsed::<u16>(&mut split)?.saturating_sub(1);
 
     // When button 3 in Cb is used to represent mouse release, you can't tell which button was
     // released. SGR mode solves this by having the sequence end with a lowercase m if it's a
@@ -420,12 +788,18 @@ pub(crate) fn parse_csi_sgr_mouse(buffer: &[u8]) -> Result<Option<InternalEvent>
         kind
     };
 
-    Ok(Some(InternalEvent::Event(Event::Mouse(MouseEvent {
+    let event = MouseEvent {
         kind,
         column: cx,
         row: cy,
         modifiers,
-    }))))
+    };
+
+    if is_pixel_mouse_capture_enabled() {
+        Ok(Some(InternalEvent::Event(Event::PixelMouse(event))))
+    } else {
+        Ok(Some(InternalEvent::Event(Event::Mouse(event))))
+    }
 }
 
 /// Cb is the byte of a mouse input that contains the button being used, the key modifiers being
@@ -456,6 +830,8 @@ fn parse_cb(cb: u8) -> Result<(MouseEventKind, KeyModifiers)> {
         (3, true) | (4, true) | (5, true) => MouseEventKind::Moved,
         (4, false) => MouseEventKind::ScrollUp,
         (5, false) => MouseEventKind::ScrollDown,
+        (6, false) => MouseEventKind::ScrollLeft,
+        (7, false) => MouseEventKind::ScrollRight,
         // We do not support other buttons.
         _ => return Err(could_not_parse_event_error()),
     };
@@ -574,7 +950,7 @@ mod tests {
         // parse_csi_cursor_position
         assert_eq!(
             parse_event(b"\x1B[20;10R", false).unwrap(),
-            Some(InternalEvent::CursorPosition(9, 19))
+            Some(InternalEvent::Response(Response::CursorPosition(9, 19)))
         );
 
         // parse_csi
@@ -653,7 +1029,109 @@ mod tests {
     fn test_parse_csi_cursor_position() {
         assert_eq!(
             parse_csi_cursor_position(b"\x1B[20;10R").unwrap(),
-            Some(InternalEvent::CursorPosition(9, 19))
+            Some(InternalEvent::Response(Response::CursorPosition(9, 19)))
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_dec_mode_report() {
+        assert_eq!(
+            parse_csi_dec_mode_report(b"\x1B[?7;1$y").unwrap(),
+            Some(InternalEvent::Response(Response::DecModeReport(
+                7,
+                Some(true)
+            )))
+        );
+        assert_eq!(
+            parse_csi_dec_mode_report(b"\x1B[?7;4$y").unwrap(),
+            Some(InternalEvent::Response(Response::DecModeReport(
+                7,
+                Some(false)
+            )))
+        );
+        assert_eq!(
+            parse_csi_dec_mode_report(b"\x1B[?2026;0$y").unwrap(),
+            Some(InternalEvent::Response(Response::DecModeReport(2026, None)))
+        );
+        // Incomplete
+        assert_eq!(parse_csi(b"\x1B[?7;2$").unwrap(), None);
+        assert_eq!(
+            parse_csi(b"\x1B[?7;2$y").unwrap(),
+            Some(InternalEvent::Response(Response::DecModeReport(
+                7,
+                Some(false)
+            )))
+        );
+    }
+
+    #[test]
+    fn test_parse_csi_keyboard_enhancement_query_answers() {
+        assert_eq!(
+            parse_csi(b"\x1B[?3u").unwrap(),
+            Some(InternalEvent::Response(Response::KeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+            )))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[?0u").unwrap(),
+            Some(InternalEvent::Response(Response::KeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::empty()
+            )))
+        );
+        assert_eq!(
+            parse_csi(b"\x1B[?62;22c").unwrap(),
+            Some(InternalEvent::Response(Response::PrimaryDeviceAttributes(
+                vec![62, 22]
+            )))
+        );
+        // Incomplete
+        assert_eq!(parse_csi(b"\x1B[?62;22").unwrap(), None);
+        assert_eq!(
+            parse_csi(b"\x1B[>41;390;0c").unwrap(),
+            Some(InternalEvent::Response(
+                Response::SecondaryDeviceAttributes(41, 390)
+            ))
+        );
+        assert_eq!(parse_csi(b"\x1B[>41;39").unwrap(), None);
+        assert!(parse_csi(b"\x1B[>0;0q").is_err());
+    }
+
+    #[test]
+    fn test_parse_osc_query_answers() {
+        assert_eq!(
+            parse_event(b"\x1B]11;rgb:ffff/8080/0000\x1B\\", false).unwrap(),
+            Some(InternalEvent::Response(Response::BackgroundColor(
+                255, 128, 0
+            )))
+        );
+        assert_eq!(
+            parse_event(b"\x1B]10;rgb:f/0/8\x07", false).unwrap(),
+            Some(InternalEvent::Response(Response::ForegroundColor(
+                255, 0, 136
+            )))
+        );
+        assert_eq!(
+            parse_event(b"\x1B]lvim\x1B\\", false).unwrap(),
+            Some(InternalEvent::Response(Response::Title("vim".to_string())))
+        );
+        // Incomplete
+        assert_eq!(parse_event(b"\x1B]11;rgb:ff", true).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B]11;rgb:ff/ff/ff\x1B", true).unwrap(),
+            None
+        );
+        assert!(parse_event(b"\x1B]11;cmyk:ff/ff/ff/ff\x07", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_alt_right_bracket() {
+        assert_eq!(
+            parse_event(b"\x1B]", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char(']'),
+                KeyModifiers::ALT
+            ))))
         );
     }
 
@@ -676,6 +1154,125 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_csi_u_encoded_key_code() {
+        assert_eq!(
+            parse_csi_u_encoded_key_code(b"\x1B[97;7u").unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('a'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            )))),
+        );
+        // Shifted key
+        assert_eq!(
+            parse_csi_u_encoded_key_code(b"\x1B[97:65;2u").unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('A'),
+                KeyModifiers::SHIFT
+            )))),
+        );
+        // AltGr+Q on the German layout
+        assert_eq!(
+            parse_csi_u_encoded_key_code(b"\x1B[64::113;7u").unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('@'),
+                KeyModifiers::empty()
+            )))),
+        );
+        // Ctrl+Alt+A on the Russian layout
+        assert_eq!(
+            parse_csi_u_encoded_key_code("\x1B[1092::97;7u".as_bytes()).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('ф'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            )))),
+        );
+        // Physical key
+        match parse_csi_u_encoded_key_code("\x1B[1094::119u".as_bytes()).unwrap() {
+            Some(InternalEvent::Event(Event::Key(key_event))) => {
+                assert_eq!(key_event.physical_key, Some(PhysicalKey::KeyW));
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_parse_key_event_kinds_and_modifiers() {
+        let key = |code, modifiers, kind| {
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new_with_kind(
+                code, modifiers, kind,
+            ))))
+        };
+
+        assert_eq!(
+            parse_event(b"\x1B[97;1:3u", false).unwrap(),
+            key(
+                KeyCode::Char('a'),
+                KeyModifiers::NONE,
+                KeyEventKind::Release
+            )
+        );
+        assert_eq!(
+            parse_event(b"\x1B[1;5:2A", false).unwrap(),
+            key(KeyCode::Up, KeyModifiers::CONTROL, KeyEventKind::Repeat)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[3;1:3~", false).unwrap(),
+            key(KeyCode::Delete, KeyModifiers::NONE, KeyEventKind::Release)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[1;2P", false).unwrap(),
+            key(KeyCode::F(1), KeyModifiers::SHIFT, KeyEventKind::Press)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[115;9u", false).unwrap(),
+            key(KeyCode::Char('s'), KeyModifiers::SUPER, KeyEventKind::Press)
+        );
+        assert_eq!(
+            parse_event(b"\x1B[97;49u", false).unwrap(),
+            key(
+                KeyCode::Char('a'),
+                KeyModifiers::HYPER | KeyModifiers::META,
+                KeyEventKind::Press
+            )
+        );
+        // Ctrl+I is distinguished from Tab
+        assert_eq!(
+            parse_event(b"\x1B[105;5u", false).unwrap(),
+            key(
+                KeyCode::Char('i'),
+                KeyModifiers::CONTROL,
+                KeyEventKind::Press
+            )
+        );
+        assert_eq!(
+            parse_event(b"\x1B[9u", false).unwrap(),
+            key(KeyCode::Tab, KeyModifiers::NONE, KeyEventKind::Press)
+        );
+        assert!(parse_event(b"\x1B[97;1:4u", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_csi_bracketed_paste() {
+        assert_eq!(parse_event(b"\x1B[200~foo", true).unwrap(), None);
+        assert_eq!(parse_event(b"\x1B[200~foo\x1B[20", true).unwrap(), None);
+        assert_eq!(
+            parse_event(b"\x1B[200~foo\r\x1B[Abar\x1B[201~", false).unwrap(),
+            Some(InternalEvent::Event(Event::Paste(
+                "foo\r\x1B[Abar".to_string()
+            ))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[200~\x1B[201~", false).unwrap(),
+            Some(InternalEvent::Event(Event::Paste(String::new()))),
+        );
+        // F9 isn't a paste
+        assert_eq!(
+            parse_event(b"\x1B[20~", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyCode::F(9).into()))),
+        );
+    }
+
     #[test]
     fn test_parse_csi_special_key_code() {
         assert_eq!(
@@ -695,6 +1292,107 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_csi_modify_other_keys() {
+        assert_eq!(
+            parse_event(b"\x1B[27;5;105~", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('i'),
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[27;6;65~", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Char('A'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[27;5;13~", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::Enter,
+                KeyModifiers::CONTROL
+            )))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[27;2;9~", false).unwrap(),
+            Some(InternalEvent::Event(Event::Key(KeyEvent::new(
+                KeyCode::BackTab,
+                KeyModifiers::SHIFT
+            )))),
+        );
+        assert!(parse_event(b"\x1B[27;5~", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_csi_win32_input_mode() {
+        assert_eq!(
+            parse_event(b"\x1B[65;30;97;1;8;3_", false).unwrap(),
+            Some(InternalEvent::Win32Key(Win32KeyRecord {
+                key_down: true,
+                repeat_count: 3,
+                virtual_key_code: 65,
+                virtual_scan_code: 30,
+                u_char: 97,
+                control_key_state: 8,
+            })),
+        );
+        // The omitted parameters
+        assert_eq!(
+            parse_event(b"\x1B[65;;97_", false).unwrap(),
+            Some(InternalEvent::Win32Key(Win32KeyRecord {
+                key_down: false,
+                repeat_count: 1,
+                virtual_key_code: 65,
+                virtual_scan_code: 0,
+                u_char: 97,
+                control_key_state: 0,
+            })),
+        );
+        assert!(parse_event(b"\x1B[0;0;128512;1;0;1_", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_csi_in_band_resize() {
+        assert_eq!(
+            parse_event(b"\x1B[48;24;80;480;800t", false).unwrap(),
+            Some(InternalEvent::InBandResize {
+                columns: 80,
+                rows: 24,
+                width: 800,
+                height: 480,
+            }),
+        );
+        // The omitted sizes in pixels
+        assert_eq!(
+            parse_event(b"\x1B[48;24;80t", false).unwrap(),
+            Some(InternalEvent::InBandResize {
+                columns: 80,
+                rows: 24,
+                width: 0,
+                height: 0,
+            }),
+        );
+        assert!(parse_event(b"\x1B[8;24;80t", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_csi_color_scheme_report() {
+        assert_eq!(
+            parse_event(b"\x1B[?997;1n", false).unwrap(),
+            Some(InternalEvent::Event(Event::ThemeChanged(ColorScheme::Dark))),
+        );
+        assert_eq!(
+            parse_event(b"\x1B[?997;2n", false).unwrap(),
+            Some(InternalEvent::Event(Event::ThemeChanged(
+                ColorScheme::Light
+            ))),
+        );
+        assert!(parse_event(b"\x1B[?997;3n", false).is_err());
+        assert!(parse_event(b"\x1B[?6n", false).is_err());
+    }
+
     #[test]
     fn test_parse_csi_rxvt_mouse() {
         assert_eq!(
@@ -759,6 +1457,34 @@ mod tests {
                 modifiers: KeyModifiers::empty(),
             })))
         );
+        assert_eq!(
+            parse_csi_sgr_mouse(b"\x1B[<66;20;10M").unwrap(),
+            Some(InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::ScrollLeft,
+                column: 19,
+                row: 9,
+                modifiers: KeyModifiers::empty(),
+            })))
+        );
+        assert_eq!(
+            parse_csi_sgr_mouse(b"\x1B[<71;20;10M").unwrap(),
+            Some(InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::ScrollRight,
+                column: 19,
+                row: 9,
+                modifiers: KeyModifiers::SHIFT,
+            })))
+        );
+        // The pixel (SGR-Pixel mode) 0 isn't valid, but it's sent by some terminals
+        assert_eq!(
+            parse_csi_sgr_mouse(b"\x1B[<0;0;0M").unwrap(),
+            Some(InternalEvent::Event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 0,
+                row: 0,
+                modifiers: KeyModifiers::empty(),
+            })))
+        );
     }
 
     #[test]
@@ -0,0 +1,188 @@
+use libc::c_int;
+use mio::{Interest, Registry, Token};
+use parking_lot::Mutex;
+use signal_hook::{
+    consts::{SIGCONT, SIGHUP, SIGTERM, SIGTSTP, SIGUSR1, SIGUSR2, SIGWINCH},
+    low_level::{emulate_default_handler, unregister},
+    SigId,
+};
+use signal_hook_mio::v0_7::Signals;
+
+use crate::{
+    event::{Signal, SuspendHandling},
+    Result,
+};
+
+/// Signals registered with `register_signal`.
+static REGISTERED_SIGNALS: Mutex<Vec<Signal>> = parking_lot::const_mutex(Vec::new());
+
+/// Handling of the `SIGTSTP` and `SIGCONT` set with `enable_suspend_signals`, `None` if
+/// they aren't delivered as events.
+static SUSPEND_HANDLING: Mutex<Option<SuspendHandling>> = parking_lot::const_mutex(None);
+
+/// Actions performing the default action of the signals unregistered by `restore_signals`.
+static DEFAULT_ACTIONS: Mutex<Vec<(c_int, SigId)>> = parking_lot::const_mutex(Vec::new());
+
+/// Signals handle of the event source, `None` if there's no event source yet.
+///
+/// It lives outside of the event source, because the event source is locked for the
+/// whole duration of the `read`/`poll` and signals must be registered immediately.
+static SIGNALS: Mutex<Option<Signals>> = parking_lot::const_mutex(None);
+
+/// Registers the signal to be delivered as an event.
+pub(crate) fn register_signal(signal: Signal) -> Result<()> {
+    let mut registered = REGISTERED_SIGNALS.lock();
+    add_signal(signal_number(signal))?;
+
+    if !registered.contains(&signal) {
+        registered.push(signal);
+    }
+
+    Ok(())
+}
+
+/// Delivers the `SIGTSTP` and `SIGCONT` as events, handled as specified.
+pub(crate) fn enable_suspend_signals(handling: SuspendHandling) -> Result<()> {
+    let mut suspend_handling = SUSPEND_HANDLING.lock();
+    add_signal(SIGTSTP)?;
+    add_signal(SIGCONT)?;
+
+    *suspend_handling = Some(handling);
+    Ok(())
+}
+
+/// Stops delivering the `SIGTSTP` and `SIGCONT` as events, their default action is
+/// performed again.
+pub(crate) fn disable_suspend_signals() -> Result<()> {
+    let mut suspend_handling = SUSPEND_HANDLING.lock();
+    if suspend_handling.take().is_some() {
+        let mut default_actions = DEFAULT_ACTIONS.lock();
+        default_actions.push((SIGTSTP, register_default_action(SIGTSTP)?));
+        default_actions.push((SIGCONT, register_default_action(SIGCONT)?));
+    }
+    Ok(())
+}
+
+/// Returns the handling of the `SIGTSTP` and `SIGCONT`, `None` if they aren't delivered as
+/// events.
+pub(crate) fn suspend_handling() -> Option<SuspendHandling> {
+    *SUSPEND_HANDLING.lock()
+}
+
+/// Adds the signal to the signals handle, the emulated default action is dropped.
+fn add_signal(number: c_int) -> Result<()> {
+    // The signal is delivered as an event again
+    DEFAULT_ACTIONS.lock().retain(|&(action_number, id)| {
+        if action_number == number {
+            unregister(id);
+        }
+        action_number != number
+    });
+
+    if let Some(signals) = SIGNALS.lock().as_ref() {
+        signals.add_signal(number)?;
+    }
+    Ok(())
+}
+
+/// Registers an action emulating the default action of the signal.
+///
+/// The signal-hook handler can't be uninstalled, it emulates the default action instead.
+fn register_default_action(number: c_int) -> Result<SigId> {
+    // Safe, `emulate_default_handler` is async-signal-safe
+    unsafe {
+        signal_hook::low_level::register(number, move || {
+            let _ = emulate_default_handler(number);
+        })
+    }
+}
+
+/// Creates signals handle (`SIGWINCH` & all the registered signals) and registers it
+/// in the given registry.
+pub(crate) fn register_signals(registry: &Registry, token: Token) -> Result<()> {
+    let registered = REGISTERED_SIGNALS.lock();
+
+    let suspend_signals = match suspend_handling() {
+        Some(_) => &[SIGTSTP, SIGCONT][..],
+        None => &[],
+    };
+
+    let mut signals = Signals::new(
+        std::iter::once(SIGWINCH)
+            .chain(registered.iter().copied().map(signal_number))
+            .chain(suspend_signals.iter().copied()),
+    )?;
+    registry.register(&mut signals, token, Interest::READABLE)?;
+
+    *SIGNALS.lock() = Some(signals);
+    Ok(())
+}
+
+/// Drops the signals handle created with `register_signals`.
+pub(crate) fn unregister_signals() {
+    *SIGNALS.lock() = None;
+}
+
+/// Forgets the signals registered with `register_signal` and the suspend handling, their
+/// default action (process termination, stop) is performed again.
+pub(crate) fn restore_signals() -> Result<()> {
+    disable_suspend_signals()?;
+
+    let mut registered = REGISTERED_SIGNALS.lock();
+    let mut default_actions = DEFAULT_ACTIONS.lock();
+
+    for signal in registered.drain(..) {
+        let number = signal_number(signal);
+        default_actions.push((number, register_default_action(number)?));
+    }
+
+    Ok(())
+}
+
+/// Returns all the signals received since the last call.
+pub(crate) fn pending_signals() -> Vec<c_int> {
+    match SIGNALS.lock().as_mut() {
+        Some(signals) => signals.pending().collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Converts the signal number back to the `Signal`.
+pub(crate) fn signal_from_number(number: c_int) -> Option<Signal> {
+    match number {
+        SIGHUP => Some(Signal::Hangup),
+        SIGTERM => Some(Signal::Terminate),
+        SIGUSR1 => Some(Signal::User1),
+        SIGUSR2 => Some(Signal::User2),
+        _ => None,
+    }
+}
+
+fn signal_number(signal: Signal) -> c_int {
+    match signal {
+        Signal::Hangup => SIGHUP,
+        Signal::Terminate => SIGTERM,
+        Signal::User1 => SIGUSR1,
+        Signal::User2 => SIGUSR2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::Signal;
+
+    use super::{signal_from_number, signal_number};
+
+    #[test]
+    fn test_signal_number_round_trip() {
+        for signal in [
+            Signal::Hangup,
+            Signal::Terminate,
+            Signal::User1,
+            Signal::User2,
+        ] {
+            assert_eq!(signal_from_number(signal_number(signal)), Some(signal));
+        }
+        assert_eq!(signal_from_number(signal_hook::consts::SIGWINCH), None);
+    }
+}
@@ -0,0 +1,179 @@
+use std::io;
+use std::os::unix::io::RawFd;
+
+use mio::{event::Event as MioEvent, unix::SourceFd, Interest, Registry};
+use parking_lot::Mutex;
+
+use crate::{
+    event::{Readiness, Token},
+    Result,
+};
+
+/// The first `mio::Token` used for the user file descriptors, the ones below are
+/// reserved for the event source.
+pub(crate) const FIRST_USER_TOKEN: usize = 16;
+
+/// File descriptors registered with `register_fd`.
+static USER_FDS: Mutex<Vec<UserFd>> = parking_lot::const_mutex(Vec::new());
+
+/// Registry of the event source, `None` if there's no event source yet.
+///
+/// It's a clone of the event source registry, because the event source is locked
+/// for the whole duration of the `read`/`poll` and file descriptors must be
+/// registered immediately.
+static REGISTRY: Mutex<Option<Registry>> = parking_lot::const_mutex(None);
+
+#[derive(Debug, Clone, Copy)]
+struct UserFd {
+    fd: RawFd,
+    token: mio::Token,
+    interest: Interest,
+}
+
+/// Registers the file descriptor or updates its registration if it's already registered.
+pub(crate) fn register_fd(fd: RawFd, token: Token, interest: Readiness) -> Result<()> {
+    let user_fd = UserFd {
+        fd,
+        token: mio_token(token)?,
+        interest: mio_interest(interest)?,
+    };
+
+    let mut user_fds = USER_FDS.lock();
+    let existing = user_fds.iter().position(|x| x.fd == fd);
+
+    if let Some(registry) = REGISTRY.lock().as_ref() {
+        let mut source = SourceFd(&user_fd.fd);
+        if existing.is_some() {
+            registry.reregister(&mut source, user_fd.token, user_fd.interest)?;
+        } else {
+            registry.register(&mut source, user_fd.token, user_fd.interest)?;
+        }
+    }
+
+    match existing {
+        Some(idx) => user_fds[idx] = user_fd,
+        None => user_fds.push(user_fd),
+    };
+
+    Ok(())
+}
+
+/// Deregisters the file descriptor registered with `register_fd`.
+pub(crate) fn deregister_fd(fd: RawFd) -> Result<()> {
+    let mut user_fds = USER_FDS.lock();
+    let idx = user_fds
+        .iter()
+        .position(|x| x.fd == fd)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "File descriptor not registered"))?;
+
+    if let Some(registry) = REGISTRY.lock().as_ref() {
+        registry.deregister(&mut SourceFd(&fd))?;
+    }
+
+    user_fds.remove(idx);
+    Ok(())
+}
+
+/// Registers all the user file descriptors in the registry of a new event source.
+pub(crate) fn register_user_fds(registry: &Registry) -> Result<()> {
+    let user_fds = USER_FDS.lock();
+
+    for user_fd in user_fds.iter() {
+        registry.register(&mut SourceFd(&user_fd.fd), user_fd.token, user_fd.interest)?;
+    }
+
+    *REGISTRY.lock() = Some(registry.try_clone()?);
+    Ok(())
+}
+
+/// Drops the registry clone created with `register_user_fds`.
+pub(crate) fn unregister_user_fds() {
+    *REGISTRY.lock() = None;
+}
+
+/// Forgets all the file descriptors registered with `register_fd`, the next event source
+/// doesn't poll them.
+pub(crate) fn forget_user_fds() {
+    USER_FDS.lock().clear();
+}
+
+/// Converts the mio event of a user file descriptor to the token & readiness pair.
+pub(crate) fn user_fd_event(event: &MioEvent) -> (Token, Readiness) {
+    let mut readiness = Readiness::empty();
+    if event.is_readable() {
+        readiness |= Readiness::READABLE;
+    }
+    if event.is_writable() {
+        readiness |= Readiness::WRITABLE;
+    }
+    if event.is_read_closed() {
+        readiness |= Readiness::READ_CLOSED;
+    }
+    if event.is_write_closed() {
+        readiness |= Readiness::WRITE_CLOSED;
+    }
+    if event.is_error() {
+        readiness |= Readiness::ERROR;
+    }
+
+    (Token(event.token().0 - FIRST_USER_TOKEN), readiness)
+}
+
+fn mio_token(token: Token) -> Result<mio::Token> {
+    token
+        .0
+        .checked_add(FIRST_USER_TOKEN)
+        .map(mio::Token)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Token is too big"))
+}
+
+fn mio_interest(interest: Readiness) -> Result<Interest> {
+    match (
+        interest.contains(Readiness::READABLE),
+        interest.contains(Readiness::WRITABLE),
+    ) {
+        (true, true) => Ok(Interest::READABLE | Interest::WRITABLE),
+        (true, false) => Ok(Interest::READABLE),
+        (false, true) => Ok(Interest::WRITABLE),
+        (false, false) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Interest must contain READABLE and/or WRITABLE",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    use crate::event::{Readiness, Token};
+
+    use super::{deregister_fd, mio_interest, mio_token, register_fd, USER_FDS};
+
+    #[test]
+    fn test_mio_token() {
+        assert_eq!(mio_token(Token(0)).unwrap(), mio::Token(16));
+        assert!(mio_token(Token(usize::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_mio_interest() {
+        assert!(mio_interest(Readiness::READABLE).unwrap().is_readable());
+        assert!(mio_interest(Readiness::WRITABLE).unwrap().is_writable());
+        assert!(mio_interest(Readiness::READ_CLOSED).is_err());
+    }
+
+    #[test]
+    fn test_register_deregister_fd() {
+        let (stream, _other) = UnixStream::pair().unwrap();
+        let fd = stream.as_raw_fd();
+
+        register_fd(fd, Token(1), Readiness::READABLE).unwrap();
+        register_fd(fd, Token(2), Readiness::READABLE).unwrap();
+        assert_eq!(USER_FDS.lock().iter().filter(|x| x.fd == fd).count(), 1);
+
+        deregister_fd(fd).unwrap();
+        assert!(deregister_fd(fd).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! The key events of the Windows console input.
+//!
+//! They're read from the console on Windows and reported by the ConPTY in the
+//! `win32-input-mode` (`CSI Vk ; Sc ; Uc ; Kd ; Cs ; Rc _`), e.g. to the applications
+//! running in the WSL under the Windows Terminal.
+
+use std::iter;
+
+use crate::event::{
+    is_interrupt_events_enabled, keyboard_enhancement_flags, normalize::normalize_key_event, Event,
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags, PhysicalKey,
+    RawKeyCodes,
+};
+
+// The virtual key codes
+const VK_BACK: u16 = 0x08;
+const VK_RETURN: u16 = 0x0D;
+const VK_SHIFT: u16 = 0x10;
+const VK_CONTROL: u16 = 0x11;
+const VK_MENU: u16 = 0x12;
+const VK_ESCAPE: u16 = 0x1B;
+const VK_PRIOR: u16 = 0x21;
+const VK_NEXT: u16 = 0x22;
+const VK_END: u16 = 0x23;
+const VK_HOME: u16 = 0x24;
+const VK_LEFT: u16 = 0x25;
+const VK_UP: u16 = 0x26;
+const VK_RIGHT: u16 = 0x27;
+const VK_DOWN: u16 = 0x28;
+const VK_INSERT: u16 = 0x2D;
+const VK_DELETE: u16 = 0x2E;
+const VK_F1: u16 = 0x70;
+const VK_F24: u16 = 0x87;
+
+// The control key state flags
+const RIGHT_ALT_PRESSED: u32 = 0x0001;
+const LEFT_ALT_PRESSED: u32 = 0x0002;
+const RIGHT_CTRL_PRESSED: u32 = 0x0004;
+const LEFT_CTRL_PRESSED: u32 = 0x0008;
+const SHIFT_PRESSED: u32 = 0x0010;
+
+/// A key event record of the console input.
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Hash, Eq)]
+pub(crate) struct Win32KeyRecord {
+    /// The key was pressed, released otherwise.
+    pub(crate) key_down: bool,
+    /// The number of the merged repeats.
+    pub(crate) repeat_count: u16,
+    pub(crate) virtual_key_code: u16,
+    pub(crate) virtual_scan_code: u16,
+    /// The UTF-16 code unit of the produced character.
+    pub(crate) u_char: u16,
+    /// The `*_PRESSED` flags.
+    pub(crate) control_key_state: u32,
+}
+
+/// The state kept between the key events.
+#[derive(Debug, Default)]
+pub(crate) struct KeyEventState {
+    /// The high surrogate of the character split between the key events.
+    surrogate_buffer: Option<u16>,
+    /// The virtual key code of the key held down, its next key down event is a repeat.
+    held_key: Option<u16>,
+}
+
+/// Parses the key event.
+///
+/// The characters outside of the BMP (e.g. emoji typed via IME) are delivered as two key
+/// events with the UTF-16 surrogates, the high one is kept in the `surrogate_buffer`
+/// until the low one arrives.
+///
+/// The releases and the repeats are reported only if the
+/// [`KeyboardEnhancementFlags::REPORT_EVENT_TYPES`] are pushed, like the terminals do.
+/// The console merges the repeats which weren't read in time into one key event, they're
+/// split into the separate events again.
+pub(crate) fn handle_key_event(
+    key_event: Win32KeyRecord,
+    state: &mut KeyEventState,
+) -> impl Iterator<Item = Event> {
+    let report_event_types =
+        keyboard_enhancement_flags().contains(KeyboardEnhancementFlags::REPORT_EVENT_TYPES);
+    let (event, repeats) = parse_key_event_kind(key_event, state, report_event_types);
+
+    let repeat = event.clone().and_then(|event| match event {
+        Event::Key(event) if report_event_types && event.kind != KeyEventKind::Release => {
+            Some(Event::Key(KeyEvent {
+                kind: KeyEventKind::Repeat,
+                ..event
+            }))
+        }
+        _ => None,
+    });
+
+    event.into_iter().chain(
+        repeat
+            .into_iter()
+            .flat_map(move |repeat| iter::repeat_n(repeat, repeats)),
+    )
+}
+
+/// Parses the key event and its kind, returns the number of the merged repeats too.
+fn parse_key_event_kind(
+    key_event: Win32KeyRecord,
+    state: &mut KeyEventState,
+    report_event_types: bool,
+) -> (Option<Event>, usize) {
+    let key = key_event.virtual_key_code;
+
+    let kind = if key_event.key_down {
+        // Ctrl-C produces the ETX control character
+        if key_event.u_char == 0x03 && is_interrupt_events_enabled() {
+            return (Some(Event::Interrupt), 0);
+        }
+
+        if state.held_key == Some(key) {
+            KeyEventKind::Repeat
+        } else {
+            state.held_key = Some(key);
+            KeyEventKind::Press
+        }
+    } else {
+        if state.held_key == Some(key) {
+            state.held_key = None;
+        }
+
+        if !report_event_types || (key_event.u_char == 0x03 && is_interrupt_events_enabled()) {
+            return (None, 0);
+        }
+        KeyEventKind::Release
+    };
+
+    // The repeats are the presses for the applications not asking for the event types
+    let kind = if report_event_types {
+        kind
+    } else {
+        KeyEventKind::Press
+    };
+
+    let event = parse_key_event_record(&key_event, &mut state.surrogate_buffer)
+        .map(|event| Event::Key(normalize_key_event(KeyEvent { kind, ..event })));
+    let repeats = usize::from(key_event.repeat_count.saturating_sub(1));
+    (event, repeats)
+}
+
+/// Returns the modifiers of the control key state.
+fn parse_control_key_state(state: u32) -> KeyModifiers {
+    let mut modifier = KeyModifiers::empty();
+
+    if state & SHIFT_PRESSED != 0 {
+        modifier |= KeyModifiers::SHIFT;
+    }
+    if state & (LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED) != 0 {
+        modifier |= KeyModifiers::CONTROL;
+    }
+    if state & (LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED) != 0 {
+        modifier |= KeyModifiers::ALT;
+    }
+
+    modifier
+}
+
+fn parse_key_event_record(
+    key_event: &Win32KeyRecord,
+    surrogate_buffer: &mut Option<u16>,
+) -> Option<KeyEvent> {
+    let mut modifiers = parse_control_key_state(key_event.control_key_state);
+
+    let parse_result = match key_event.virtual_key_code {
+        VK_SHIFT | VK_CONTROL | VK_MENU => None,
+        VK_BACK => Some(KeyCode::Backspace),
+        VK_ESCAPE => Some(KeyCode::Esc),
+        VK_RETURN => Some(KeyCode::Enter),
+        VK_F1..=VK_F24 => Some(KeyCode::F((key_event.virtual_key_code - 111) as u8)),
+        VK_LEFT => Some(KeyCode::Left),
+        VK_UP => Some(KeyCode::Up),
+        VK_RIGHT => Some(KeyCode::Right),
+        VK_DOWN => Some(KeyCode::Down),
+        VK_PRIOR => Some(KeyCode::PageUp),
+        VK_NEXT => Some(KeyCode::PageDown),
+        VK_HOME => Some(KeyCode::Home),
+        VK_END => Some(KeyCode::End),
+        VK_DELETE => Some(KeyCode::Delete),
+        VK_INSERT => Some(KeyCode::Insert),
+        _ => {
+            // Modifier Keys (Ctrl, Alt, Shift) Support
+            let character_raw = key_event.u_char;
+
+            if character_raw < 255 {
+                // Invalid character
+                if character_raw == 0 {
+                    return None;
+                }
+
+                let mut character = character_raw as u8 as char;
+
+                if modifiers.contains(KeyModifiers::CONTROL) {
+                    // we need to do some parsing
+                    // Control character will take the ASCII code produced by the key and bitwise AND
+                    // it with 31, forcing bits 6 and bits 7 to zero.
+                    // So we can make a bitwise OR back to see what's the raw control character.
+                    let c = character_raw as u8;
+                    if c <= b'\x1F' {
+                        character = (c | b'\x40') as char;
+                    } else if modifiers.contains(KeyModifiers::ALT) {
+                        // AltGr is reported as Ctrl+Alt, but the printable character is
+                        // produced only by the AltGr, Ctrl+Alt produces the control one
+                        modifiers.remove(KeyModifiers::CONTROL | KeyModifiers::ALT);
+                    } else {
+                        return None;
+                    }
+                }
+
+                if modifiers.contains(KeyModifiers::SHIFT) && character == '\t' {
+                    Some(KeyCode::BackTab)
+                } else if character == '\t' {
+                    Some(KeyCode::Tab)
+                } else {
+                    Some(KeyCode::Char(character))
+                }
+            } else {
+                if modifiers.contains(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+                    // Produced by the AltGr, e.g. the euro sign
+                    modifiers.remove(KeyModifiers::CONTROL | KeyModifiers::ALT);
+                }
+                decode_utf16(character_raw, surrogate_buffer).map(KeyCode::Char)
+            }
+        }
+    };
+
+    if let Some(key_code) = parse_result {
+        let raw_codes = RawKeyCodes {
+            scan_code: Some(key_event.virtual_scan_code),
+            virtual_key_code: Some(key_event.virtual_key_code),
+        };
+        let mut event = KeyEvent::new(key_code, modifiers).with_raw_codes(raw_codes);
+        event.physical_key = PhysicalKey::from_scan_code(key_event.virtual_scan_code);
+        return Some(event);
+    }
+
+    None
+}
+
+/// Decodes the UTF-16 code unit, the high surrogate is kept in the buffer until the low
+/// one arrives.
+fn decode_utf16(code_unit: u16, surrogate_buffer: &mut Option<u16>) -> Option<char> {
+    match code_unit {
+        0xD800..=0xDBFF => {
+            *surrogate_buffer = Some(code_unit);
+            None
+        }
+        0xDC00..=0xDFFF => {
+            let high_surrogate = surrogate_buffer.take()?;
+            std::char::decode_utf16([high_surrogate, code_unit])
+                .next()?
+                .ok()
+        }
+        _ => {
+            *surrogate_buffer = None;
+            std::char::from_u32(code_unit as u32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{Event, KeyCode, KeyEventKind};
+
+    use super::{decode_utf16, parse_key_event_kind, KeyEventState, Win32KeyRecord};
+
+    fn key_record(key_down: bool, repeat_count: u16) -> Win32KeyRecord {
+        Win32KeyRecord {
+            key_down,
+            repeat_count,
+            virtual_key_code: 0x41,
+            virtual_scan_code: 0,
+            u_char: 'a' as u16,
+            control_key_state: 0,
+        }
+    }
+
+    fn kind(event: Option<Event>) -> Option<KeyEventKind> {
+        match event {
+            Some(Event::Key(event)) => {
+                assert_eq!(event.code, KeyCode::Char('a'));
+                Some(event.kind)
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_key_event_kinds() {
+        let mut state = KeyEventState::default();
+        let mut parse = |key_down, repeat_count, report_event_types| {
+            let (event, repeats) = parse_key_event_kind(
+                key_record(key_down, repeat_count),
+                &mut state,
+                report_event_types,
+            );
+            (kind(event), repeats)
+        };
+
+        assert_eq!(parse(true, 1, true), (Some(KeyEventKind::Press), 0));
+        assert_eq!(parse(true, 3, true), (Some(KeyEventKind::Repeat), 2));
+        assert_eq!(parse(false, 1, true), (Some(KeyEventKind::Release), 0));
+        assert_eq!(parse(true, 1, true), (Some(KeyEventKind::Press), 0));
+
+        // The legacy behavior, the repeats are presses and the releases are dropped
+        assert_eq!(parse(true, 1, false), (Some(KeyEventKind::Press), 0));
+        assert_eq!(parse(false, 1, false), (None, 0));
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        let mut surrogate_buffer = None;
+
+        assert_eq!(decode_utf16(0x20AC, &mut surrogate_buffer), Some('€'));
+
+        // U+1F600 GRINNING FACE
+        assert_eq!(decode_utf16(0xD83D, &mut surrogate_buffer), None);
+        assert_eq!(decode_utf16(0xDE00, &mut surrogate_buffer), Some('😀'));
+
+        // Unpaired surrogates are dropped
+        assert_eq!(decode_utf16(0xDE00, &mut surrogate_buffer), None);
+        assert_eq!(decode_utf16(0xD83D, &mut surrogate_buffer), None);
+        assert_eq!(decode_utf16(0x20AC, &mut surrogate_buffer), Some('€'));
+        assert_eq!(surrogate_buffer, None);
+    }
+}
@@ -2,9 +2,17 @@
 
 use std::convert::TryFrom;
 use std::io;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
-use crossterm_winapi::{ConsoleMode, Handle};
+use crossterm_winapi::ConsoleMode;
+use winapi::{
+    shared::minwindef::{BOOL, DWORD, FALSE, TRUE},
+    um::{
+        consoleapi::SetConsoleCtrlHandler,
+        wincon::{WriteConsoleInputW, CTRL_C_EVENT, INPUT_RECORD, KEY_EVENT, LEFT_CTRL_PRESSED},
+    },
+};
 
 use crate::Result;
 
@@ -16,6 +24,10 @@ pub(crate) mod poll;
 
 const ENABLE_MOUSE_MODE: u32 = 0x0010 | 0x0080 | 0x0008;
 
+/// The Quick Edit mode lets the user select the text with the mouse, the console doesn't
+/// report the mouse events while it's enabled.
+const ENABLE_QUICK_EDIT_MODE: u32 = 0x0040;
+
 /// This is a either `u64::MAX` if it's uninitialized or a valid `u32` that stores the original
 /// console mode if it's initialized.
 static ORIGINAL_CONSOLE_MODE: AtomicU64 = AtomicU64::new(u64::MAX);
@@ -33,19 +45,75 @@ fn init_original_console_mode(original_mode: u32) {
 /// Returns the original console color, make sure to call `init_console_color` before calling this function. Otherwise this function will panic.
 fn original_console_mode() -> Result<u32> {
     u32::try_from(ORIGINAL_CONSOLE_MODE.load(Ordering::Relaxed))
-        .map_err(|_| io::Error::new(io::ErrorKind::Other, "Initial console modes not set"))
+        .map_err(|_| io::Error::other("Initial console modes not set"))
 }
 
 pub(crate) fn enable_mouse_capture() -> Result<()> {
-    let mode = ConsoleMode::from(Handle::current_in_handle()?);
+    let mode = ConsoleMode::from(crate::terminal::sys::input_handle()?);
     init_original_console_mode(mode.mode()?);
-    mode.set_mode(ENABLE_MOUSE_MODE)?;
+    mode.set_mode(ENABLE_MOUSE_MODE & !ENABLE_QUICK_EDIT_MODE)?;
 
     Ok(())
 }
 
 pub(crate) fn disable_mouse_capture() -> Result<()> {
-    let mode = ConsoleMode::from(Handle::current_in_handle()?);
+    let mode = ConsoleMode::from(crate::terminal::sys::input_handle()?);
     mode.set_mode(original_console_mode()?)?;
+    // The next capture remembers the mode (and the Quick Edit mode) it replaces
+    ORIGINAL_CONSOLE_MODE.store(u64::MAX, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Whether the `interrupt_handler` was registered.
+static INTERRUPT_HANDLER_REGISTERED: AtomicBool = AtomicBool::new(false);
+
+/// Registers the console control handler converting `CTRL_C_EVENT` into an input event.
+/// It will be skipped if it has already been registered.
+pub(crate) fn register_interrupt_handler() -> Result<()> {
+    if INTERRUPT_HANDLER_REGISTERED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    if unsafe { SetConsoleCtrlHandler(Some(interrupt_handler), TRUE) } == 0 {
+        INTERRUPT_HANDLER_REGISTERED.store(false, Ordering::SeqCst);
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+unsafe extern "system" fn interrupt_handler(ctrl_type: DWORD) -> BOOL {
+    if ctrl_type != CTRL_C_EVENT || !crate::event::is_interrupt_events_enabled() {
+        // Let the next handler (the default one terminates the process) handle it
+        return FALSE;
+    }
+
+    // The handler is called on a separate thread. Inject the Ctrl-C key press into the
+    // input buffer, the event source converts it into the `Event::Interrupt`.
+    match write_interrupt_key_record() {
+        Ok(()) => TRUE,
+        Err(_) => FALSE,
+    }
+}
+
+fn write_interrupt_key_record() -> Result<()> {
+    let handle = crate::terminal::sys::input_handle()?;
+
+    let mut record: INPUT_RECORD = unsafe { mem::zeroed() };
+    record.EventType = KEY_EVENT;
+    unsafe {
+        let key_event = record.Event.KeyEvent_mut();
+        key_event.bKeyDown = TRUE;
+        key_event.wRepeatCount = 1;
+        key_event.wVirtualKeyCode = b'C' as u16;
+        *key_event.uChar.UnicodeChar_mut() = 0x03;
+        key_event.dwControlKeyState = LEFT_CTRL_PRESSED;
+    }
+
+    let mut written = 0;
+    if unsafe { WriteConsoleInputW(*handle, &record, 1, &mut written) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
     Ok(())
 }
@@ -1,35 +1,69 @@
 use crossterm_winapi::{ControlKeyState, EventFlags, KeyEventRecord, MouseEvent, ScreenBuffer};
-use winapi::um::{
-    wincon::{
-        LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, RIGHT_ALT_PRESSED, RIGHT_CTRL_PRESSED, SHIFT_PRESSED,
-    },
-    winuser::{
-        VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_F24, VK_HOME,
-        VK_INSERT, VK_LEFT, VK_MENU, VK_NEXT, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_UP,
-    },
+use winapi::um::wincon::{
+    LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, RIGHT_ALT_PRESSED, RIGHT_CTRL_PRESSED, SHIFT_PRESSED,
 };
 
 use crate::{
-    event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind},
+    event::{
+        mouse_capture_mode, sys::win32_key::Win32KeyRecord, Event, KeyModifiers, MouseButton,
+        MouseCaptureMode, MouseEventKind,
+    },
     Result,
 };
 
-pub(crate) fn handle_mouse_event(mouse_event: MouseEvent) -> Option<Event> {
-    if let Ok(Some(event)) = parse_mouse_event_record(&mouse_event) {
-        return Some(Event::Mouse(event));
-    }
+/// The wheel delta of one notch.
+const WHEEL_DELTA: i32 = 120;
+
+/// Parses the mouse event record, the wheel rotated by several notches produces one scroll
+/// event per notch like the VT terminals do.
+pub(crate) fn handle_mouse_event(mouse_event: MouseEvent) -> impl Iterator<Item = Event> {
+    let event = parse_mouse_event_record(&mouse_event).ok().flatten();
+    let count = match event.map(|event| event.kind) {
+        Some(
+            MouseEventKind::ScrollUp
+            | MouseEventKind::ScrollDown
+            | MouseEventKind::ScrollLeft
+            | MouseEventKind::ScrollRight,
+        ) => wheel_notches(&mouse_event),
+        Some(_) => 1,
+        None => 0,
+    };
 
-    None
+    event
+        .into_iter()
+        .flat_map(move |event| std::iter::repeat(Event::Mouse(event)).take(count))
 }
 
-pub(crate) fn handle_key_event(key_event: KeyEventRecord) -> Option<Event> {
-    if key_event.key_down {
-        if let Some(event) = parse_key_event_record(&key_event) {
-            return Some(Event::Key(event));
+/// Returns the number of the notches the wheel was rotated by, at least one.
+///
+/// The high word of the `button_state` holds the signed wheel delta.
+fn wheel_notches(mouse_event: &MouseEvent) -> usize {
+    let delta = mouse_event.button_state.state() >> 16;
+    (delta.unsigned_abs() / WHEEL_DELTA as u32).max(1) as usize
+}
+
+impl From<KeyEventRecord> for Win32KeyRecord {
+    fn from(record: KeyEventRecord) -> Self {
+        let control_key_state = [
+            RIGHT_ALT_PRESSED,
+            LEFT_ALT_PRESSED,
+            RIGHT_CTRL_PRESSED,
+            LEFT_CTRL_PRESSED,
+            SHIFT_PRESSED,
+        ]
+        .into_iter()
+        .filter(|&flag| record.control_key_state.has_state(flag))
+        .fold(0, |state, flag| state | flag);
+
+        Win32KeyRecord {
+            key_down: record.key_down,
+            repeat_count: record.repeat_count,
+            virtual_key_code: record.virtual_key_code,
+            virtual_scan_code: record.virtual_scan_code,
+            u_char: record.u_char,
+            control_key_state,
         }
     }
-
-    None
 }
 
 impl From<ControlKeyState> for KeyModifiers {
@@ -54,74 +88,6 @@ impl From<ControlKeyState> for KeyModifiers {
     }
 }
 
-fn parse_key_event_record(key_event: &KeyEventRecord) -> Option<KeyEvent> {
-    let modifiers = KeyModifiers::from(key_event.control_key_state);
-
-    let key_code = key_event.virtual_key_code as i32;
-
-    let parse_result = match key_code {
-        VK_SHIFT | VK_CONTROL | VK_MENU => None,
-        VK_BACK => Some(KeyCode::Backspace),
-        VK_ESCAPE => Some(KeyCode::Esc),
-        VK_RETURN => Some(KeyCode::Enter),
-        VK_F1..=VK_F24 => Some(KeyCode::F((key_event.virtual_key_code - 111) as u8)),
-        VK_LEFT => Some(KeyCode::Left),
-        VK_UP => Some(KeyCode::Up),
-        VK_RIGHT => Some(KeyCode::Right),
-        VK_DOWN => Some(KeyCode::Down),
-        VK_PRIOR => Some(KeyCode::PageUp),
-        VK_NEXT => Some(KeyCode::PageDown),
-        VK_HOME => Some(KeyCode::Home),
-        VK_END => Some(KeyCode::End),
-        VK_DELETE => Some(KeyCode::Delete),
-        VK_INSERT => Some(KeyCode::Insert),
-        _ => {
-            // Modifier Keys (Ctrl, Alt, Shift) Support
-            let character_raw = key_event.u_char;
-
-            if character_raw < 255 {
-                // Invalid character
-                if character_raw == 0 {
-                    return None;
-                }
-
-                let mut character = character_raw as u8 as char;
-
-                if modifiers.contains(KeyModifiers::CONTROL)
-                    && !modifiers.contains(KeyModifiers::ALT)
-                {
-                    // we need to do some parsing
-                    // Control character will take the ASCII code produced by the key and bitwise AND
-                    // it with 31, forcing bits 6 and bits 7 to zero.
-                    // So we can make a bitwise OR back to see what's the raw control character.
-                    let c = character_raw as u8;
-                    if c <= b'\x1F' {
-                        character = (c | b'\x40') as char;
-                    } else {
-                        return None;
-                    }
-                }
-
-                if modifiers.contains(KeyModifiers::SHIFT) && character == '\t' {
-                    Some(KeyCode::BackTab)
-                } else if character == '\t' {
-                    Some(KeyCode::Tab)
-                } else {
-                    Some(KeyCode::Char(character))
-                }
-            } else {
-                std::char::from_u32(character_raw as u32).map(KeyCode::Char)
-            }
-        }
-    };
-
-    if let Some(key_code) = parse_result {
-        return Some(KeyEvent::new(key_code, modifiers));
-    }
-
-    None
-}
-
 // The 'y' position of a mouse event or resize event is not relative to the window but absolute to screen buffer.
 // This means that when the mouse cursor is at the top left it will be x: 0, y: 2295 (e.g. y = number of cells conting from the absolute buffer height) instead of relative x: 0, y: 0 to the window.
 pub fn parse_relative_y(y: i16) -> Result<i16> {
@@ -173,10 +139,27 @@ fn parse_mouse_event_record(event: &MouseEvent) -> Result<Option<crate::event::M
             }
         }
         EventFlags::DoubleClick => None, // double click not supported by unix terminals
-        EventFlags::MouseHwheeled => None, // horizontal scroll not supported by unix terminals
+        EventFlags::MouseHwheeled => {
+            // if `button_state` is negative then the wheel was rotated to the left.
+            if button_state.scroll_left() {
+                Some(MouseEventKind::ScrollLeft)
+            } else if button_state.scroll_right() {
+                Some(MouseEventKind::ScrollRight)
+            } else {
+                None
+            }
+        }
         _ => None,
     };
 
+    // The console reports all the motion
+    let kind = kind.filter(|kind| match (kind, mouse_capture_mode()) {
+        (MouseEventKind::Moved, MouseCaptureMode::AllMotion) => true,
+        (MouseEventKind::Moved, _) => false,
+        (MouseEventKind::Drag(_), MouseCaptureMode::Buttons) => false,
+        _ => true,
+    });
+
     Ok(kind.map(|kind| crate::event::MouseEvent {
         kind,
         column: xpos,
@@ -1,7 +1,6 @@
 use std::io;
 use std::time::Duration;
 
-use crossterm_winapi::Handle;
 use winapi::{
     shared::winerror::WAIT_TIMEOUT,
     um::{
@@ -10,7 +9,7 @@ use winapi::{
     },
 };
 
-use crate::Result;
+use crate::{event::wake::wake_semaphore, Result};
 
 #[cfg(feature = "event-stream")]
 pub(crate) use super::waker::Waker;
@@ -38,19 +37,26 @@ impl WinApiPoll {
 impl WinApiPoll {
     pub fn poll(&mut self, timeout: Option<Duration>) -> Result<Option<bool>> {
         let dw_millis = if let Some(duration) = timeout {
-            duration.as_millis() as u32
+            // Round up, the sub-millisecond leftovers would spin until the deadline
+            let millis = duration.as_nanos().div_ceil(1_000_000);
+            millis.min(u128::from(INFINITE - 1)) as u32
         } else {
             INFINITE
         };
 
-        let console_handle = Handle::current_in_handle()?;
+        let console_handle = crate::terminal::sys::input_handle()?;
+        let wake_semaphore = wake_semaphore()?;
 
         #[cfg(feature = "event-stream")]
         let semaphore = self.waker.semaphore();
         #[cfg(feature = "event-stream")]
-        let handles = &[*console_handle, **semaphore.handle()];
+        let handles = &[
+            *console_handle,
+            **wake_semaphore.handle(),
+            **semaphore.handle(),
+        ];
         #[cfg(not(feature = "event-stream"))]
-        let handles = &[*console_handle];
+        let handles = &[*console_handle, **wake_semaphore.handle()];
 
         let output =
             unsafe { WaitForMultipleObjects(handles.len() as u32, handles.as_ptr(), 0, dw_millis) };
@@ -60,23 +66,25 @@ impl WinApiPoll {
                 // input handle triggered
                 Ok(Some(true))
             }
-            #[cfg(feature = "event-stream")]
             output if output == WAIT_OBJECT_0 + 1 => {
+                // user wake semaphore triggered, tokens are queued by the `Waker`
+                Ok(Some(false))
+            }
+            #[cfg(feature = "event-stream")]
+            output if output == WAIT_OBJECT_0 + 2 => {
                 // semaphore handle triggered
                 let _ = self.waker.reset();
                 Err(io::Error::new(
                     io::ErrorKind::Interrupted,
                     "Poll operation was woken up by `Waker::wake`",
-                )
-                .into())
+                ))
             }
             WAIT_TIMEOUT | WAIT_ABANDONED_0 => {
                 // timeout elapsed
                 Ok(None)
             }
             WAIT_FAILED => Err(io::Error::last_os_error()),
-            _ => Err(io::Error::new(
-                io::ErrorKind::Other,
+            _ => Err(io::Error::other(
                 "WaitForMultipleObjects returned unexpected result.",
             )),
         }
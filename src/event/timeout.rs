@@ -1,18 +1,45 @@
 use std::time::{Duration, Instant};
 
-/// Keeps track of the elapsed time since the moment the polling started.
+use super::clock::{Clock, SystemClock};
+
+/// Keeps track of the deadline of the polling.
+///
+/// The deadline is fixed when the polling starts, the retries (interrupted system calls,
+/// incomplete sequences, ...) don't extend it.
 #[derive(Debug, Clone)]
-pub struct PollTimeout {
-    timeout: Option<Duration>,
-    start: Instant,
+pub(crate) struct PollTimeout<C = SystemClock> {
+    deadline: Option<Instant>,
+    clock: C,
 }
 
 impl PollTimeout {
     /// Constructs a new `PollTimeout` with the given optional `Duration`.
     pub fn new(timeout: Option<Duration>) -> PollTimeout {
+        PollTimeout::with_clock(timeout, SystemClock)
+    }
+
+    /// Constructs a new `PollTimeout` elapsing at the given deadline.
+    pub fn until(deadline: Instant) -> PollTimeout {
+        PollTimeout::until_with_clock(deadline, SystemClock)
+    }
+}
+
+impl<C: Clock> PollTimeout<C> {
+    /// Constructs a new `PollTimeout` measuring the time with the given clock.
+    ///
+    /// The timeout too long to be represented never elapses.
+    pub fn with_clock(timeout: Option<Duration>, clock: C) -> PollTimeout<C> {
         PollTimeout {
-            timeout,
-            start: Instant::now(),
+            deadline: timeout.and_then(|timeout| clock.now().checked_add(timeout)),
+            clock,
+        }
+    }
+
+    /// Constructs a new `PollTimeout` elapsing at the given deadline of the given clock.
+    pub fn until_with_clock(deadline: Instant, clock: C) -> PollTimeout<C> {
+        PollTimeout {
+            deadline: Some(deadline),
+            clock,
         }
     }
 
@@ -20,29 +47,23 @@ impl PollTimeout {
     ///
     /// It always returns `false` if the initial timeout was set to `None`.
     pub fn elapsed(&self) -> bool {
-        self.timeout
-            .map(|timeout| self.start.elapsed() >= timeout)
+        self.deadline
+            .map(|deadline| self.clock.now() >= deadline)
             .unwrap_or(false)
     }
 
-    /// Returns the timeout leftover (initial timeout duration - elapsed duration).
+    /// Returns the timeout leftover (the duration until the deadline).
     pub fn leftover(&self) -> Option<Duration> {
-        self.timeout.map(|timeout| {
-            let elapsed = self.start.elapsed();
-
-            if elapsed >= timeout {
-                Duration::from_secs(0)
-            } else {
-                timeout - elapsed
-            }
-        })
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(self.clock.now()))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::{Duration, Instant};
+    use std::time::Duration;
 
+    use super::super::clock::{Clock, MockClock};
     use super::PollTimeout;
 
     #[test]
@@ -53,7 +74,10 @@ mod tests {
 
     #[test]
     pub fn test_timeout_without_duration_never_elapses() {
-        let timeout = PollTimeout::new(None);
+        let clock = MockClock::new();
+        let timeout = PollTimeout::with_clock(None, clock.clone());
+
+        clock.advance(Duration::from_secs(3600));
         assert!(!timeout.elapsed());
     }
 
@@ -61,11 +85,13 @@ mod tests {
     pub fn test_timeout_elapses() {
         const TIMEOUT_MILLIS: u64 = 100;
 
-        let timeout = PollTimeout {
-            timeout: Some(Duration::from_millis(TIMEOUT_MILLIS)),
-            start: Instant::now() - Duration::from_millis(2 * TIMEOUT_MILLIS),
-        };
+        let clock = MockClock::new();
+        let timeout =
+            PollTimeout::with_clock(Some(Duration::from_millis(TIMEOUT_MILLIS)), clock.clone());
 
+        clock.advance(Duration::from_millis(TIMEOUT_MILLIS - 1));
+        assert!(!timeout.elapsed());
+        clock.advance(Duration::from_millis(1));
         assert!(timeout.elapsed());
     }
 
@@ -73,20 +99,46 @@ mod tests {
     pub fn test_elapsed_timeout_has_zero_leftover() {
         const TIMEOUT_MILLIS: u64 = 100;
 
-        let timeout = PollTimeout {
-            timeout: Some(Duration::from_millis(TIMEOUT_MILLIS)),
-            start: Instant::now() - Duration::from_millis(2 * TIMEOUT_MILLIS),
-        };
+        let clock = MockClock::new();
+        let timeout =
+            PollTimeout::with_clock(Some(Duration::from_millis(TIMEOUT_MILLIS)), clock.clone());
 
+        clock.advance(Duration::from_millis(2 * TIMEOUT_MILLIS));
         assert!(timeout.elapsed());
         assert_eq!(timeout.leftover(), Some(Duration::from_millis(0)));
     }
 
     #[test]
     pub fn test_not_elapsed_timeout_has_positive_leftover() {
-        let timeout = PollTimeout::new(Some(Duration::from_secs(60)));
+        let clock = MockClock::new();
+        let timeout = PollTimeout::with_clock(Some(Duration::from_millis(100)), clock.clone());
 
+        clock.advance(Duration::from_millis(60));
         assert!(!timeout.elapsed());
-        assert!(timeout.leftover().unwrap() > Duration::from_secs(0));
+        assert_eq!(timeout.leftover(), Some(Duration::from_millis(40)));
+    }
+
+    #[test]
+    pub fn test_timeout_elapses_at_deadline() {
+        let clock = MockClock::new();
+        let deadline = clock.now() + Duration::from_millis(100);
+        clock.advance(Duration::from_millis(30));
+
+        let timeout = PollTimeout::until_with_clock(deadline, clock.clone());
+        assert_eq!(timeout.leftover(), Some(Duration::from_millis(70)));
+
+        clock.advance(Duration::from_millis(70));
+        assert!(timeout.elapsed());
+    }
+
+    #[test]
+    pub fn test_past_deadline_has_zero_leftover() {
+        let clock = MockClock::new();
+        let deadline = clock.now();
+        clock.advance(Duration::from_millis(10));
+
+        let timeout = PollTimeout::until_with_clock(deadline, clock);
+        assert!(timeout.elapsed());
+        assert_eq!(timeout.leftover(), Some(Duration::from_millis(0)));
     }
 }
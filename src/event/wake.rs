@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(windows)]
+use crossterm_winapi::Semaphore;
+use parking_lot::Mutex;
+
+use crate::Result;
+
+use super::{Event, Token};
+
+/// Events passed to `inject` (and the `Waker::wake` tokens) which weren't delivered yet.
+static INJECTED_EVENTS: Mutex<VecDeque<Event>> = parking_lot::const_mutex(VecDeque::new());
+
+/// Set by `interrupt_source`, cleared by the event source returning because of it.
+static SOURCE_INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Writing end of the socket pair registered in the event source, `None` if there's
+/// no event source yet.
+#[cfg(unix)]
+static WAKE_STREAM: Mutex<Option<std::os::unix::net::UnixStream>> = parking_lot::const_mutex(None);
+
+/// Semaphore the event source waits for along with the console input handle.
+#[cfg(windows)]
+static WAKE_SEMAPHORE: Mutex<Option<Semaphore>> = parking_lot::const_mutex(None);
+
+/// A handle allowing to wake up the blocked [read](./fn.read.html)/[poll](./fn.poll.html)
+/// from another thread.
+///
+/// The woken up function yields an [`Event::Wake`](enum.Event.html#variant.Wake) with the
+/// given token. It can be used to ask the thread handling the events to redraw the screen,
+/// to let it know that a background job is done, ...
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::thread;
+///
+/// use crossterm::event::{read, Event, Token, Waker};
+///
+/// const REDRAW: Token = Token(0);
+///
+/// fn main() -> crossterm::Result<()> {
+///     let waker = Waker::new();
+///     thread::spawn(move || waker.wake(REDRAW));
+///
+///     if let Event::Wake(REDRAW) = read()? {
+///         // Redraw the screen
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Waker {
+    _private: (),
+}
+
+impl Waker {
+    /// Creates a new `Waker`.
+    pub fn new() -> Waker {
+        Waker { _private: () }
+    }
+
+    /// Wakes up the blocked [read](./fn.read.html)/[poll](./fn.poll.html), which yields
+    /// an [`Event::Wake`](enum.Event.html#variant.Wake) with the given token.
+    ///
+    /// The token is delivered by the next `read` if there's no blocked function.
+    pub fn wake(&self, token: Token) -> Result<()> {
+        inject(Event::Wake(token))
+    }
+}
+
+/// Pushes the event into the queue drained by the [read](./fn.read.html)/
+/// [poll](./fn.poll.html), the blocked function is woken up.
+///
+/// The injected events are delivered in order, before the terminal events which weren't
+/// read from the terminal yet. They're delivered as they are, e.g. the
+/// [key normalization](fn.set_key_normalization.html) isn't applied to them. It allows to
+/// drive the application in the integration tests and the scripted demos, or to deliver
+/// the events of other input devices.
+///
+/// The event source must be available, i.e. the terminal or the
+/// [custom event source](fn.set_event_source.html) (e.g. a source never yielding any
+/// events for the tests without a terminal). The injected events are delivered without
+/// one, but the `read` fails once they're drained.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::event::{self, Event, KeyCode};
+///
+/// fn main() -> crossterm::Result<()> {
+///     event::inject(Event::Key(KeyCode::Char('q').into()))?;
+///
+///     assert_eq!(event::read()?, Event::Key(KeyCode::Char('q').into()));
+///     Ok(())
+/// }
+/// ```
+pub fn inject(event: Event) -> Result<()> {
+    let mut events = INJECTED_EVENTS.lock();
+    events.push_back(event);
+
+    notify_source()
+}
+
+/// Makes the blocked event source return an `Interrupted` error, even without the
+/// `event-stream` feature.
+pub(crate) fn interrupt_source() -> Result<()> {
+    SOURCE_INTERRUPTED.store(true, Ordering::SeqCst);
+    notify_source()
+}
+
+/// Returns whether the event source was interrupted with `interrupt_source`, the request
+/// is cleared.
+pub(crate) fn take_source_interrupt() -> bool {
+    SOURCE_INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+/// Wakes up the event source waiting for the input.
+fn notify_source() -> Result<()> {
+    #[cfg(unix)]
+    if let Some(stream) = WAKE_STREAM.lock().as_ref() {
+        use std::io::{self, Write};
+
+        // The socket buffer is full, the event source is going to be woken up anyway
+        match (&*stream).write(&[0]) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            result => {
+                result?;
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        // Can't be released more than once, the event source is going to be woken up anyway
+        let _ = wake_semaphore()?.release();
+    }
+
+    Ok(())
+}
+
+/// Returns the next event passed to the `inject` (or the `Waker::wake` token).
+pub(crate) fn next_injected_event() -> Option<Event> {
+    INJECTED_EVENTS.lock().pop_front()
+}
+
+/// Creates the socket pair used to wake up the event source. The reading end must be
+/// registered by the event source.
+#[cfg(unix)]
+pub(crate) fn register_wake_stream() -> Result<std::os::unix::net::UnixStream> {
+    let (read, write) = std::os::unix::net::UnixStream::pair()?;
+    read.set_nonblocking(true)?;
+    write.set_nonblocking(true)?;
+
+    *WAKE_STREAM.lock() = Some(write);
+    Ok(read)
+}
+
+/// Drops the writing end of the socket pair created with `register_wake_stream`.
+#[cfg(unix)]
+pub(crate) fn unregister_wake_stream() {
+    *WAKE_STREAM.lock() = None;
+}
+
+/// Returns the semaphore released by the `Waker::wake`.
+#[cfg(windows)]
+pub(crate) fn wake_semaphore() -> Result<Semaphore> {
+    let mut semaphore = WAKE_SEMAPHORE.lock();
+
+    if semaphore.is_none() {
+        *semaphore = Some(Semaphore::new()?);
+    }
+
+    Ok(semaphore.as_ref().unwrap().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::KeyCode;
+
+    use super::{inject, next_injected_event, Event, Token, Waker};
+
+    #[test]
+    fn test_injected_events_are_delivered_in_order() {
+        // The injected events are shared with the tests reading a custom source
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let waker = Waker::new();
+        waker.wake(Token(1)).unwrap();
+        inject(Event::Key(KeyCode::Enter.into())).unwrap();
+        waker.clone().wake(Token(2)).unwrap();
+
+        assert_eq!(next_injected_event(), Some(Event::Wake(Token(1))));
+        assert_eq!(
+            next_injected_event(),
+            Some(Event::Key(KeyCode::Enter.into()))
+        );
+        assert_eq!(next_injected_event(), Some(Event::Wake(Token(2))));
+        assert_eq!(next_injected_event(), None);
+    }
+}
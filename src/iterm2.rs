@@ -0,0 +1,336 @@
+//! # iTerm2
+//!
+//! The `iterm2` module provides the commands for the proprietary `OSC 1337` sequences of
+//! the iTerm2:
+//!
+//! * the user variables, which can be shown in the status bar and in the titles,
+//! * the badge, the text shown in the top right corner of the session,
+//! * the attention requests, bouncing the dock icon,
+//! * the Touch Bar labels of the function keys.
+//!
+//! The commands require the
+//! [ITerm2Extensions](../capabilities/enum.Capability.html#variant.ITerm2Extensions)
+//! capability, detected by the `TERM_PROGRAM` and the `LC_TERMINAL` environment variables.
+//! The other terminals ignore them or print them as text, check the capability first.
+//! The user variables are supported by the WezTerm as well, they require the
+//! [UserVars](../capabilities/enum.Capability.html#variant.UserVars) capability.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::{stdout, Write};
+//!
+//! use crossterm::{
+//!     capabilities, execute,
+//!     iterm2::{Attention, RequestAttention, SetBadge},
+//!     Result,
+//! };
+//!
+//! fn main() -> Result<()> {
+//!     if capabilities::capabilities().iterm2_extensions() {
+//!         execute!(
+//!             stdout(),
+//!             SetBadge("build #42"),
+//!             RequestAttention(Attention::Once)
+//!         )?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::capabilities::Capability;
+#[cfg(windows)]
+use crate::Result;
+use crate::{impl_display, Command};
+
+/// A command that sets a user variable of the session, `OSC 1337;SetUserVar`.
+///
+/// The variable is accessible as `user.<name>` in the interpolated strings of the iTerm2
+/// (status bar, titles, badge). The WezTerm emits the `user-var-changed` event.
+///
+/// # Notes
+///
+/// * Requires the [UserVars](../capabilities/enum.Capability.html#variant.UserVars)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetUserVar<'a> {
+    /// The name of the variable.
+    pub name: &'a str,
+    /// The value of the variable.
+    pub value: &'a str,
+}
+
+impl Command for SetUserVar<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1B]1337;SetUserVar={}=", self.name)?;
+        write_base64(f, self.value.as_bytes())?;
+        f.write_str("\x1B\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::UserVars)
+    }
+}
+
+/// A command that sets the badge of the session, `OSC 1337;SetBadgeFormat`.
+///
+/// The text may contain the interpolated expressions, e.g. `\(user.branch)`. An empty
+/// text removes the badge.
+///
+/// # Notes
+///
+/// * Requires the [ITerm2Extensions](../capabilities/enum.Capability.html#variant.ITerm2Extensions)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetBadge<'a>(pub &'a str);
+
+impl Command for SetBadge<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B]1337;SetBadgeFormat=")?;
+        write_base64(f, self.0.as_bytes())?;
+        f.write_str("\x1B\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::ITerm2Extensions)
+    }
+}
+
+/// The kinds of the attention requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attention {
+    /// Bounces the dock icon until the iTerm2 is activated.
+    Start,
+    /// Bounces the dock icon once.
+    Once,
+    /// Cancels the previous request.
+    Stop,
+    /// Shows the fireworks at the cursor position.
+    Fireworks,
+}
+
+/// A command that requests the attention of the user, `OSC 1337;RequestAttention`.
+///
+/// # Notes
+///
+/// * Requires the [ITerm2Extensions](../capabilities/enum.Capability.html#variant.ITerm2Extensions)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestAttention(pub Attention);
+
+impl Command for RequestAttention {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let value = match self.0 {
+            Attention::Start => "yes",
+            Attention::Once => "once",
+            Attention::Stop => "no",
+            Attention::Fireworks => "fireworks",
+        };
+        write!(f, "\x1B]1337;RequestAttention={}\x1B\\", value)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::ITerm2Extensions)
+    }
+}
+
+/// A command that sets the Touch Bar label of a function key, `OSC 1337;SetKeyLabel`.
+///
+/// The keys are named `F1` to `F24`, the `status` key is the label of the status button
+/// of the Touch Bar.
+///
+/// # Notes
+///
+/// * Requires the [ITerm2Extensions](../capabilities/enum.Capability.html#variant.ITerm2Extensions)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetKeyLabel<'a> {
+    /// The name of the key.
+    pub key: &'a str,
+    /// The label shown in the Touch Bar.
+    pub label: &'a str,
+}
+
+impl Command for SetKeyLabel<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1B]1337;SetKeyLabel={}={}\x1B\\", self.key, self.label)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::ITerm2Extensions)
+    }
+}
+
+/// A command that saves the Touch Bar labels, `OSC 1337;PushKeyLabels`.
+///
+/// The labels set afterwards are discarded by the [PopKeyLabels](struct.PopKeyLabels.html)
+/// with the same name, the nested pushes are discarded with it.
+///
+/// # Notes
+///
+/// * Requires the [ITerm2Extensions](../capabilities/enum.Capability.html#variant.ITerm2Extensions)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PushKeyLabels<'a>(pub Option<&'a str>);
+
+impl Command for PushKeyLabels<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self.0 {
+            Some(name) => write!(f, "\x1B]1337;PushKeyLabels={}\x1B\\", name),
+            None => f.write_str("\x1B]1337;PushKeyLabels\x1B\\"),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::ITerm2Extensions)
+    }
+}
+
+/// A command that restores the Touch Bar labels saved with the
+/// [PushKeyLabels](struct.PushKeyLabels.html), `OSC 1337;PopKeyLabels`.
+///
+/// # Notes
+///
+/// * Requires the [ITerm2Extensions](../capabilities/enum.Capability.html#variant.ITerm2Extensions)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PopKeyLabels<'a>(pub Option<&'a str>);
+
+impl Command for PopKeyLabels<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self.0 {
+            Some(name) => write!(f, "\x1B]1337;PopKeyLabels={}\x1B\\", name),
+            None => f.write_str("\x1B]1337;PopKeyLabels\x1B\\"),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::ITerm2Extensions)
+    }
+}
+
+impl_display!(for SetUserVar<'_>);
+impl_display!(for SetBadge<'_>);
+impl_display!(for RequestAttention);
+impl_display!(for SetKeyLabel<'_>);
+impl_display!(for PushKeyLabels<'_>);
+impl_display!(for PopKeyLabels<'_>);
+
+/// Writes the bytes encoded in the standard base64 (with the padding).
+fn write_base64(f: &mut impl fmt::Write, bytes: &[u8]) -> fmt::Result {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3F;
+                f.write_char(char::from(ALPHABET[index as usize]))?;
+            } else {
+                f.write_char('=')?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Command;
+
+    use super::{
+        write_base64, Attention, PopKeyLabels, PushKeyLabels, RequestAttention, SetBadge,
+        SetKeyLabel, SetUserVar,
+    };
+
+    fn ansi(command: impl Command) -> String {
+        let mut result = String::new();
+        command.write_ansi(&mut result).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_base64() {
+        let encode = |bytes: &[u8]| {
+            let mut result = String::new();
+            write_base64(&mut result, bytes).unwrap();
+            result
+        };
+
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "Zg==");
+        assert_eq!(encode(b"fo"), "Zm8=");
+        assert_eq!(encode(b"foo"), "Zm9v");
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode(&[0xFF, 0xFE]), "//4=");
+    }
+
+    #[test]
+    fn test_sequences() {
+        assert_eq!(
+            ansi(SetUserVar {
+                name: "branch",
+                value: "main"
+            }),
+            "\x1B]1337;SetUserVar=branch=bWFpbg==\x1B\\"
+        );
+        assert_eq!(ansi(SetBadge("foo")), "\x1B]1337;SetBadgeFormat=Zm9v\x1B\\");
+        assert_eq!(
+            ansi(RequestAttention(Attention::Stop)),
+            "\x1B]1337;RequestAttention=no\x1B\\"
+        );
+        assert_eq!(
+            ansi(SetKeyLabel {
+                key: "F1",
+                label: "Help"
+            }),
+            "\x1B]1337;SetKeyLabel=F1=Help\x1B\\"
+        );
+        assert_eq!(
+            ansi(PushKeyLabels(Some("editor"))),
+            "\x1B]1337;PushKeyLabels=editor\x1B\\"
+        );
+        assert_eq!(ansi(PopKeyLabels(None)), "\x1B]1337;PopKeyLabels\x1B\\");
+    }
+}
@@ -48,23 +48,40 @@
 //!     [`MoveLeft`](cursor/struct.MoveLeft.html), [`MoveRight`](cursor/struct.MoveRight.html),
 //!     [`MoveTo`](cursor/struct.MoveTo.html), [`MoveToColumn`](cursor/struct.MoveToColumn.html),[`MoveToRow`](cursor/struct.MoveToRow.html),
 //!     [`MoveToNextLine`](cursor/struct.MoveToNextLine.html), [`MoveToPreviousLine`](cursor/struct.MoveToPreviousLine.html),
+//!     [`SmartMoveTo`](cursor/struct.SmartMoveTo.html),
+//!   - Clamped movement -
+//!     [`MoveToClamped`](cursor/struct.MoveToClamped.html), [`MoveUpClamped`](cursor/struct.MoveUpClamped.html),
+//!     [`MoveDownClamped`](cursor/struct.MoveDownClamped.html), [`MoveLeftClamped`](cursor/struct.MoveLeftClamped.html),
+//!     [`MoveRightClamped`](cursor/struct.MoveRightClamped.html)
 //!    - Shape -
 //!      [`SetCursorShape`](cursor/struct.SetCursorShape.html)
 //! - Module [`event`](event/index.html)
 //!   - Mouse events - [`EnableMouseCapture`](event/struct.EnableMouseCapture.html),
 //!     [`DisableMouseCapture`](event/struct.DisableMouseCapture.html)
+//!   - Keyboard enhancement - [`PushKeyboardEnhancementFlags`](event/struct.PushKeyboardEnhancementFlags.html),
+//!     [`PopKeyboardEnhancementFlags`](event/struct.PopKeyboardEnhancementFlags.html),
+//!     [`PopAllKeyboardEnhancementFlags`](event/struct.PopAllKeyboardEnhancementFlags.html),
+//!     [`EnableModifyOtherKeys`](event/struct.EnableModifyOtherKeys.html),
+//!     [`DisableModifyOtherKeys`](event/struct.DisableModifyOtherKeys.html),
+//!     [`EnableWin32InputMode`](event/struct.EnableWin32InputMode.html),
+//!     [`DisableWin32InputMode`](event/struct.DisableWin32InputMode.html)
 //! - Module [`style`](style/index.html)
 //!   - Colors - [`SetForegroundColor`](style/struct.SetForegroundColor.html),
 //!     [`SetBackgroundColor`](style/struct.SetBackgroundColor.html),
 //!     [`ResetColor`](style/struct.ResetColor.html), [`SetColors`](style/struct.SetColors.html)
 //!   - Attributes - [`SetAttribute`](style/struct.SetAttribute.html), [`SetAttributes`](style/struct.SetAttributes.html),
 //!     [`PrintStyledContent`](style/struct.PrintStyledContent.html)
+//!   - Printing - [`Print`](style/struct.Print.html), [`PrintNoWrap`](style/struct.PrintNoWrap.html)
+//!   - Hyperlinks - [`SetHyperlink`](style/struct.SetHyperlink.html),
+//!     [`ResetHyperlink`](style/struct.ResetHyperlink.html)
 //! - Module [`terminal`](terminal/index.html)
 //!   - Scrolling - [`ScrollUp`](terminal/struct.ScrollUp.html),
 //!     [`ScrollDown`](terminal/struct.ScrollDown.html)
 //!   - Miscellaneous - [`Clear`](terminal/struct.Clear.html),
 //!     [`SetSize`](terminal/struct.SetSize.html)
-//!     [`SetTitle`](terminal/struct.SetTitle.html)
+//!     [`SetTitle`](terminal/struct.SetTitle.html),
+//!     [`SetWindowTitle`](terminal/struct.SetWindowTitle.html),
+//!     [`SetIconName`](terminal/struct.SetIconName.html),
 //!     [`DisableLineWrap`](terminal/struct.DisableLineWrap.html)
 //!     [`EnableLineWrap`](terminal/struct.EnableLineWrap.html)
 //!   - Alternate screen - [`EnterAlternateScreen`](terminal/struct.EnterAlternateScreen.html),
@@ -167,6 +184,23 @@
 //! `execute!(stdout, MoveTo(5, 5), Clear(ClearType::All))` and they will be executed in the given order from
 //! left to right.
 //!
+//! #### Rendering
+//!
+//! The [render](./macro.render.html) macro writes the commands into a [std::fmt::Write][fmt_write]
+//! target (e.g. a `String`), so the escape sequences can be rendered once and reused.
+//!
+//! ```
+//! use crossterm::{render, cursor, terminal};
+//!
+//! let mut clear = String::new();
+//! render!(clear, cursor::MoveTo(0, 0), terminal::Clear(terminal::ClearType::All)).unwrap();
+//! ```
+//!
+//! #### Dry Run
+//!
+//! The [dry run](./fn.set_dry_run.html) records the queued and executed commands instead of
+//! writing them, e.g. to compare the rendering with a golden file in the tests.
+//!
 //! ## Examples
 //!
 //! Print a rectangle colored with magenta and use both direct execution and lazy execution.
@@ -231,23 +265,64 @@
 //! [stdout]: https://doc.rust-lang.org/std/io/fn.stdout.html
 //! [stderr]: https://doc.rust-lang.org/std/io/fn.stderr.html
 //! [flush]: https://doc.rust-lang.org/std/io/trait.Write.html#tymethod.flush
+//! [fmt_write]: https://doc.rust-lang.org/std/fmt/trait.Write.html
 
 pub use crate::{
-    command::{Command, ExecutableCommand, QueueableCommand},
-    error::{ErrorKind, Result},
+    command::{
+        is_dry_run, set_dry_run, take_recorded_commands, Command, ExecutableCommand,
+        QueueableCommand, RecordedCommand,
+    },
+    error::{ErrorKind, NotATty, Result},
 };
 
+/// A module to announce the state changes to the assistive technologies.
+#[cfg(feature = "terminal")]
+pub mod accessibility;
+/// A module to render frames of styled cells.
+#[cfg(all(feature = "style", feature = "cursor"))]
+pub mod buffer;
+/// A module to detect and override the features supported by the terminal.
+pub mod capabilities;
 /// A module to work with the terminal cursor
+#[cfg(feature = "cursor")]
 pub mod cursor;
+// The cursor position is tracked by the printing and terminal commands
+#[cfg(all(feature = "terminal", not(feature = "cursor")))]
+#[allow(dead_code)]
+mod cursor {
+    pub(crate) mod tracking;
+}
+/// A module to draw the lines and the borders.
+#[cfg(feature = "cursor")]
+pub mod draw;
 /// A module to read events.
+#[cfg(feature = "events")]
 pub mod event;
+/// A module to use the iTerm2 proprietary sequences.
+#[cfg(feature = "iterm2")]
+pub mod iterm2;
+/// A module to log the escape sequences written to the terminal.
+#[cfg(feature = "log")]
+pub mod logging;
+/// A module to look up known bugs of the terminals.
+pub mod quirks;
 /// A module to apply attributes and colors on your text.
+#[cfg(feature = "style")]
 pub mod style;
 /// A module to work with the terminal.
+#[cfg(feature = "terminal")]
 pub mod terminal;
+/// A module to measure the text displayed in the terminal.
+pub mod text;
 
 /// A module to query if the current instance is a tty.
 pub mod tty;
+/// A module to integrate with the WezTerm panes.
+#[cfg(feature = "wezterm")]
+pub mod wezterm;
+/// A module to use the extensions of the Windows Terminal and the ConEmu.
+#[cfg(feature = "terminal")]
+pub mod windows_terminal;
 
 #[cfg(windows)]
 /// A module that exposes one function to check if the current terminal supports ANSI sequences.
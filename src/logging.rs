@@ -0,0 +1,357 @@
+//! # Logging
+//!
+//! The `logging` module provides the [LoggingWriter](struct.LoggingWriter.html), which
+//! logs the escape sequences written to the terminal with the [log] crate. Each sequence
+//! is logged with its symbolic decoding, e.g. `CSI 2 J = Clear All`, it helps to debug
+//! the rendering on the unusual terminals.
+//!
+//! The records can be forwarded to the `tracing` subscribers with the `tracing-log` crate.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::{stdout, Write};
+//!
+//! use crossterm::{
+//!     execute,
+//!     logging::LoggingWriter,
+//!     terminal::{Clear, ClearType},
+//!     Result,
+//! };
+//!
+//! fn main() -> Result<()> {
+//!     // The logger is initialized by the application
+//!     let mut stdout = LoggingWriter::new(stdout());
+//!
+//!     // Logs `CSI 2 J = Clear All` at the debug level
+//!     execute!(stdout, Clear(ClearType::All))
+//! }
+//! ```
+//!
+//! [log]: https://docs.rs/log
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::capabilities::is_sequence_complete;
+
+/// The default target of the log records.
+const DEFAULT_TARGET: &str = "crossterm::sequences";
+
+/// A writer logging the escape sequences written to it.
+///
+/// The bytes are passed to the underlying writer untouched. The escape sequences are
+/// logged at the debug level once they're flushed, the text is not logged.
+#[derive(Debug)]
+pub struct LoggingWriter<W: Write> {
+    inner: W,
+    target: &'static str,
+    /// The escape sequence split between the writes.
+    sequence: Vec<u8>,
+    /// The complete escape sequences written since the last flush.
+    written: Vec<Vec<u8>>,
+}
+
+impl<W: Write> LoggingWriter<W> {
+    /// Creates a new `LoggingWriter` logging to the `crossterm::sequences` target.
+    pub fn new(inner: W) -> LoggingWriter<W> {
+        LoggingWriter {
+            inner,
+            target: DEFAULT_TARGET,
+            sequence: Vec::new(),
+            written: Vec::new(),
+        }
+    }
+
+    /// Sets the target of the log records.
+    pub fn with_target(mut self, target: &'static str) -> LoggingWriter<W> {
+        self.target = target;
+        self
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// The sequences written directly to the underlying writer aren't logged.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps this `LoggingWriter`, returning the underlying writer.
+    ///
+    /// The sequences which weren't flushed aren't logged.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for LoggingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+
+        for &byte in &buf[..written] {
+            if self.sequence.is_empty() && byte != b'\x1B' {
+                continue;
+            }
+
+            self.sequence.push(byte);
+            if is_sequence_complete(&self.sequence) {
+                self.written.push(std::mem::take(&mut self.sequence));
+            }
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if log::log_enabled!(target: self.target, log::Level::Debug) {
+            for sequence in &self.written {
+                log::debug!(target: self.target, "{}", decode(sequence));
+            }
+        }
+        self.written.clear();
+
+        self.inner.flush()
+    }
+}
+
+/// Returns the symbolic form of the escape sequence followed by its meaning.
+fn decode(sequence: &[u8]) -> String {
+    let sequence = String::from_utf8_lossy(sequence);
+    let body = &sequence[1..];
+
+    let (symbolic, meaning) = match body.chars().next() {
+        Some('[') => decode_csi(&body[1..]),
+        Some(']') => decode_osc(&body[1..]),
+        Some('P') => (
+            format!("DCS {}", string_body(&body[1..])),
+            "Device Control String",
+        ),
+        Some('_') => (
+            format!("APC {}", string_body(&body[1..])),
+            "Application Program Command",
+        ),
+        Some('7') => ("ESC 7".to_string(), "Save Cursor Position"),
+        Some('8') => ("ESC 8".to_string(), "Restore Cursor Position"),
+        Some('c') => ("ESC c".to_string(), "Full Reset"),
+        _ => (format!("ESC {}", escape(body)), "Unknown"),
+    };
+
+    format!("{} = {}", symbolic, meaning)
+}
+
+/// Decodes the Control Sequence Introducer sequences (without the `ESC [`).
+fn decode_csi(body: &str) -> (String, &'static str) {
+    let final_byte = body.chars().last().unwrap_or_default();
+    let prefix = body.strip_suffix(final_byte).unwrap_or(body);
+
+    // The private marker and the intermediate bytes select the variant of the sequence
+    let marker_len = prefix.len() - prefix.trim_start_matches(['<', '=', '>', '?']).len();
+    let (marker, rest) = prefix.split_at(marker_len);
+    let params_len = rest.trim_end_matches(|c| (' '..='/').contains(&c)).len();
+    let (params, intermediate) = rest.split_at(params_len);
+    let private = marker == "?";
+
+    let mut symbolic = String::from("CSI");
+    for part in [marker, params, &intermediate.replace(' ', "SP")] {
+        if !part.is_empty() {
+            let _ = write!(symbolic, " {}", escape(part));
+        }
+    }
+    let _ = write!(symbolic, " {}", final_byte.escape_debug());
+
+    let meaning = match (private, intermediate, final_byte) {
+        (true, "", 'h') => dec_mode(params, true),
+        (true, "", 'l') => dec_mode(params, false),
+        (true, "$", 'p') => "Request DEC Private Mode",
+        (false, "", 'A') => "Cursor Up",
+        (false, "", 'B') => "Cursor Down",
+        (false, "", 'C') => "Cursor Forward",
+        (false, "", 'D') => "Cursor Back",
+        (false, "", 'E') => "Cursor Next Line",
+        (false, "", 'F') => "Cursor Previous Line",
+        (false, "", 'G') => "Cursor Horizontal Absolute",
+        (false, "", 'H' | 'f') => "Cursor Position",
+        (false, "", 'J') => match params {
+            "" | "0" => "Clear From Cursor Down",
+            "1" => "Clear From Cursor Up",
+            "2" => "Clear All",
+            "3" => "Clear Purge",
+            _ => "Erase In Display",
+        },
+        (false, "", 'K') => match params {
+            "" | "0" => "Clear Until New Line",
+            "1" => "Clear From Line Start",
+            "2" => "Clear Current Line",
+            _ => "Erase In Line",
+        },
+        (false, "", 'L') => "Insert Lines",
+        (false, "", 'M') => "Delete Lines",
+        (false, "", 'S') => "Scroll Up",
+        (false, "", 'T') => "Scroll Down",
+        (false, "", 'm') if params.is_empty() || params == "0" => "Reset Graphic Rendition",
+        (false, "", 'm') => "Select Graphic Rendition",
+        (false, "", 'n') => "Device Status Report",
+        (false, "", 'r') => "Set Top Bottom Margins",
+        (false, "", 's') if params.is_empty() => "Save Cursor Position",
+        (false, "", 's') => "Set Left Right Margins",
+        (false, "", 'u') => "Restore Cursor Position",
+        (false, "", 't') => "Window Manipulation",
+        (false, " ", 'q') => "Set Cursor Style",
+        (false, " ", 't') => "Set Warning Bell Volume",
+        (false, " ", 'u') => "Set Margin Bell Volume",
+        _ => "Unknown",
+    };
+
+    (symbolic, meaning)
+}
+
+/// Returns the meaning of setting/resetting the DEC private mode(s).
+fn dec_mode(params: &str, set: bool) -> &'static str {
+    match (params, set) {
+        ("25", true) => "Show Cursor",
+        ("25", false) => "Hide Cursor",
+        ("12", true) => "Enable Cursor Blinking",
+        ("12", false) => "Disable Cursor Blinking",
+        ("7", true) => "Enable Line Wrap",
+        ("7", false) => "Disable Line Wrap",
+        ("69", true) => "Enable Left Right Margins",
+        ("69", false) => "Disable Left Right Margins",
+        ("1049", true) => "Enter Alternate Screen",
+        ("1049", false) => "Leave Alternate Screen",
+        ("1004", true) => "Enable Focus Change",
+        ("1004", false) => "Disable Focus Change",
+        ("2004", true) => "Enable Bracketed Paste",
+        ("2004", false) => "Disable Bracketed Paste",
+        (params, true) if is_mouse_mode(params) => "Enable Mouse Capture",
+        (params, false) if is_mouse_mode(params) => "Disable Mouse Capture",
+        (_, true) => "Set DEC Private Mode",
+        (_, false) => "Reset DEC Private Mode",
+    }
+}
+
+fn is_mouse_mode(params: &str) -> bool {
+    params.split(';').all(|mode| {
+        matches!(
+            mode,
+            "1000" | "1002" | "1003" | "1005" | "1006" | "1015" | "1016"
+        )
+    })
+}
+
+/// Decodes the Operating System Command sequences (without the `ESC ]`).
+fn decode_osc(body: &str) -> (String, &'static str) {
+    let meaning = match body.split(';').next() {
+        Some("0") => "Set Title",
+        Some("1") => "Set Icon Name",
+        Some("2") => "Set Window Title",
+        Some("4") => "Change Color Palette",
+        Some("7") => "Set Current Directory",
+        Some("8") => "Hyperlink",
+        Some("9") => "ConEmu Extension",
+        Some("52") => "Clipboard",
+        Some("1337") => "iTerm2 Extension",
+        _ => "Operating System Command",
+    };
+
+    (format!("OSC {}", string_body(body)), meaning)
+}
+
+/// Replaces the string terminator (`BEL` or `ST`) with its name.
+fn string_body(body: &str) -> String {
+    if let Some(body) = body.strip_suffix('\x07') {
+        format!("{} BEL", escape(body))
+    } else if let Some(body) = body.strip_suffix("\x1B\\") {
+        format!("{} ST", escape(body))
+    } else {
+        escape(body)
+    }
+}
+
+/// Escapes the control characters.
+fn escape(text: &str) -> String {
+    text.escape_debug().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use log::{Log, Metadata, Record};
+    use parking_lot::Mutex;
+
+    use super::{decode, LoggingWriter};
+
+    /// Collects the records of the `crossterm::tests` target.
+    struct TestLogger(Mutex<Vec<String>>);
+
+    impl Log for TestLogger {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target() == "crossterm::tests"
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: TestLogger = TestLogger(parking_lot::const_mutex(Vec::new()));
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"\x1B[2J"), "CSI 2 J = Clear All");
+        assert_eq!(decode(b"\x1B[K"), "CSI K = Clear Until New Line");
+        assert_eq!(decode(b"\x1B[3;7H"), "CSI 3;7 H = Cursor Position");
+        assert_eq!(decode(b"\x1B[?25l"), "CSI ? 25 l = Hide Cursor");
+        assert_eq!(
+            decode(b"\x1B[?1000;1006h"),
+            "CSI ? 1000;1006 h = Enable Mouse Capture"
+        );
+        assert_eq!(
+            decode(b"\x1B[?7$p"),
+            "CSI ? 7 $ p = Request DEC Private Mode"
+        );
+        assert_eq!(decode(b"\x1B[2 q"), "CSI 2 SP q = Set Cursor Style");
+        assert_eq!(decode(b"\x1B[0m"), "CSI 0 m = Reset Graphic Rendition");
+        assert_eq!(
+            decode(b"\x1B[38;5;9m"),
+            "CSI 38;5;9 m = Select Graphic Rendition"
+        );
+        assert_eq!(
+            decode(b"\x1B]2;title\x07"),
+            "OSC 2;title BEL = Set Window Title"
+        );
+        assert_eq!(decode(b"\x1B]8;;\x1B\\"), "OSC 8;; ST = Hyperlink");
+        assert_eq!(decode(b"\x1B7"), "ESC 7 = Save Cursor Position");
+        assert_eq!(decode(b"\x1B[5~"), "CSI 5 ~ = Unknown");
+    }
+
+    #[test]
+    fn test_sequences_are_logged_on_flush() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let mut writer = LoggingWriter::new(Vec::new()).with_target("crossterm::tests");
+        writer.write_all(b"text\x1B[2J\x1B[?2").unwrap();
+        writer.write_all(b"5h more text").unwrap();
+        assert!(LOGGER.0.lock().is_empty());
+
+        writer.flush().unwrap();
+        assert_eq!(
+            *LOGGER.0.lock(),
+            vec!["CSI 2 J = Clear All", "CSI ? 25 h = Show Cursor"]
+        );
+        assert_eq!(
+            writer.into_inner(),
+            b"text\x1B[2J\x1B[?25h more text".to_vec()
+        );
+    }
+}
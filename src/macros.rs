@@ -101,23 +101,70 @@ macro_rules! queue {
 ///
 /// * In the case of UNIX and Windows 10, ANSI codes are written to the given 'writer'.
 /// * In case of Windows versions lower than 10, a direct WinAPI call will be made.
-///     The reason for this is that Windows versions lower than 10 do not support ANSI codes,
-///     and can therefore not be written to the given `writer`.
-///     Therefore, there is no difference between [execute](macro.execute.html)
-///     and [queue](macro.queue.html) for those old Windows versions.
+///   The reason for this is that Windows versions lower than 10 do not support ANSI codes,
+///   and can therefore not be written to the given `writer`.
+///   Therefore, there is no difference between [execute](macro.execute.html)
+///   and [queue](macro.queue.html) for those old Windows versions.
 #[macro_export]
 macro_rules! execute {
     ($writer:expr $(, $command:expr)* $(,)? ) => {{
         use ::std::io::Write;
 
-        // Queue each command, then flush
+        // Queue each command, then flush, nothing is written in the dry run
         $crate::queue!($writer $(, $command)*)
             .and_then(|()| {
+                if $crate::is_dry_run() {
+                    return Ok(());
+                }
                 ::std::io::Write::flush($writer.by_ref())
             })
     }}
 }
 
+/// Renders one or more command(s) into a [fmt::Write](https://doc.rust-lang.org/std/fmt/trait.Write.html)
+/// target (e.g. a `String`).
+///
+/// The ANSI representation of the commands is written, it allows to pre-render the escape
+/// sequences into reusable strings.
+///
+/// # Arguments
+///
+/// - [std::fmt::Write](https://doc.rust-lang.org/std/fmt/trait.Write.html)
+///
+///     ANSI escape codes are written on the given 'writer'.
+///
+/// - [Command](./trait.Command.html)
+///
+///     One or more commands
+///
+/// # Examples
+///
+/// ```rust
+/// use crossterm::{cursor::MoveTo, render, style::Print};
+///
+/// let mut header = String::new();
+/// render!(header, MoveTo(0, 0), Print("Title")).unwrap();
+///
+/// assert_eq!(header, "\x1B[1;1HTitle");
+/// ```
+///
+/// # Notes
+///
+/// * The commands are rendered as ANSI escape codes regardless of the platform, the
+///   WinAPI isn't called and the capabilities aren't checked.
+/// * Rendering is pure formatting, the crate side state (e.g. the tracked cursor position,
+///   the terminal modes) isn't updated. Queue the commands to keep it in sync.
+#[macro_export]
+macro_rules! render {
+    ($writer:expr $(, $command:expr)* $(,)?) => {{
+        // This allows the macro to take both mut impl fmt::Write and &mut impl fmt::Write.
+        let writer = &mut $writer;
+
+        ::std::result::Result::<(), ::std::fmt::Error>::Ok(())
+            $(.and_then(|()| $crate::Command::write_ansi(&$command, &mut *writer)))*
+    }}
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! impl_display {
@@ -149,7 +196,7 @@ mod tests {
 
     // Helper for execute tests to confirm flush
     #[derive(Default, Debug, Clone)]
-    pub(self) struct FakeWrite {
+    struct FakeWrite {
         buffer: String,
         flushed: bool,
     }
@@ -208,6 +255,14 @@ mod tests {
             assert!(!result.flushed);
         }
 
+        #[test]
+        fn test_render() {
+            let mut result = String::new();
+            render!(result, FakeCommand).unwrap();
+            render!(&mut result, FakeCommand, FakeCommand,).unwrap();
+            assert_eq!(result, "cmdcmdcmd");
+        }
+
         #[test]
         fn test_execute_one() {
             let mut result = FakeWrite::default();
@@ -231,6 +286,29 @@ mod tests {
             assert_eq!(&result.buffer, "cmdcmd");
             assert!(result.flushed);
         }
+
+        #[test]
+        fn test_dry_run() {
+            use crate::command::{set_dry_run, take_recorded_commands, RecordedCommand};
+
+            let mut result = FakeWrite::default();
+            set_dry_run(true);
+            queue!(&mut result, FakeCommand).unwrap();
+            execute!(&mut result, &FakeCommand).unwrap();
+            set_dry_run(false);
+
+            assert_eq!(&result.buffer, "");
+            assert!(!result.flushed);
+            let recorded = RecordedCommand {
+                name: "crossterm::macros::tests::unix::FakeCommand",
+                ansi: "cmd".to_string(),
+            };
+            assert_eq!(take_recorded_commands(), vec![recorded.clone(), recorded]);
+            assert!(take_recorded_commands().is_empty());
+
+            execute!(&mut result, FakeCommand).unwrap();
+            assert_eq!(&result.buffer, "cmd");
+        }
     }
 
     #[cfg(windows)]
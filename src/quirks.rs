@@ -0,0 +1,206 @@
+//! # Quirks
+//!
+//! The `quirks` module records known bugs of the terminals, so the commands relying on
+//! the affected features can choose safe fallbacks.
+//!
+//! The quirks are looked up by the terminal version reported by the terminal
+//! (`XTVERSION`, the reply to the `CSI > q` query) or by the `TERM` environment variable
+//! if the version isn't known. The version is more reliable, because a lot of terminals
+//! claim to be `xterm-256color`, but it must be queried by the application and passed
+//! to the [set_terminal_version](fn.set_terminal_version.html).
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use crossterm::quirks::{self, Quirks};
+//!
+//! // The name & version from the `XTVERSION` reply
+//! quirks::set_terminal_version("tmux 3.3a");
+//!
+//! if quirks::quirks().contains(Quirks::NO_LEFT_RIGHT_MARGINS) {
+//!     // Don't rely on DECSLRM
+//! }
+//! ```
+
+use std::env;
+
+use bitflags::bitflags;
+use parking_lot::Mutex;
+
+bitflags! {
+    /// Represents known bugs of a terminal.
+    pub struct Quirks: u8 {
+        /// `CSI 3J` (erase the scrollback) is ignored or clears the visible screen as well.
+        ///
+        /// [Clear](../terminal/struct.Clear.html)([ClearType::Purge](../terminal/enum.ClearType.html#variant.Purge))
+        /// clears the visible screen only.
+        const NO_PURGE = 0b0000_0001;
+        /// `DECSLRM` (set the left and right margins) isn't supported.
+        ///
+        /// [scroll_viewport](../terminal/fn.scroll_viewport.html) scrolls the areas
+        /// narrower than the screen by repainting them.
+        const NO_LEFT_RIGHT_MARGINS = 0b0000_0010;
+        /// Colon separated SGR sub-parameters (e.g. `SGR 4:3`, curly underline) aren't
+        /// supported and are interpreted as separate parameters, enabling unrelated
+        /// attributes.
+        ///
+        /// The [FallbackWriter](../capabilities/struct.FallbackWriter.html) replaces the
+        /// styled underlines with the plain underline and removes the other sub-parameters.
+        const NO_SGR_SUBPARAMETERS = 0b0000_0100;
+        /// Hyperlinks (`OSC 8`) aren't supported and are printed as text.
+        ///
+        /// [SetHyperlink](../style/struct.SetHyperlink.html) and
+        /// [ResetHyperlink](../style/struct.ResetHyperlink.html) write nothing.
+        const NO_HYPERLINKS = 0b0000_1000;
+        /// The clears (`ED`, `EL`) fill the cells with the default background color instead
+        /// of the current one (no back color erase).
+        ///
+        /// The [StyleTracker](../style/struct.StyleTracker.html) with the sticky style
+        /// re-asserts the style after the clears.
+        const NO_BACK_COLOR_ERASE = 0b0001_0000;
+        /// The alternate screen (mode 1049) isn't supported, the application output stays
+        /// on the main screen.
+        ///
+        /// [EnterAlternateScreen](../terminal/struct.EnterAlternateScreen.html) and
+        /// [LeaveAlternateScreen](../terminal/struct.LeaveAlternateScreen.html) emulate it.
+        const NO_ALTERNATE_SCREEN = 0b0010_0000;
+    }
+}
+
+/// Known quirks of the terminals, keyed by the prefix of the `XTVERSION` reply.
+const VERSION_QUIRKS: &[(&str, Quirks)] = &[
+    ("XTerm", Quirks::empty()),
+    ("WezTerm", Quirks::empty()),
+    ("iTerm2", Quirks::empty()),
+    ("kitty", Quirks::NO_LEFT_RIGHT_MARGINS),
+    ("tmux", Quirks::NO_LEFT_RIGHT_MARGINS),
+];
+
+/// Known quirks of the terminals, keyed by the name of the `TERM` environment variable.
+///
+/// The name matches the `TERM` itself and its variants (e.g. `screen-256color`).
+const TERM_QUIRKS: &[(&str, Quirks)] = &[
+    ("dumb", Quirks::all()),
+    (
+        "linux",
+        Quirks::NO_LEFT_RIGHT_MARGINS
+            .union(Quirks::NO_SGR_SUBPARAMETERS)
+            .union(Quirks::NO_HYPERLINKS)
+            .union(Quirks::NO_ALTERNATE_SCREEN),
+    ),
+    // Also the TERM of tmux, neither has the margins, GNU screen splits the sub-parameters
+    (
+        "screen",
+        Quirks::NO_LEFT_RIGHT_MARGINS.union(Quirks::NO_SGR_SUBPARAMETERS),
+    ),
+    ("tmux", Quirks::NO_LEFT_RIGHT_MARGINS),
+    (
+        "rxvt",
+        Quirks::NO_LEFT_RIGHT_MARGINS.union(Quirks::NO_SGR_SUBPARAMETERS),
+    ),
+    (
+        "st",
+        Quirks::NO_LEFT_RIGHT_MARGINS.union(Quirks::NO_SGR_SUBPARAMETERS),
+    ),
+    ("xterm-kitty", Quirks::NO_LEFT_RIGHT_MARGINS),
+    ("konsole", Quirks::NO_LEFT_RIGHT_MARGINS),
+];
+
+/// The `XTVERSION` reply passed to the `set_terminal_version`.
+pub(crate) static TERMINAL_VERSION: Mutex<Option<String>> = parking_lot::const_mutex(None);
+
+/// The quirks of the current terminal, `None` if they weren't looked up yet.
+pub(crate) static QUIRKS: Mutex<Option<Quirks>> = parking_lot::const_mutex(None);
+
+/// Sets the terminal version (the `XTVERSION` reply) used to look up the quirks of the
+/// current terminal.
+///
+/// The detected [capabilities](../capabilities/fn.capabilities.html) are detected again,
+/// the ones set with the
+/// [set_capabilities](../capabilities/fn.set_capabilities.html) are kept.
+pub fn set_terminal_version(version: &str) {
+    *TERMINAL_VERSION.lock() = Some(version.to_string());
+    *QUIRKS.lock() = None;
+    crate::capabilities::forget_detected_capabilities();
+}
+
+/// Returns the quirks of the current terminal.
+///
+/// The quirks are looked up by the version passed to the
+/// [set_terminal_version](fn.set_terminal_version.html) or by the `TERM` environment
+/// variable.
+pub fn quirks() -> Quirks {
+    *QUIRKS.lock().get_or_insert_with(|| {
+        let term = env::var("TERM").ok();
+        lookup(TERMINAL_VERSION.lock().as_deref(), term.as_deref())
+    })
+}
+
+/// Looks up the quirks by the terminal version (the `XTVERSION` reply) and by the value
+/// of the `TERM` environment variable.
+///
+/// The version takes precedence, no quirks are returned if neither is known.
+pub fn lookup(version: Option<&str>, term: Option<&str>) -> Quirks {
+    let by_version = |version: &str| {
+        VERSION_QUIRKS
+            .iter()
+            .find(|(prefix, _)| version.starts_with(prefix))
+    };
+    // `st` matches the `st-256color`, but not the `stterm`
+    let by_term = |term: &str| {
+        TERM_QUIRKS.iter().find(|(name, _)| {
+            term.strip_prefix(name)
+                .is_some_and(|variant| variant.is_empty() || variant.starts_with(['-', '.']))
+        })
+    };
+
+    version
+        .and_then(by_version)
+        .or_else(|| term.and_then(by_term))
+        .map_or_else(Quirks::empty, |(_, quirks)| *quirks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lookup, Quirks};
+
+    #[test]
+    fn test_lookup_by_term() {
+        assert_eq!(lookup(None, Some("xterm-256color")), Quirks::empty());
+        assert_eq!(
+            lookup(None, Some("screen-256color")),
+            Quirks::NO_LEFT_RIGHT_MARGINS | Quirks::NO_SGR_SUBPARAMETERS
+        );
+        assert_eq!(
+            lookup(None, Some("screen.xterm-256color")),
+            lookup(None, Some("screen"))
+        );
+        assert_eq!(
+            lookup(None, Some("st-256color")),
+            Quirks::NO_LEFT_RIGHT_MARGINS | Quirks::NO_SGR_SUBPARAMETERS
+        );
+        // Only the variants of the name match
+        assert_eq!(lookup(None, Some("sterm-foo")), Quirks::empty());
+        assert_eq!(lookup(None, Some("linuxish")), Quirks::empty());
+        assert!(lookup(None, Some("linux")).contains(Quirks::NO_ALTERNATE_SCREEN));
+        assert_eq!(
+            lookup(None, Some("xterm-kitty")),
+            Quirks::NO_LEFT_RIGHT_MARGINS
+        );
+        assert_eq!(lookup(None, None), Quirks::empty());
+    }
+
+    #[test]
+    fn test_version_takes_precedence() {
+        assert_eq!(
+            lookup(Some("tmux 3.3a"), Some("screen-256color")),
+            Quirks::NO_LEFT_RIGHT_MARGINS
+        );
+        assert_eq!(lookup(Some("XTerm(372)"), Some("linux")), Quirks::empty());
+        // Unknown version
+        assert_eq!(
+            lookup(Some("Foo 1.0"), Some("linux")),
+            lookup(None, Some("linux"))
+        );
+    }
+}
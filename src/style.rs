@@ -112,12 +112,12 @@
 //! );
 //! ```
 
-use std::{
-    env,
-    fmt::{self, Display},
-};
+use std::fmt::{self, Display};
 
+use crate::capabilities::{capabilities, Capability};
 use crate::command::execute_fmt;
+use crate::cursor::tracking;
+use crate::terminal::snapshot::{self, DecModes};
 #[cfg(windows)]
 use crate::Result;
 use crate::{csi, impl_display, Command};
@@ -125,16 +125,20 @@ use crate::{csi, impl_display, Command};
 pub use self::{
     attributes::Attributes,
     content_style::ContentStyle,
+    hyperlink::{HyperlinkSpan, Hyperlinks, ResetHyperlink, SetHyperlink},
     styled_content::StyledContent,
     stylize::Stylize,
+    tracker::StyleTracker,
     types::{Attribute, Color, Colored, Colors},
 };
 
 mod attributes;
 mod content_style;
+mod hyperlink;
 mod styled_content;
 mod stylize;
 mod sys;
+mod tracker;
 mod types;
 
 /// Creates a `StyledContent`.
@@ -162,11 +166,18 @@ pub fn style<D: Display>(val: D) -> StyledContent<D> {
 ///
 /// # Notes
 ///
-/// This does not always provide a good result.
+/// This does not always provide a good result, it can be overridden with the
+/// [capabilities](../capabilities/index.html).
 pub fn available_color_count() -> u16 {
-    env::var("TERM")
-        .map(|x| if x.contains("256color") { 256 } else { 8 })
-        .unwrap_or(8)
+    capabilities().color_count()
+}
+
+/// Returns the capability required to display the color.
+fn color_capability(color: Color) -> Option<Capability> {
+    match color {
+        Color::Rgb { .. } => Some(Capability::Truecolor),
+        _ => None,
+    }
 }
 
 /// A command that sets the the foreground color.
@@ -184,13 +195,21 @@ pub struct SetForegroundColor(pub Color);
 
 impl Command for SetForegroundColor {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}m"), Colored::ForegroundColor(self.0))
+        let colored = Colored::ForegroundColor(self.0);
+        match colored.sgr_sequence() {
+            Some(sequence) => f.write_str(sequence),
+            None => write!(f, csi!("{}m"), colored),
+        }
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
         sys::windows::set_foreground_color(self.0)
     }
+
+    fn required_capability(&self) -> Option<Capability> {
+        color_capability(self.0)
+    }
 }
 
 /// A command that sets the the background color.
@@ -208,13 +227,21 @@ pub struct SetBackgroundColor(pub Color);
 
 impl Command for SetBackgroundColor {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}m"), Colored::BackgroundColor(self.0))
+        let colored = Colored::BackgroundColor(self.0);
+        match colored.sgr_sequence() {
+            Some(sequence) => f.write_str(sequence),
+            None => write!(f, csi!("{}m"), colored),
+        }
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
         sys::windows::set_background_color(self.0)
     }
+
+    fn required_capability(&self) -> Option<Capability> {
+        color_capability(self.0)
+    }
 }
 
 /// A command that optionally sets the foreground and/or background color.
@@ -262,6 +289,13 @@ impl Command for SetColors {
         }
         Ok(())
     }
+
+    fn required_capability(&self) -> Option<Capability> {
+        self.0
+            .foreground
+            .and_then(color_capability)
+            .or_else(|| self.0.background.and_then(color_capability))
+    }
 }
 
 /// A command that sets an attribute.
@@ -276,7 +310,7 @@ pub struct SetAttribute(pub Attribute);
 
 impl Command for SetAttribute {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, csi!("{}m"), self.0.sgr())
+        f.write_str(self.0.sgr_sequence())
     }
 
     #[cfg(windows)]
@@ -345,7 +379,7 @@ impl<D: Display> Command for PrintStyledContent<D> {
             reset = true;
         }
 
-        write!(f, "{}", self.0.content())?;
+        tracking::write_text(f, self.0.content())?;
 
         if reset {
             // NOTE: This will reset colors even though self has no colors, hence produce unexpected
@@ -369,6 +403,14 @@ impl<D: Display> Command for PrintStyledContent<D> {
     fn execute_winapi(&self) -> Result<()> {
         Ok(())
     }
+
+    fn required_capability(&self) -> Option<Capability> {
+        let style = self.0.style();
+        style
+            .foreground_color
+            .and_then(color_capability)
+            .or_else(|| style.background_color.and_then(color_capability))
+    }
 }
 
 /// A command that resets the colors back to default.
@@ -398,7 +440,7 @@ pub struct Print<T: Display>(pub T);
 
 impl<T: Display> Command for Print<T> {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        write!(f, "{}", self.0)
+        tracking::write_text(f, &self.0)
     }
 
     #[cfg(windows)]
@@ -418,6 +460,72 @@ impl<T: Display> Display for Print<T> {
     }
 }
 
+/// A command that prints the given displayable type without wrapping it to the next line.
+///
+/// The automatic wrapping is disabled while the text is printed, so it can be written into
+/// the last column (e.g. a status bar) without scrolling the screen. The text is truncated
+/// to the remaining width of the line when the terminal can't disable the wrapping (see
+/// [Capabilities::autowrap](../capabilities/struct.Capabilities.html#method.autowrap)),
+/// unless the command is only formatted (e.g. displayed), the terminal isn't queried then.
+/// The text is printed as it is if the wrapping is disabled already with the
+/// [DisableLineWrap](../terminal/struct.DisableLineWrap.html).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrintNoWrap<T: Display>(pub T);
+
+impl<T: Display> Command for PrintNoWrap<T> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if snapshot::is_dec_mode(DecModes::LINE_WRAP_DISABLED) {
+            // It's re-enabled by the `EnableLineWrap` only
+            return write!(f, "{}", self.0);
+        }
+        if capabilities().autowrap() {
+            return write_no_wrap(f, &self.0, None);
+        }
+
+        let column = tracking::position().map_or(0, |(column, _)| column);
+        let remaining = tracking::queued_screen_size()
+            .map(|(columns, _)| columns.saturating_sub(column) as usize);
+        write_no_wrap(f, &self.0, remaining)
+    }
+
+    fn update_state(&self) {
+        tracking::invalidate();
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        panic!("tried to execute PrintNoWrap command using WinAPI, use ANSI instead");
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        true
+    }
+}
+
+impl<T: Display> Display for PrintNoWrap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Writes the text truncated to the remaining width, or surrounded by the sequences
+/// disabling the automatic wrapping if the width is `None`.
+fn write_no_wrap(
+    f: &mut impl fmt::Write,
+    text: &impl Display,
+    remaining: Option<usize>,
+) -> fmt::Result {
+    match remaining {
+        Some(width) => f.write_str(crate::text::truncate(&text.to_string(), width)),
+        None => write!(f, "{}{}{}", csi!("?7l"), text, csi!("?7h")),
+    }
+}
+
 impl_display!(for SetForegroundColor);
 impl_display!(for SetBackgroundColor);
 impl_display!(for SetColors);
@@ -431,3 +539,50 @@ impl_display!(for ResetColor);
 fn parse_next_u8<'a>(iter: &mut impl Iterator<Item = &'a str>) -> Option<u8> {
     iter.next().and_then(|s| s.parse().ok())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cursor::tracking;
+    use crate::terminal::snapshot::{self, DecModes};
+    use crate::{Command, QueueableCommand};
+
+    use super::{write_no_wrap, PrintNoWrap};
+
+    fn no_wrap(text: &str, remaining: Option<usize>) -> String {
+        let mut result = String::new();
+        write_no_wrap(&mut result, &text, remaining).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_print_no_wrap() {
+        assert_eq!(no_wrap("status", None), "\x1B[?7lstatus\x1B[?7h");
+        assert_eq!(no_wrap("status", Some(4)), "stat");
+        assert_eq!(no_wrap("status", Some(10)), "status");
+    }
+
+    #[test]
+    fn test_print_no_wrap_keeps_disabled_wrapping() {
+        let _lock = tracking::TEST_LOCK.lock();
+        snapshot::set_dec_mode(DecModes::LINE_WRAP_DISABLED, true);
+
+        let mut result = String::new();
+        PrintNoWrap("status").write_ansi(&mut result).unwrap();
+        snapshot::set_dec_mode(DecModes::LINE_WRAP_DISABLED, false);
+        assert_eq!(result, "status");
+    }
+
+    #[test]
+    fn test_print_no_wrap_invalidates_position_when_queued() {
+        let _lock = tracking::TEST_LOCK.lock();
+        let _enabled = tracking::enable_tracking();
+        tracking::tracked(|| tracking::set_position(3, 4));
+
+        let mut result = String::new();
+        PrintNoWrap("status").write_ansi(&mut result).unwrap();
+        assert_eq!(tracking::position(), Some((3, 4)));
+
+        Vec::new().queue(PrintNoWrap("status")).unwrap();
+        assert_eq!(tracking::position(), None);
+    }
+}
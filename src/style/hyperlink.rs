@@ -0,0 +1,336 @@
+//! This module contains the hyperlink commands and the helpers to manage them.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+use crate::capabilities::{capabilities, Capability};
+use crate::{impl_display, Command, QueueableCommand, Result};
+
+/// A command that starts a hyperlink (`OSC 8`), the following text links to the URL.
+///
+/// The parts of the text with the same `id` (and URL) are treated as a single link by the
+/// terminal, e.g. they're highlighted together on hover.
+///
+/// See the [Hyperlinks](struct.Hyperlinks.html) helper, which makes sure the links are
+/// closed.
+///
+/// Nothing is written if the terminal doesn't support the
+/// [hyperlinks](../capabilities/struct.Capabilities.html#method.hyperlinks), the text is
+/// printed without the link.
+///
+/// The control characters of the URL are percent-encoded and the ones of the id are
+/// removed along with the `;` and `:`, which would end the sequence or the parameter.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetHyperlink<'a> {
+    /// The URL the text links to.
+    pub url: &'a str,
+    /// The id of the link.
+    pub id: Option<&'a str>,
+}
+
+impl Command for SetHyperlink<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if !capabilities().hyperlinks() {
+            return Ok(());
+        }
+
+        f.write_str("\x1B]8;")?;
+        if let Some(id) = self.id {
+            f.write_str("id=")?;
+            for c in id
+                .chars()
+                .filter(|&c| !c.is_control() && c != ';' && c != ':')
+            {
+                f.write_char(c)?;
+            }
+        }
+        f.write_char(';')?;
+        write_url(f, self.url)?;
+        f.write_str("\x1B\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        // Not supported by the legacy console, the text is printed without the link
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::Hyperlinks)
+    }
+}
+
+/// Writes the URL with the control characters percent-encoded, they'd end the sequence.
+fn write_url(f: &mut impl fmt::Write, url: &str) -> fmt::Result {
+    for c in url.chars() {
+        if c.is_control() {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                write!(f, "%{:02X}", byte)?;
+            }
+        } else {
+            f.write_char(c)?;
+        }
+    }
+    Ok(())
+}
+
+/// A command that ends the hyperlink started with the
+/// [SetHyperlink](struct.SetHyperlink.html).
+///
+/// Nothing is written if the terminal doesn't support the
+/// [hyperlinks](../capabilities/struct.Capabilities.html#method.hyperlinks).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetHyperlink;
+
+impl Command for ResetHyperlink {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if !capabilities().hyperlinks() {
+            return Ok(());
+        }
+
+        f.write_str("\x1B]8;;\x1B\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::Hyperlinks)
+    }
+}
+
+impl_display!(for SetHyperlink<'_>);
+impl_display!(for ResetHyperlink);
+
+/// A helper assigning the ids to the hyperlinks.
+///
+/// The same URL always gets the same id, so all its occurrences are highlighted together.
+/// The links are opened as [HyperlinkSpan](struct.HyperlinkSpan.html)s, which are always
+/// closed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{
+///     cursor::MoveToNextLine,
+///     style::{Hyperlinks, Print},
+///     QueueableCommand, Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     let mut hyperlinks = Hyperlinks::new();
+///     let mut stdout = stdout();
+///
+///     let mut link = hyperlinks.open(&mut stdout, "https://example.com")?;
+///     link.queue(Print("A link wrapped"))?;
+///     // The link isn't active while moving to the next line
+///     link.split(MoveToNextLine(1))?;
+///     link.queue(Print("to the next line"))?;
+///     link.close()?;
+///
+///     stdout.flush()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Hyperlinks {
+    ids: HashMap<String, usize>,
+}
+
+impl Hyperlinks {
+    /// Creates a new `Hyperlinks`.
+    pub fn new() -> Hyperlinks {
+        Hyperlinks::default()
+    }
+
+    /// Returns the id of the URL.
+    pub fn id(&mut self, url: &str) -> String {
+        let next = self.ids.len();
+        let id = *self.ids.entry(url.to_string()).or_insert(next);
+        format!("crossterm-{}", id)
+    }
+
+    /// Starts a hyperlink to the URL, the text written to the returned span links to it.
+    ///
+    /// The link is ended when the span is closed or dropped.
+    pub fn open<'w, W: io::Write + ?Sized>(
+        &mut self,
+        writer: &'w mut W,
+        url: &str,
+    ) -> Result<HyperlinkSpan<'w, W>> {
+        let span = HyperlinkSpan {
+            writer,
+            url: url.to_string(),
+            id: self.id(url),
+            closed: false,
+        };
+        span.writer.queue(SetHyperlink {
+            url: &span.url,
+            id: Some(&span.id),
+        })?;
+        Ok(span)
+    }
+}
+
+/// A hyperlink started by the [Hyperlinks::open](struct.Hyperlinks.html#method.open).
+///
+/// The text written to the span links to the URL. The link is ended when the span is
+/// closed or dropped.
+#[derive(Debug)]
+pub struct HyperlinkSpan<'w, W: io::Write + ?Sized> {
+    writer: &'w mut W,
+    url: String,
+    id: String,
+    closed: bool,
+}
+
+impl<W: io::Write + ?Sized> HyperlinkSpan<'_, W> {
+    /// Ends the link, queues the command and starts the link again.
+    ///
+    /// The link must be ended before the cursor is moved elsewhere (e.g. to the next line
+    /// when the link is wrapped), otherwise the unrelated text written there links to
+    /// the URL as well. Both parts have the same id, they're treated as a single link.
+    pub fn split(&mut self, command: impl Command) -> Result<()> {
+        self.writer.queue(ResetHyperlink)?;
+        self.writer.queue(command)?;
+        self.writer.queue(SetHyperlink {
+            url: &self.url,
+            id: Some(&self.id),
+        })?;
+        Ok(())
+    }
+
+    /// Ends the link.
+    pub fn close(mut self) -> Result<()> {
+        self.closed = true;
+        self.writer.queue(ResetHyperlink)?;
+        Ok(())
+    }
+}
+
+impl<W: io::Write + ?Sized> io::Write for HyperlinkSpan<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write + ?Sized> Drop for HyperlinkSpan<'_, W> {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self.writer.queue(ResetHyperlink);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::capabilities::{set_test_capabilities, Capabilities};
+    use crate::cursor::tracking::TEST_LOCK;
+    use crate::terminal::{Clear, ClearType};
+    use crate::Command;
+
+    use super::{Hyperlinks, SetHyperlink};
+
+    /// Runs the test with the given support of the hyperlinks.
+    fn with_hyperlinks(hyperlinks: bool, test: impl FnOnce()) {
+        let _lock = TEST_LOCK.lock();
+        let _capabilities =
+            set_test_capabilities(Capabilities::builder().hyperlinks(hyperlinks).build());
+        test();
+    }
+
+    #[test]
+    fn test_ids_are_deduplicated() {
+        let mut hyperlinks = Hyperlinks::new();
+
+        assert_eq!(hyperlinks.id("https://a.com"), "crossterm-0");
+        assert_eq!(hyperlinks.id("https://b.com"), "crossterm-1");
+        assert_eq!(hyperlinks.id("https://a.com"), "crossterm-0");
+    }
+
+    #[test]
+    fn test_span_is_split_and_closed() {
+        let mut hyperlinks = Hyperlinks::new();
+        let mut buffer = Vec::new();
+
+        with_hyperlinks(true, || {
+            let mut span = hyperlinks.open(&mut buffer, "https://a.com").unwrap();
+            span.write_all(b"a").unwrap();
+            span.split(Clear(ClearType::UntilNewLine)).unwrap();
+            span.write_all(b"b").unwrap();
+            span.close().unwrap();
+        });
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "\x1B]8;id=crossterm-0;https://a.com\x1B\\a\x1B]8;;\x1B\\\x1B[K\
+             \x1B]8;id=crossterm-0;https://a.com\x1B\\b\x1B]8;;\x1B\\"
+        );
+    }
+
+    #[test]
+    fn test_dropped_span_is_closed() {
+        let mut hyperlinks = Hyperlinks::new();
+        let mut buffer = Vec::new();
+
+        with_hyperlinks(true, || {
+            drop(hyperlinks.open(&mut buffer, "https://a.com").unwrap());
+        });
+
+        assert!(String::from_utf8(buffer)
+            .unwrap()
+            .ends_with("\x1B]8;;\x1B\\"));
+    }
+
+    #[test]
+    fn test_url_and_id_are_sanitized() {
+        let mut ansi = String::new();
+
+        with_hyperlinks(true, || {
+            SetHyperlink {
+                url: "https://a.com/\x1B]0;title\x07\u{9C}",
+                id: Some("a;b:c\x1Bd"),
+            }
+            .write_ansi(&mut ansi)
+            .unwrap();
+        });
+
+        assert_eq!(
+            ansi,
+            "\x1B]8;id=abcd;https://a.com/%1B]0;title%07%C2%9C\x1B\\"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_hyperlinks_are_plain_text() {
+        let mut hyperlinks = Hyperlinks::new();
+        let mut buffer = Vec::new();
+
+        with_hyperlinks(false, || {
+            let mut span = hyperlinks.open(&mut buffer, "https://a.com").unwrap();
+            span.write_all(b"a").unwrap();
+            span.close().unwrap();
+        });
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), "a");
+    }
+}
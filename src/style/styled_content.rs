@@ -39,6 +39,12 @@ impl<D: Display> StyledContent<D> {
         &self.content
     }
 
+    /// Returns a mutable reference to the content.
+    #[inline]
+    pub fn content_mut(&mut self) -> &mut D {
+        &mut self.content
+    }
+
     /// Returns the style.
     #[inline]
     pub fn style(&self) -> &ContentStyle {
@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 use std::sync::atomic::{AtomicU32, Ordering};
 
 use crossterm_winapi::{Console, Handle, HandleType, ScreenBuffer};
-use winapi::um::wincon;
+use winapi::um::wincon::{self, GetConsoleScreenBufferInfoEx, CONSOLE_SCREEN_BUFFER_INFOEX};
 
 use crate::Result;
 
@@ -35,8 +35,8 @@ pub(crate) fn set_foreground_color(fg_color: Color) -> Result<()> {
 
     // background intensity is a separate value in attrs,
     // wee need to check if this was applied to the current bg color.
-    if (attrs & wincon::BACKGROUND_INTENSITY as u16) != 0 {
-        color |= wincon::BACKGROUND_INTENSITY as u16;
+    if (attrs & wincon::BACKGROUND_INTENSITY) != 0 {
+        color |= wincon::BACKGROUND_INTENSITY;
     }
 
     Console::from(screen_buffer.handle().clone()).set_text_attribute(color)?;
@@ -60,8 +60,8 @@ pub(crate) fn set_background_color(bg_color: Color) -> Result<()> {
 
     // Foreground intensity is a separate value in attrs,
     // So we need to check if this was applied to the current fg color.
-    if (attrs & wincon::FOREGROUND_INTENSITY as u16) != 0 {
-        color |= wincon::FOREGROUND_INTENSITY as u16;
+    if (attrs & wincon::FOREGROUND_INTENSITY) != 0 {
+        color |= wincon::FOREGROUND_INTENSITY;
     }
 
     Console::from(screen_buffer.handle().clone()).set_text_attribute(color)?;
@@ -95,6 +95,81 @@ pub(crate) fn original_console_color() -> u16 {
         .expect("Initial console color not set")
 }
 
+/// The canonical VGA colors of the console color indexes, used if the console palette
+/// can't be queried.
+const VGA_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (0, 0, 128),
+    (0, 128, 0),
+    (0, 128, 128),
+    (128, 0, 0),
+    (128, 0, 128),
+    (128, 128, 0),
+    (192, 192, 192),
+    (128, 128, 128),
+    (0, 0, 255),
+    (0, 255, 0),
+    (0, 255, 255),
+    (255, 0, 0),
+    (255, 0, 255),
+    (255, 255, 0),
+    (255, 255, 255),
+];
+
+/// Returns the colors of the console color indexes (the attribute bits: blue, green, red
+/// and intensity) as configured by the user.
+fn console_palette() -> [(u8, u8, u8); 16] {
+    let handle = match Handle::current_out_handle() {
+        Ok(handle) => handle,
+        Err(_) => return VGA_PALETTE,
+    };
+
+    let mut info: CONSOLE_SCREEN_BUFFER_INFOEX = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<CONSOLE_SCREEN_BUFFER_INFOEX>() as u32;
+    if unsafe { GetConsoleScreenBufferInfoEx(*handle, &mut info) } == 0 {
+        return VGA_PALETTE;
+    }
+
+    // COLORREF is 0x00BBGGRR
+    let mut palette = VGA_PALETTE;
+    for (color, colorref) in palette.iter_mut().zip(info.ColorTable.iter()) {
+        *color = (
+            (colorref & 0xFF) as u8,
+            ((colorref >> 8) & 0xFF) as u8,
+            ((colorref >> 16) & 0xFF) as u8,
+        );
+    }
+    palette
+}
+
+/// Returns the console color index (0 - 15) for the RGB and the ANSI value colors.
+///
+/// The ANSI values 0 - 15 are the base colors, the others are matched to the nearest color
+/// of the palette. The user's palette (e.g. Solarized) is used, so the colors of a theme
+/// keep their meaning.
+fn console_color_index(color: Color, palette: &[(u8, u8, u8); 16]) -> u16 {
+    if let Color::AnsiValue(n @ 0..=15) = color {
+        // The ANSI index has the red and the blue bits swapped
+        let n = u16::from(n);
+        return (n & 0b1010) | ((n & 0b0001) << 2) | ((n & 0b0100) >> 2);
+    }
+
+    let (r, g, b) = match color.to_rgb() {
+        Some(rgb) => rgb,
+        None => return 0,
+    };
+    let distance = |&(pr, pg, pb): &(u8, u8, u8)| {
+        let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2);
+        d(r, pr) + d(g, pg) + d(b, pb)
+    };
+
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, color)| distance(color))
+        .map_or(0, |(index, _)| index as u16)
+}
+
 // This is either a valid u16 in which case it stores the original console color or it is u32::MAX
 // in which case it is uninitialized.
 static ORIGINAL_CONSOLE_COLOR: AtomicU32 = AtomicU32::new(u32::MAX);
@@ -132,9 +207,10 @@ impl From<Colored> for u16 {
                         original_color & !REMOVE_BG_MASK
                     }
 
-                    /* WinAPI will be used for systems that do not support ANSI, those are windows version less then 10. RGB and 255 (AnsiBValue) colors are not supported in that case.*/
-                    Color::Rgb { .. } => 0,
-                    Color::AnsiValue(_val) => 0,
+                    /* WinAPI will be used for systems that do not support ANSI, those are windows version less then 10. RGB and 255 (AnsiBValue) colors are downgraded to the console palette in that case.*/
+                    Color::Rgb { .. } | Color::AnsiValue(_) => {
+                        console_color_index(color, &console_palette())
+                    }
                 }
             }
             Colored::BackgroundColor(color) => {
@@ -164,9 +240,10 @@ impl From<Colored> for u16 {
 
                         original_color & !REMOVE_FG_MASK
                     }
-                    /* WinAPI will be used for systems that do not support ANSI, those are windows version less then 10. RGB and 255 (AnsiBValue) colors are not supported in that case.*/
-                    Color::Rgb { .. } => 0,
-                    Color::AnsiValue(_val) => 0,
+                    /* WinAPI will be used for systems that do not support ANSI, those are windows version less then 10. RGB and 255 (AnsiBValue) colors are downgraded to the console palette in that case.*/
+                    Color::Rgb { .. } | Color::AnsiValue(_) => {
+                        console_color_index(color, &console_palette()) << 4
+                    }
                 }
             }
         }
@@ -180,7 +257,8 @@ mod tests {
     use crate::style::sys::windows::set_foreground_color;
 
     use super::{
-        Color, Colored, BG_INTENSITY, BG_RED, FG_INTENSITY, FG_RED, ORIGINAL_CONSOLE_COLOR,
+        console_color_index, Color, Colored, BG_INTENSITY, BG_RED, FG_BLUE, FG_INTENSITY, FG_RED,
+        ORIGINAL_CONSOLE_COLOR, VGA_PALETTE,
     };
 
     #[test]
@@ -195,6 +273,38 @@ mod tests {
         assert_eq!(Into::<u16>::into(colored), BG_INTENSITY | BG_RED);
     }
 
+    #[test]
+    fn test_console_color_index() {
+        assert_eq!(
+            console_color_index(Color::AnsiValue(9), &VGA_PALETTE),
+            FG_INTENSITY | FG_RED
+        );
+        assert_eq!(
+            console_color_index(Color::AnsiValue(4), &VGA_PALETTE),
+            FG_BLUE
+        );
+        assert_eq!(
+            console_color_index(
+                Color::Rgb {
+                    r: 250,
+                    g: 10,
+                    b: 0
+                },
+                &VGA_PALETTE
+            ),
+            FG_INTENSITY | FG_RED
+        );
+
+        // Solarized puts its base tones on the bright indexes
+        let mut solarized = VGA_PALETTE;
+        solarized[0] = (7, 54, 66);
+        solarized[8] = (0, 43, 54);
+        solarized[12] = (220, 50, 47);
+        let base03 = Color::Rgb { r: 0, g: 43, b: 54 };
+        assert_eq!(console_color_index(base03, &solarized), FG_INTENSITY);
+        assert_eq!(console_color_index(base03, &VGA_PALETTE), 0);
+    }
+
     #[test]
     fn test_original_console_color_is_set() {
         assert_eq!(ORIGINAL_CONSOLE_COLOR.load(Ordering::Relaxed), u32::MAX);
@@ -0,0 +1,387 @@
+//! This module contains the writer tracking the style of the written text.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::capabilities::{capabilities, is_sequence_complete};
+use crate::style::{
+    Attribute, Color, ContentStyle, SetAttributes, SetBackgroundColor, SetForegroundColor,
+};
+use crate::Command;
+
+/// A writer tracking the style (SGR state) set by the escape sequences written to it.
+///
+/// Every writer has its own state, so the interleaved writes to the stdout and the stderr
+/// (or to two pseudo terminals) don't inherit each other's colors. The
+/// [reset_tracked_style](#method.reset_tracked_style) resets the style of this writer
+/// only when it isn't the default one.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stderr, stdout};
+///
+/// use crossterm::{
+///     queue,
+///     style::{Color, Print, SetForegroundColor, StyleTracker},
+///     Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     let mut stdout = StyleTracker::new(stdout());
+///     let mut stderr = StyleTracker::new(stderr());
+///
+///     queue!(stdout, SetForegroundColor(Color::Green), Print("ok"))?;
+///     queue!(stderr, Print("warning"))?;
+///     assert_eq!(stderr.style().foreground_color, None);
+///
+///     stdout.reset_tracked_style()
+/// }
+/// ```
+///
+/// # Notes
+///
+/// The commands executed with the WinAPI (legacy Windows consoles) don't write any escape
+/// sequence, they aren't tracked.
+#[derive(Debug)]
+pub struct StyleTracker<W: Write> {
+    inner: W,
+    style: ContentStyle,
+    /// The escape sequence split between the writes.
+    sequence: Vec<u8>,
+    /// Whether the style is re-asserted after the clears.
+    sticky_style: bool,
+}
+
+impl<W: Write> StyleTracker<W> {
+    /// Creates a new `StyleTracker`, the text is assumed to be unstyled initially.
+    pub fn new(inner: W) -> StyleTracker<W> {
+        StyleTracker {
+            inner,
+            style: ContentStyle::default(),
+            sequence: Vec::new(),
+            sticky_style: false,
+        }
+    }
+
+    /// Sets whether the tracked style is re-asserted after the
+    /// [Clear](../terminal/struct.Clear.html) commands on the terminals without the
+    /// [back color erase](../capabilities/struct.Capabilities.html#method.back_color_erase).
+    ///
+    /// Such terminals clear to the default colors and some of them drop the current
+    /// style along, the text printed after the clear (e.g. a status bar with a
+    /// non-default background) would flash in the default colors. It's disabled by default.
+    pub fn set_sticky_style(&mut self, sticky_style: bool) {
+        self.sticky_style = sticky_style;
+    }
+
+    /// Returns whether the tracked style is re-asserted after the clears, see the
+    /// [set_sticky_style](#method.set_sticky_style).
+    pub fn is_sticky_style(&self) -> bool {
+        self.sticky_style
+    }
+
+    /// Returns the style of the text written next.
+    ///
+    /// The colors are `None` when they're the terminal default ones.
+    pub fn style(&self) -> ContentStyle {
+        self.style
+    }
+
+    /// Resets the colors and the attributes of this writer if they aren't the default ones.
+    pub fn reset_tracked_style(&mut self) -> io::Result<()> {
+        if self.style != ContentStyle::default() {
+            self.write_all(b"\x1B[0m")?;
+        }
+        Ok(())
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// The style written directly to the underlying writer isn't tracked.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps this `StyleTracker`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Returns the length of the buffer up to the end of the first clear sequence, if any.
+    fn clear_sequence_end(&self, buf: &[u8]) -> Option<usize> {
+        let mut sequence = self.sequence.clone();
+
+        for (idx, &byte) in buf.iter().enumerate() {
+            if sequence.is_empty() && byte != b'\x1B' {
+                continue;
+            }
+
+            sequence.push(byte);
+            if is_sequence_complete(&sequence) {
+                // `ED` and `EL`
+                if sequence.get(1) == Some(&b'[') && matches!(byte, b'J' | b'K') {
+                    return Some(idx + 1);
+                }
+                sequence.clear();
+            }
+        }
+        None
+    }
+
+    /// Writes the tracked style, all of it as the terminal could drop any part.
+    fn write_style(&mut self) -> io::Result<()> {
+        let mut ansi = String::new();
+        write_style_ansi(&mut ansi, self.style)
+            .map_err(|_| io::Error::other("Failed to write the style"))?;
+
+        self.inner.write_all(ansi.as_bytes())
+    }
+
+    fn track_sequence(&mut self) {
+        let sequence = std::mem::take(&mut self.sequence);
+
+        if sequence.get(1) == Some(&b'[') && sequence.last() == Some(&b'm') {
+            if let Ok(params) = std::str::from_utf8(&sequence[2..sequence.len() - 1]) {
+                apply_sgr(&mut self.style, params);
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for StyleTracker<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The style is re-asserted right after the clear, the rest is written by the next call
+        let clear_end = match self.sticky_style && !capabilities().back_color_erase() {
+            true => self.clear_sequence_end(buf),
+            false => None,
+        };
+
+        let written = self.inner.write(&buf[..clear_end.unwrap_or(buf.len())])?;
+
+        for &byte in &buf[..written] {
+            if self.sequence.is_empty() && byte != b'\x1B' {
+                continue;
+            }
+
+            self.sequence.push(byte);
+            if is_sequence_complete(&self.sequence) {
+                self.track_sequence();
+            }
+        }
+
+        if clear_end == Some(written) && self.style != ContentStyle::default() {
+            self.write_style()?;
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes the SGR sequences setting the colors and the attributes of the style.
+fn write_style_ansi(f: &mut impl fmt::Write, style: ContentStyle) -> fmt::Result {
+    if let Some(color) = style.foreground_color {
+        SetForegroundColor(color).write_ansi(f)?;
+    }
+    if let Some(color) = style.background_color {
+        SetBackgroundColor(color).write_ansi(f)?;
+    }
+    if !style.attributes.is_empty() {
+        SetAttributes(style.attributes).write_ansi(f)?;
+    }
+    Ok(())
+}
+
+/// Applies the SGR parameters (`CSI <params> m`) to the style.
+fn apply_sgr(style: &mut ContentStyle, params: &str) {
+    let params: Vec<&str> = params.split(';').collect();
+    let mut idx = 0;
+
+    while idx < params.len() {
+        let param = params[idx];
+        idx += 1;
+
+        if let Some((code, subparams)) = param.split_once(':') {
+            match code {
+                "4" if subparams == "0" => style.attributes.unset(Attribute::Underlined),
+                "4" => style.attributes.set(Attribute::Underlined),
+                "38" | "48" => {
+                    // The color space identifier is optional
+                    let values: Vec<&str> = subparams.split(':').collect();
+                    let values = match values.as_slice() {
+                        ["2", .., r, g, b] => vec!["2", r, g, b],
+                        values => values.to_vec(),
+                    };
+                    let color = Color::parse_ansi_iter(&mut values.into_iter());
+                    set_color(style, code == "38", color);
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let code = match param {
+            "" => 0,
+            _ => match param.parse::<u8>() {
+                Ok(code) => code,
+                Err(_) => continue,
+            },
+        };
+
+        match code {
+            0 => *style = ContentStyle::default(),
+            21 => style.attributes.unset(Attribute::Bold),
+            22 => {
+                style.attributes.unset(Attribute::Bold);
+                style.attributes.unset(Attribute::Dim);
+            }
+            23 => {
+                style.attributes.unset(Attribute::Italic);
+                style.attributes.unset(Attribute::Fraktur);
+            }
+            24 => style.attributes.unset(Attribute::Underlined),
+            25 => {
+                style.attributes.unset(Attribute::SlowBlink);
+                style.attributes.unset(Attribute::RapidBlink);
+            }
+            27 => style.attributes.unset(Attribute::Reverse),
+            28 => style.attributes.unset(Attribute::Hidden),
+            29 => style.attributes.unset(Attribute::CrossedOut),
+            54 => {
+                style.attributes.unset(Attribute::Framed);
+                style.attributes.unset(Attribute::Encircled);
+            }
+            55 => style.attributes.unset(Attribute::OverLined),
+            30..=37 | 90..=97 => set_color(style, true, Some(ansi_color(code % 10, code >= 90))),
+            40..=47 | 100..=107 => {
+                set_color(style, false, Some(ansi_color(code % 10, code >= 100)))
+            }
+            39 => style.foreground_color = None,
+            49 => style.background_color = None,
+            38 | 48 | 58 => {
+                // `5;<n>` or `2;<r>;<g>;<b>`
+                let len = match params.get(idx) {
+                    Some(&"5") => 2,
+                    Some(&"2") => 4,
+                    _ => continue,
+                };
+                let end = (idx + len).min(params.len());
+                let color = Color::parse_ansi_iter(&mut params[idx..end].iter().copied());
+                idx = end;
+
+                // The underline color isn't tracked
+                if code != 58 {
+                    set_color(style, code == 38, color);
+                }
+            }
+            _ => {
+                if let Some(attribute) =
+                    Attribute::iterator().find(|attribute| attribute.sgr() == i16::from(code))
+                {
+                    style.attributes.set(attribute);
+                }
+            }
+        }
+    }
+}
+
+fn set_color(style: &mut ContentStyle, foreground: bool, color: Option<Color>) {
+    if let Some(color) = color {
+        if foreground {
+            style.foreground_color = Some(color);
+        } else {
+            style.background_color = Some(color);
+        }
+    }
+}
+
+/// Returns the color of the 3/4 bit SGR parameter (`30`-`37`, `90`-`97`, ...).
+fn ansi_color(index: u8, bright: bool) -> Color {
+    let value = index + if bright { 8 } else { 0 };
+    Color::parse_ansi(&format!("5;{}", value)).unwrap_or(Color::Reset)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::capabilities::{set_test_capabilities, Capabilities};
+    use crate::style::{Attribute, Color, ContentStyle};
+
+    use super::StyleTracker;
+
+    #[test]
+    fn test_style_is_tracked_per_writer() {
+        let mut stdout = StyleTracker::new(Vec::new());
+        let mut stderr = StyleTracker::new(Vec::new());
+
+        stdout.write_all(b"\x1B[1;38;5;9mbold red").unwrap();
+        stderr.write_all(b"\x1B[48;2;1;2;3mplain").unwrap();
+
+        let style = stdout.style();
+        assert_eq!(style.foreground_color, Some(Color::Red));
+        assert_eq!(style.background_color, None);
+        assert!(style.attributes.has(Attribute::Bold));
+
+        let style = stderr.style();
+        assert_eq!(style.foreground_color, None);
+        assert_eq!(
+            style.background_color,
+            Some(Color::Rgb { r: 1, g: 2, b: 3 })
+        );
+        assert!(style.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_split_sequences_and_resets() {
+        let mut writer = StyleTracker::new(Vec::new());
+
+        writer.write_all(b"\x1B[3").unwrap();
+        writer.write_all(b"1;4:3mtext").unwrap();
+        assert_eq!(writer.style().foreground_color, Some(Color::DarkRed));
+        assert!(writer.style().attributes.has(Attribute::Underlined));
+
+        writer.write_all(b"\x1B[24;39m").unwrap();
+        assert_eq!(writer.style(), ContentStyle::default());
+
+        // Nothing to reset
+        writer.reset_tracked_style().unwrap();
+        writer.write_all(b"\x1B[7m").unwrap();
+        writer.reset_tracked_style().unwrap();
+        assert_eq!(writer.style(), ContentStyle::default());
+
+        assert_eq!(
+            writer.into_inner(),
+            b"\x1B[31;4:3mtext\x1B[24;39m\x1B[7m\x1B[0m"
+        );
+    }
+
+    #[test]
+    fn test_sticky_style_is_reasserted_after_clear() {
+        let _lock = crate::cursor::tracking::TEST_LOCK.lock();
+        let _capabilities =
+            set_test_capabilities(Capabilities::builder().back_color_erase(false).build());
+
+        let mut writer = StyleTracker::new(Vec::new());
+        writer.set_sticky_style(true);
+
+        // Nothing to re-assert
+        writer.write_all(b"\x1B[K").unwrap();
+        writer.write_all(b"\x1B[44mstatus\x1B[2Kbar").unwrap();
+        assert_eq!(writer.style().background_color, Some(Color::DarkBlue));
+
+        assert_eq!(
+            writer.into_inner(),
+            b"\x1B[K\x1B[44mstatus\x1B[2K\x1B[48;5;4mbar"
+        );
+    }
+}
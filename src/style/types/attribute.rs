@@ -3,11 +3,13 @@ use std::fmt::Display;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::csi;
+
 use super::super::SetAttribute;
 
 // This macro generates the Attribute enum, its iterator
-// function, and the static array containing the sgr code
-// of each attribute
+// function, and the static arrays containing the sgr code
+// and the sgr sequence of each attribute
 macro_rules! Attribute {
     (
         $(
@@ -79,6 +81,11 @@ macro_rules! Attribute {
             $($sgr,)*
         ];
 
+        /// Precomputed `CSI <sgr> m` sequences of the attributes.
+        static SGR_SEQUENCES: &[&str] = &[
+            $(csi!($sgr, "m"),)*
+        ];
+
         impl Attribute {
             /// Iterates over all the variants of the Attribute enum.
             pub fn iterator() -> impl Iterator<Item = Attribute> {
@@ -166,4 +173,24 @@ impl Attribute {
     pub fn sgr(self) -> i16 {
         SGR[self as usize]
     }
+
+    /// Returns the precomputed `CSI <sgr> m` sequence.
+    pub(crate) fn sgr_sequence(self) -> &'static str {
+        SGR_SEQUENCES[self as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Attribute;
+
+    #[test]
+    fn test_sgr_sequence() {
+        for attribute in Attribute::iterator() {
+            assert_eq!(
+                attribute.sgr_sequence(),
+                format!("\x1B[{}m", attribute.sgr())
+            );
+        }
+    }
 }
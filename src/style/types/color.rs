@@ -90,6 +90,26 @@ pub enum Color {
     AnsiValue(u8),
 }
 
+/// The colors of the ANSI values 0 - 15.
+const ANSI_BASE_COLORS: [Color; 16] = [
+    Color::Black,       // 0
+    Color::DarkRed,     // 1
+    Color::DarkGreen,   // 2
+    Color::DarkYellow,  // 3
+    Color::DarkBlue,    // 4
+    Color::DarkMagenta, // 5
+    Color::DarkCyan,    // 6
+    Color::Grey,        // 7
+    Color::DarkGrey,    // 8
+    Color::Red,         // 9
+    Color::Green,       // 10
+    Color::Yellow,      // 11
+    Color::Blue,        // 12
+    Color::Magenta,     // 13
+    Color::Cyan,        // 14
+    Color::White,       // 15
+];
+
 impl Color {
     /// Parses an ANSI color sequence.
     ///
@@ -121,28 +141,10 @@ impl Color {
             5 => {
                 let n = parse_next_u8(values)?;
 
-                use Color::*;
-                [
-                    Black,       // 0
-                    DarkRed,     // 1
-                    DarkGreen,   // 2
-                    DarkYellow,  // 3
-                    DarkBlue,    // 4
-                    DarkMagenta, // 5
-                    DarkCyan,    // 6
-                    Grey,        // 7
-                    DarkGrey,    // 8
-                    Red,         // 9
-                    Green,       // 10
-                    Yellow,      // 11
-                    Blue,        // 12
-                    Magenta,     // 13
-                    Cyan,        // 14
-                    White,       // 15
-                ]
-                .get(n as usize)
-                .copied()
-                .unwrap_or(Color::AnsiValue(n))
+                ANSI_BASE_COLORS
+                    .get(n as usize)
+                    .copied()
+                    .unwrap_or(Color::AnsiValue(n))
             }
 
             // 24 bit colors: `2;<r>;<g>;<b>`
@@ -160,6 +162,152 @@ impl Color {
         }
         Some(color)
     }
+
+    /// Returns the `(r, g, b)` components of the color, `None` for the [`Color::Reset`].
+    ///
+    /// The 16 base colors (and the first 16 ANSI values) use the default xterm palette, the
+    /// actual colors depend on the terminal theme.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossterm::style::Color;
+    ///
+    /// assert_eq!(Color::DarkRed.to_rgb(), Some((205, 0, 0)));
+    /// assert_eq!(Color::AnsiValue(196).to_rgb(), Some((255, 0, 0)));
+    /// assert_eq!(Color::Reset.to_rgb(), None);
+    /// ```
+    pub fn to_rgb(self) -> Option<(u8, u8, u8)> {
+        const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+        let rgb = match self {
+            Color::Reset => return None,
+            Color::Black => (0, 0, 0),
+            Color::DarkRed => (205, 0, 0),
+            Color::DarkGreen => (0, 205, 0),
+            Color::DarkYellow => (205, 205, 0),
+            Color::DarkBlue => (0, 0, 238),
+            Color::DarkMagenta => (205, 0, 205),
+            Color::DarkCyan => (0, 205, 205),
+            Color::Grey => (229, 229, 229),
+            Color::DarkGrey => (127, 127, 127),
+            Color::Red => (255, 0, 0),
+            Color::Green => (0, 255, 0),
+            Color::Yellow => (255, 255, 0),
+            Color::Blue => (92, 92, 255),
+            Color::Magenta => (255, 0, 255),
+            Color::Cyan => (0, 255, 255),
+            Color::White => (255, 255, 255),
+            Color::Rgb { r, g, b } => (r, g, b),
+            Color::AnsiValue(n @ 0..=15) => return ANSI_BASE_COLORS[n as usize].to_rgb(),
+            // 6x6x6 color cube
+            Color::AnsiValue(n @ 16..=231) => {
+                let n = n - 16;
+                (
+                    CUBE_LEVELS[(n / 36) as usize],
+                    CUBE_LEVELS[(n / 6 % 6) as usize],
+                    CUBE_LEVELS[(n % 6) as usize],
+                )
+            }
+            // Grayscale ramp
+            Color::AnsiValue(n) => {
+                let level = 8 + 10 * (n - 232);
+                (level, level, level)
+            }
+        };
+        Some(rgb)
+    }
+
+    /// Composites the `over` color with the `alpha` (`0.0` transparent, `1.0` opaque) over
+    /// this color, returns the [`Color::Rgb`].
+    ///
+    /// Returns `None` if any of the colors is the [`Color::Reset`], its value is unknown. See
+    /// the [`Color::to_rgb`] for the values of the other colors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossterm::style::Color;
+    ///
+    /// // A selection highlight tinting the background
+    /// let background = Color::Rgb { r: 0, g: 0, b: 0 };
+    /// assert_eq!(
+    ///     background.blend(Color::Rgb { r: 0, g: 0, b: 200 }, 0.25),
+    ///     Some(Color::Rgb { r: 0, g: 0, b: 50 })
+    /// );
+    /// ```
+    pub fn blend(self, over: Color, alpha: f32) -> Option<Color> {
+        let (r, g, b) = self.to_rgb()?;
+        let (over_r, over_g, over_b) = over.to_rgb()?;
+        let alpha = alpha.clamp(0.0, 1.0);
+        let channel = |below: u8, over: u8| {
+            (over as f32 * alpha + below as f32 * (1.0 - alpha)).round() as u8
+        };
+
+        Some(Color::Rgb {
+            r: channel(r, over_r),
+            g: channel(g, over_g),
+            b: channel(b, over_b),
+        })
+    }
+
+    /// Returns the relative luminance (`0.0` black to `1.0` white) as defined by the
+    /// [WCAG](https://www.w3.org/TR/WCAG21/#dfn-relative-luminance), `None` for the
+    /// [`Color::Reset`].
+    pub fn luminance(self) -> Option<f32> {
+        let (r, g, b) = self.to_rgb()?;
+        let linear = |channel: u8| {
+            let c = channel as f32 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+    }
+
+    /// Returns the [WCAG contrast ratio](https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio)
+    /// (`1.0` to `21.0`) of the colors, `None` if any of them is the [`Color::Reset`].
+    ///
+    /// The WCAG recommends at least `4.5` for the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossterm::style::Color;
+    ///
+    /// let ratio = Color::Black.contrast_ratio(Color::White).unwrap();
+    /// assert!((ratio - 21.0).abs() < 0.001);
+    /// assert!(Color::Yellow.contrast_ratio(Color::White).unwrap() < 4.5);
+    /// ```
+    pub fn contrast_ratio(self, other: Color) -> Option<f32> {
+        let (l1, l2) = (self.luminance()?, other.luminance()?);
+        let (lighter, darker) = if l1 > l2 { (l1, l2) } else { (l2, l1) };
+        Some((lighter + 0.05) / (darker + 0.05))
+    }
+
+    /// Returns the [`Color::Black`] or the [`Color::White`], whichever is more readable over
+    /// this (background) color, `None` for the [`Color::Reset`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossterm::style::Color;
+    ///
+    /// assert_eq!(Color::Yellow.readable_foreground(), Some(Color::Black));
+    /// assert_eq!(Color::DarkBlue.readable_foreground(), Some(Color::White));
+    /// ```
+    pub fn readable_foreground(self) -> Option<Color> {
+        let black = self.contrast_ratio(Color::Black)?;
+        let white = self.contrast_ratio(Color::White)?;
+        Some(if black >= white {
+            Color::Black
+        } else {
+            Color::White
+        })
+    }
 }
 
 impl TryFrom<&str> for Color {
@@ -362,6 +510,51 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_to_rgb() {
+        assert_eq!(Color::AnsiValue(1).to_rgb(), Color::DarkRed.to_rgb());
+        assert_eq!(Color::AnsiValue(16).to_rgb(), Some((0, 0, 0)));
+        assert_eq!(Color::AnsiValue(110).to_rgb(), Some((135, 175, 215)));
+        assert_eq!(Color::AnsiValue(231).to_rgb(), Some((255, 255, 255)));
+        assert_eq!(Color::AnsiValue(232).to_rgb(), Some((8, 8, 8)));
+        assert_eq!(Color::AnsiValue(255).to_rgb(), Some((238, 238, 238)));
+    }
+
+    #[test]
+    fn test_blend() {
+        let red = Color::Rgb { r: 255, g: 0, b: 0 };
+        assert_eq!(Color::Black.blend(red, 0.0), Some(Color::from((0, 0, 0))));
+        assert_eq!(Color::Black.blend(red, 1.0), Some(red));
+        assert_eq!(
+            Color::White.blend(red, 0.5),
+            Some(Color::from((255, 128, 128)))
+        );
+        // Out of range alpha is clamped
+        assert_eq!(Color::Black.blend(red, 2.0), Some(red));
+        assert_eq!(Color::Reset.blend(red, 0.5), None);
+        assert_eq!(red.blend(Color::Reset, 0.5), None);
+    }
+
+    #[test]
+    fn test_contrast() {
+        assert_eq!(Color::Black.luminance(), Some(0.0));
+        assert_eq!(Color::White.luminance(), Some(1.0));
+        assert_eq!(Color::White.contrast_ratio(Color::White), Some(1.0));
+        assert!((Color::Black.contrast_ratio(Color::White).unwrap() - 21.0).abs() < 0.001);
+        assert_eq!(
+            Color::Black.contrast_ratio(Color::White),
+            Color::White.contrast_ratio(Color::Black)
+        );
+        // #777777 on white is the commonly cited borderline gray
+        let ratio = Color::from((0x77, 0x77, 0x77))
+            .contrast_ratio(Color::White)
+            .unwrap();
+        assert!((ratio - 4.48).abs() < 0.01, "{}", ratio);
+        assert_eq!(Color::Reset.contrast_ratio(Color::White), None);
+        assert_eq!(Color::Grey.readable_foreground(), Some(Color::Black));
+        assert_eq!(Color::Reset.readable_foreground(), None);
+    }
 }
 
 #[cfg(test)]
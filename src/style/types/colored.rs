@@ -3,8 +3,38 @@ use std::fmt::{self, Formatter};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::csi;
 use crate::style::{parse_next_u8, Color};
 
+/// Generates a table of `CSI <prefix>;5;<n>m` sequences for all the 256 ANSI values.
+macro_rules! ansi_value_sequences {
+    ($prefix:literal) => {
+        ansi_value_sequences!(
+            $prefix;
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30
+            31 32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58
+            59 60 61 62 63 64 65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80 81 82 83 84 85 86
+            87 88 89 90 91 92 93 94 95 96 97 98 99 100 101 102 103 104 105 106 107 108 109 110
+            111 112 113 114 115 116 117 118 119 120 121 122 123 124 125 126 127 128 129 130 131
+            132 133 134 135 136 137 138 139 140 141 142 143 144 145 146 147 148 149 150 151 152
+            153 154 155 156 157 158 159 160 161 162 163 164 165 166 167 168 169 170 171 172 173
+            174 175 176 177 178 179 180 181 182 183 184 185 186 187 188 189 190 191 192 193 194
+            195 196 197 198 199 200 201 202 203 204 205 206 207 208 209 210 211 212 213 214 215
+            216 217 218 219 220 221 222 223 224 225 226 227 228 229 230 231 232 233 234 235 236
+            237 238 239 240 241 242 243 244 245 246 247 248 249 250 251 252 253 254 255
+        )
+    };
+    ($prefix:literal; $($n:literal)*) => {
+        [$(csi!($prefix, ";5;", $n, "m"),)*]
+    };
+}
+
+/// Precomputed foreground color sequences for all the ANSI values.
+static FOREGROUND_ANSI_VALUE_SEQUENCES: [&str; 256] = ansi_value_sequences!("38");
+
+/// Precomputed background color sequences for all the ANSI values.
+static BACKGROUND_ANSI_VALUE_SEQUENCES: [&str; 256] = ansi_value_sequences!("48");
+
 /// Represents a foreground or background color.
 ///
 /// This can be converted to a [Colors](struct.Colors.html) by calling `into()` and applied
@@ -61,6 +91,46 @@ impl Colored {
     }
 }
 
+impl Colored {
+    /// Returns the precomputed `CSI ... m` sequence setting this color.
+    ///
+    /// Returns `None` for the RGB colors, they have to be formatted.
+    pub(crate) fn sgr_sequence(self) -> Option<&'static str> {
+        let (color, sequences, reset) = match self {
+            Colored::ForegroundColor(color) => {
+                (color, &FOREGROUND_ANSI_VALUE_SEQUENCES, csi!("39m"))
+            }
+            Colored::BackgroundColor(color) => {
+                (color, &BACKGROUND_ANSI_VALUE_SEQUENCES, csi!("49m"))
+            }
+        };
+
+        let value = match color {
+            Color::Reset => return Some(reset),
+            Color::Black => 0,
+            Color::DarkGrey => 8,
+            Color::Red => 9,
+            Color::DarkRed => 1,
+            Color::Green => 10,
+            Color::DarkGreen => 2,
+            Color::Yellow => 11,
+            Color::DarkYellow => 3,
+            Color::Blue => 12,
+            Color::DarkBlue => 4,
+            Color::Magenta => 13,
+            Color::DarkMagenta => 5,
+            Color::Cyan => 14,
+            Color::DarkCyan => 6,
+            Color::White => 15,
+            Color::Grey => 7,
+            Color::AnsiValue(value) => value,
+            Color::Rgb { .. } => return None,
+        };
+
+        Some(sequences[value as usize])
+    }
+}
+
 impl fmt::Display for Colored {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let color;
@@ -148,6 +218,30 @@ mod tests {
         assert_eq!(colored.to_string(), "38;5;255");
     }
 
+    #[test]
+    fn test_sgr_sequence() {
+        assert_eq!(
+            Colored::ForegroundColor(Color::Reset).sgr_sequence(),
+            Some("\x1B[39m")
+        );
+        assert_eq!(
+            Colored::BackgroundColor(Color::Rgb { r: 1, g: 2, b: 3 }).sgr_sequence(),
+            None
+        );
+
+        for value in 0..=255 {
+            for colored in [
+                Colored::ForegroundColor(Color::AnsiValue(value)),
+                Colored::BackgroundColor(Color::AnsiValue(value)),
+            ] {
+                assert_eq!(
+                    colored.sgr_sequence().unwrap(),
+                    format!("\x1B[{}m", colored)
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_parse_ansi_fg() {
         test_parse_ansi(Colored::ForegroundColor)
@@ -83,19 +83,35 @@
 //!
 //! For manual execution control check out [crossterm::queue](../macro.queue.html).
 
-use std::fmt;
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
+#[cfg(windows)]
+use std::os::windows::io::RawHandle;
+use std::{
+    fmt, io,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 #[cfg(windows)]
 use crossterm_winapi::{ConsoleMode, Handle, ScreenBuffer};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 #[cfg(windows)]
-use winapi::um::wincon::ENABLE_WRAP_AT_EOL_OUTPUT;
+use winapi::um::{wincon::ENABLE_WRAP_AT_EOL_OUTPUT, wincontypes::HPCON};
 
+use crate::capabilities::{capabilities, Capabilities, Capability};
+use crate::cursor::tracking;
 #[doc(no_inline)]
 use crate::Command;
-use crate::{csi, impl_display, Result};
+use crate::{csi, impl_display, QueueableCommand, Result};
+
+use self::snapshot::DecModes;
 
+pub use self::capture::{clear_captured_output, OutputCapture};
+pub use self::snapshot::ModeSnapshot;
+
+mod capture;
+pub(crate) mod snapshot;
 pub(crate) mod sys;
 
 /// Tells whether the raw mode is enabled.
@@ -127,13 +143,513 @@ pub fn disable_raw_mode() -> Result<()> {
     sys::disable_raw_mode()
 }
 
+/// Hands the terminal over to the closure, e.g. to run an external editor.
+///
+/// The current modes are captured with the [ModeSnapshot](struct.ModeSnapshot.html), the
+/// raw mode is disabled and the main screen with the visible cursor is restored before
+/// the closure runs. The captured modes are restored afterwards and a resize event is
+/// emitted, the application redraws its screen as on every resize.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::process::Command;
+///
+/// use crossterm::{terminal, Result};
+///
+/// fn edit(path: &str) -> Result<()> {
+///     let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+///     terminal::suspend_to(|| Command::new(editor).arg(path).status())??;
+///     Ok(())
+/// }
+/// ```
+///
+/// # Notes
+///
+/// * The commands are written to the [output stream](fn.output_stream.html).
+/// * The resize event is emitted with the `events` feature only.
+pub fn suspend_to<T>(f: impl FnOnce() -> T) -> Result<T> {
+    let mut output = output_stream().writer();
+    let snapshot = ModeSnapshot::capture()?;
+
+    snapshot::reset(&mut output)?;
+    let result = f();
+    snapshot.restore(&mut output)?;
+
+    #[cfg(feature = "events")]
+    sys::emit_resize()?;
+
+    Ok(result)
+}
+
+/// Stops the process like the `Ctrl+Z` in the shell does, the terminal is restored while
+/// it's stopped.
+///
+/// It's the [suspend_to](fn.suspend_to.html) stopping the process (`SIGSTOP`), it returns
+/// when the process is continued (e.g. `fg`) and the modes are restored. The raw mode turns
+/// off the signal generating keys, the applications call it on the `Ctrl+Z` key event.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{
+///     event::{read, Event, KeyCode, KeyEvent, KeyModifiers},
+///     terminal, Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     terminal::enable_raw_mode()?;
+///
+///     loop {
+///         match read()? {
+///             Event::Key(KeyEvent {
+///                 code: KeyCode::Char('z'),
+///                 modifiers: KeyModifiers::CONTROL,
+///                 ..
+///             }) => terminal::suspend_process()?,
+///             Event::Key(KeyEvent {
+///                 code: KeyCode::Esc, ..
+///             }) => break,
+///             _ => {}
+///         }
+///     }
+///
+///     terminal::disable_raw_mode()
+/// }
+/// ```
+#[cfg(unix)]
+pub fn suspend_process() -> Result<()> {
+    suspend_to(sys::stop_process)?
+}
+
+/// The stream the terminal interface is drawn to.
+///
+/// The convenience functions writing to the terminal on their own (the
+/// [suspend_to](fn.suspend_to.html), the [exit guard](fn.enable_exit_guard.html), the
+/// terminal queries, ...) write to it. The interfaces drawn to the stderr keep the stdout
+/// for the data, e.g. `cd "$(picker)"`, see the [set_output_stream](fn.set_output_stream.html).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputStream {
+    /// The standard output.
+    #[default]
+    Stdout,
+    /// The standard error.
+    Stderr,
+}
+
+impl OutputStream {
+    /// Returns a writer of the stream.
+    pub(crate) fn writer(self) -> Box<dyn io::Write> {
+        match self {
+            OutputStream::Stdout => Box::new(io::stdout()),
+            OutputStream::Stderr => Box::new(io::stderr()),
+        }
+    }
+}
+
+/// `true` if the interface is drawn to the stderr.
+static OUTPUT_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Sets the stream the convenience functions write to, the stdout by default.
+///
+/// The commands executed/queued by the application are written where it writes them, this
+/// is for the functions writing to the terminal on their own.
+pub fn set_output_stream(stream: OutputStream) {
+    OUTPUT_TO_STDERR.store(stream == OutputStream::Stderr, Ordering::SeqCst);
+}
+
+/// Returns the stream the convenience functions write to.
+pub fn output_stream() -> OutputStream {
+    if OUTPUT_TO_STDERR.load(Ordering::SeqCst) {
+        OutputStream::Stderr
+    } else {
+        OutputStream::Stdout
+    }
+}
+
+/// Whether the exit guard was registered.
+static EXIT_GUARD_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Registers a cleanup resetting the terminal when the process exits.
+///
+/// The cleanup runs on the abrupt exits bypassing the `Drop` implementations and the panic
+/// hooks, e.g. the `std::process::exit`. It resets the colors and the attributes, turns off
+/// the DEC private modes set by the commands (like the [ModeSnapshot](struct.ModeSnapshot.html)
+/// restore does) and leaves the raw mode. Calling it again does nothing.
+///
+/// # Notes
+///
+/// * The cleanup is registered with the `atexit` on UNIX. It's a console control handler
+///   (closing the console window, logging off, `Ctrl+Break`) on Windows.
+/// * It can't be unregistered. The reset is harmless if the application cleaned up already,
+///   just the modes still turned on are turned off.
+/// * The commands are written to the [output stream](fn.output_stream.html).
+pub fn enable_exit_guard() -> Result<()> {
+    if EXIT_GUARD_ENABLED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let result = sys::register_exit_guard();
+    if result.is_err() {
+        EXIT_GUARD_ENABLED.store(false, Ordering::SeqCst);
+    }
+    result
+}
+
+/// Resets the terminal when the process exits, run by the exit guard.
+pub(crate) fn reset_at_exit() {
+    let mut output = output_stream().writer();
+
+    #[cfg(feature = "style")]
+    let _ = output.queue(crate::style::ResetColor);
+    let _ = snapshot::reset(&mut output);
+}
+
 /// Returns the terminal size `(columns, rows)`.
 ///
-/// The top left cell is represented `(1, 1)`.
+/// The top left cell is represented `(1, 1)`. The tuple converts to a
+/// [Size](struct.Size.html) with the `Size::from`.
+///
+/// The controlling terminal is queried on UNIX. If there's none, the stdout, the stderr and
+/// the stdin are tried in turn, so the stderr only interfaces get the size with the stdout
+/// redirected.
 pub fn size() -> Result<(u16, u16)> {
     sys::size()
 }
 
+/// Returns the number of the terminal columns.
+///
+/// It's a shortcut for the first element of the [size](fn.size.html).
+pub fn columns() -> Result<u16> {
+    size().map(|(columns, _)| columns)
+}
+
+/// Returns the number of the terminal rows.
+///
+/// It's a shortcut for the second element of the [size](fn.size.html).
+pub fn rows() -> Result<u16> {
+    size().map(|(_, rows)| rows)
+}
+
+/// Returns the size `(columns, rows)` of the terminal the file descriptor refers to.
+///
+/// Unlike the [size](fn.size.html), which queries the controlling terminal (`/dev/tty`)
+/// or the stdout, it's meant for the programs writing to another terminal, e.g. the
+/// stderr only interfaces. It fails if the descriptor isn't a terminal.
+#[cfg(unix)]
+pub fn size_of(fd: RawFd) -> Result<(u16, u16)> {
+    sys::size_of(fd)
+}
+
+/// Returns the size `(columns, rows)` of the console screen buffer the handle refers to.
+///
+/// Unlike the [size](fn.size.html), which queries the active screen buffer (`CONOUT$`),
+/// it's meant for the programs writing to another console handle, e.g. the stderr only
+/// interfaces. It fails if the handle isn't a console screen buffer.
+#[cfg(windows)]
+pub fn size_of(handle: RawHandle) -> Result<(u16, u16)> {
+    sys::size_of(handle)
+}
+
+/// Sets the size `(columns, rows)` of the pseudo terminal.
+///
+/// Unlike the [SetSize](struct.SetSize.html), which asks the terminal emulator to resize
+/// its window, it's meant for the programs owning the master side of a pseudo terminal
+/// (e.g. multiplexers), which must report the size of the area the pty is displayed in to
+/// the program running in it. The program is notified with the `SIGWINCH`.
+#[cfg(unix)]
+pub fn set_size_of(fd: RawFd, columns: u16, rows: u16) -> Result<()> {
+    sys::set_size_of(fd, columns, rows)
+}
+
+/// Sets the size `(columns, rows)` of the pseudo console.
+///
+/// Unlike the [SetSize](struct.SetSize.html), which resizes the console window, it's meant
+/// for the programs owning a pseudo console (e.g. multiplexers), which must report the
+/// size of the area the console is displayed in to the program running in it.
+#[cfg(windows)]
+pub fn set_size_of(console: HPCON, columns: u16, rows: u16) -> Result<()> {
+    sys::set_size_of(console, columns, rows)
+}
+
+/// A rectangular area of the terminal screen.
+///
+/// The top left cell of the screen is represented as `0,0`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// The column of the top left cell.
+    pub x: u16,
+    /// The row of the top left cell.
+    pub y: u16,
+    /// The number of columns.
+    pub width: u16,
+    /// The number of rows.
+    pub height: u16,
+}
+
+impl Rect {
+    /// Creates a new `Rect`.
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates a new `Rect` of the given size with the top left cell at the position.
+    pub fn from_parts(position: Position, size: Size) -> Rect {
+        Rect::new(position.x, position.y, size.width, size.height)
+    }
+
+    /// Returns the position of the top left cell.
+    pub fn position(&self) -> Position {
+        Position::new(self.x, self.y)
+    }
+
+    /// Returns the size of the area.
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    /// Returns whether the area contains no cells.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns whether the cell (column, row) lies within the area.
+    pub fn contains(&self, column: u16, row: u16) -> bool {
+        column >= self.x
+            && row >= self.y
+            && u32::from(column) < u32::from(self.x) + u32::from(self.width)
+            && u32::from(row) < u32::from(self.y) + u32::from(self.height)
+    }
+
+    /// Returns the cell of the area closest to the given cell (column, row), `None` if the
+    /// area is empty.
+    pub fn clamp(&self, column: u16, row: u16) -> Option<(u16, u16)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let right = self.x.saturating_add(self.width - 1);
+        let bottom = self.y.saturating_add(self.height - 1);
+        Some((column.clamp(self.x, right), row.clamp(self.y, bottom)))
+    }
+}
+
+/// A cell of the terminal screen.
+///
+/// The position is 0-based, the top left cell is represented as `0,0`. Both the cursor
+/// commands and the mouse events use the 0-based positions, the 1-based positions used
+/// by the escape sequences can be converted with the
+/// [from_one_based](struct.Position.html#method.from_one_based) and the
+/// [to_one_based](struct.Position.html#method.to_one_based).
+///
+/// The tuples convert to the positions in the (column, row) order, the one used by the
+/// functions returning the positions (e.g. the `cursor::position`).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// The 0-based column.
+    pub x: u16,
+    /// The 0-based row.
+    pub y: u16,
+}
+
+impl Position {
+    /// Creates a new `Position` from the 0-based column and row.
+    pub fn new(x: u16, y: u16) -> Position {
+        Position { x, y }
+    }
+
+    /// Creates a new `Position` from the 1-based column and row.
+    ///
+    /// The `0` is treated as `1`, like the terminals do.
+    pub fn from_one_based(x: u16, y: u16) -> Position {
+        Position::new(x.saturating_sub(1), y.saturating_sub(1))
+    }
+
+    /// Returns the 1-based column and row.
+    pub fn to_one_based(self) -> (u16, u16) {
+        (self.x.saturating_add(1), self.y.saturating_add(1))
+    }
+}
+
+impl From<(u16, u16)> for Position {
+    fn from((x, y): (u16, u16)) -> Position {
+        Position::new(x, y)
+    }
+}
+
+impl From<Position> for (u16, u16) {
+    fn from(position: Position) -> (u16, u16) {
+        (position.x, position.y)
+    }
+}
+
+/// The size of the terminal screen or of an area of it.
+///
+/// The tuples convert to the sizes in the (columns, rows) order, the one used by the
+/// [size](fn.size.html) and the `Event::Resize`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Size {
+    /// The number of columns.
+    pub width: u16,
+    /// The number of rows.
+    pub height: u16,
+}
+
+impl Size {
+    /// Creates a new `Size`.
+    pub fn new(width: u16, height: u16) -> Size {
+        Size { width, height }
+    }
+
+    /// Returns whether the size contains no cells.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns whether the position lies within the area of this size starting at `0,0`.
+    pub fn contains(&self, position: Position) -> bool {
+        position.x < self.width && position.y < self.height
+    }
+}
+
+impl From<(u16, u16)> for Size {
+    fn from((width, height): (u16, u16)) -> Size {
+        Size::new(width, height)
+    }
+}
+
+impl From<Size> for (u16, u16) {
+    fn from(size: Size) -> (u16, u16) {
+        (size.width, size.height)
+    }
+}
+
+/// An index of a column or a row, stating whether it's 0-based or 1-based.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Index {
+    /// The 0-based index, used by the crate (the first column is `0`).
+    ZeroBased(u16),
+    /// The 1-based index, used by the escape sequences (the first column is `1`).
+    ///
+    /// The `0` is treated as `1`, like the terminals do.
+    OneBased(u16),
+}
+
+impl Index {
+    /// Returns the 0-based index.
+    pub fn zero_based(self) -> u16 {
+        match self {
+            Index::ZeroBased(index) => index,
+            Index::OneBased(index) => index.saturating_sub(1),
+        }
+    }
+
+    /// Returns the 1-based index.
+    pub fn one_based(self) -> u16 {
+        self.zero_based().saturating_add(1)
+    }
+}
+
+/// Returns whether the line wrapping is enabled.
+///
+/// It allows to restore the original setting after the wrapping was disabled temporarily
+/// with the [DisableLineWrap](struct.DisableLineWrap.html).
+///
+/// # Notes
+///
+/// * On UNIX, the mode is queried with the `DECRQM` escape sequence, it fails if the terminal
+///   doesn't answer within 2 seconds or doesn't recognize the mode.
+/// * On Windows, the console mode of the current screen buffer is checked.
+/// * On UNIX, it requires the `events` feature to read the answer.
+#[cfg(any(windows, feature = "events"))]
+pub fn is_line_wrap_enabled() -> Result<bool> {
+    sys::is_line_wrap_enabled()
+}
+
+/// Returns whether the terminal supports the [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/),
+/// i.e. whether the [PushKeyboardEnhancementFlags](../event/struct.PushKeyboardEnhancementFlags.html)
+/// has an effect.
+///
+/// The answers to the query are consumed, they don't show up as the events. The events
+/// the user triggers while the terminal answers are kept for the [read](../event/fn.read.html).
+///
+/// # Notes
+///
+/// * On UNIX, the flags are queried with the `CSI ? u` escape sequence followed by the primary
+///   device attributes query, it fails if the terminal doesn't answer within 2 seconds.
+/// * On Windows, it returns `false`. The console reports the key releases and repeats
+///   natively, see the [PushKeyboardEnhancementFlags](../event/struct.PushKeyboardEnhancementFlags.html).
+#[cfg(feature = "events")]
+pub fn supports_keyboard_enhancement() -> Result<bool> {
+    #[cfg(unix)]
+    {
+        sys::supports_keyboard_enhancement()
+    }
+
+    #[cfg(windows)]
+    {
+        Ok(false)
+    }
+}
+
+/// The device attributes reported by the terminal, see the
+/// [device_attributes](fn.device_attributes.html).
+#[cfg(feature = "events")]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Hash)]
+pub struct DeviceAttributes {
+    /// The terminal class (the conformance level), e.g. `62` for the VT220 and `64` for the
+    /// VT420 one, `1` for the VT100.
+    pub class: u16,
+    /// The supported features, e.g. `4` for the sixel graphics and `22` for the ANSI colors.
+    pub features: Vec<u16>,
+    /// The terminal type, e.g. `41` for the xterm and `1` for many of the terminals
+    /// emulating the VT220. `None` if the terminal doesn't report it.
+    pub terminal_type: Option<u16>,
+    /// The version of the terminal, its meaning depends on the terminal. `None` if the
+    /// terminal doesn't report it.
+    pub version: Option<u16>,
+}
+
+/// Returns the primary and the secondary device attributes (`DA1` and `DA2`) of the terminal,
+/// its capabilities and its type and version.
+///
+/// The answers to the query are consumed, they don't show up as the events, even while an
+/// [EventStream](../event/struct.EventStream.html) is reading them. The events the user
+/// triggers while the terminal answers are kept for the [read](../event/fn.read.html).
+///
+/// # Notes
+///
+/// * On UNIX, the secondary device attributes are queried with the `CSI > c` escape sequence
+///   followed by the primary ones (`CSI c`), it fails if the terminal doesn't answer within
+///   2 seconds.
+/// * On Windows, it fails with the `Unsupported` error, the console input doesn't report
+///   the answers.
+#[cfg(feature = "events")]
+pub fn device_attributes() -> Result<DeviceAttributes> {
+    #[cfg(unix)]
+    {
+        sys::device_attributes()
+    }
+
+    #[cfg(windows)]
+    {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "The device attributes can't be queried on Windows",
+        ))
+    }
+}
+
 /// Disables line wrapping.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DisableLineWrap;
@@ -151,6 +667,10 @@ impl Command for DisableLineWrap {
         console_mode.set_mode(new_mode)?;
         Ok(())
     }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::LINE_WRAP_DISABLED, true);
+    }
 }
 
 /// Enable line wrapping.
@@ -170,14 +690,29 @@ impl Command for EnableLineWrap {
         console_mode.set_mode(new_mode)?;
         Ok(())
     }
+
+    fn update_state(&self) {
+        snapshot::set_dec_mode(DecModes::LINE_WRAP_DISABLED, false);
+    }
 }
 
 /// A command that switches to alternate screen.
 ///
+/// The alternate screen is emulated on the terminals without it (see the
+/// [alternate_screen](../capabilities/struct.Capabilities.html#method.alternate_screen)
+/// capability): the screen is cleared. The
+/// [LeaveAlternateScreen](./struct.LeaveAlternateScreen.html) clears the screen again and
+/// re-prints the text captured with the [OutputCapture](struct.OutputCapture.html), the
+/// cursor is left after it. The emulated alternate screen isn't entered twice.
+///
 /// # Notes
 ///
 /// * Commands must be executed/queued for execution otherwise they do nothing.
 /// * Use [LeaveAlternateScreen](./struct.LeaveAlternateScreen.html) command to leave the entered alternate screen.
+/// * The [tracked position](../cursor/fn.tracked_position.html) of the cursor on the main
+///   screen is restored by the [LeaveAlternateScreen](./struct.LeaveAlternateScreen.html),
+///   some terminals don't restore it themselves. Call the
+///   [position](../cursor/fn.position.html) first if it's unknown.
 ///
 /// # Examples
 ///
@@ -199,6 +734,14 @@ pub struct EnterAlternateScreen;
 
 impl Command for EnterAlternateScreen {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if !capabilities().alternate_screen() {
+            if snapshot::is_dec_mode(DecModes::ALTERNATE_SCREEN) {
+                // The emulated screen would be cleared
+                return Ok(());
+            }
+            // Clear the screen & move the cursor home
+            return f.write_str(concat!(csi!("2J"), csi!("H")));
+        }
         f.write_str(csi!("?1049h"))
     }
 
@@ -208,10 +751,28 @@ impl Command for EnterAlternateScreen {
         alternate_screen.show()?;
         Ok(())
     }
+
+    fn update_state(&self) {
+        let active = snapshot::is_dec_mode(DecModes::ALTERNATE_SCREEN);
+        if !capabilities().alternate_screen() && active {
+            return;
+        }
+
+        if !active {
+            tracking::save_main_screen_position();
+        }
+        tracking::invalidate();
+        snapshot::set_dec_mode(DecModes::ALTERNATE_SCREEN, true);
+    }
 }
 
 /// A command that switches back to the main screen.
 ///
+/// The cursor is moved back to its [tracked position](../cursor/fn.tracked_position.html)
+/// on the main screen before the [EnterAlternateScreen](./struct.EnterAlternateScreen.html),
+/// if it was known. The prompt of an inline tool going full-screen for a while is
+/// returned to the exact spot, even by the terminals not restoring the cursor.
+///
 /// # Notes
 ///
 /// * Commands must be executed/queued for execution otherwise they do nothing.
@@ -237,15 +798,169 @@ pub struct LeaveAlternateScreen;
 
 impl Command for LeaveAlternateScreen {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
-        f.write_str(csi!("?1049l"))
+        if !capabilities().alternate_screen() {
+            if !snapshot::is_dec_mode(DecModes::ALTERNATE_SCREEN) {
+                // The main screen would be cleared
+                return Ok(());
+            }
+            // The cursor is left after the re-printed text, where it was on the main screen
+            let rows = size().map_or(usize::MAX, |(_, rows)| usize::from(rows));
+            f.write_str(concat!(csi!("2J"), csi!("H")))?;
+            return f.write_str(&capture::captured_lines(rows));
+        }
+        f.write_str(csi!("?1049l"))?;
+
+        if let Some((column, row)) = tracking::main_screen_position() {
+            write!(
+                f,
+                csi!("{};{}H"),
+                row.saturating_add(1),
+                column.saturating_add(1)
+            )?;
+        }
+        Ok(())
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
+        // The console keeps the cursor of the main screen buffer
         let screen_buffer = ScreenBuffer::from(Handle::current_out_handle()?);
         screen_buffer.show()?;
         Ok(())
     }
+
+    fn update_state(&self) {
+        if !capabilities().alternate_screen() && !snapshot::is_dec_mode(DecModes::ALTERNATE_SCREEN)
+        {
+            return;
+        }
+
+        tracking::invalidate();
+        snapshot::set_dec_mode(DecModes::ALTERNATE_SCREEN, false);
+        // The emulated screen leaves the cursor after the re-printed text
+        if capabilities().alternate_screen() {
+            tracking::restore_main_screen_position();
+        } else {
+            capture::clear_captured_output();
+        }
+    }
+}
+
+/// A command that tells the terminal to hold the screen updates until the
+/// [EndSynchronizedUpdate](./struct.EndSynchronizedUpdate.html).
+///
+/// The terminal presents the whole frame at once, the user doesn't see it drawn partially.
+/// See the [begin_frame](./fn.begin_frame.html) too.
+///
+/// # Notes
+///
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+/// * The terminals which don't support it (mode 2026) ignore it.
+/// * It does nothing on Windows with the WinAPI, the console updates the screen per call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeginSynchronizedUpdate;
+
+impl Command for BeginSynchronizedUpdate {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2026h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command that tells the terminal to present the updates held since the
+/// [BeginSynchronizedUpdate](./struct.BeginSynchronizedUpdate.html).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndSynchronizedUpdate;
+
+impl Command for EndSynchronizedUpdate {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str(csi!("?2026l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Begins a synchronized update of the screen, see the
+/// [BeginSynchronizedUpdate](./struct.BeginSynchronizedUpdate.html).
+///
+/// The returned guard writes to the `writer`. It ends the update and flushes the `writer`
+/// when it's dropped, even if the drawing returns early with an error or panics, so the
+/// terminal never keeps a partially drawn frame. Call the [end](./struct.FrameGuard.html#method.end)
+/// to get the error of the final flush.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{cursor::MoveTo, queue, style::Print, terminal, Result};
+///
+/// fn draw() -> Result<()> {
+///     let mut stdout = stdout();
+///     let mut frame = terminal::begin_frame(&mut stdout)?;
+///
+///     queue!(frame, MoveTo(0, 0), Print("Hello"))?;
+///     // An error returns early, the dropped guard presents the frame anyway
+///     queue!(frame, MoveTo(0, 1), Print("world"))?;
+///
+///     frame.end()
+/// }
+/// ```
+pub fn begin_frame<W: io::Write>(writer: &mut W) -> Result<FrameGuard<'_, W>> {
+    writer.queue(BeginSynchronizedUpdate)?;
+    Ok(FrameGuard {
+        writer,
+        ended: false,
+    })
+}
+
+/// A synchronized update of the screen, returned by the [begin_frame](./fn.begin_frame.html).
+///
+/// The frame is drawn by writing (queueing the commands) to the guard.
+#[derive(Debug)]
+pub struct FrameGuard<'a, W: io::Write> {
+    writer: &'a mut W,
+    ended: bool,
+}
+
+impl<W: io::Write> FrameGuard<'_, W> {
+    /// Ends the synchronized update and flushes the writer.
+    pub fn end(mut self) -> Result<()> {
+        self.ended = true;
+        self.writer.queue(EndSynchronizedUpdate)?;
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write> io::Write for FrameGuard<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write> Drop for FrameGuard<'_, W> {
+    fn drop(&mut self) {
+        if !self.ended {
+            // There's no way to report the errors, the terminal may be gone already
+            let _ = self.writer.queue(EndSynchronizedUpdate);
+            let _ = self.writer.flush();
+        }
+    }
 }
 
 /// Different ways to clear the terminal buffer.
@@ -310,6 +1025,114 @@ impl Command for ScrollDown {
     }
 }
 
+/// The way the [scroll_viewport](fn.scroll_viewport.html) scrolled the area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollStrategy {
+    /// The area was scrolled within the scroll margins (`DECSTBM`, `DECSLRM`).
+    ScrollRegion,
+    /// The lines were deleted and inserted (`DL`, `IL`).
+    InsertDeleteLines,
+    /// Nothing was written, the whole area must be repainted.
+    Repaint,
+}
+
+/// Scrolls the content of the given area of the screen by the given number of lines.
+///
+/// A positive number of lines moves the content up, the new lines appear at the bottom
+/// of the area. A negative one moves the content down.
+///
+/// The strategy is picked according to the [capabilities](../capabilities/index.html):
+///
+/// * the scroll margins, if the terminal supports them (and the left and right margins
+///   if the area doesn't span the whole screen width),
+/// * the insertion and the deletion of the lines, if the area spans the whole screen
+///   width,
+/// * nothing is written otherwise, or if the area scrolls out of view entirely.
+///
+/// The returned strategy tells how the content of the area was moved, the callers
+/// shift their buffers accordingly, or repaint the whole area on
+/// [Repaint](enum.ScrollStrategy.html#variant.Repaint). The blank lines have to be
+/// painted in any case. The cursor is left at the top left cell of the area, unless
+/// nothing was written.
+///
+/// # Notes
+///
+/// The sequences are written to the writer, they aren't flushed.
+pub fn scroll_viewport(
+    writer: &mut impl io::Write,
+    region: Rect,
+    lines: i16,
+) -> Result<ScrollStrategy> {
+    let (columns, _) = size()?;
+    scroll_viewport_with(writer, region, lines, &capabilities(), columns)
+}
+
+fn scroll_viewport_with(
+    writer: &mut impl io::Write,
+    region: Rect,
+    lines: i16,
+    capabilities: &Capabilities,
+    columns: u16,
+) -> Result<ScrollStrategy> {
+    let count = lines.unsigned_abs();
+    let full_width = region.x == 0 && region.width >= columns;
+
+    let strategy = if region.is_empty() || count >= region.height {
+        ScrollStrategy::Repaint
+    } else if capabilities.scroll_region() && (full_width || capabilities.left_right_margins()) {
+        ScrollStrategy::ScrollRegion
+    } else if capabilities.insert_delete_lines() && full_width {
+        ScrollStrategy::InsertDeleteLines
+    } else {
+        ScrollStrategy::Repaint
+    };
+
+    if count == 0 || strategy == ScrollStrategy::Repaint {
+        return Ok(strategy);
+    }
+
+    // The escape sequences are 1-based
+    let top = u32::from(region.y) + 1;
+    let bottom = u32::from(region.y) + u32::from(region.height);
+
+    if strategy == ScrollStrategy::ScrollRegion {
+        if !full_width {
+            let left = u32::from(region.x) + 1;
+            let right = u32::from(region.x) + u32::from(region.width);
+            write!(writer, concat!(csi!("?69h"), csi!("{};{}s")), left, right)?;
+        }
+        write!(writer, csi!("{};{}r"), top, bottom)?;
+        if lines > 0 {
+            write!(writer, csi!("{}S"), count)?;
+        } else {
+            write!(writer, csi!("{}T"), count)?;
+        }
+        write!(writer, csi!("r"))?;
+        if !full_width {
+            // Disabling the left and right margins mode resets the margins
+            write!(writer, csi!("?69l"))?;
+        }
+    } else {
+        // The lines below the area stay in place, as many lines are inserted as deleted
+        let (delete_at, insert_at) = if lines > 0 {
+            (top, bottom + 1 - u32::from(count))
+        } else {
+            (bottom + 1 - u32::from(count), top)
+        };
+        write!(
+            writer,
+            concat!(csi!("{};1H"), csi!("{}M"), csi!("{};1H"), csi!("{}L")),
+            delete_at, count, insert_at, count
+        )?;
+    }
+
+    // Both `DECSTBM` and `DL`/`IL` move the cursor
+    write!(writer, csi!("{};{}H"), top, u32::from(region.x) + 1)?;
+    tracking::tracked(|| tracking::set_position(region.x, region.y));
+
+    Ok(strategy)
+}
+
 /// A command that clears the terminal screen buffer.
 ///
 /// See the [`ClearType`](enum.ClearType.html) enum.
@@ -317,13 +1140,40 @@ impl Command for ScrollDown {
 /// # Notes
 ///
 /// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Clearing [`UntilNewLine`](enum.ClearType.html#variant.UntilNewLine) from the right part
+/// of a wide character (CJK, emoji) printed by crossterm clears the whole character, its
+/// left part is replaced by spaces. The terminals leave half of the character on the
+/// screen otherwise. The cursor position doesn't change.
+///
+/// The terminals without the back color erase clear to the default colors, the
+/// [StyleTracker](../style/struct.StyleTracker.html) can re-assert the current style
+/// after the clear, see its [set_sticky_style](../style/struct.StyleTracker.html#method.set_sticky_style).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Clear(pub ClearType);
 
 impl Command for Clear {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let offset = match self.0 {
+            ClearType::UntilNewLine => tracking::wide_grapheme_offset(),
+            _ => None,
+        };
+        forget_cleared_wide_graphemes(self.0);
+
+        if let Some(offset) = offset {
+            // Back up to the start of the wide character and blank its left part
+            return write!(
+                f,
+                concat!(csi!("{}D"), csi!("K"), "{:2$}"),
+                offset,
+                "",
+                usize::from(offset)
+            );
+        }
+
         f.write_str(match self.0 {
             ClearType::All => csi!("2J"),
+            ClearType::Purge if !capabilities().purge() => csi!("2J"),
             ClearType::Purge => csi!("3J"),
             ClearType::FromCursorDown => csi!("J"),
             ClearType::FromCursorUp => csi!("1J"),
@@ -334,7 +1184,29 @@ impl Command for Clear {
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        sys::clear(self.0)
+        let offset = match self.0 {
+            ClearType::UntilNewLine => tracking::wide_grapheme_offset().unwrap_or(0),
+            _ => 0,
+        };
+        forget_cleared_wide_graphemes(self.0);
+        sys::clear(self.0, offset)
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        match self.0 {
+            ClearType::Purge => Some(Capability::Purge),
+            _ => None,
+        }
+    }
+}
+
+/// Forgets the wide characters erased by the clear.
+fn forget_cleared_wide_graphemes(clear_type: ClearType) {
+    match (clear_type, tracking::position()) {
+        (ClearType::UntilNewLine, Some((column, _))) => {
+            tracking::forget_wide_graphemes_from(column)
+        }
+        _ => tracking::forget_wide_graphemes(),
     }
 }
 
@@ -359,6 +1231,10 @@ impl Command for SetSize {
 
 /// A command that sets the terminal title
 ///
+/// Both the window title and the icon name (e.g. the taskbar entry) are set, see
+/// [SetWindowTitle](struct.SetWindowTitle.html) and [SetIconName](struct.SetIconName.html)
+/// to set only one of them.
+///
 /// # Notes
 ///
 /// Commands must be executed/queued for execution otherwise they do nothing.
@@ -376,19 +1252,435 @@ impl<T: fmt::Display> Command for SetTitle<T> {
     }
 }
 
+/// A command that sets the window title, the icon name is left unchanged.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetWindowTitle<T>(pub T);
+
+impl<T: fmt::Display> Command for SetWindowTitle<T> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1B]2;{}\x07", &self.0)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        sys::set_window_title(&self.0)
+    }
+}
+
+/// A command that sets the icon name, the window title is left unchanged.
+///
+/// The icon name is shown by some window managers and taskbars instead of the window
+/// title, e.g. for the minimized windows.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetIconName<T>(pub T);
+
+impl<T: fmt::Display> Command for SetIconName<T> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1B]1;{}\x07", &self.0)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        // The legacy console has no icon name
+        Ok(())
+    }
+}
+
+/// A command that reports the current directory to the terminal, `OSC 7`.
+///
+/// The terminals open the new tabs and panes in the reported directory and show it in
+/// the titles. The path is sent as a `file://` URL, the characters which aren't allowed
+/// in it are percent encoded.
+///
+/// # Notes
+///
+/// * Requires the [CurrentDirectory](../capabilities/enum.Capability.html#variant.CurrentDirectory)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetCurrentDirectory<'a> {
+    /// The name of the host, it tells the terminal whether the directory is remote.
+    pub host: &'a str,
+    /// The absolute path of the directory.
+    pub path: &'a str,
+}
+
+impl Command for SetCurrentDirectory<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1B]7;file://{}", self.host)?;
+        for byte in self.path.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'.' | b'_' | b'~' => {
+                    f.write_char(char::from(byte))?
+                }
+                _ => write!(f, "%{:02X}", byte)?,
+            }
+        }
+        f.write_str("\x1B\\")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        // The legacy console doesn't open the new tabs
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::CurrentDirectory)
+    }
+}
+
+/// The volume of the bells.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BellVolume {
+    /// The bell is silent.
+    Off,
+    /// The low volume.
+    Low,
+    /// The high volume.
+    High,
+}
+
+impl BellVolume {
+    fn parameter(self) -> u8 {
+        match self {
+            BellVolume::Off => 1,
+            BellVolume::Low => 4,
+            BellVolume::High => 8,
+        }
+    }
+}
+
+/// A command that sets the volume of the warning bell (`BEL`), `DECSWBV`.
+///
+/// See the [BellVolume](enum.BellVolume.html) enum.
+///
+/// # Notes
+///
+/// * Requires the [BellVolume](../capabilities/enum.Capability.html#variant.BellVolume)
+///   capability, the other terminals ignore it.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetBellVolume(pub BellVolume);
+
+impl Command for SetBellVolume {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, csi!("{} t"), self.0.parameter())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        // The legacy console has no bell volume
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::BellVolume)
+    }
+}
+
+/// A command that sets the volume of the margin bell, `DECSMBV`.
+///
+/// The margin bell rings when the cursor gets close to the right margin, if it's enabled
+/// in the terminal.
+///
+/// # Notes
+///
+/// * Requires the [BellVolume](../capabilities/enum.Capability.html#variant.BellVolume)
+///   capability, the other terminals ignore it.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetMarginBellVolume(pub BellVolume);
+
+impl Command for SetMarginBellVolume {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, csi!("{} u"), self.0.parameter())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        // The legacy console has no margin bell
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::BellVolume)
+    }
+}
+
 impl_display!(for ScrollUp);
 impl_display!(for ScrollDown);
 impl_display!(for SetSize);
 impl_display!(for Clear);
+impl_display!(for SetBellVolume);
+impl_display!(for SetMarginBellVolume);
+impl_display!(for SetCurrentDirectory<'_>);
 
 #[cfg(test)]
 mod tests {
-    use std::{io::stdout, thread, time};
+    use std::{
+        io::{stdout, Write},
+        thread, time,
+    };
 
     use crate::execute;
 
     use super::*;
 
+    #[test]
+    fn test_scroll_viewport() {
+        let _guard = tracking::TEST_LOCK.lock();
+        let _enabled = tracking::enable_tracking();
+        let all = Capabilities::builder()
+            .scroll_region(true)
+            .left_right_margins(true)
+            .insert_delete_lines(true)
+            .build();
+        let no_margins = Capabilities::builder()
+            .scroll_region(false)
+            .left_right_margins(false)
+            .insert_delete_lines(true)
+            .build();
+        let none = Capabilities::builder()
+            .scroll_region(false)
+            .insert_delete_lines(false)
+            .build();
+
+        let scroll = |region, lines, capabilities| {
+            let mut result = Vec::new();
+            let strategy =
+                scroll_viewport_with(&mut result, region, lines, capabilities, 80).unwrap();
+            (strategy, String::from_utf8(result).unwrap())
+        };
+
+        let full = Rect::new(0, 2, 80, 5);
+        assert_eq!(
+            scroll(full, 2, &all),
+            (
+                ScrollStrategy::ScrollRegion,
+                "\x1B[3;7r\x1B[2S\x1B[r\x1B[3;1H".to_string()
+            )
+        );
+        assert_eq!(tracking::position(), Some((0, 2)));
+        assert_eq!(
+            scroll(Rect::new(10, 2, 20, 5), -1, &all),
+            (
+                ScrollStrategy::ScrollRegion,
+                "\x1B[?69h\x1B[11;30s\x1B[3;7r\x1B[1T\x1B[r\x1B[?69l\x1B[3;11H".to_string()
+            )
+        );
+        assert_eq!(
+            scroll(full, 2, &no_margins),
+            (
+                ScrollStrategy::InsertDeleteLines,
+                "\x1B[3;1H\x1B[2M\x1B[6;1H\x1B[2L\x1B[3;1H".to_string()
+            )
+        );
+        assert_eq!(
+            scroll(full, -2, &no_margins),
+            (
+                ScrollStrategy::InsertDeleteLines,
+                "\x1B[6;1H\x1B[2M\x1B[3;1H\x1B[2L\x1B[3;1H".to_string()
+            )
+        );
+
+        let (_, last_column) = scroll(Rect::new(u16::MAX, 2, 1, 5), 1, &all);
+        assert!(last_column.starts_with("\x1B[?69h\x1B[65536;65536s"));
+
+        let repaint = (ScrollStrategy::Repaint, String::new());
+        assert_eq!(scroll(Rect::new(10, 2, 20, 5), 1, &no_margins), repaint);
+        assert_eq!(scroll(full, 1, &none), repaint);
+        assert_eq!(scroll(full, 5, &all), repaint);
+        assert_eq!(
+            scroll(full, 0, &all),
+            (ScrollStrategy::ScrollRegion, String::new())
+        );
+    }
+
+    #[test]
+    fn test_alternate_screen_emulation() {
+        let _guard = tracking::TEST_LOCK.lock();
+        let _capabilities = crate::capabilities::set_test_capabilities(
+            Capabilities::builder().alternate_screen(false).build(),
+        );
+        clear_captured_output();
+
+        let mut output = OutputCapture::new(Vec::new());
+        write!(output, "\x1B[1mfirst\x1B[0m\nsecond\n$ ").unwrap();
+        // Leaving the main screen doesn't clear it
+        output.queue(LeaveAlternateScreen).unwrap();
+        output.queue(EnterAlternateScreen).unwrap();
+        write!(output, "application").unwrap();
+        // Formatting alone doesn't drain the captured text
+        let mut formatted = String::new();
+        LeaveAlternateScreen.write_ansi(&mut formatted).unwrap();
+        LeaveAlternateScreen.write_ansi(&mut formatted).unwrap();
+        assert_eq!(formatted, "\x1B[2J\x1B[Hfirst\r\nsecond\r\n$ ".repeat(2));
+        // Entering twice doesn't clear the alternate screen
+        output.queue(EnterAlternateScreen).unwrap();
+        output.queue(LeaveAlternateScreen).unwrap();
+        output.queue(LeaveAlternateScreen).unwrap();
+
+        let output = String::from_utf8(output.into_inner()).unwrap();
+        assert!(output.ends_with("$ \x1B[2J\x1B[Happlication\x1B[2J\x1B[Hfirst\r\nsecond\r\n$ "));
+        assert_eq!(output.matches("\x1B[2J").count(), 2);
+    }
+
+    #[test]
+    fn test_main_screen_position_is_restored() {
+        let _guard = tracking::TEST_LOCK.lock();
+        let _capabilities = crate::capabilities::set_test_capabilities(
+            Capabilities::builder().alternate_screen(true).build(),
+        );
+        let _enabled = tracking::enable_tracking();
+        // The position is set by the test as if it was queued
+        let _tracking = tracking::start_test_tracking(80, 24);
+
+        tracking::set_position(2, 5);
+        let mut output = Vec::new();
+        output.queue(EnterAlternateScreen).unwrap();
+        tracking::set_position(10, 10);
+        output.queue(EnterAlternateScreen).unwrap();
+        output.queue(LeaveAlternateScreen).unwrap();
+
+        assert_eq!(output, b"\x1B[?1049h\x1B[?1049h\x1B[?1049l\x1B[6;3H");
+        assert_eq!(tracking::position(), Some((2, 5)));
+    }
+
+    #[test]
+    fn test_output_stream_is_selected() {
+        /// Restores the stream selected before the test when dropped.
+        struct RestoreStream(OutputStream);
+
+        impl Drop for RestoreStream {
+            fn drop(&mut self) {
+                set_output_stream(self.0);
+            }
+        }
+
+        let _guard = tracking::TEST_LOCK.lock();
+        let _stream = RestoreStream(output_stream());
+        assert_eq!(output_stream(), OutputStream::default());
+
+        set_output_stream(OutputStream::Stderr);
+        assert_eq!(output_stream(), OutputStream::Stderr);
+
+        set_output_stream(OutputStream::Stdout);
+        assert_eq!(output_stream(), OutputStream::Stdout);
+    }
+
+    #[test]
+    fn test_bell_volume_sequences() {
+        let mut result = String::new();
+        SetBellVolume(BellVolume::Off)
+            .write_ansi(&mut result)
+            .unwrap();
+        SetBellVolume(BellVolume::High)
+            .write_ansi(&mut result)
+            .unwrap();
+        SetMarginBellVolume(BellVolume::Low)
+            .write_ansi(&mut result)
+            .unwrap();
+
+        assert_eq!(result, "\x1B[1 t\x1B[8 t\x1B[4 u");
+        assert_eq!(
+            SetBellVolume(BellVolume::Low).required_capability(),
+            Some(Capability::BellVolume)
+        );
+    }
+
+    #[test]
+    fn test_title_sequences() {
+        let mut result = String::new();
+        SetTitle("a").write_ansi(&mut result).unwrap();
+        SetIconName("b").write_ansi(&mut result).unwrap();
+        SetWindowTitle("c").write_ansi(&mut result).unwrap();
+
+        assert_eq!(result, "\x1B]0;a\x07\x1B]1;b\x07\x1B]2;c\x07");
+    }
+
+    #[test]
+    fn test_frame_guard_ends_the_update() {
+        let mut result = Vec::new();
+        let frame = begin_frame(&mut result).unwrap();
+        frame.end().unwrap();
+        assert_eq!(result, b"\x1B[?2026h\x1B[?2026l");
+
+        // Dropped while unwinding
+        let mut result = Vec::new();
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut frame = begin_frame(&mut result).unwrap();
+            frame.write_all(b"partial").unwrap();
+            panic!("drawing failed");
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(result, b"\x1B[?2026hpartial\x1B[?2026l");
+    }
+
+    #[test]
+    fn test_current_directory_sequence() {
+        let mut result = String::new();
+        SetCurrentDirectory {
+            host: "box",
+            path: "/home/me/my dir/é",
+        }
+        .write_ansi(&mut result)
+        .unwrap();
+
+        assert_eq!(result, "\x1B]7;file://box/home/me/my%20dir/%C3%A9\x1B\\");
+    }
+
+    #[test]
+    fn test_position() {
+        let position = Position::from_one_based(1, 5);
+
+        assert_eq!(position, Position::new(0, 4));
+        assert_eq!(position.to_one_based(), (1, 5));
+        assert_eq!(Position::from_one_based(0, 0), Position::new(0, 0));
+        assert_eq!(<(u16, u16)>::from(Position::from((3, 7))), (3, 7));
+
+        let size = Size::from((80, 24));
+        assert_eq!(size, Size::new(80, 24));
+        assert!(size.contains(Position::new(79, 23)));
+        assert!(!size.contains(Position::new(80, 0)));
+        assert!(Size::new(0, 24).is_empty());
+
+        let rect = Rect::from_parts(Position::new(2, 3), size);
+        assert_eq!(rect, Rect::new(2, 3, 80, 24));
+        assert_eq!((rect.position(), rect.size()), (Position::new(2, 3), size));
+
+        assert_eq!(Index::OneBased(3).zero_based(), 2);
+        assert_eq!(Index::ZeroBased(3).one_based(), 4);
+        assert_eq!(Index::OneBased(0).zero_based(), 0);
+    }
+
+    #[test]
+    fn test_rect() {
+        let rect = Rect::new(2, 3, 4, 5);
+
+        assert!(rect.contains(2, 3));
+        assert!(rect.contains(5, 7));
+        assert!(!rect.contains(6, 7));
+        assert!(!rect.contains(1, 3));
+
+        assert_eq!(rect.clamp(0, 100), Some((2, 7)));
+        assert_eq!(rect.clamp(4, 4), Some((4, 4)));
+        assert_eq!(Rect::new(0, 0, 0, 5).clamp(0, 0), None);
+        assert_eq!(
+            Rect::new(u16::MAX, 0, 10, 1).clamp(0, 0),
+            Some((u16::MAX, 0))
+        );
+    }
+
     // Test is disabled, because it's failing on Travis CI
     #[test]
     #[ignore]
@@ -411,6 +1703,39 @@ mod tests {
         assert_eq!((width, height), size().unwrap());
     }
 
+    #[cfg(unix)]
+    #[test]
+    #[allow(clippy::useless_conversion)]
+    fn test_set_size_of() {
+        let master = unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY) };
+        assert!(master >= 0, "{}", std::io::Error::last_os_error());
+
+        set_size_of(master, 100, 30).unwrap();
+
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let result = unsafe { libc::ioctl(master, libc::TIOCGWINSZ.into(), &mut size) };
+        unsafe { libc::close(master) };
+
+        assert_eq!(result, 0);
+        assert_eq!((size.ws_col, size.ws_row), (100, 30));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_size_of() {
+        let master = unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY) };
+        assert!(master >= 0, "{}", std::io::Error::last_os_error());
+
+        set_size_of(master, 120, 40).unwrap();
+        let size = size_of(master);
+        unsafe { libc::close(master) };
+        assert_eq!(size.unwrap(), (120, 40));
+
+        // Not a terminal
+        let file = std::fs::File::open("/dev/null").unwrap();
+        assert!(size_of(std::os::unix::io::AsRawFd::as_raw_fd(&file)).is_err());
+    }
+
     #[test]
     fn test_raw_mode() {
         // check we start from normal mode (may fail on some test harnesses)
@@ -0,0 +1,198 @@
+//! This module contains the capture of the output re-printed by the emulated alternate screen.
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+use parking_lot::Mutex;
+
+use crate::capabilities::is_sequence_complete;
+
+use super::snapshot::{self, DecModes};
+
+/// The most lines kept by the capture, the older ones are dropped.
+const MAX_CAPTURED_LINES: usize = 1000;
+
+/// The lines of the text written through the `OutputCapture`s on the main screen, the
+/// last one is unfinished.
+static CAPTURED_LINES: Mutex<VecDeque<Vec<u8>>> = parking_lot::const_mutex(VecDeque::new());
+
+/// A writer capturing the text written on the main screen.
+///
+/// The terminals without the alternate screen (see the
+/// [alternate_screen](../capabilities/struct.Capabilities.html#method.alternate_screen)
+/// capability) draw the application over the main screen. The
+/// [LeaveAlternateScreen](struct.LeaveAlternateScreen.html) re-prints the captured text
+/// then, the output printed before the application started isn't lost.
+///
+/// Just the text is captured, the escape sequences (colors, cursor movements, ...) are
+/// dropped. The text written while the alternate screen is active isn't captured.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+///
+/// use crossterm::{
+///     execute,
+///     terminal::{EnterAlternateScreen, LeaveAlternateScreen, OutputCapture},
+///     Result,
+/// };
+///
+/// fn main() -> Result<()> {
+///     let mut stdout = OutputCapture::new(stdout());
+///     writeln!(stdout, "Building...")?;
+///
+///     execute!(stdout, EnterAlternateScreen)?;
+///     // Draw the application
+///     execute!(stdout, LeaveAlternateScreen)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct OutputCapture<W: Write> {
+    inner: W,
+    /// The escape sequence split between the writes.
+    sequence: Vec<u8>,
+}
+
+impl<W: Write> OutputCapture<W> {
+    /// Creates a new `OutputCapture`.
+    pub fn new(inner: W) -> OutputCapture<W> {
+        OutputCapture {
+            inner,
+            sequence: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// The text written directly to the underlying writer isn't captured.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps this `OutputCapture`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for OutputCapture<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+
+        // The mode is switched when the command is queued, the text written before it
+        // and flushed after it isn't captured
+        if snapshot::is_dec_mode(DecModes::ALTERNATE_SCREEN) {
+            self.sequence.clear();
+            return Ok(written);
+        }
+
+        let mut captured = CAPTURED_LINES.lock();
+        for &byte in &buf[..written] {
+            if self.sequence.is_empty() && byte != b'\x1B' {
+                if byte != b'\r' {
+                    push_byte(&mut captured, byte);
+                }
+                continue;
+            }
+
+            self.sequence.push(byte);
+            if is_sequence_complete(&self.sequence) {
+                self.sequence.clear();
+            }
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Drops the text captured by the [OutputCapture](struct.OutputCapture.html)s, e.g. after
+/// the screen was cleared.
+pub fn clear_captured_output() {
+    CAPTURED_LINES.lock().clear();
+}
+
+/// Returns the last `rows` lines of the captured text, the capture is kept.
+///
+/// The lines are separated with `\r\n`, they're printed in the raw mode too.
+pub(crate) fn captured_lines(rows: usize) -> String {
+    join_lines(&CAPTURED_LINES.lock(), rows)
+}
+
+fn join_lines(lines: &VecDeque<Vec<u8>>, rows: usize) -> String {
+    lines
+        .iter()
+        .skip(lines.len().saturating_sub(rows))
+        .map(|line| String::from_utf8_lossy(line))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+/// Appends the byte to the captured lines, the oldest line is dropped once there are more
+/// than `MAX_CAPTURED_LINES` finished ones.
+fn push_byte(lines: &mut VecDeque<Vec<u8>>, byte: u8) {
+    if lines.is_empty() {
+        lines.push_back(Vec::new());
+    }
+
+    if byte == b'\n' {
+        if lines.len() > MAX_CAPTURED_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(Vec::new());
+    } else if let Some(line) = lines.back_mut() {
+        line.push(byte);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::io::Write;
+
+    use crate::cursor::tracking::TEST_LOCK;
+
+    use super::{
+        captured_lines, clear_captured_output, join_lines, push_byte, OutputCapture,
+        MAX_CAPTURED_LINES,
+    };
+
+    #[test]
+    fn test_text_is_captured_without_escape_sequences() {
+        let _lock = TEST_LOCK.lock();
+        clear_captured_output();
+
+        let mut capture = OutputCapture::new(Vec::new());
+        capture
+            .write_all(b"\x1B[31mred\x1B[0m\r\n\x1B]8;;")
+            .unwrap();
+        capture.write_all(b"url\x1B\\link").unwrap();
+
+        assert_eq!(captured_lines(usize::MAX), "red\r\nlink");
+        assert_eq!(captured_lines(1), "link");
+        assert!(capture.get_ref().starts_with(b"\x1B[31mred"));
+    }
+
+    #[test]
+    fn test_oldest_lines_are_dropped() {
+        let mut lines = VecDeque::new();
+        for byte in format!("{}last", "line\n".repeat(MAX_CAPTURED_LINES + 2)).bytes() {
+            push_byte(&mut lines, byte);
+        }
+
+        assert_eq!(lines.len(), MAX_CAPTURED_LINES + 1);
+        assert_eq!(
+            join_lines(&lines, usize::MAX),
+            format!("{}last", "line\r\n".repeat(MAX_CAPTURED_LINES))
+        );
+    }
+}
@@ -0,0 +1,442 @@
+//! This module contains the snapshot of the terminal modes.
+
+use std::io;
+
+use bitflags::bitflags;
+use parking_lot::Mutex;
+
+#[cfg(feature = "cursor")]
+use crate::cursor::{Hide, Show};
+#[cfg(feature = "events")]
+use crate::event::{
+    self, DisableBracketedPaste, DisableInBandResize, DisableModifyOtherKeys, DisableMouseCapture,
+    DisablePixelMouseCapture, DisableThemeChangeEvents, DisableWin32InputMode,
+    EnableBracketedPaste, EnableInBandResize, EnableModifyOtherKeys, EnableMouseCapture,
+    EnablePixelMouseCapture, EnableThemeChangeEvents, EnableWin32InputMode,
+    KeyboardEnhancementFlags, ModifyOtherKeysLevel, MouseCaptureMode,
+    PopAllKeyboardEnhancementFlags, PushKeyboardEnhancementFlags, SetMouseCaptureMode,
+};
+use crate::{Command, QueueableCommand, Result};
+
+use super::{sys, DisableLineWrap, EnableLineWrap, EnterAlternateScreen, LeaveAlternateScreen};
+
+bitflags! {
+    /// The DEC private modes changed by the commands, empty is the initial state.
+    pub(crate) struct DecModes: u16 {
+        /// The cursor is hidden (`DECTCEM`).
+        const CURSOR_HIDDEN = 0b0000_0000_0001;
+        /// The line wrapping is disabled (`DECAWM`).
+        const LINE_WRAP_DISABLED = 0b0000_0000_0010;
+        /// The alternate screen is active.
+        const ALTERNATE_SCREEN = 0b0000_0000_0100;
+        /// The bracketed paste is enabled (mode 2004).
+        const BRACKETED_PASTE = 0b0000_0000_1000;
+        /// The in-band resize notifications are enabled (mode 2048).
+        const IN_BAND_RESIZE = 0b0000_0001_0000;
+        /// The color scheme change notifications are enabled (mode 2031).
+        const THEME_CHANGE_EVENTS = 0b0000_0010_0000;
+        /// The mouse events are reported in pixels (mode 1016).
+        const PIXEL_MOUSE_CAPTURE = 0b0000_0100_0000;
+        /// The mouse capture reports the buttons only (mode 1000), not the motion.
+        const MOUSE_BUTTONS_ONLY = 0b0000_1000_0000;
+        /// The mouse capture reports the drag (mode 1002), not the motion with no button held.
+        const MOUSE_DRAG_ONLY = 0b0001_0000_0000;
+        /// The `modifyOtherKeys` level 1 is set.
+        const MODIFY_OTHER_KEYS_1 = 0b0010_0000_0000;
+        /// The `modifyOtherKeys` level 2 is set.
+        const MODIFY_OTHER_KEYS_2 = 0b0100_0000_0000;
+        /// The `win32-input-mode` is enabled (mode 9001).
+        const WIN32_INPUT_MODE = 0b1000_0000_0000;
+    }
+}
+
+/// The DEC private modes set by the executed/queued commands.
+static DEC_MODES: Mutex<DecModes> = parking_lot::const_mutex(DecModes::empty());
+
+/// Turns the tracked DEC private mode on/off.
+pub(crate) fn set_dec_mode(mode: DecModes, enabled: bool) {
+    DEC_MODES.lock().set(mode, enabled);
+}
+
+/// Returns the tracked DEC private modes.
+#[cfg(feature = "events")]
+pub(crate) fn dec_modes() -> DecModes {
+    *DEC_MODES.lock()
+}
+
+/// Returns whether the tracked DEC private mode is on.
+pub(crate) fn is_dec_mode(mode: DecModes) -> bool {
+    DEC_MODES.lock().contains(mode)
+}
+
+/// A snapshot of the terminal modes.
+///
+/// It records the termios mode (the console modes on Windows), the cursor visibility and
+/// the DEC private modes (line wrapping, alternate screen, mouse capture, bracketed paste,
+/// the input modes, ...) and the keyboard enhancement flags changed by the crossterm commands. The [restore](#method.restore) puts everything back, e.g. after
+/// the terminal was handed over to an external editor.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+/// use std::process::Command;
+///
+/// use crossterm::{terminal::ModeSnapshot, Result};
+///
+/// fn main() -> Result<()> {
+///     let snapshot = ModeSnapshot::capture()?;
+///
+///     // The editor changes the modes as it likes
+///     Command::new("vi").status()?;
+///
+///     snapshot.restore(&mut stdout())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModeSnapshot {
+    mode: sys::TerminalMode,
+    dec_modes: DecModes,
+    #[cfg(feature = "events")]
+    mouse_capture_count: usize,
+    #[cfg(feature = "events")]
+    keyboard_enhancement_flags: Vec<KeyboardEnhancementFlags>,
+}
+
+impl ModeSnapshot {
+    /// Captures the current terminal modes.
+    pub fn capture() -> Result<ModeSnapshot> {
+        Ok(ModeSnapshot {
+            mode: sys::capture_mode()?,
+            dec_modes: *DEC_MODES.lock(),
+            #[cfg(feature = "events")]
+            mouse_capture_count: event::mouse_capture_count(),
+            #[cfg(feature = "events")]
+            keyboard_enhancement_flags: event::pushed_keyboard_enhancement_flags(),
+        })
+    }
+
+    /// Returns whether the cursor was hidden.
+    pub fn is_cursor_hidden(&self) -> bool {
+        self.dec_modes.contains(DecModes::CURSOR_HIDDEN)
+    }
+
+    /// Restores the captured terminal modes.
+    ///
+    /// The commands switching the DEC private modes are written to the writer and flushed
+    /// before the termios mode is restored.
+    pub fn restore(&self, writer: &mut impl io::Write) -> Result<()> {
+        queue_modes(
+            writer,
+            self.dec_modes,
+            #[cfg(feature = "events")]
+            self.mouse_capture_count,
+            #[cfg(feature = "events")]
+            &self.keyboard_enhancement_flags,
+        )?;
+
+        writer.flush()?;
+        sys::restore_mode(&self.mode)
+    }
+}
+
+/// Leaves the raw mode, turns off all the DEC private modes set by the commands and pops
+/// the keyboard enhancement flags.
+///
+/// The modes are restored with the snapshot captured before.
+pub(crate) fn reset(writer: &mut impl io::Write) -> Result<()> {
+    queue_modes(
+        writer,
+        DecModes::empty(),
+        #[cfg(feature = "events")]
+        0,
+        #[cfg(feature = "events")]
+        &[],
+    )?;
+
+    writer.flush()?;
+    sys::disable_raw_mode()
+}
+
+/// Queues the commands switching the tracked modes to the given ones.
+fn queue_modes(
+    writer: &mut impl io::Write,
+    dec_modes: DecModes,
+    #[cfg(feature = "events")] mouse_capture_count: usize,
+    #[cfg(feature = "events")] keyboard_enhancement_flags: &[KeyboardEnhancementFlags],
+) -> Result<()> {
+    let current = *DEC_MODES.lock();
+    let changed = current ^ dec_modes;
+
+    queue_mode(
+        writer,
+        changed,
+        dec_modes,
+        DecModes::ALTERNATE_SCREEN,
+        EnterAlternateScreen,
+        LeaveAlternateScreen,
+    )?;
+    queue_mode(
+        writer,
+        changed,
+        dec_modes,
+        DecModes::LINE_WRAP_DISABLED,
+        DisableLineWrap,
+        EnableLineWrap,
+    )?;
+    #[cfg(feature = "cursor")]
+    queue_mode(
+        writer,
+        changed,
+        dec_modes,
+        DecModes::CURSOR_HIDDEN,
+        Hide,
+        Show,
+    )?;
+
+    #[cfg(feature = "events")]
+    {
+        queue_mode(
+            writer,
+            changed,
+            dec_modes,
+            DecModes::BRACKETED_PASTE,
+            EnableBracketedPaste,
+            DisableBracketedPaste,
+        )?;
+        queue_mode(
+            writer,
+            changed,
+            dec_modes,
+            DecModes::IN_BAND_RESIZE,
+            EnableInBandResize,
+            DisableInBandResize,
+        )?;
+        queue_mode(
+            writer,
+            changed,
+            dec_modes,
+            DecModes::THEME_CHANGE_EVENTS,
+            EnableThemeChangeEvents,
+            DisableThemeChangeEvents,
+        )?;
+        queue_mode(
+            writer,
+            changed,
+            dec_modes,
+            DecModes::WIN32_INPUT_MODE,
+            EnableWin32InputMode,
+            DisableWin32InputMode,
+        )?;
+
+        let modify_other_keys = DecModes::MODIFY_OTHER_KEYS_1 | DecModes::MODIFY_OTHER_KEYS_2;
+        if changed.intersects(modify_other_keys) {
+            if dec_modes.contains(DecModes::MODIFY_OTHER_KEYS_1) {
+                writer.queue(EnableModifyOtherKeys(ModifyOtherKeysLevel::Level1))?;
+            } else if dec_modes.contains(DecModes::MODIFY_OTHER_KEYS_2) {
+                writer.queue(EnableModifyOtherKeys(ModifyOtherKeysLevel::Level2))?;
+            } else {
+                writer.queue(DisableModifyOtherKeys)?;
+            }
+        }
+
+        if changed.intersects(DecModes::MOUSE_BUTTONS_ONLY | DecModes::MOUSE_DRAG_ONLY) {
+            writer.queue(SetMouseCaptureMode(MouseCaptureMode::from_dec_modes(
+                dec_modes,
+            )))?;
+        }
+        // The pixel capture holds one mouse capture, it's balanced below
+        queue_mode(
+            writer,
+            changed,
+            dec_modes,
+            DecModes::PIXEL_MOUSE_CAPTURE,
+            EnablePixelMouseCapture,
+            DisablePixelMouseCapture,
+        )?;
+    }
+
+    // The capture is reference counted, the commands are balanced to match the count
+    #[cfg(feature = "events")]
+    {
+        let mut count = event::mouse_capture_count();
+        while count < mouse_capture_count {
+            writer.queue(EnableMouseCapture)?;
+            count += 1;
+        }
+        while count > mouse_capture_count {
+            writer.queue(DisableMouseCapture)?;
+            count -= 1;
+        }
+
+        // The pushed flags are replaced as a whole, the terminal keeps a stack of them
+        if event::pushed_keyboard_enhancement_flags() != keyboard_enhancement_flags {
+            writer.queue(PopAllKeyboardEnhancementFlags)?;
+            for flags in keyboard_enhancement_flags {
+                writer.queue(PushKeyboardEnhancementFlags(*flags))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Queues the command switching the mode if it's changed.
+fn queue_mode(
+    writer: &mut impl io::Write,
+    changed: DecModes,
+    dec_modes: DecModes,
+    mode: DecModes,
+    enable: impl Command,
+    disable: impl Command,
+) -> Result<()> {
+    if changed.contains(mode) {
+        if dec_modes.contains(mode) {
+            writer.queue(enable)?;
+        } else {
+            writer.queue(disable)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cursor::tracking::TEST_LOCK;
+    #[cfg(feature = "cursor")]
+    use crate::cursor::{Hide, Show};
+    #[cfg(feature = "events")]
+    use crate::event::{
+        DisableModifyOtherKeys, EnableBracketedPaste, EnableInBandResize, EnableModifyOtherKeys,
+        EnableThemeChangeEvents, EnableWin32InputMode, ModifyOtherKeysLevel,
+    };
+    use crate::terminal::{DisableLineWrap, EnableLineWrap};
+    use crate::{Command, ExecutableCommand, QueueableCommand};
+
+    #[cfg(feature = "events")]
+    use super::{event, queue_modes};
+    use super::{DecModes, DEC_MODES};
+
+    /// Returns the ANSI representation of the command, formatted only.
+    fn formatted(command: impl Command) -> String {
+        let mut ansi = String::new();
+        command.write_ansi(&mut ansi).unwrap();
+        ansi
+    }
+
+    /// Restores the tracked modes when dropped, they're cleared meanwhile.
+    struct RestoreModes(DecModes);
+
+    impl RestoreModes {
+        fn new() -> RestoreModes {
+            RestoreModes(std::mem::replace(&mut *DEC_MODES.lock(), DecModes::empty()))
+        }
+    }
+
+    impl Drop for RestoreModes {
+        fn drop(&mut self) {
+            *DEC_MODES.lock() = self.0;
+        }
+    }
+
+    #[test]
+    fn test_dec_modes_are_tracked() {
+        let _lock = TEST_LOCK.lock();
+        let _modes = RestoreModes::new();
+        let mut sink = Vec::new();
+
+        // Formatting alone doesn't change the modes
+        assert_eq!(formatted(DisableLineWrap), "\x1B[?7l");
+        #[cfg(feature = "cursor")]
+        assert_eq!(format!("{}", Hide), "\x1B[?25l");
+        assert!(DEC_MODES.lock().is_empty());
+
+        #[cfg(feature = "cursor")]
+        sink.queue(Hide).unwrap();
+        sink.execute(DisableLineWrap).unwrap();
+        let modes = *DEC_MODES.lock();
+        assert!(modes.contains(DecModes::LINE_WRAP_DISABLED));
+        #[cfg(feature = "cursor")]
+        assert!(modes.contains(DecModes::CURSOR_HIDDEN));
+
+        assert_eq!(formatted(EnableLineWrap), "\x1B[?7h");
+        assert_eq!(*DEC_MODES.lock(), modes);
+
+        #[cfg(feature = "cursor")]
+        sink.execute(Show).unwrap();
+        sink.queue(EnableLineWrap).unwrap();
+        let modes = *DEC_MODES.lock();
+        assert!(!modes.intersects(DecModes::CURSOR_HIDDEN | DecModes::LINE_WRAP_DISABLED));
+    }
+
+    #[cfg(feature = "events")]
+    #[test]
+    fn test_event_modes_are_tracked() {
+        let _lock = TEST_LOCK.lock();
+        let _modes = RestoreModes::new();
+        let mut sink = Vec::new();
+
+        // Formatting alone doesn't change the modes
+        assert_eq!(formatted(EnableBracketedPaste), "\x1B[?2004h");
+        assert_eq!(
+            formatted(EnableModifyOtherKeys(ModifyOtherKeysLevel::Level2)),
+            "\x1B[>4;2m"
+        );
+        assert!(DEC_MODES.lock().is_empty());
+
+        sink.queue(EnableBracketedPaste).unwrap();
+        sink.queue(EnableModifyOtherKeys(ModifyOtherKeysLevel::Level1))
+            .unwrap();
+        sink.queue(EnableModifyOtherKeys(ModifyOtherKeysLevel::Level2))
+            .unwrap();
+        let modes = *DEC_MODES.lock();
+        assert!(modes.contains(DecModes::BRACKETED_PASTE | DecModes::MODIFY_OTHER_KEYS_2));
+        assert!(!modes.contains(DecModes::MODIFY_OTHER_KEYS_1));
+
+        assert_eq!(formatted(EnableWin32InputMode), "\x1B[?9001h");
+        assert!(!DEC_MODES.lock().contains(DecModes::WIN32_INPUT_MODE));
+        sink.queue(EnableWin32InputMode).unwrap();
+        assert!(DEC_MODES.lock().contains(DecModes::WIN32_INPUT_MODE));
+
+        assert_eq!(formatted(EnableInBandResize), "\x1B[?2048h");
+        assert!(!DEC_MODES.lock().contains(DecModes::IN_BAND_RESIZE));
+        sink.queue(EnableInBandResize).unwrap();
+        assert!(DEC_MODES.lock().contains(DecModes::IN_BAND_RESIZE));
+
+        assert_eq!(formatted(EnableThemeChangeEvents), "\x1B[?2031h");
+        assert!(!DEC_MODES.lock().contains(DecModes::THEME_CHANGE_EVENTS));
+        sink.queue(EnableThemeChangeEvents).unwrap();
+        assert!(DEC_MODES.lock().contains(DecModes::THEME_CHANGE_EVENTS));
+
+        sink.queue(DisableModifyOtherKeys).unwrap();
+        assert!(!DEC_MODES
+            .lock()
+            .intersects(DecModes::MODIFY_OTHER_KEYS_1 | DecModes::MODIFY_OTHER_KEYS_2));
+    }
+
+    #[cfg(feature = "events")]
+    #[test]
+    fn test_event_modes_are_reset() {
+        let _lock = TEST_LOCK.lock();
+        let _modes = RestoreModes::new();
+        *DEC_MODES.lock() = DecModes::BRACKETED_PASTE
+            | DecModes::IN_BAND_RESIZE
+            | DecModes::THEME_CHANGE_EVENTS
+            | DecModes::MODIFY_OTHER_KEYS_2
+            | DecModes::WIN32_INPUT_MODE;
+
+        let mut output = Vec::new();
+        queue_modes(
+            &mut output,
+            DecModes::empty(),
+            event::mouse_capture_count(),
+            &event::pushed_keyboard_enhancement_flags(),
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(DEC_MODES.lock().is_empty());
+        for sequence in ["?2004l", "?2048l", "?2031l", ">4m", "?9001l"] {
+            assert!(output.contains(sequence), "{:?}", output);
+        }
+    }
+}
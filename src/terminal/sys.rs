@@ -1,15 +1,28 @@
 //! This module provides platform related functions.
 
 #[cfg(unix)]
-pub(crate) use self::unix::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, size};
+pub(crate) use self::unix::{
+    capture_mode, disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, register_exit_guard,
+    restore_mode, set_size_of, size, size_of, stop_process, TerminalMode,
+};
+#[cfg(all(unix, feature = "events"))]
+pub(crate) use self::unix::{
+    device_attributes, emit_resize, is_line_wrap_enabled, query_raw, set_interrupt_signal,
+    supports_keyboard_enhancement,
+};
 #[cfg(windows)]
 pub(crate) use self::windows::{
-    clear, disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, scroll_down, scroll_up,
-    set_size, set_window_title, size,
+    capture_mode, clear, disable_raw_mode, enable_raw_mode, input_handle, is_line_wrap_enabled,
+    is_raw_mode_enabled, register_exit_guard, restore_mode, scroll_down, scroll_up, set_size,
+    set_size_of, set_window_title, size, size_of, TerminalMode,
 };
+#[cfg(all(windows, feature = "events"))]
+pub(crate) use self::windows::{emit_resize, set_input_handle, set_interrupt_signal};
 
 #[cfg(windows)]
 mod windows;
 
+#[cfg(unix)]
+pub(crate) mod file_descriptor;
 #[cfg(unix)]
 mod unix;
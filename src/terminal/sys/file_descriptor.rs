@@ -3,9 +3,15 @@ use std::{
     os::unix::io::{IntoRawFd, RawFd},
 };
 
+#[cfg(feature = "events")]
 use libc::size_t;
 
-use crate::Result;
+use parking_lot::Mutex;
+
+use crate::{NotATty, Result};
+
+/// The input file descriptor set by the application, `None` for the terminal.
+static INPUT_FD: Mutex<Option<RawFd>> = parking_lot::const_mutex(None);
 
 /// A file descriptor wrapper.
 ///
@@ -28,6 +34,7 @@ impl FileDesc {
         FileDesc { fd, close_on_drop }
     }
 
+    #[cfg(feature = "events")]
     pub fn read(&self, buffer: &mut [u8], size: usize) -> Result<usize> {
         let result = unsafe {
             libc::read(
@@ -63,20 +70,32 @@ impl Drop for FileDesc {
     }
 }
 
-/// Creates a file descriptor pointing to the standard input or `/dev/tty`.
+/// Sets the file descriptor returned by the `tty_fd`, `None` for the terminal.
+#[cfg(feature = "events")]
+pub(crate) fn set_input_fd(fd: Option<RawFd>) {
+    *INPUT_FD.lock() = fd;
+}
+
+/// Creates a file descriptor pointing to the standard input or `/dev/tty`, or the one set
+/// with the `set_input_fd`.
+///
+/// Fails with the [NotATty](../../../../struct.NotATty.html) error if there's no terminal.
 pub fn tty_fd() -> Result<FileDesc> {
-    let (fd, close_on_drop) = if unsafe { libc::isatty(libc::STDIN_FILENO) == 1 } {
-        (libc::STDIN_FILENO, false)
-    } else {
-        (
-            fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open("/dev/tty")?
-                .into_raw_fd(),
-            true,
-        )
-    };
+    if let Some(fd) = *INPUT_FD.lock() {
+        return Ok(FileDesc::new(fd, false));
+    }
+
+    if unsafe { libc::isatty(libc::STDIN_FILENO) == 1 } {
+        return Ok(FileDesc::new(libc::STDIN_FILENO, false));
+    }
+
+    // The standard input is redirected, fallback to the controlling terminal, which is
+    // missing if the process was started from the GUI (app bundles, launchd, ...)
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, NotATty))?;
 
-    Ok(FileDesc::new(fd, close_on_drop))
+    Ok(FileDesc::new(file.into_raw_fd(), true))
 }
@@ -2,16 +2,21 @@
 
 use std::fs::File;
 use std::os::unix::io::{IntoRawFd, RawFd};
-use std::{io, mem, process};
+use std::{fmt, io, mem, process};
 
+#[cfg(feature = "events")]
+use libc::ISIG;
 use libc::{
-    cfmakeraw, ioctl, tcgetattr, tcsetattr, termios as Termios, winsize, STDOUT_FILENO, TCSANOW,
-    TIOCGWINSZ,
+    cfmakeraw, ioctl, tcgetattr, tcsetattr, termios as Termios, winsize, STDERR_FILENO,
+    STDIN_FILENO, STDOUT_FILENO, TCSANOW, TIOCGWINSZ, TIOCSWINSZ,
 };
 use parking_lot::Mutex;
 
 use crate::error::Result;
-use crate::event::sys::unix::file_descriptor::{tty_fd, FileDesc};
+#[cfg(feature = "events")]
+use crate::event::{query, DecModeQuery, DeviceAttributesQuery, KeyboardEnhancementFlagsQuery};
+
+use super::file_descriptor::{tty_fd, FileDesc};
 
 // Some(Termios) -> we're in the raw mode and this is the previous mode
 // None -> we're not in the raw mode
@@ -21,8 +26,22 @@ pub(crate) fn is_raw_mode_enabled() -> bool {
     TERMINAL_MODE_PRIOR_RAW_MODE.lock().is_some()
 }
 
-#[allow(clippy::useless_conversion)]
 pub(crate) fn size() -> Result<(u16, u16)> {
+    let file = File::open("/dev/tty").map(|file| FileDesc::new(file.into_raw_fd(), true));
+    if let Ok(file) = &file {
+        return size_of(file.raw_fd()).or_else(|error| tput_size().ok_or(error));
+    }
+
+    // Fallback to the standard streams if /dev/tty is missing, the stdout may be redirected
+    // by the stderr only interfaces
+    size_of(STDOUT_FILENO)
+        .or_else(|_| size_of(STDERR_FILENO))
+        .or_else(|_| size_of(STDIN_FILENO))
+        .or_else(|error| tput_size().ok_or(error))
+}
+
+#[allow(clippy::useless_conversion)]
+pub(crate) fn size_of(fd: RawFd) -> Result<(u16, u16)> {
     // http://rosettacode.org/wiki/Terminal_control/Dimensions#Library:_BSD_libc
     let mut size = winsize {
         ws_row: 0,
@@ -31,21 +50,93 @@ pub(crate) fn size() -> Result<(u16, u16)> {
         ws_ypixel: 0,
     };
 
-    let file = File::open("/dev/tty").map(|file| (FileDesc::new(file.into_raw_fd(), true)));
-    let fd = if let Ok(file) = &file {
-        file.raw_fd()
-    } else {
-        // Fallback to libc::STDOUT_FILENO if /dev/tty is missing
-        STDOUT_FILENO
+    wrap_with_result(unsafe { ioctl(fd, TIOCGWINSZ.into(), &mut size) })?;
+    Ok((size.ws_col, size.ws_row))
+}
+
+#[allow(clippy::useless_conversion)]
+pub(crate) fn set_size_of(fd: RawFd, columns: u16, rows: u16) -> Result<()> {
+    let size = winsize {
+        ws_row: rows,
+        ws_col: columns,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
     };
 
-    if wrap_with_result(unsafe { ioctl(fd, TIOCGWINSZ.into(), &mut size) }).is_ok() {
-        Ok((size.ws_col, size.ws_row))
+    // The kernel sends the SIGWINCH to the foreground process group of the pty
+    wrap_with_result(unsafe { ioctl(fd, TIOCSWINSZ.into(), &size) })
+}
+
+#[cfg(feature = "events")]
+pub(crate) fn is_line_wrap_enabled() -> Result<bool> {
+    // DECAWM
+    query(DecModeQuery(7))?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "The terminal doesn't recognize the line wrapping mode",
+        )
+    })
+}
+
+#[cfg(feature = "events")]
+pub(crate) fn supports_keyboard_enhancement() -> Result<bool> {
+    Ok(query(KeyboardEnhancementFlagsQuery::new())?.is_some())
+}
+
+#[cfg(feature = "events")]
+pub(crate) fn device_attributes() -> Result<super::super::DeviceAttributes> {
+    query(DeviceAttributesQuery::new())
+}
+
+/// Runs the query in the raw mode, the answer isn't echoed and is read right away.
+#[cfg(feature = "events")]
+pub(crate) fn query_raw<T>(query: impl FnOnce() -> Result<T>) -> Result<T> {
+    if is_raw_mode_enabled() {
+        query()
     } else {
-        tput_size().ok_or_else(|| std::io::Error::last_os_error().into())
+        enable_raw_mode()?;
+        let result = query();
+        disable_raw_mode()?;
+        result
+    }
+}
+
+/// The termios mode of the terminal.
+#[derive(Clone, Copy)]
+pub(crate) struct TerminalMode {
+    termios: Termios,
+    prior_raw_mode: Option<Termios>,
+}
+
+impl fmt::Debug for TerminalMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TerminalMode")
+            .field("raw_mode", &self.prior_raw_mode.is_some())
+            .finish_non_exhaustive()
     }
 }
 
+pub(crate) fn capture_mode() -> Result<TerminalMode> {
+    let original_mode = TERMINAL_MODE_PRIOR_RAW_MODE.lock();
+
+    let tty = tty_fd()?;
+    Ok(TerminalMode {
+        termios: get_terminal_attr(tty.raw_fd())?,
+        prior_raw_mode: *original_mode,
+    })
+}
+
+/// Restores the termios mode, including the mode restored by [disable_raw_mode].
+pub(crate) fn restore_mode(mode: &TerminalMode) -> Result<()> {
+    let mut original_mode = TERMINAL_MODE_PRIOR_RAW_MODE.lock();
+
+    let tty = tty_fd()?;
+    set_terminal_attr(tty.raw_fd(), &mode.termios)?;
+    *original_mode = mode.prior_raw_mode;
+
+    Ok(())
+}
+
 pub(crate) fn enable_raw_mode() -> Result<()> {
     let mut original_mode = TERMINAL_MODE_PRIOR_RAW_MODE.lock();
 
@@ -85,6 +176,68 @@ pub(crate) fn disable_raw_mode() -> Result<()> {
     Ok(())
 }
 
+/// Turns the generation of signals for the INTR, QUIT & SUSP characters on/off, returns
+/// whether it was on.
+///
+/// Signals are always off in the raw mode. The mode restored by [disable_raw_mode]
+/// is updated instead of the current one in this case.
+#[cfg(feature = "events")]
+pub(crate) fn set_interrupt_signal(enabled: bool) -> Result<bool> {
+    let mut original_mode = TERMINAL_MODE_PRIOR_RAW_MODE.lock();
+
+    if let Some(original_mode_ios) = original_mode.as_mut() {
+        return Ok(set_isig(original_mode_ios, enabled));
+    }
+
+    let tty = tty_fd()?;
+    let fd = tty.raw_fd();
+    let mut ios = get_terminal_attr(fd)?;
+    let previous = set_isig(&mut ios, enabled);
+    set_terminal_attr(fd, &ios)?;
+    Ok(previous)
+}
+
+/// Sets the `ISIG` flag, returns whether it was set.
+#[cfg(feature = "events")]
+fn set_isig(termios: &mut Termios, enabled: bool) -> bool {
+    let previous = termios.c_lflag & ISIG != 0;
+    if enabled {
+        termios.c_lflag |= ISIG;
+    } else {
+        termios.c_lflag &= !ISIG;
+    }
+    previous
+}
+
+/// Registers the `atexit` handler resetting the terminal.
+pub(crate) fn register_exit_guard() -> Result<()> {
+    extern "C" fn exit_guard() {
+        crate::terminal::reset_at_exit();
+    }
+
+    if unsafe { libc::atexit(exit_guard) } != 0 {
+        return Err(io::Error::other("Failed to register the exit guard"));
+    }
+    Ok(())
+}
+
+/// Sends the `SIGWINCH` to the current process, the event source reports a resize.
+#[cfg(feature = "events")]
+pub(crate) fn emit_resize() -> Result<()> {
+    if unsafe { libc::raise(libc::SIGWINCH) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Stops the process, returns when it's continued.
+pub(crate) fn stop_process() -> Result<()> {
+    if unsafe { libc::raise(libc::SIGSTOP) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
 /// execute tput with the given argument and parse
 /// the output as a u16.
 ///
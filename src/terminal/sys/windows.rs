@@ -2,20 +2,57 @@
 
 use std::fmt::{self, Write};
 use std::io;
+use std::os::windows::io::RawHandle;
 
 use crossterm_winapi::{Console, ConsoleMode, Coord, Handle, ScreenBuffer, Size};
+use parking_lot::Mutex;
+#[cfg(feature = "events")]
+use winapi::um::{
+    wincon::WriteConsoleInputW,
+    wincontypes::{INPUT_RECORD, WINDOW_BUFFER_SIZE_EVENT, WINDOW_BUFFER_SIZE_RECORD},
+};
 use winapi::{
-    shared::minwindef::DWORD,
-    um::wincon::{SetConsoleTitleW, ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT},
+    shared::minwindef::{BOOL, DWORD, FALSE, TRUE},
+    um::{
+        consoleapi::{ResizePseudoConsole, SetConsoleCtrlHandler},
+        wincon::{
+            GetConsoleScreenBufferInfo, SetConsoleCursorPosition, SetConsoleTitleW,
+            CONSOLE_SCREEN_BUFFER_INFO, CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT,
+            CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT, ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT,
+            ENABLE_PROCESSED_INPUT, ENABLE_WRAP_AT_EOL_OUTPUT,
+        },
+        wincontypes::{COORD, HPCON},
+    },
 };
 
-use crate::{cursor, terminal::ClearType, ErrorKind, Result};
+use crate::{terminal::ClearType, ErrorKind, Result};
+
+/// The console input handle set by the application, `None` for the `CONIN$`.
+///
+/// The raw `HANDLE` isn't `Send`, it's stored as an integer.
+static INPUT_HANDLE: Mutex<Option<usize>> = parking_lot::const_mutex(None);
+
+/// Sets the handle returned by the `input_handle`, `None` for the `CONIN$`.
+#[cfg(feature = "events")]
+pub(crate) fn set_input_handle(handle: Option<RawHandle>) {
+    *INPUT_HANDLE.lock() = handle.map(|handle| handle as usize);
+}
+
+/// Returns the console input handle, the `CONIN$` or the one set with the
+/// `set_input_handle`.
+pub(crate) fn input_handle() -> Result<Handle> {
+    match *INPUT_HANDLE.lock() {
+        // Not closed when dropped, the application owns it
+        Some(handle) => Ok(unsafe { Handle::from_raw(handle as RawHandle as _) }),
+        None => Handle::current_in_handle(),
+    }
+}
 
 /// bits which can't be set in raw mode
 const NOT_RAW_MODE_MASK: DWORD = ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT;
 
 pub(crate) fn is_raw_mode_enabled() -> Result<bool> {
-    let console_mode = ConsoleMode::from(Handle::current_in_handle()?);
+    let console_mode = ConsoleMode::from(input_handle()?);
 
     let dw_mode = console_mode.mode()?;
 
@@ -25,8 +62,28 @@ pub(crate) fn is_raw_mode_enabled() -> Result<bool> {
     )
 }
 
+/// The modes of the console input and the active screen buffer.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TerminalMode {
+    input: DWORD,
+    output: DWORD,
+}
+
+pub(crate) fn capture_mode() -> Result<TerminalMode> {
+    Ok(TerminalMode {
+        input: ConsoleMode::from(input_handle()?).mode()?,
+        output: ConsoleMode::from(Handle::current_out_handle()?).mode()?,
+    })
+}
+
+pub(crate) fn restore_mode(mode: &TerminalMode) -> Result<()> {
+    ConsoleMode::from(input_handle()?).set_mode(mode.input)?;
+    ConsoleMode::from(Handle::current_out_handle()?).set_mode(mode.output)?;
+    Ok(())
+}
+
 pub(crate) fn enable_raw_mode() -> Result<()> {
-    let console_mode = ConsoleMode::from(Handle::current_in_handle()?);
+    let console_mode = ConsoleMode::from(input_handle()?);
 
     let dw_mode = console_mode.mode()?;
 
@@ -38,17 +95,101 @@ pub(crate) fn enable_raw_mode() -> Result<()> {
 }
 
 pub(crate) fn disable_raw_mode() -> Result<()> {
-    let console_mode = ConsoleMode::from(Handle::current_in_handle()?);
+    let console_mode = ConsoleMode::from(input_handle()?);
 
     let dw_mode = console_mode.mode()?;
 
-    let new_mode = dw_mode | NOT_RAW_MODE_MASK;
+    #[allow(unused_mut)]
+    let mut new_mode = dw_mode | NOT_RAW_MODE_MASK;
+
+    // Ctrl-C is delivered as an event, keep the processed input off
+    #[cfg(feature = "events")]
+    if crate::event::is_interrupt_events_enabled() {
+        new_mode &= !ENABLE_PROCESSED_INPUT;
+    }
 
     console_mode.set_mode(new_mode)?;
 
     Ok(())
 }
 
+/// Turns the processing of Ctrl-C by the system on/off, returns whether it was on.
+///
+/// A console control handler converting the `CTRL_C_EVENT` into an input event is
+/// registered when turned off. Processing is always off in the raw mode, the mode is
+/// updated when the raw mode is disabled in this case.
+#[cfg(feature = "events")]
+pub(crate) fn set_interrupt_signal(enabled: bool) -> Result<bool> {
+    if !enabled {
+        crate::event::sys::windows::register_interrupt_handler()?;
+    }
+
+    if is_raw_mode_enabled()? {
+        // The processing is turned on when the raw mode is disabled
+        return Ok(true);
+    }
+
+    let console_mode = ConsoleMode::from(input_handle()?);
+
+    let dw_mode = console_mode.mode()?;
+
+    let new_mode = if enabled {
+        dw_mode | ENABLE_PROCESSED_INPUT
+    } else {
+        dw_mode & !ENABLE_PROCESSED_INPUT
+    };
+
+    console_mode.set_mode(new_mode)?;
+
+    Ok(dw_mode & ENABLE_PROCESSED_INPUT != 0)
+}
+
+/// Registers the console control handler resetting the terminal before the process is
+/// terminated.
+pub(crate) fn register_exit_guard() -> Result<()> {
+    if unsafe { SetConsoleCtrlHandler(Some(exit_guard), TRUE) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+unsafe extern "system" fn exit_guard(ctrl_type: DWORD) -> BOOL {
+    match ctrl_type {
+        // Delivered as an input event, the process isn't terminated
+        #[cfg(feature = "events")]
+        CTRL_C_EVENT if crate::event::is_interrupt_events_enabled() => {}
+        CTRL_C_EVENT | CTRL_BREAK_EVENT | CTRL_CLOSE_EVENT | CTRL_LOGOFF_EVENT
+        | CTRL_SHUTDOWN_EVENT => crate::terminal::reset_at_exit(),
+        _ => {}
+    }
+
+    // The next handler (the default one terminates the process) is called
+    FALSE
+}
+
+/// Writes a window buffer size record with the current size to the console input.
+#[cfg(feature = "events")]
+pub(crate) fn emit_resize() -> Result<()> {
+    let (columns, rows) = size()?;
+
+    let mut record: INPUT_RECORD = unsafe { std::mem::zeroed() };
+    record.EventType = WINDOW_BUFFER_SIZE_EVENT;
+    unsafe {
+        *record.Event.WindowBufferSizeEvent_mut() = WINDOW_BUFFER_SIZE_RECORD {
+            dwSize: COORD {
+                X: columns as i16,
+                Y: rows as i16,
+            },
+        };
+    }
+
+    let mut written = 0;
+    if unsafe { WriteConsoleInputW(*input_handle()?, &record, 1, &mut written) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
 pub(crate) fn size() -> Result<(u16, u16)> {
     let terminal_size = ScreenBuffer::current()?.info()?.terminal_size();
     // windows starts counting at 0, unix at 1, add one to replicated unix behaviour.
@@ -58,7 +199,28 @@ pub(crate) fn size() -> Result<(u16, u16)> {
     ))
 }
 
-pub(crate) fn clear(clear_type: ClearType) -> Result<()> {
+pub(crate) fn is_line_wrap_enabled() -> Result<bool> {
+    let console_mode = ConsoleMode::from(Handle::current_out_handle()?);
+    Ok(console_mode.mode()? & ENABLE_WRAP_AT_EOL_OUTPUT != 0)
+}
+
+pub(crate) fn size_of(handle: RawHandle) -> Result<(u16, u16)> {
+    // The handle is borrowed, crossterm_winapi::Handle would close it on drop
+    let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { std::mem::zeroed() };
+    if unsafe { GetConsoleScreenBufferInfo(handle as _, &mut info) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let window = info.srWindow;
+    Ok((
+        (window.Right - window.Left + 1) as u16,
+        (window.Bottom - window.Top + 1) as u16,
+    ))
+}
+
+/// Clears the screen buffer, `wide_offset` is the number of columns between the start of
+/// the wide character the cursor is on and the cursor, it's cleared as a whole.
+pub(crate) fn clear(clear_type: ClearType, wide_offset: u16) -> Result<()> {
     let screen_buffer = ScreenBuffer::current()?;
     let csbi = screen_buffer.info()?;
 
@@ -73,7 +235,9 @@ pub(crate) fn clear(clear_type: ClearType) -> Result<()> {
         ClearType::FromCursorDown => clear_after_cursor(pos, buffer_size, current_attribute)?,
         ClearType::FromCursorUp => clear_before_cursor(pos, buffer_size, current_attribute)?,
         ClearType::CurrentLine => clear_current_line(pos, buffer_size, current_attribute)?,
-        ClearType::UntilNewLine => clear_until_line(pos, buffer_size, current_attribute)?,
+        ClearType::UntilNewLine => {
+            clear_until_line(pos, wide_offset, buffer_size, current_attribute)?
+        }
         _ => {
             clear_entire_screen(buffer_size, current_attribute)?;
         } //TODO: make purge flush the entire screen buffer not just the visible window.
@@ -113,6 +277,19 @@ pub(crate) fn scroll_down(row_count: u16) -> Result<()> {
     Ok(())
 }
 
+pub(crate) fn set_size_of(console: HPCON, columns: u16, rows: u16) -> Result<()> {
+    let size = COORD {
+        X: columns as i16,
+        Y: rows as i16,
+    };
+
+    let result = unsafe { ResizePseudoConsole(console, size) };
+    if result < 0 {
+        return Err(io::Error::from_raw_os_error(result));
+    }
+    Ok(())
+}
+
 pub(crate) fn set_size(width: u16, height: u16) -> Result<()> {
     if width <= 1 {
         return Err(ErrorKind::new(
@@ -144,7 +321,7 @@ pub(crate) fn set_size(width: u16, height: u16) -> Result<()> {
 
     let width = width as i16;
     if current_size.width < window.left + width {
-        if window.left >= i16::max_value() - width {
+        if window.left >= i16::MAX - width {
             return Err(ErrorKind::new(
                 io::ErrorKind::InvalidInput,
                 "terminal width too large",
@@ -156,7 +333,7 @@ pub(crate) fn set_size(width: u16, height: u16) -> Result<()> {
     }
     let height = height as i16;
     if current_size.height < window.top + height {
-        if window.top >= i16::max_value() - height {
+        if window.top >= i16::MAX - height {
             return Err(ErrorKind::new(
                 io::ErrorKind::InvalidInput,
                 "terminal height too large",
@@ -227,7 +404,7 @@ fn clear_after_cursor(location: Coord, buffer_size: Size, current_attribute: u16
     let (mut x, mut y) = (location.x, location.y);
 
     // if cursor position is at the outer right position
-    if x as i16 > buffer_size.width {
+    if x > buffer_size.width {
         y += 1;
         x = 0;
     }
@@ -270,7 +447,7 @@ fn clear_entire_screen(buffer_size: Size, current_attribute: u16) -> Result<()>
     clear_winapi(start_location, cells_to_write, current_attribute)?;
 
     // put the cursor back at cell 0,0
-    cursor::sys::move_to(0, 0)?;
+    move_cursor_to(0, 0)?;
     Ok(())
 }
 
@@ -285,24 +462,39 @@ fn clear_current_line(location: Coord, buffer_size: Size, current_attribute: u16
     clear_winapi(start_location, cells_to_write, current_attribute)?;
 
     // put the cursor back at cell 1 on current row
-    cursor::sys::move_to(0, location.y as u16)?;
+    move_cursor_to(0, location.y)?;
     Ok(())
 }
 
-fn clear_until_line(location: Coord, buffer_size: Size, current_attribute: u16) -> Result<()> {
+fn clear_until_line(
+    location: Coord,
+    wide_offset: u16,
+    buffer_size: Size,
+    current_attribute: u16,
+) -> Result<()> {
     let (x, y) = (location.x, location.y);
 
-    // location where to start clearing
-    let start_location = Coord::new(x, y);
+    // location where to start clearing, the wide character under the cursor is cleared
+    // as a whole
+    let start_x = x.saturating_sub(wide_offset as i16).max(0);
+    let start_location = Coord::new(start_x, y);
 
     // get sum cells before cursor
-    let cells_to_write = (buffer_size.width - x as i16) as u32;
+    let cells_to_write = (buffer_size.width - start_x) as u32;
 
     // clear until the current line
     clear_winapi(start_location, cells_to_write, current_attribute)?;
 
     // put the cursor back at original cursor position before we did the clearing
-    cursor::sys::move_to(x as u16, y as u16)?;
+    move_cursor_to(x, y)?;
+    Ok(())
+}
+
+fn move_cursor_to(x: i16, y: i16) -> Result<()> {
+    let handle = Handle::current_out_handle()?;
+    if unsafe { SetConsoleCursorPosition(*handle, COORD { X: x, Y: y }) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
     Ok(())
 }
 
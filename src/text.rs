@@ -0,0 +1,183 @@
+//! # Text
+//!
+//! The `text` module provides helpers to measure the text displayed in the terminal.
+//!
+//! The number of columns occupied by a character is looked up in the width table. The
+//! default [UnicodeTable](struct.UnicodeTable.html) follows the current Unicode version,
+//! but terminals often use older or different tables. The table can be swapped at runtime
+//! with the [set_width_table](fn.set_width_table.html) to match the terminal, otherwise
+//! the layout gets corrupted by the characters (mostly emoji) the application and the
+//! terminal disagree on.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use crossterm::text::{self, WcwidthTable};
+//!
+//! assert_eq!(text::str_width("❤️"), 2);
+//!
+//! // The terminal measures the text code point by code point
+//! text::set_width_table(WcwidthTable);
+//!
+//! assert_eq!(text::str_width("❤️"), 1);
+//! ```
+
+use parking_lot::RwLock;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+#[cfg(feature = "style")]
+pub use self::wrap::{wrap_styled, StyledLine};
+
+#[cfg(feature = "style")]
+mod wrap;
+
+/// The Unicode version of the width data of the [UnicodeTable](struct.UnicodeTable.html)
+/// and the [CjkTable](struct.CjkTable.html).
+pub const UNICODE_VERSION: (u8, u8, u8) = unicode_width::UNICODE_VERSION;
+
+/// The table set with the `set_width_table`, `None` if it's the `UnicodeTable`.
+static WIDTH_TABLE: RwLock<Option<Box<dyn WidthTable>>> = parking_lot::const_rwlock(None);
+
+/// A table of the number of columns occupied by the characters.
+pub trait WidthTable: Send + Sync {
+    /// Returns the number of columns occupied by the character, `None` if it's a control
+    /// character.
+    fn char_width(&self, c: char) -> Option<usize>;
+
+    /// Returns the number of columns occupied by the string.
+    ///
+    /// The default implementation sums the widths of the characters.
+    fn str_width(&self, s: &str) -> usize {
+        s.chars().filter_map(|c| self.char_width(c)).sum()
+    }
+}
+
+/// The width table of the current Unicode version.
+///
+/// The ambiguous characters occupy one column. The strings are measured by the grapheme
+/// clusters, e.g. an emoji presentation sequence (`❤️`) occupies two columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnicodeTable;
+
+impl WidthTable for UnicodeTable {
+    fn char_width(&self, c: char) -> Option<usize> {
+        c.width()
+    }
+
+    fn str_width(&self, s: &str) -> usize {
+        s.width()
+    }
+}
+
+/// The width table of the current Unicode version for the CJK contexts.
+///
+/// Same as the [UnicodeTable](struct.UnicodeTable.html), but the ambiguous characters
+/// occupy two columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CjkTable;
+
+impl WidthTable for CjkTable {
+    fn char_width(&self, c: char) -> Option<usize> {
+        c.width_cjk()
+    }
+
+    fn str_width(&self, s: &str) -> usize {
+        s.width_cjk()
+    }
+}
+
+/// The width table compatible with the `wcwidth` function.
+///
+/// The characters have the same width as in the [UnicodeTable](struct.UnicodeTable.html),
+/// but the strings are measured code point by code point, e.g. an emoji presentation
+/// sequence (`❤️`) occupies one column. It matches the terminals using the `wcwidth`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WcwidthTable;
+
+impl WidthTable for WcwidthTable {
+    fn char_width(&self, c: char) -> Option<usize> {
+        c.width()
+    }
+}
+
+/// Sets the width table used by the [char_width](fn.char_width.html) and the
+/// [str_width](fn.str_width.html).
+pub fn set_width_table(table: impl WidthTable + 'static) {
+    *WIDTH_TABLE.write() = Some(Box::new(table));
+}
+
+/// Returns the number of columns occupied by the character, `None` if it's a control
+/// character.
+pub fn char_width(c: char) -> Option<usize> {
+    match WIDTH_TABLE.read().as_ref() {
+        Some(table) => table.char_width(c),
+        None => UnicodeTable.char_width(c),
+    }
+}
+
+/// Returns the number of columns occupied by the string.
+pub fn str_width(s: &str) -> usize {
+    match WIDTH_TABLE.read().as_ref() {
+        Some(table) => table.str_width(s),
+        None => UnicodeTable.str_width(s),
+    }
+}
+
+/// Returns the longest prefix of the string occupying at most the given number of columns.
+///
+/// The zero width characters following the last character are kept.
+pub fn truncate(s: &str, width: usize) -> &str {
+    let table = WIDTH_TABLE.read();
+    let char_width = |c| match table.as_ref() {
+        Some(table) => table.char_width(c),
+        None => UnicodeTable.char_width(c),
+    };
+
+    let mut total = 0;
+    for (idx, c) in s.char_indices() {
+        total += char_width(c).unwrap_or(0);
+        if total > width {
+            return &s[..idx];
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate, CjkTable, UnicodeTable, WcwidthTable, WidthTable};
+
+    #[test]
+    fn test_char_width() {
+        for table in [&UnicodeTable as &dyn WidthTable, &CjkTable, &WcwidthTable] {
+            assert_eq!(table.char_width('a'), Some(1));
+            assert_eq!(table.char_width('你'), Some(2));
+            assert_eq!(table.char_width('😀'), Some(2));
+            assert_eq!(table.char_width('\x07'), None);
+        }
+
+        // Ambiguous
+        assert_eq!(UnicodeTable.char_width('¡'), Some(1));
+        assert_eq!(CjkTable.char_width('¡'), Some(2));
+    }
+
+    #[test]
+    fn test_str_width() {
+        assert_eq!(UnicodeTable.str_width("a你😀"), 5);
+        assert_eq!(WcwidthTable.str_width("a你😀"), 5);
+
+        // Emoji presentation sequence
+        assert_eq!(UnicodeTable.str_width("\u{2764}\u{FE0F}"), 2);
+        assert_eq!(WcwidthTable.str_width("\u{2764}\u{FE0F}"), 1);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("abc", 2), "ab");
+        assert_eq!(truncate("abc", 5), "abc");
+        assert_eq!(truncate("a你b", 2), "a");
+        assert_eq!(truncate("a你b", 3), "a你");
+        assert_eq!(truncate("e\u{301}x", 1), "e\u{301}");
+        assert_eq!(truncate("abc", 0), "");
+    }
+}
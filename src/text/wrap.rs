@@ -0,0 +1,275 @@
+//! This module contains the word wrapping of the styled content.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::style::{ContentStyle, StyledContent};
+
+use super::{char_width, str_width};
+
+/// The zero width joiner, the following character belongs to the same grapheme cluster.
+const ZWJ: char = '\u{200D}';
+
+/// A line of the styled content wrapped by the [wrap_styled](fn.wrap_styled.html).
+///
+/// The line is displayed with the styles of its spans, each span sets and resets its own
+/// style, no style leaks into the following line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StyledLine {
+    spans: Vec<StyledContent<String>>,
+    width: usize,
+}
+
+impl StyledLine {
+    /// Returns the spans of the line.
+    pub fn spans(&self) -> &[StyledContent<String>] {
+        &self.spans
+    }
+
+    /// Returns the number of columns occupied by the line.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    fn push(&mut self, style: ContentStyle, text: &str, width: usize) {
+        match self.spans.last_mut() {
+            Some(span) if *span.style() == style => span.content_mut().push_str(text),
+            _ => self.spans.push(StyledContent::new(style, text.to_string())),
+        }
+        self.width += width;
+    }
+}
+
+impl Display for StyledLine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for span in &self.spans {
+            write!(f, "{}", span)?;
+        }
+        Ok(())
+    }
+}
+
+/// A grapheme cluster (approximated) with the style of its span.
+struct Cluster<'a> {
+    style: ContentStyle,
+    text: &'a str,
+    width: usize,
+}
+
+impl Cluster<'_> {
+    fn is_whitespace(&self) -> bool {
+        self.text.chars().all(char::is_whitespace)
+    }
+}
+
+/// Splits the text into the grapheme clusters.
+///
+/// A cluster is a character followed by the zero width characters (combining marks,
+/// variation selectors) and the characters joined with the zero width joiner.
+fn clusters(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let mut chars = rest.char_indices();
+        let (_, first) = chars.next()?;
+        let mut previous = first;
+        let mut end = rest.len();
+        for (idx, c) in chars {
+            let extends = c != '\n' && (previous == ZWJ || char_width(c) == Some(0));
+            if !extends || first == '\n' {
+                end = idx;
+                break;
+            }
+            previous = c;
+        }
+
+        let (cluster, tail) = rest.split_at(end);
+        rest = tail;
+        Some(cluster)
+    })
+}
+
+/// The state of the wrapping.
+struct Wrapper {
+    width: usize,
+    lines: Vec<StyledLine>,
+    line: StyledLine,
+}
+
+impl Wrapper {
+    fn break_line(&mut self) {
+        self.lines.push(std::mem::take(&mut self.line));
+    }
+
+    fn push(&mut self, cluster: &Cluster) {
+        self.line.push(cluster.style, cluster.text, cluster.width);
+    }
+
+    /// Places the word preceded by the whitespace, the whitespace is dropped if the word
+    /// starts a new line.
+    fn push_word(&mut self, space: &[Cluster], word: &[Cluster]) {
+        let space_width: usize = space.iter().map(|c| c.width).sum();
+        let word_width: usize = word.iter().map(|c| c.width).sum();
+
+        if self.line.width + space_width + word_width <= self.width {
+            space.iter().chain(word).for_each(|c| self.push(c));
+            return;
+        }
+
+        if self.line.width > 0 {
+            self.break_line();
+        }
+
+        // The word longer than the line is broken between the clusters
+        for cluster in word {
+            if self.line.width > 0 && self.line.width + cluster.width > self.width {
+                self.break_line();
+            }
+            self.push(cluster);
+        }
+    }
+}
+
+/// Wraps the styled content to the lines occupying at most `width` columns.
+///
+/// The lines are broken at the whitespace, the words continue across the span boundaries.
+/// The words longer than the line are broken between the grapheme clusters. The whitespace
+/// at the wrapped line ends is dropped, the newline characters start a new line.
+///
+/// The content is measured with the current [width table](fn.set_width_table.html).
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm::{style::Stylize, text};
+///
+/// let spans = vec!["Press ".stylize(), "Ctrl+Q".bold(), " to quit the viewer".stylize()];
+/// for line in text::wrap_styled(spans, 16) {
+///     println!("{}", line);
+/// }
+/// // Press Ctrl+Q to
+/// // quit the viewer
+/// ```
+pub fn wrap_styled<D: Display>(
+    spans: impl IntoIterator<Item = StyledContent<D>>,
+    width: usize,
+) -> impl Iterator<Item = StyledLine> {
+    let texts: Vec<(ContentStyle, String)> = spans
+        .into_iter()
+        .map(|span| (*span.style(), span.content().to_string()))
+        .collect();
+
+    let mut wrapper = Wrapper {
+        width: width.max(1),
+        lines: Vec::new(),
+        line: StyledLine::default(),
+    };
+
+    let clusters: Vec<Cluster> = texts
+        .iter()
+        .flat_map(|(style, text)| {
+            clusters(text).map(move |text| Cluster {
+                style: *style,
+                text,
+                width: str_width(text),
+            })
+        })
+        .collect();
+
+    let mut rest = &clusters[..];
+    while !rest.is_empty() {
+        if rest[0].text == "\n" {
+            wrapper.break_line();
+            rest = &rest[1..];
+            continue;
+        }
+
+        let space_len = rest
+            .iter()
+            .take_while(|c| c.text != "\n" && c.is_whitespace())
+            .count();
+        let word_len = rest[space_len..]
+            .iter()
+            .take_while(|c| !c.is_whitespace())
+            .count();
+        let (space, tail) = rest.split_at(space_len);
+        let (word, tail) = tail.split_at(word_len);
+
+        if word.is_empty() {
+            // The trailing whitespace is kept unless it overflows the line
+            for cluster in space {
+                if wrapper.line.width + cluster.width > wrapper.width {
+                    break;
+                }
+                wrapper.push(cluster);
+            }
+        } else {
+            wrapper.push_word(space, word);
+        }
+        rest = tail;
+    }
+
+    if !wrapper.line.spans.is_empty() {
+        wrapper.break_line();
+    }
+    wrapper.lines.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::style::Stylize;
+
+    use super::{clusters, wrap_styled};
+
+    fn wrap(spans: Vec<&str>, width: usize) -> Vec<String> {
+        wrap_styled(spans.into_iter().map(|s| s.stylize()), width)
+            .map(|line| {
+                line.spans()
+                    .iter()
+                    .map(|span| span.content().as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_clusters() {
+        let split = |s| clusters(s).collect::<Vec<_>>();
+        assert_eq!(split("ab"), vec!["a", "b"]);
+        assert_eq!(split("e\u{301}x"), vec!["e\u{301}", "x"]);
+        assert_eq!(split("\u{2764}\u{FE0F}!"), vec!["\u{2764}\u{FE0F}", "!"]);
+        // Family: man, ZWJ, woman
+        assert_eq!(
+            split("\u{1F468}\u{200D}\u{1F469}a"),
+            vec!["\u{1F468}\u{200D}\u{1F469}", "a"]
+        );
+        assert_eq!(split("a\n\u{301}"), vec!["a", "\n", "\u{301}"]);
+    }
+
+    #[test]
+    fn test_wrap_styled() {
+        assert_eq!(
+            wrap(vec!["the quick brown fox"], 10),
+            vec!["the quick", "brown fox"]
+        );
+        // The words continue across the spans
+        assert_eq!(wrap(vec!["ab", "cd ef"], 4), vec!["abcd", "ef"]);
+        assert_eq!(wrap(vec!["abcdefg"], 3), vec!["abc", "def", "g"]);
+        assert_eq!(wrap(vec!["a\n\nb"], 3), vec!["a", "", "b"]);
+        assert_eq!(wrap(vec!["  indented"], 20), vec!["  indented"]);
+        assert_eq!(wrap(vec!["a你好"], 2), vec!["a", "你", "好"]);
+        assert_eq!(wrap(vec!["cafe\u{301} bar"], 4), vec!["cafe\u{301}", "bar"]);
+        assert!(wrap(vec![""], 10).is_empty());
+    }
+
+    #[test]
+    fn test_wrapped_spans_keep_their_styles() {
+        let spans = vec!["one ".stylize(), "two three".bold(), " four".stylize()];
+        let lines: Vec<_> = wrap_styled(spans, 10).collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].spans().len(), 2);
+        assert_eq!(lines[0].spans()[1].content(), "two");
+        assert_eq!(lines[1].spans()[0].content(), "three");
+        assert_eq!(lines[1].width(), 10);
+        assert_eq!(lines[1].spans()[0].style(), "".bold().style());
+    }
+}
@@ -0,0 +1,79 @@
+//! # WezTerm
+//!
+//! The `wezterm` module helps to integrate with the WezTerm panes, so the `wezterm.lua`
+//! configuration and the `wezterm cli` scripts can react to the application:
+//!
+//! * the [SetUserVar](struct.SetUserVar.html) command sets a variable of the pane, the
+//!   `user-var-changed` event is emitted and the value is returned by the
+//!   `pane:get_user_vars()`,
+//! * the [SetCurrentDirectory](../terminal/struct.SetCurrentDirectory.html) command
+//!   reports the directory returned by the `pane:get_current_working_dir()`,
+//! * the [pane_id](fn.pane_id.html) and the [socket_path](fn.socket_path.html) functions
+//!   tell which pane the application runs in, e.g. to pass it to the `wezterm cli`.
+//!
+//! The user variables require the
+//! [UserVars](../capabilities/enum.Capability.html#variant.UserVars) capability, detected
+//! by the `TERM_PROGRAM` and the `WEZTERM_PANE` environment variables.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::{stdout, Write};
+//!
+//! use crossterm::{execute, wezterm, Result};
+//!
+//! fn main() -> Result<()> {
+//!     if wezterm::pane_id().is_some() {
+//!         execute!(
+//!             stdout(),
+//!             wezterm::SetUserVar {
+//!                 name: "build_status",
+//!                 value: "failed"
+//!             }
+//!         )?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::env;
+use std::path::PathBuf;
+
+pub use crate::iterm2::SetUserVar;
+
+/// Returns the id of the WezTerm pane the application runs in, `None` if it doesn't run
+/// in the WezTerm.
+///
+/// The id is read from the `WEZTERM_PANE` environment variable, it's accepted by the
+/// `--pane-id` option of the `wezterm cli` commands.
+pub fn pane_id() -> Option<u64> {
+    parse_pane_id(env::var("WEZTERM_PANE").ok()?.as_str())
+}
+
+/// Returns the path of the socket of the WezTerm mux server, `None` if the application
+/// doesn't run in the WezTerm.
+///
+/// The path is read from the `WEZTERM_UNIX_SOCKET` environment variable, the
+/// `wezterm cli` uses it to connect to the instance running the pane.
+pub fn socket_path() -> Option<PathBuf> {
+    env::var_os("WEZTERM_UNIX_SOCKET")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+fn parse_pane_id(value: &str) -> Option<u64> {
+    value.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_pane_id;
+
+    #[test]
+    fn test_parse_pane_id() {
+        assert_eq!(parse_pane_id("42"), Some(42));
+        assert_eq!(parse_pane_id(" 7\n"), Some(7));
+        assert_eq!(parse_pane_id(""), None);
+        assert_eq!(parse_pane_id("pane"), None);
+    }
+}
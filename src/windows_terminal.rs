@@ -0,0 +1,233 @@
+//! # Windows Terminal
+//!
+//! The `windows_terminal` module provides the commands for the extensions of the Windows
+//! Terminal and the ConEmu:
+//!
+//! * the progress shown in the tab and in the taskbar,
+//! * the working directory used to open the new tabs and panes,
+//! * the cursor style named after the `cursorShape` profile setting.
+//!
+//! The progress and the working directory require the
+//! [ConEmuExtensions](../capabilities/enum.Capability.html#variant.ConEmuExtensions)
+//! capability, detected by the `WT_SESSION` and the `ConEmuANSI` environment variables.
+//! The other terminals may print them as text, check the capability first.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use std::io::{stdout, Write};
+//!
+//! use crossterm::{
+//!     capabilities, execute,
+//!     windows_terminal::{Progress, SetProgress},
+//!     Result,
+//! };
+//!
+//! fn main() -> Result<()> {
+//!     if capabilities::capabilities().conemu_extensions() {
+//!         execute!(stdout(), SetProgress(Progress::Normal(42)))?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::capabilities::Capability;
+#[cfg(windows)]
+use crate::Result;
+use crate::{csi, impl_display, Command};
+
+/// The state of the progress shown by the terminal.
+///
+/// The values are percents, clamped to `100`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Progress {
+    /// The progress is hidden.
+    Hidden,
+    /// The progress of the running operation.
+    Normal(u8),
+    /// The operation failed.
+    Error(u8),
+    /// The operation is running, but its progress isn't known.
+    Indeterminate,
+    /// The operation is paused.
+    Paused(u8),
+}
+
+/// A command that sets the progress shown in the tab and in the taskbar, `OSC 9;4`.
+///
+/// # Notes
+///
+/// * Requires the [ConEmuExtensions](../capabilities/enum.Capability.html#variant.ConEmuExtensions)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetProgress(pub Progress);
+
+impl Command for SetProgress {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let (state, value) = match self.0 {
+            Progress::Hidden => (0, 0),
+            Progress::Normal(value) => (1, value),
+            Progress::Error(value) => (2, value),
+            Progress::Indeterminate => (3, 0),
+            Progress::Paused(value) => (4, value),
+        };
+        write!(f, "\x1B]9;4;{};{}\x1B\\", state, value.min(100))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        // The legacy console has no progress
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::ConEmuExtensions)
+    }
+}
+
+/// A command that tells the terminal the working directory, `OSC 9;9`.
+///
+/// The new tabs and panes are opened in this directory, if the profile is set to
+/// duplicate the current one. The path should be a Windows path, e.g. the output of the
+/// `wslpath -w` in the WSL.
+///
+/// # Notes
+///
+/// * Requires the [ConEmuExtensions](../capabilities/enum.Capability.html#variant.ConEmuExtensions)
+///   capability.
+/// * Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetWorkingDirectory<T>(pub T);
+
+impl<T: fmt::Display> Command for SetWorkingDirectory<T> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1B]9;9;{}\x1B\\", self.0)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        // The legacy console doesn't open the new tabs
+        Ok(())
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        Some(Capability::ConEmuExtensions)
+    }
+}
+
+/// The cursor styles of the `cursorShape` profile setting, which can be set with the
+/// escape sequences.
+///
+/// The `emptyBox`, `doubleUnderscore` and `vintage` shapes can be set in the profile
+/// only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorStyle {
+    /// The shape set in the profile.
+    Default,
+    /// `bar`, a vertical line.
+    Bar {
+        /// Whether the cursor blinks.
+        blinking: bool,
+    },
+    /// `underscore`, a horizontal line.
+    Underscore {
+        /// Whether the cursor blinks.
+        blinking: bool,
+    },
+    /// `filledBox`, the whole cell.
+    FilledBox {
+        /// Whether the cursor blinks.
+        blinking: bool,
+    },
+}
+
+impl CursorStyle {
+    /// Returns the steady cursor style of the `cursorShape` setting value, `None` if it
+    /// can't be set with the escape sequences.
+    pub fn from_setting(shape: &str) -> Option<CursorStyle> {
+        match shape {
+            "bar" => Some(CursorStyle::Bar { blinking: false }),
+            "underscore" => Some(CursorStyle::Underscore { blinking: false }),
+            "filledBox" => Some(CursorStyle::FilledBox { blinking: false }),
+            _ => None,
+        }
+    }
+}
+
+/// A command that sets the cursor style (`DECSCUSR`).
+///
+/// Unlike the [SetCursorShape](../cursor/struct.SetCursorShape.html), it allows to
+/// restore the shape set in the profile.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetCursorStyle(pub CursorStyle);
+
+impl Command for SetCursorStyle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let style = match self.0 {
+            CursorStyle::Default => 0,
+            CursorStyle::FilledBox { blinking } => 2 - u8::from(blinking),
+            CursorStyle::Underscore { blinking } => 4 - u8::from(blinking),
+            CursorStyle::Bar { blinking } => 6 - u8::from(blinking),
+        };
+        write!(f, csi!("{} q"), style)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl_display!(for SetProgress);
+impl_display!(for SetCursorStyle);
+
+#[cfg(test)]
+mod tests {
+    use crate::Command;
+
+    use super::{CursorStyle, Progress, SetCursorStyle, SetProgress, SetWorkingDirectory};
+
+    fn ansi(command: impl Command) -> String {
+        let mut result = String::new();
+        command.write_ansi(&mut result).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_progress_sequences() {
+        assert_eq!(
+            ansi(SetProgress(Progress::Normal(42))),
+            "\x1B]9;4;1;42\x1B\\"
+        );
+        assert_eq!(
+            ansi(SetProgress(Progress::Error(200))),
+            "\x1B]9;4;2;100\x1B\\"
+        );
+        assert_eq!(ansi(SetProgress(Progress::Hidden)), "\x1B]9;4;0;0\x1B\\");
+        assert_eq!(
+            ansi(SetWorkingDirectory(r"C:\Users")),
+            "\x1B]9;9;C:\\Users\x1B\\"
+        );
+    }
+
+    #[test]
+    fn test_cursor_style_sequences() {
+        assert_eq!(ansi(SetCursorStyle(CursorStyle::Default)), "\x1B[0 q");
+        assert_eq!(
+            ansi(SetCursorStyle(CursorStyle::Bar { blinking: true })),
+            "\x1B[5 q"
+        );
+        assert_eq!(
+            CursorStyle::from_setting("underscore").map(|style| ansi(SetCursorStyle(style))),
+            Some("\x1B[4 q".to_string())
+        );
+        assert_eq!(CursorStyle::from_setting("vintage"), None);
+    }
+}